@@ -0,0 +1,101 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use lambdaworks_math::field::{
+    fields::montgomery_backed_prime_fields::IsModulus,
+    fields::fft_friendly::stark_252_prime_field::{
+        Stark252PrimeField,
+        MontgomeryConfigStark252PrimeField
+    },
+    element::FieldElement
+};
+
+use stark101::{air, common, prover};
+
+type F = Stark252PrimeField;
+type FConfig = MontgomeryConfigStark252PrimeField;
+type FE = FieldElement<F>;
+
+// evaluation domain is 2^3 times larger than the trace
+const BLOWUP_TWO_POWER: usize = 3;
+const NUM_QUERIES: usize = 10;
+
+fn fibonacci_square(c: &mut Criterion) {
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        10,
+        10 + BLOWUP_TWO_POWER,
+        NUM_QUERIES,
+        FE::one(),
+        FE::from_hex_unchecked("6A317721EF632FF24FB815C9BBD4D4582BC7E21A43CFBDD89A8B8F0BDA68252"),
+        None,
+    );
+    let options = common::ProofOptions::default();
+
+    c.bench_function("fibonacci_square", |b| {
+        b.iter(|| prover::generate_proof(public_input.clone(), &options))
+    });
+}
+
+// the hash chain sizes are large enough for the fft, merkle and
+// fri costs to dominate over the per-proof setup
+fn hash_chain(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_chain");
+    group.sample_size(10);
+
+    for steps_log2 in [12, 14, 16] {
+        let trace_length = 1 << steps_log2;
+        let seed = FE::from(3141592_u64);
+        let trace = air::HashChain::trace(&seed, trace_length);
+        let digest = trace[trace_length - 1];
+        let public_input = common::PublicInput(
+            FConfig::MODULUS,
+            steps_log2,
+            steps_log2 + BLOWUP_TWO_POWER,
+            NUM_QUERIES,
+            seed,
+            digest,
+            None,
+        );
+        let air = air::HashChain { trace_length, seed, digest };
+        let options = common::ProofOptions::default();
+
+        group.bench_with_input(BenchmarkId::from_parameter(steps_log2), &trace, |b, trace| {
+            b.iter(|| prover::prove(&air, trace, public_input.clone(), &options))
+        });
+    }
+    group.finish();
+}
+
+// throughput of many small proofs sharing their evaluation domains
+fn prove_many(c: &mut Criterion) {
+    let steps_log2 = 10;
+    let trace_length = 1 << steps_log2;
+    let witnesses = (0..8_u64)
+        .map(|i| {
+            let seed = FE::from(i);
+            let trace = air::HashChain::trace(&seed, trace_length);
+            let digest = trace[trace_length - 1];
+            let public_input = common::PublicInput(
+                FConfig::MODULUS,
+                steps_log2,
+                steps_log2 + BLOWUP_TWO_POWER,
+                NUM_QUERIES,
+                seed,
+                digest,
+                None,
+            );
+            (air::HashChain { trace_length, seed, digest }, trace, public_input)
+        })
+        .collect::<Vec<_>>();
+    let parallelism = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let options = common::ProofOptions::default();
+
+    let mut group = c.benchmark_group("prove_many");
+    group.sample_size(10);
+    group.bench_function("hash_chain_8x1024", |b| {
+        b.iter(|| prover::prove_many(&witnesses, parallelism, &options))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, fibonacci_square, hash_chain, prove_many);
+criterion_main!(benches);
@@ -0,0 +1,857 @@
+use std::fmt;
+use std::sync::Arc;
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+use lambdaworks_math::polynomial::Polynomial;
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use crate::expr::Expr;
+use crate::trace::TraceBuilder;
+
+// evaluation of a transition constraint on a frame
+pub type ConstraintFn<F> = Arc<dyn Fn(&[FieldElement<F>]) -> FieldElement<F> + Send + Sync>;
+
+// a transition constraint relates frame_size consecutive rows of
+// the trace and holds on every step except on its exemption steps,
+// usually the last ones where the rows it refers to do not exist.
+// a constraint holding on some rows of blocks of steps only, e.g. the
+// last row of each block, declares these rows instead of exempting
+// every other step, see on_rows.
+// the frame starts origin rows before the step, the first ones for a
+// frame reaching backwards, and wraps around the trace: on the first
+// steps its rows before the step are the last ones of the trace
+#[derive(Clone)]
+pub struct TransitionConstraint<F: IsField> {
+    pub frame_size: usize,
+    // row of the frame at the step the constraint applies to, zero
+    // unless the frame reaches backwards
+    pub origin: usize,
+    // rows of the frame read by the constraint, sorted. only these are
+    // opened at the queries, the others are zero in the frames given
+    // to evaluate. every row of the frame unless declared otherwise
+    pub offsets: Vec<usize>,
+    // degree of the constraint as a polynomial in the frame values
+    pub degree: usize,
+    // the constraint applies on the steps whose row in blocks of period
+    // steps is one of rows, sorted, every step by default
+    pub period: usize,
+    pub rows: Vec<usize>,
+    // steps of these rows on which the constraint does not hold
+    pub exemptions: Vec<usize>,
+    // evaluates the constraint on a frame of consecutive rows,
+    // the result is zero when the constraint holds. a closure, so
+    // that constraints can be built at run time
+    pub evaluate: ConstraintFn<F>,
+}
+
+impl<F: IsField> TransitionConstraint<F> {
+
+    pub fn new(
+            frame_size: usize,
+            degree: usize,
+            exemptions: &[usize],
+            evaluate: impl Fn(&[FieldElement<F>]) -> FieldElement<F> + Send + Sync + 'static
+        ) -> Self {
+        Self {
+            frame_size,
+            origin: 0,
+            offsets: (0..frame_size).collect(),
+            degree,
+            period: 1,
+            rows: vec![0],
+            exemptions: exemptions.to_vec(),
+            evaluate: Arc::new(evaluate),
+        }
+    }
+
+    // the constraint written as an expression, reading the rows of the
+    // expression within the smallest frame holding them, of the degree
+    // of the expression
+    pub fn from_expr(exemptions: &[usize], expr: Expr<F>) -> Self
+        where
+            F: 'static,
+            F::BaseType: Send + Sync {
+
+        let offsets = expr.offsets();
+        Self {
+            frame_size: offsets.last().map_or(1, |k| k + 1),
+            origin: 0,
+            offsets,
+            degree: expr.degree(),
+            period: 1,
+            rows: vec![0],
+            exemptions: exemptions.to_vec(),
+            evaluate: Arc::new(move |frame| expr.evaluate(frame)),
+        }
+    }
+
+    // declares the only rows of the frame the constraint reads, so
+    // that a wide frame does not open all of its rows at each query
+    pub fn reading(mut self, offsets: &[usize]) -> Self {
+        assert!(offsets.windows(2).all(|w| w[0] < w[1]), "the offsets are sorted without repetitions");
+        assert!(offsets.iter().all(|k| *k < self.frame_size), "the offsets are rows of the frame");
+        self.offsets = offsets.to_vec();
+        self
+    }
+
+    // applies the constraint at the row origin of its frame, the rows
+    // before it reaching backwards, e.g. a(n) = a(n-1)^2 + a(n-2)^2 as
+    // a frame of three rows starting at 2
+    pub fn starting_at(mut self, origin: usize) -> Self {
+        assert!(origin < self.frame_size, "the origin is a row of the frame");
+        self.origin = origin;
+        self
+    }
+
+    // applies the constraint on the given rows of each block of period
+    // steps only, e.g. on the last row of blocks of 16 steps. the steps
+    // it applies on vanish on x^(n/period) - g^(row n/period) for each
+    // row, a zerofier of a few terms however many steps it covers, and
+    // the period of the trace length on a single step
+    pub fn on_rows(mut self, period: usize, rows: &[usize]) -> Self {
+        assert!(period.is_power_of_two(), "the period {} is a power of two", period);
+        assert!(!rows.is_empty() && rows.windows(2).all(|w| w[0] < w[1]), "the rows are sorted without repetitions");
+        assert!(rows.iter().all(|row| *row < period), "the rows are rows of a block");
+        self.period = period;
+        self.rows = rows.to_vec();
+        self
+    }
+
+    // whether the constraint holds on the step
+    pub fn applies(&self, step: usize) -> bool {
+        self.rows.contains(&(step % self.period)) && !self.exemptions.contains(&step)
+    }
+
+    // the number of steps of its rows, exempted ones included, in a
+    // trace of the given length
+    pub fn row_steps(&self, trace_length: usize) -> usize {
+        trace_length / self.period * self.rows.len()
+    }
+
+    // the number of rows from the step to the row k of the frame, in a
+    // trace of the given length. the rows before the step wrap around
+    // to the end of the trace, as g^(n - r) is g^(-r)
+    pub fn rotation(&self, k: usize, trace_length: usize) -> usize {
+        (k + trace_length - self.origin) % trace_length
+    }
+
+    // the rotations of the rows read by the constraint
+    pub fn rotations(&self, trace_length: usize) -> Vec<usize> {
+        self.offsets
+            .iter()
+            .map(|k| self.rotation(*k, trace_length))
+            .collect()
+    }
+
+    // the frame given to evaluate, with the value of the trace at the
+    // rotation of its row k, row(rotation), at the offsets read by the
+    // constraint and zero elsewhere
+    pub fn frame(&self, trace_length: usize, row: impl Fn(usize) -> FieldElement<F>) -> Vec<FieldElement<F>> {
+        let mut frame = vec![FieldElement::<F>::zero(); self.frame_size];
+        for k in &self.offsets {
+            frame[*k] = row(self.rotation(*k, trace_length));
+        }
+        frame
+    }
+
+    // builds the product of (x - g^s) over the exemption steps s,
+    // which cancels the zeros of the constraint zerofier at those steps
+    pub fn exemptions_polynomial(&self, g: &FieldElement<F>) -> Polynomial<FieldElement<F>> {
+        let one = FieldElement::<F>::one();
+        self.exemptions
+            .iter()
+            .fold(Polynomial::new_monomial(one.clone(), 0), |product, step| {
+                product * Polynomial::new(&[-g.pow(*step), one.clone()])
+            })
+    }
+
+    // evaluates the exemption factors at a single point, as
+    // needed by the verifier at each query
+    pub fn evaluate_exemptions(&self, g: &FieldElement<F>, x: &FieldElement<F>) -> FieldElement<F> {
+        self.exemptions
+            .iter()
+            .fold(FieldElement::<F>::one(), |product, step| {
+                product * (x - g.pow(*step))
+            })
+    }
+}
+
+// the fibonacci square transition a(n+2) = a(n+1)^2 + a(n)^2
+// relates three consecutive rows, so it does not apply on the
+// last three steps of the trace
+pub fn fibonacci_square_transition<F>(trace_length: usize) -> TransitionConstraint<F>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync {
+
+    TransitionConstraint::from_expr(
+        &[trace_length - 3, trace_length - 2, trace_length - 1],
+        Expr::row(2) - Expr::next(0).square() - Expr::col(0).square()
+    )
+}
+
+// a round of the hash chain, x(n+1) = (x(n) + c)^3
+fn hash_chain_round<F: IsField>() -> Expr<F> {
+    Expr::next(0) - (Expr::col(0) + FieldElement::<F>::from(HASH_CHAIN_ROUND_CONSTANT)).pow(3)
+}
+
+// a boundary constraint fixes the value of the trace at a given step
+#[derive(Clone)]
+pub struct BoundaryConstraint<F: IsField> {
+    pub step: usize,
+    pub value: FieldElement<F>,
+}
+
+// all the boundary constraints are enforced together by a single
+// quotient (t(x) - I(x)) / Z(x), where I interpolates the boundary
+// values and Z vanishes on the boundary steps
+#[derive(Clone)]
+pub struct BoundaryConstraints<F: IsField> {
+    pub constraints: Vec<BoundaryConstraint<F>>,
+}
+
+impl<F: IsField> BoundaryConstraints<F> {
+
+    pub fn new(constraints: Vec<BoundaryConstraint<F>>) -> Self {
+        Self {
+            constraints,
+        }
+    }
+
+    // builds the lowest degree polynomial taking every boundary
+    // value at the point g^step of its step
+    pub fn interpolant(&self, g: &FieldElement<F>) -> Polynomial<FieldElement<F>> {
+        let (xs, ys): (Vec<FieldElement<F>>, Vec<FieldElement<F>>) = self.constraints
+            .iter()
+            .map(|c| (g.pow(c.step), c.value.clone()))
+            .unzip();
+
+        Polynomial::interpolate(&xs, &ys).unwrap()
+    }
+
+    // builds the product of (x - g^step) over the boundary steps
+    pub fn zerofier(&self, g: &FieldElement<F>) -> Polynomial<FieldElement<F>> {
+        let one = FieldElement::<F>::one();
+        self.constraints
+            .iter()
+            .fold(Polynomial::new_monomial(one.clone(), 0), |product, c| {
+                product * Polynomial::new(&[-g.pow(c.step), one.clone()])
+            })
+    }
+
+    // evaluates the boundary zerofier at a single point
+    pub fn evaluate_zerofier(&self, g: &FieldElement<F>, x: &FieldElement<F>) -> FieldElement<F> {
+        self.constraints
+            .iter()
+            .fold(FieldElement::<F>::one(), |product, c| {
+                product * (x - g.pow(c.step))
+            })
+    }
+}
+
+// the fibonacci square statement fixes the first element and
+// the element at step 1022 of the sequence
+pub fn fibonacci_square_boundary<F: IsField>(
+        fib_squared_0: &FieldElement<F>,
+        fib_squared_1022: &FieldElement<F>
+    ) -> BoundaryConstraints<F> {
+
+    BoundaryConstraints::new(vec![
+        BoundaryConstraint { step: 0, value: fib_squared_0.clone() },
+        BoundaryConstraint { step: 1022, value: fib_squared_1022.clone() },
+    ])
+}
+
+// first constraint violated by a raw trace
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TraceError {
+    Boundary { step: usize },
+    Transition { constraint: usize, step: usize },
+    // the trace does not have the length of the statement
+    Length { expected: usize, found: usize },
+}
+
+impl fmt::Display for TraceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TraceError::Boundary { step } => write!(f, "boundary constraint violated at step {}", step),
+            TraceError::Transition { constraint, step } => write!(f, "transition constraint {} violated at step {}", constraint, step),
+            TraceError::Length { expected, found } => write!(f, "trace of {} rows instead of {}", found, expected),
+        }
+    }
+}
+
+impl std::error::Error for TraceError {}
+
+// evaluates every boundary and transition constraint directly on
+// the rows of the trace and reports the first violated one. this
+// is much more informative than a non-zero division remainder
+pub fn check_trace<F: IsField>(
+        trace: &[FieldElement<F>],
+        boundary: &BoundaryConstraints<F>,
+        transitions: &[TransitionConstraint<F>]
+    ) -> Result<(), TraceError> {
+
+    if let Some(c) = boundary.constraints.iter().find(|c| trace[c.step] != c.value) {
+        return Err(TraceError::Boundary { step: c.step })
+    }
+
+    let trace_length = trace.len();
+    for (constraint, transition) in transitions.iter().enumerate() {
+        let violated_step = (0..trace_length)
+            .filter(|step| transition.applies(*step))
+            .find(|step| {
+                // as the verifier sees it, so that a constraint reading
+                // rows it does not declare fails here
+                let frame = transition.frame(trace_length, |k| trace[(step + k) % trace_length].clone());
+                (transition.evaluate)(&frame) != FieldElement::<F>::zero()
+            });
+
+        if let Some(step) = violated_step {
+            return Err(TraceError::Transition { constraint, step })
+        }
+    }
+
+    Ok(())
+}
+
+// a single column statement, described by the constraints its
+// trace must satisfy. prover and verifier derive the composition
+// polynomial and its evaluations at the queries from them
+pub trait Air<F: IsField> {
+    fn trace_length(&self) -> usize;
+    // name of the statement, distinct for every air, bound into the
+    // transcript by the digest of the verifying key
+    fn identifier(&self) -> String;
+    fn boundary_constraints(&self) -> BoundaryConstraints<F>;
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>>;
+
+    // number of consecutive rows opened at each query
+    fn frame_size(&self) -> usize {
+        self.transition_constraints()
+            .iter()
+            .map(|t| t.frame_size)
+            .max()
+            .unwrap_or(1)
+    }
+
+    // rows opened at each query, as rotations from the row of the query:
+    // the row itself, read by the boundary constraints, and the ones
+    // read by any transition, the rows before it wrapped to the end of
+    // the trace
+    fn frame_offsets(&self) -> Vec<usize> {
+        let trace_length = self.trace_length();
+        let mut offsets = self.transition_constraints()
+            .into_iter()
+            .flat_map(|t| t.rotations(trace_length))
+            .chain([0])
+            .collect::<Vec<usize>>();
+        offsets.sort_unstable();
+        offsets.dedup();
+        offsets
+    }
+
+    // bound on the degree of the composition polynomial, declared by
+    // the statement rather than read from the polynomial the prover
+    // commits to. the boundary quotient has degree below n - |B| and a
+    // transition quotient below d * (n - 1) + |E| - |S| + 1, S the steps
+    // of its rows
+    fn composition_degree_bound(&self) -> usize {
+        let n = self.trace_length();
+        let boundary_bound = n - self.boundary_constraints().constraints.len();
+        self.transition_constraints()
+            .iter()
+            .map(|t| (t.degree * (n - 1) + t.exemptions.len() + 1).saturating_sub(t.row_steps(n)))
+            .fold(boundary_bound, usize::max)
+            .max(1)
+    }
+
+    // values the constraints are built from besides the public input,
+    // absorbed into the transcript right after it so that a proof only
+    // holds for these values. none for the statements described by
+    // their public input alone, whose proofs are unchanged
+    fn parameters(&self) -> Vec<FieldElement<F>> {
+        vec![]
+    }
+
+    // steps of the trace holding the outputs of the computation, whose
+    // values the prover exports with the proof, see output. none for
+    // the statements whose only result is their validity
+    fn output_steps(&self) -> Vec<usize> {
+        vec![]
+    }
+}
+
+// the fibonacci square statement of the stark 101 tutorial
+#[derive(Clone)]
+pub struct FibonacciSquare<F: IsField> {
+    pub trace_length: usize,
+    pub fib_squared_0: FieldElement<F>,
+    pub fib_squared_1022: FieldElement<F>,
+}
+
+impl<F: IsField> FibonacciSquare<F> {
+
+    // the squared fibonacci sequence from its first element and the
+    // witness, a_(n+2) = a_n^2 + a_(n+1)^2. the last row is not part
+    // of the sequence and is left zero
+    pub fn trace(fib_squared_0: &FieldElement<F>, witness: &FieldElement<F>, trace_length: usize) -> Vec<FieldElement<F>> {
+        TraceBuilder::new(trace_length)
+            .row(fib_squared_0.clone())
+            .row(witness.clone())
+            .rows(trace_length - 3, |rows| {
+                let i = rows.len();
+                rows[i-2].square() + rows[i-1].square()
+            })
+            .pad(FieldElement::<F>::zero())
+            .build()
+            .expect("the sequence is padded to the trace length")
+    }
+    // the element a_1022 claimed by the statement, from the first
+    // element and a witness candidate, without building the trace. with
+    // it, the public input of any witness is built programmatically
+    pub fn fib_squared_1022(fib_squared_0: &FieldElement<F>, witness: &FieldElement<F>) -> FieldElement<F> {
+        (2..=1022)
+            .fold((fib_squared_0.clone(), witness.clone()), |(a, b), _| {
+                let next = a.square() + b.square();
+                (b, next)
+            })
+            .1
+    }
+}
+
+impl<F> Air<F> for FibonacciSquare<F>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "fibonacci-square".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        fibonacci_square_boundary(&self.fib_squared_0, &self.fib_squared_1022)
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        vec![fibonacci_square_transition(self.trace_length)]
+    }
+}
+
+// the quadratic recurrence a(n+2) = alpha * a(n+1)^2 + beta * a(n)^2 + gamma,
+// whose coefficients are parameters of the statement, from a public
+// first element to a public element on the row before the last. the
+// fibonacci square is the one of coefficients 1, 1 and 0
+#[derive(Clone)]
+pub struct QuadraticRecurrence<F: IsField> {
+    pub trace_length: usize,
+    pub alpha: FieldElement<F>,
+    pub beta: FieldElement<F>,
+    pub gamma: FieldElement<F>,
+    pub first: FieldElement<F>,
+    pub last: FieldElement<F>,
+}
+
+impl<F: IsField> QuadraticRecurrence<F> {
+
+    // the recurrence from the first element and the witness, the last
+    // row is not part of the sequence and is left zero
+    pub fn trace(&self, witness: &FieldElement<F>) -> Vec<FieldElement<F>> {
+        TraceBuilder::new(self.trace_length)
+            .row(self.first.clone())
+            .row(witness.clone())
+            .rows(self.trace_length - 3, |rows| {
+                let i = rows.len();
+                &self.alpha * rows[i-1].square() + &self.beta * rows[i-2].square() + &self.gamma
+            })
+            .pad(FieldElement::<F>::zero())
+            .build()
+            .expect("the sequence is padded to the trace length")
+    }
+}
+
+// the constraint holds the coefficients, shared between threads
+impl<F> Air<F> for QuadraticRecurrence<F>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "quadratic-recurrence".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        BoundaryConstraints::new(vec![
+            BoundaryConstraint { step: 0, value: self.first.clone() },
+            BoundaryConstraint { step: self.trace_length - 2, value: self.last.clone() },
+        ])
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        vec![TransitionConstraint::from_expr(
+            &[self.trace_length - 3, self.trace_length - 2, self.trace_length - 1],
+            Expr::row(2)
+                - Expr::constant(self.alpha.clone()) * Expr::next(0).square()
+                - Expr::constant(self.beta.clone()) * Expr::col(0).square()
+                - self.gamma.clone()
+        )]
+    }
+
+    fn parameters(&self) -> Vec<FieldElement<F>> {
+        vec![self.alpha.clone(), self.beta.clone(), self.gamma.clone()]
+    }
+}
+
+// round constant added before each cubing of the hash chain
+pub const HASH_CHAIN_ROUND_CONSTANT: u64 = 7;
+
+// an arithmetic hash chain x(n+1) = (x(n) + c)^3 over every step
+// of the trace. cubing is a permutation of the stark252 field, so
+// the chain does not collapse. it is meant to benchmark the prover
+// on large traces rather than to be a secure hash
+#[derive(Clone)]
+pub struct HashChain<F: IsField> {
+    pub trace_length: usize,
+    pub seed: FieldElement<F>,
+    pub digest: FieldElement<F>,
+}
+
+impl<F: IsField> HashChain<F> {
+
+    // runs the chain from the seed, the last element is the digest
+    pub fn trace(seed: &FieldElement<F>, trace_length: usize) -> Vec<FieldElement<F>> {
+        let round_constant = FieldElement::<F>::from(HASH_CHAIN_ROUND_CONSTANT);
+        TraceBuilder::new(trace_length)
+            .row(seed.clone())
+            .rows(trace_length - 1, |rows| (&rows[rows.len()-1] + &round_constant).pow(3_u64))
+            .build()
+            .expect("the chain has one row per step")
+    }
+}
+
+impl<F> Air<F> for HashChain<F>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "hash-chain".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        BoundaryConstraints::new(vec![
+            BoundaryConstraint { step: 0, value: self.seed.clone() },
+            BoundaryConstraint { step: self.trace_length - 1, value: self.digest.clone() },
+        ])
+    }
+
+    // the chain relates two consecutive rows, so it does not apply
+    // on the last step of the trace
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        vec![TransitionConstraint::from_expr(&[self.trace_length - 1], hash_chain_round())]
+    }
+}
+
+// rounds of the hash chain permutation committing to a witness
+pub const WITNESS_COMMITMENT_ROUNDS: usize = 4;
+
+// the commitment to a witness bound by CommittedWitness: the hash
+// chain run from the witness for WITNESS_COMMITMENT_ROUNDS steps. it
+// is binding, as the rounds are permutations, but it is not hiding
+pub fn commit_witness<F: IsField>(witness: &FieldElement<F>) -> FieldElement<F> {
+    HashChain::trace(witness, WITNESS_COMMITMENT_ROUNDS + 1).pop().unwrap()
+}
+
+// a witness derived from a seed string, so that a statement can be
+// handed out and reproduced from its seed alone: the keccak256 hash of
+// a domain separator and the seed, reduced modulo the field
+pub fn witness_from_seed<F: IsField>(seed: &str) -> FieldElement<F>
+    where
+        FieldElement<F>: ByteConversion {
+
+    let mut hasher = DefaultTranscript::<F>::new(b"stark101/witness-seed");
+    hasher.append_bytes(seed.as_bytes());
+    hasher.sample_field_element()
+}
+
+// the statement of the inner air, additionally binding its witness,
+// the value of the inner trace at witness_row, to a public commitment.
+// the inner trace is followed by the commitment hash chain, at the end
+// of a trace long enough for both, so that the chain reaches row 0 by
+// wrapping around:
+//
+//   rows 0..n            the inner trace
+//   rows n..m            unconstrained, zero
+//   rows m..m+r+1        the chain, from a copy of the witness to the
+//                        commitment on the last row
+//
+// the copy is enforced by a constraint whose frame spans from row m
+// to the witness row, so the witness row is best one of the first
+#[derive(Clone)]
+pub struct CommittedWitness<F: IsField, A> {
+    pub inner: A,
+    pub witness_row: usize,
+    pub commitment: FieldElement<F>,
+}
+
+impl<F, A> CommittedWitness<F, A>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync,
+        A: Air<F> {
+
+    // first row of the commitment chain
+    fn chain_start(&self) -> usize {
+        self.trace_length() - WITNESS_COMMITMENT_ROUNDS - 1
+    }
+
+    // extends a trace of the inner statement with the commitment chain
+    pub fn trace(&self, inner_trace: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let mut trace = inner_trace.to_vec();
+        trace.resize(self.chain_start(), FieldElement::<F>::zero());
+        trace.extend(HashChain::trace(&inner_trace[self.witness_row], WITNESS_COMMITMENT_ROUNDS + 1));
+        trace
+    }
+}
+
+impl<F, A> Air<F> for CommittedWitness<F, A>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync,
+        A: Air<F> {
+
+    fn trace_length(&self) -> usize {
+        (self.inner.trace_length() + WITNESS_COMMITMENT_ROUNDS + 1).next_power_of_two()
+    }
+
+    fn identifier(&self) -> String {
+        format!("committed-witness({})", self.inner.identifier())
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        let mut boundary = self.inner.boundary_constraints();
+        boundary.constraints.push(BoundaryConstraint { step: self.trace_length() - 1, value: self.commitment.clone() });
+        boundary
+    }
+
+    // the inner constraints hold on the inner trace only, the rounds
+    // of the chain on its steps and the copy on its first row
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let trace_length = self.trace_length();
+        let chain_start = self.chain_start();
+        let inner_length = self.inner.trace_length();
+
+        let mut transitions = self.inner.transition_constraints()
+            .into_iter()
+            .map(|mut transition| {
+                let padding = (inner_length..trace_length)
+                    .filter(|step| transition.applies(*step))
+                    .collect::<Vec<usize>>();
+                transition.exemptions.extend(padding);
+                transition
+            })
+            .collect::<Vec<TransitionConstraint<F>>>();
+
+        let chain_exemptions = (0..chain_start)
+            .chain([trace_length - 1])
+            .collect::<Vec<usize>>();
+        transitions.push(TransitionConstraint::from_expr(&chain_exemptions, hash_chain_round()));
+
+        // on the single step of the first row of the chain, with the
+        // zerofier x - g^step
+        let copy_frame_size = trace_length - chain_start + self.witness_row + 1;
+        transitions.push(
+            TransitionConstraint::from_expr(&[], Expr::col(0) - Expr::row(copy_frame_size - 1))
+                .on_rows(trace_length, &[chain_start])
+        );
+        transitions
+    }
+
+    fn parameters(&self) -> Vec<FieldElement<F>> {
+        self.inner.parameters()
+    }
+
+    // the inner trace keeps its rows
+    fn output_steps(&self) -> Vec<usize> {
+        self.inner.output_steps()
+    }
+}
+
+// the statement of the inner air, additionally claiming the values
+// of its trace at some steps, e.g. intermediate results of the
+// computation. the claims are boundary constraints, enforced by the
+// boundary quotient of the composition together with the ones of the
+// inner air, and the parameters of the statement, so that the proof
+// only holds for them
+#[derive(Clone)]
+pub struct BoundaryClaims<F: IsField, A> {
+    pub inner: A,
+    pub claims: Vec<BoundaryConstraint<F>>,
+}
+
+impl<F: IsField, A: Air<F>> Air<F> for BoundaryClaims<F, A> {
+
+    fn trace_length(&self) -> usize {
+        self.inner.trace_length()
+    }
+
+    fn identifier(&self) -> String {
+        format!("boundary-claims({})", self.inner.identifier())
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        let mut boundary = self.inner.boundary_constraints();
+        for claim in &self.claims {
+            assert!(claim.step < self.trace_length(), "claim on step {} of a trace of {} rows", claim.step, self.trace_length());
+            assert!(boundary.constraints.iter().all(|c| c.step != claim.step), "step {} is constrained twice", claim.step);
+            boundary.constraints.push(claim.clone());
+        }
+        boundary
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        self.inner.transition_constraints()
+    }
+
+    // the parameters of the inner air, then the step and value of each claim
+    fn parameters(&self) -> Vec<FieldElement<F>> {
+        let mut parameters = self.inner.parameters();
+        for claim in &self.claims {
+            parameters.push(FieldElement::<F>::from(claim.step as u64));
+            parameters.push(claim.value.clone());
+        }
+        parameters
+    }
+
+    fn output_steps(&self) -> Vec<usize> {
+        self.inner.output_steps()
+    }
+}
+
+// how the rows of a trace past the steps of its statement are filled
+// when the number of steps is not a power of two
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Padding {
+    // zero rows
+    Zero,
+    // copies of the last step
+    RepeatLast,
+}
+
+impl Padding {
+
+    fn name(&self) -> &'static str {
+        match self {
+            Padding::Zero => "zero",
+            Padding::RepeatLast => "repeat-last",
+        }
+    }
+}
+
+// the statement of the inner air, of any number of steps, over a trace
+// padded to the next power of two:
+//
+//   rows 0..n            the inner trace
+//   rows n..m            the padding
+//
+// the inner constraints hold on the inner trace only, exempted on the
+// steps of the padding, and a padding constraint on the steps from the
+// last one of the inner trace fixes the padding rows, so that the
+// prover cannot fill them freely. the number of steps and the padding
+// are in the identifier and the parameters of the statement
+#[derive(Clone)]
+pub struct Padded<A> {
+    pub inner: A,
+    pub padding: Padding,
+}
+
+impl<A> Padded<A> {
+
+    // pads a trace of the inner statement to the trace length
+    pub fn trace<F: IsField>(&self, inner_trace: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+        let value = match self.padding {
+            Padding::Zero => FieldElement::<F>::zero(),
+            Padding::RepeatLast => inner_trace.last().expect("an inner trace of at least one row").clone(),
+        };
+        let mut trace = inner_trace.to_vec();
+        trace.resize(inner_trace.len().next_power_of_two(), value);
+        trace
+    }
+}
+
+impl<F, A> Air<F> for Padded<A>
+    where
+        F: IsField + 'static,
+        F::BaseType: Send + Sync,
+        A: Air<F> {
+
+    fn trace_length(&self) -> usize {
+        self.inner.trace_length().next_power_of_two()
+    }
+
+    fn identifier(&self) -> String {
+        format!("padded({}, {}, {})", self.inner.identifier(), self.inner.trace_length(), self.padding.name())
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        self.inner.boundary_constraints()
+    }
+
+    // with no padding rows the inner constraints are unchanged
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let trace_length = self.trace_length();
+        let inner_length = self.inner.trace_length();
+
+        let mut transitions = self.inner.transition_constraints()
+            .into_iter()
+            .map(|mut transition| {
+                let padding = (inner_length..trace_length)
+                    .filter(|step| transition.applies(*step))
+                    .collect::<Vec<usize>>();
+                transition.exemptions.extend(padding);
+                transition
+            })
+            .collect::<Vec<TransitionConstraint<F>>>();
+        if inner_length == trace_length {
+            return transitions
+        }
+
+        let padding_exemptions = (0..inner_length - 1)
+            .chain([trace_length - 1])
+            .collect::<Vec<usize>>();
+        transitions.push(match self.padding {
+            Padding::Zero => TransitionConstraint::from_expr(&padding_exemptions, Expr::next(0)),
+            Padding::RepeatLast => TransitionConstraint::from_expr(&padding_exemptions, Expr::next(0) - Expr::col(0)),
+        });
+        transitions
+    }
+
+    // the parameters of the inner air, then the number of steps and
+    // the padding
+    fn parameters(&self) -> Vec<FieldElement<F>> {
+        let mut parameters = self.inner.parameters();
+        parameters.push(FieldElement::<F>::from(self.inner.trace_length() as u64));
+        parameters.push(FieldElement::<F>::from(self.padding as u64));
+        parameters
+    }
+
+    fn output_steps(&self) -> Vec<usize> {
+        self.inner.output_steps()
+    }
+}
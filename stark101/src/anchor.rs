@@ -0,0 +1,31 @@
+// a succinct anchor of a proof, to be posted on-chain while the full
+// proof is served off-chain: the keccak256 digest of
+//
+//   encoded public input | trace root [32] | layer roots [32] each
+//
+// i.e. of the statement and of every commitment of the prover, not of
+// the openings. a proof checked against the anchor is then bound to
+// the commitments the anchor was computed from
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use crate::common::{ProofAccess, PublicInput, StarkProof};
+use crate::encoding;
+
+type F = Stark252PrimeField;
+
+pub fn commitment_digest<P: ProofAccess<F> + ?Sized>(proof: &P, public_input: &PublicInput<F>) -> [u8; 32] {
+    let mut hasher = DefaultTranscript::<F>::new(&encoding::encode_public_input(public_input));
+    hasher.append_bytes(&proof.trace_root());
+    for layer in 0..proof.layer_count() {
+        hasher.append_bytes(&proof.layer_root(layer));
+    }
+    hasher.state()
+}
+
+impl StarkProof<F> {
+
+    pub fn commitment_digest(&self, public_input: &PublicInput<F>) -> [u8; 32] {
+        commitment_digest(self, public_input)
+    }
+}
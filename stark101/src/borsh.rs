@@ -0,0 +1,169 @@
+// borsh encoding of proofs and public inputs, for the tooling where
+// borsh is the wire format. the structs are encoded field by field as
+// borsh does, vectors with a u32 little endian length, but the field
+// elements keep the 32 big endian bytes of the other encoders, and
+// must be canonical. the sizes of the public input are u32, and the
+// binding of a proof is a borsh option after its fri layers
+use std::io::{self, Read, Write};
+
+use ::borsh::{BorshDeserialize, BorshSerialize};
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+use lambdaworks_math::unsigned_integer::element::U256;
+use lambdaworks_crypto::merkle_tree::proof::Proof;
+
+use crate::common::{self, InclusionProof, PublicInput, StarkProof, VectorCommitment};
+use crate::fri::{FriLayer, ValidationData};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn invalid_data(error: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, error)
+}
+
+fn serialize_element<W: Write>(element: &FE, writer: &mut W) -> io::Result<()> {
+    writer.write_all(&common::fe_to_bytes_be(element))
+}
+
+fn deserialize_element<R: Read>(reader: &mut R) -> io::Result<FE> {
+    let bytes = <[u8; 32]>::deserialize_reader(reader)?;
+    common::fe_from_bytes_be(&bytes).map_err(|_| invalid_data("non canonical field element"))
+}
+
+fn serialize_len<W: Write>(len: usize, writer: &mut W) -> io::Result<()> {
+    u32::try_from(len)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "size does not fit in u32"))?
+        .serialize(writer)
+}
+
+fn deserialize_len<R: Read>(reader: &mut R) -> io::Result<usize> {
+    u32::deserialize_reader(reader).map(|len| len as usize)
+}
+
+impl BorshSerialize for InclusionProof<F> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let InclusionProof(eval, path) = self;
+        serialize_element(eval, writer)?;
+        path.merkle_path.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for InclusionProof<F> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let eval = deserialize_element(reader)?;
+        let merkle_path = Vec::<[u8; 32]>::deserialize_reader(reader)?;
+        Ok(InclusionProof(eval, Proof { merkle_path }))
+    }
+}
+
+impl BorshSerialize for ValidationData<F> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let ValidationData { eval, proof, sym_eval, sym_proof } = self;
+        serialize_element(eval, writer)?;
+        proof.merkle_path.serialize(writer)?;
+        serialize_element(sym_eval, writer)?;
+        sym_proof.merkle_path.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for ValidationData<F> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let InclusionProof(eval, proof) = InclusionProof::deserialize_reader(reader)?;
+        let InclusionProof(sym_eval, sym_proof) = InclusionProof::deserialize_reader(reader)?;
+        Ok(ValidationData { eval, proof, sym_eval, sym_proof })
+    }
+}
+
+impl BorshSerialize for FriLayer<F> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let FriLayer { root, validation_data, landing_data, remainder } = self;
+        root.serialize(writer)?;
+        validation_data.serialize(writer)?;
+        landing_data.serialize(writer)?;
+        serialize_len(remainder.len(), writer)?;
+        for coefficient in remainder {
+            serialize_element(coefficient, writer)?;
+        }
+        Ok(())
+    }
+}
+
+impl BorshDeserialize for FriLayer<F> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(FriLayer {
+            root: <[u8; 32]>::deserialize_reader(reader)?,
+            validation_data: Vec::<ValidationData<F>>::deserialize_reader(reader)?,
+            landing_data: Vec::<InclusionProof<F>>::deserialize_reader(reader)?,
+            remainder: (0..deserialize_len(reader)?)
+                .map(|_| deserialize_element(reader))
+                .collect::<io::Result<Vec<FE>>>()?,
+        })
+    }
+}
+
+impl BorshSerialize for StarkProof<F> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let StarkProof { trace_commitment, composition_commitment, binding } = self;
+        trace_commitment.root.serialize(writer)?;
+        trace_commitment.inclusion_proofs.serialize(writer)?;
+        composition_commitment.serialize(writer)?;
+        binding.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for StarkProof<F> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let root = <[u8; 32]>::deserialize_reader(reader)?;
+        let inclusion_proofs = Vec::<InclusionProof<F>>::deserialize_reader(reader)?;
+        let composition_commitment = Vec::<FriLayer<F>>::deserialize_reader(reader)?;
+        if composition_commitment.is_empty() {
+            return Err(invalid_data("proof has no fri layer"))
+        }
+        let binding = Option::<[u8; 32]>::deserialize_reader(reader)?;
+        Ok(StarkProof {
+            trace_commitment: VectorCommitment { root, inclusion_proofs },
+            composition_commitment,
+            binding,
+        })
+    }
+}
+
+impl BorshSerialize for PublicInput<F> {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, witness_commitment) = self;
+        writer.write_all(&modulus.to_bytes_be())?;
+        serialize_len(*interp_two_power, writer)?;
+        serialize_len(*eval_two_power, writer)?;
+        serialize_len(*num_queries, writer)?;
+        serialize_element(value_0, writer)?;
+        serialize_element(value_1, writer)?;
+        match witness_commitment {
+            None => 0_u8.serialize(writer),
+            Some(commitment) => {
+                1_u8.serialize(writer)?;
+                serialize_element(commitment, writer)
+            },
+        }
+    }
+}
+
+impl BorshDeserialize for PublicInput<F> {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let modulus = U256::from_bytes_be(&<[u8; 32]>::deserialize_reader(reader)?).unwrap();
+        let interp_two_power = deserialize_len(reader)?;
+        let eval_two_power = deserialize_len(reader)?;
+        let num_queries = deserialize_len(reader)?;
+        let value_0 = deserialize_element(reader)?;
+        let value_1 = deserialize_element(reader)?;
+        let witness_commitment = match u8::deserialize_reader(reader)? {
+            0 => None,
+            1 => Some(deserialize_element(reader)?),
+            _ => return Err(invalid_data("invalid witness commitment tag")),
+        };
+        Ok(PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, witness_commitment))
+    }
+}
@@ -0,0 +1,229 @@
+#[cfg(feature = "transcript-log")]
+use std::fmt;
+
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+use lambdaworks_crypto::fiat_shamir::{
+    is_transcript::IsTranscript,
+    default_transcript::DefaultTranscript
+};
+#[cfg(feature = "transcript-log")]
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+#[cfg(feature = "transcript-log")]
+use crate::air::Air;
+#[cfg(feature = "transcript-log")]
+use crate::common::{self, ProofOptions, PublicInput, StarkProof};
+#[cfg(feature = "transcript-log")]
+use crate::{prover, verifier};
+
+// the fiat-shamir channel between prover and verifier: a transcript
+// that also hands out its raw 32 byte samples, from which the queries
+// are drawn. implemented by the default keccak transcript and, with
+// the transcript-log feature, by a transcript recording its operations
+pub trait Channel<F: IsField>: IsTranscript<F> {
+    fn sample_bytes(&mut self) -> [u8; 32];
+
+    // names the operations that follow, up to the next label, and the
+    // encoding of their bytes. only recorded by the recording channel,
+    // from which the protocol specification is generated, see spec
+    fn label(&mut self, _name: &'static str, _encoding: &'static str) {}
+}
+
+// encodings of the bytes absorbed or sampled, see Channel::label
+pub const U8: &str = "u8";
+// a usize, as 8 bytes big endian on the 64 bit targets of this crate
+pub const U64_BE: &str = "u64 big endian";
+pub const U256_BE: &str = "u256 big endian";
+pub const ELEMENT_BE: &str = "field element, canonical big endian";
+pub const DIGEST: &str = "keccak256 merkle root";
+// a sample read as a big endian integer and reduced modulo the field
+pub const SAMPLED_ELEMENT: &str = "sample reduced modulo the field";
+// a sample read as a big endian integer and reduced modulo the domain
+pub const SAMPLED_INDEX: &str = "sample reduced modulo the domain size";
+// a sample taken as it is
+pub const SAMPLED_BYTES: &str = "32 sampled bytes";
+
+impl<F> Channel<F> for DefaultTranscript<F>
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    fn sample_bytes(&mut self) -> [u8; 32] {
+        self.sample()
+    }
+}
+
+// a transcript operation, labelled by its kind
+#[cfg(feature = "transcript-log")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    Append,
+    Sample,
+    SampleFieldElement,
+    SampleU64,
+}
+
+// an operation with the bytes absorbed or sampled by it
+#[cfg(feature = "transcript-log")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptEvent {
+    pub op: TranscriptOp,
+    pub bytes: Vec<u8>,
+}
+
+// the default transcript, recording every operation applied to it
+// so that the transcripts of prover and verifier can be compared
+#[cfg(feature = "transcript-log")]
+pub struct RecordingChannel<F: IsField> {
+    inner: DefaultTranscript<F>,
+    pub events: Vec<TranscriptEvent>,
+    // the label and encoding of each event, empty for an event that
+    // followed no label
+    pub labels: Vec<(&'static str, &'static str)>,
+    label: (&'static str, &'static str),
+}
+
+#[cfg(feature = "transcript-log")]
+impl<F: IsField> RecordingChannel<F> {
+
+    pub fn new(inner: DefaultTranscript<F>) -> Self {
+        Self {
+            inner,
+            events: vec![],
+            labels: vec![],
+            label: ("", ""),
+        }
+    }
+
+    fn record(&mut self, op: TranscriptOp, bytes: &[u8]) {
+        self.events.push(TranscriptEvent { op, bytes: bytes.to_vec() });
+        self.labels.push(self.label);
+    }
+}
+
+#[cfg(feature = "transcript-log")]
+impl<F> IsTranscript<F> for RecordingChannel<F>
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    fn append_field_element(&mut self, element: &FieldElement<F>) {
+        self.append_bytes(&common::fe_to_bytes_be(element));
+    }
+
+    fn append_bytes(&mut self, new_bytes: &[u8]) {
+        self.record(TranscriptOp::Append, new_bytes);
+        self.inner.append_bytes(new_bytes);
+    }
+
+    fn state(&self) -> [u8; 32] {
+        self.inner.state()
+    }
+
+    fn sample_field_element(&mut self) -> FieldElement<F> {
+        let element = self.inner.sample_field_element();
+        self.record(TranscriptOp::SampleFieldElement, &common::fe_to_bytes_be(&element));
+        element
+    }
+
+    fn sample_u64(&mut self, upper_bound: u64) -> u64 {
+        let sample = self.inner.sample_u64(upper_bound);
+        self.record(TranscriptOp::SampleU64, &sample.to_be_bytes());
+        sample
+    }
+}
+
+#[cfg(feature = "transcript-log")]
+impl<F> Channel<F> for RecordingChannel<F>
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    fn sample_bytes(&mut self) -> [u8; 32] {
+        let sample = self.inner.sample();
+        self.record(TranscriptOp::Sample, &sample);
+        sample
+    }
+
+    fn label(&mut self, name: &'static str, encoding: &'static str) {
+        self.label = (name, encoding);
+    }
+}
+
+// the index of the first operation where the two transcripts differ,
+// the length of the shorter one if it is a strict prefix of the other
+#[cfg(feature = "transcript-log")]
+pub fn first_divergence(expected: &[TranscriptEvent], actual: &[TranscriptEvent]) -> Option<usize> {
+    expected
+        .iter()
+        .zip(actual)
+        .position(|(e, a)| e != a)
+        .or((expected.len() != actual.len()).then_some(expected.len().min(actual.len())))
+}
+
+// panics at the first operation where the two transcripts differ, or
+// if one of them is a strict prefix of the other
+#[cfg(feature = "transcript-log")]
+pub fn assert_transcripts_match(prover: &[TranscriptEvent], verifier: &[TranscriptEvent]) {
+    if let Some(i) = first_divergence(prover, verifier) {
+        panic!(
+            "transcripts diverge at operation {}: prover {}, verifier {}",
+            i,
+            prover.get(i).map_or("stops".to_string(), |e| e.to_string()),
+            verifier.get(i).map_or("stops".to_string(), |e| e.to_string())
+        );
+    }
+}
+
+// one operation per line as its label followed by its bytes in hex,
+// the format of the transcript fixtures
+#[cfg(feature = "transcript-log")]
+impl fmt::Display for TranscriptEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.op.label(), common::bytes_to_hex(&self.bytes))
+    }
+}
+
+#[cfg(feature = "transcript-log")]
+impl TranscriptOp {
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            TranscriptOp::Append => "append",
+            TranscriptOp::Sample => "sample",
+            TranscriptOp::SampleFieldElement => "sample_field_element",
+            TranscriptOp::SampleU64 => "sample_u64",
+        }
+    }
+
+    pub fn from_label(label: &str) -> Option<Self> {
+        [TranscriptOp::Append, TranscriptOp::Sample, TranscriptOp::SampleFieldElement, TranscriptOp::SampleU64]
+            .into_iter()
+            .find(|op| op.label() == label)
+    }
+}
+
+// proves the statement and verifies the proof in process, panicking
+// if the proof is rejected or if prover and verifier transcripts differ
+#[cfg(feature = "transcript-log")]
+pub fn prove_and_verify_recorded<A>(
+        air: &A,
+        trace: &[FieldElement<Stark252PrimeField>],
+        public_input: PublicInput<Stark252PrimeField>,
+        options: &ProofOptions
+    ) -> StarkProof<Stark252PrimeField>
+    where
+        A: Air<Stark252PrimeField> {
+
+    let (proof, prover_events) = prover::prove_recorded(air, trace, public_input.clone(), options);
+    let (result, verifier_events) = verifier::verify_recorded(air, public_input, &proof, options);
+    assert_transcripts_match(&prover_events, &verifier_events);
+    if let Err(e) = result {
+        panic!("proof rejected with matching transcripts: {}", e);
+    }
+    proof
+}
@@ -0,0 +1,151 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use crate::common;
+use crate::fri::LayerStore;
+
+const ELEMENT_SIZE: usize = 32;
+// transcript state after the statement was absorbed, the saved
+// state of another statement is never reused
+const STATEMENT: &str = "statement";
+pub(crate) const TRACE: &str = "trace";
+// the evaluations of the composition over the lde domain
+pub(crate) const COMPOSITION: &str = "composition_evals";
+const FRI_LAYER: &str = "fri_layer_";
+
+// directory holding the state of the completed prover phases, so that
+// an interrupted proof resumes from the last saved phase. each file is
+// a vector of 32 byte big endian field elements, written to a temporary
+// file and renamed so that an interruption never leaves it half written
+pub(crate) struct Checkpoint {
+    dir: PathBuf,
+}
+
+impl Checkpoint {
+
+    pub(crate) fn open(dir: &Path, statement: &[u8; 32]) -> Self {
+        fs::create_dir_all(dir).expect("cannot create the checkpoint directory");
+        let checkpoint = Self { dir: dir.to_path_buf() };
+        if fs::read(checkpoint.path(STATEMENT)).ok().as_deref() != Some(&statement[..]) {
+            checkpoint.clear();
+            checkpoint.write(STATEMENT, statement);
+        }
+        checkpoint
+    }
+
+    // the polynomial saved under the name, or the computed one once saved
+    pub(crate) fn polynomial<F>(
+            &self,
+            name: &str,
+            compute: impl FnOnce() -> Polynomial<FieldElement<F>>
+        ) -> Polynomial<FieldElement<F>>
+        where
+            F: IsField,
+            FieldElement<F>: ByteConversion {
+
+        if let Some(coefficients) = self.load::<F>(name) {
+            return Polynomial::new(&coefficients)
+        }
+        let polynomial = compute();
+        self.save(name, polynomial.coefficients());
+        polynomial
+    }
+
+    // the evaluations saved under the name, when there are as many as
+    // expected, or the computed ones once saved
+    pub(crate) fn evaluations<F>(
+            &self,
+            name: &str,
+            len: usize,
+            compute: impl FnOnce() -> Vec<FieldElement<F>>
+        ) -> Vec<FieldElement<F>>
+        where
+            F: IsField,
+            FieldElement<F>: ByteConversion {
+
+        if let Some(evaluations) = self.load::<F>(name).filter(|evaluations| evaluations.len() == len) {
+            return evaluations
+        }
+        let evaluations = compute();
+        self.save(name, &evaluations);
+        evaluations
+    }
+
+    // removes every file written by a checkpoint, leaving any other
+    // file of the directory untouched
+    pub(crate) fn clear(&self) {
+        let Ok(entries) = fs::read_dir(&self.dir) else {
+            return
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let name = name.strip_suffix(".tmp").unwrap_or(&name);
+            if [STATEMENT, TRACE, COMPOSITION].contains(&name) || name.starts_with(FRI_LAYER) {
+                let _ = fs::remove_file(entry.path());
+            }
+        }
+    }
+
+    fn load<F>(&self, name: &str) -> Option<Vec<FieldElement<F>>>
+        where
+            F: IsField,
+            FieldElement<F>: ByteConversion {
+
+        let bytes = fs::read(self.path(name)).ok()?;
+        if bytes.is_empty() || !bytes.len().is_multiple_of(ELEMENT_SIZE) {
+            return None
+        }
+        bytes
+            .chunks(ELEMENT_SIZE)
+            .map(|chunk| common::fe_from_bytes_be::<F>(chunk).ok())
+            .collect::<Option<Vec<FieldElement<F>>>>()
+    }
+
+    fn save<F>(&self, name: &str, values: &[FieldElement<F>])
+        where
+            F: IsField,
+            FieldElement<F>: ByteConversion {
+
+        let bytes = values
+            .iter()
+            .flat_map(common::fe_to_bytes_be)
+            .collect::<Vec<u8>>();
+        self.write(name, &bytes);
+    }
+
+    fn write(&self, name: &str, bytes: &[u8]) {
+        let path = self.path(name);
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp).expect("cannot create a checkpoint file");
+        file.write_all(bytes).expect("cannot write a checkpoint file");
+        file.sync_all().expect("cannot write a checkpoint file");
+        fs::rename(&tmp, &path).expect("cannot write a checkpoint file");
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.dir.join(name)
+    }
+}
+
+impl<F> LayerStore<F> for Checkpoint
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    fn load_layer(&self, layer: usize) -> Option<Vec<FieldElement<F>>> {
+        self.load(&format!("{}{}", FRI_LAYER, layer))
+    }
+
+    fn save_layer(&self, layer: usize, evals: &[FieldElement<F>]) {
+        self.save(&format!("{}{}", FRI_LAYER, layer), evals)
+    }
+}
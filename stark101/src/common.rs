@@ -1,19 +1,25 @@
 use lambdaworks_math::unsigned_integer::element::U256;
 use lambdaworks_math::field::{
     element::FieldElement,
-    traits::{IsField, IsFFTField}
+    traits::{IsField, IsFFTField, IsPrimeField}
 };
 use lambdaworks_math::traits::{AsBytes, ByteConversion};
-use lambdaworks_crypto::merkle_tree::{
-    merkle::MerkleTree,
-    backends::types::Keccak256Backend, 
-    proof::Proof
-};
+use lambdaworks_crypto::merkle_tree::proof::Proof;
 use lambdaworks_crypto::fiat_shamir::{
+    is_transcript::IsTranscript,
     default_transcript::DefaultTranscript
 };
 
-use crate::fri::FriCommitment;
+use std::borrow::Cow;
+use std::fmt;
+use std::path::PathBuf;
+use std::thread;
+
+use crate::channel::{self, Channel};
+use crate::fri::{FriCommitment, FriLayer, LayerAccess, OffsetSchedule, QuerySampling};
+use crate::merkle::{LeafLayout, MerkleBackend, Tree};
+use crate::storage::TraceStorage;
+use crate::threads;
 
 #[derive(Clone)]
 pub struct PublicInput<F: IsField> (
@@ -22,9 +28,220 @@ pub struct PublicInput<F: IsField> (
 	pub usize,
 	pub usize,
 	pub FieldElement<F>,
-	pub FieldElement<F>
+	pub FieldElement<F>,
+	// hash commitment to the witness, for statements whose air binds
+	// the trace to it, see air::CommittedWitness
+	pub Option<FieldElement<F>>
 );
 
+// how the public input of a statement enters the fiat-shamir transcript,
+// declared once so that prover and verifier absorb it the same way
+pub trait IsPublicInput<F: IsField> {
+	fn absorb_into<T: Channel<F>>(&self, transcript: &mut T);
+	// whether the public input describes a statement that can be proven
+	fn validate(&self) -> Result<(), PublicInputError>;
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PublicInputError {
+	// the modulus is not the one of the field
+	Modulus,
+	// the lde domain is not larger than the trace, or is larger
+	// than the largest power of two subgroup of the field
+	DomainSizes { interp_two_power: usize, eval_two_power: usize },
+	NoQueries,
+	// more queries than the verifier accepts, see DecodeLimits
+	TooManyQueries { num_queries: usize, max: usize },
+	// the statements of a conjunction have lde domains of different
+	// sizes or sample different numbers of queries, see conjunction
+	Conjunction,
+	// not one value for each output step of the statement, see output
+	OutputCount { expected: usize, received: usize },
+	// the trace leaf width of the options is not a power of two
+	// that is at most the trace length
+	LeafWidth { width: usize, trace_length: usize },
+	// the trace is shorter than the air of the statement requires
+	TraceLength { trace_length: usize, min: usize },
+	// the air or the verifier context is not the one of the statement
+	Shape,
+}
+
+impl fmt::Display for PublicInputError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			PublicInputError::Modulus => write!(f, "modulus is not the one of the field"),
+			PublicInputError::DomainSizes { interp_two_power, eval_two_power } => write!(f, "no trace of 2^{} rows with an lde of 2^{} points", interp_two_power, eval_two_power),
+			PublicInputError::NoQueries => write!(f, "no query is sampled"),
+			PublicInputError::TooManyQueries { num_queries, max } => write!(f, "{} queries sampled, at most {} accepted", num_queries, max),
+			PublicInputError::Conjunction => write!(f, "statements of a conjunction with different lde sizes or numbers of queries"),
+			PublicInputError::OutputCount { expected, received } => write!(f, "expected {} outputs, received {}", expected, received),
+			PublicInputError::LeafWidth { width, trace_length } => write!(f, "no trace leaves of {} rows for a trace of {} rows", width, trace_length),
+			PublicInputError::TraceLength { trace_length, min } => write!(f, "trace of {} rows, at least {} required", trace_length, min),
+			PublicInputError::Shape => write!(f, "air or verifier context of another statement"),
+		}
+	}
+}
+
+impl std::error::Error for PublicInputError {}
+
+// one line naming the field modulus, the domain sizes, the number of
+// queries and the claims of the statement, the way prover and verifier
+// logs refer to it, see also the digest of encoding
+impl<F> fmt::Display for PublicInput<F>
+    where
+        F: IsPrimeField<RepresentativeType = U256> {
+
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, public_value_0, public_value_1, witness_commitment) = self;
+		write!(
+			f,
+			"modulus 0x{}, trace of 2^{} rows, lde of 2^{} points, {} queries, public values 0x{} and 0x{}",
+			modulus.to_hex(),
+			interp_two_power,
+			eval_two_power,
+			num_queries,
+			public_value_0.representative().to_hex(),
+			public_value_1.representative().to_hex()
+		)?;
+		if let Some(witness_commitment) = witness_commitment {
+			write!(f, ", witness commitment 0x{}", witness_commitment.representative().to_hex())?;
+		}
+		Ok(())
+	}
+}
+
+impl<F> IsPublicInput<F> for PublicInput<F>
+    where
+        F: IsPrimeField<RepresentativeType = U256> + IsFFTField,
+        FieldElement<F>: ByteConversion {
+
+	fn absorb_into<T: Channel<F>>(&self, transcript: &mut T) {
+		let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, public_value_0, public_value_1, witness_commitment) = self;
+		transcript.label("modulus", channel::U256_BE);
+		transcript.append_bytes(&modulus.to_bytes_be());
+		transcript.label("interp_two_power", channel::U64_BE);
+		transcript.append_bytes(&interp_two_power.to_be_bytes());
+		transcript.label("eval_two_power", channel::U64_BE);
+		transcript.append_bytes(&eval_two_power.to_be_bytes());
+		transcript.label("num_queries", channel::U64_BE);
+		transcript.append_bytes(&num_queries.to_be_bytes());
+		transcript.label("public_value_0", channel::ELEMENT_BE);
+		transcript.append_bytes(&fe_to_bytes_be(public_value_0));
+		transcript.label("public_value_1", channel::ELEMENT_BE);
+		transcript.append_bytes(&fe_to_bytes_be(public_value_1));
+		// absent from the transcript of statements without one, whose
+		// proofs are unchanged
+		if let Some(witness_commitment) = witness_commitment {
+			transcript.label("witness_commitment", channel::ELEMENT_BE);
+			transcript.append_bytes(&fe_to_bytes_be(witness_commitment));
+		}
+	}
+
+	fn validate(&self) -> Result<(), PublicInputError> {
+		let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, ..) = self;
+		if *modulus != F::modulus_minus_one() + U256::from_u64(1) {
+			return Err(PublicInputError::Modulus)
+		}
+		if interp_two_power >= eval_two_power || *eval_two_power as u64 > F::TWO_ADICITY || *eval_two_power > MAX_EVAL_TWO_POWER {
+			return Err(PublicInputError::DomainSizes { interp_two_power: *interp_two_power, eval_two_power: *eval_two_power })
+		}
+		if *num_queries == 0 {
+			return Err(PublicInputError::NoQueries)
+		}
+		Ok(())
+	}
+}
+
+// the largest lde of a statement has 2^MAX_EVAL_TWO_POWER points, far
+// more than any prover extends a trace to, so that the sizes derived
+// from the two powers of a public input never overflow
+pub const MAX_EVAL_TWO_POWER: usize = 40;
+
+// identifier and version of the protocol implemented by this crate,
+// the default transcript label. it changes whenever a change of the
+// protocol makes proofs of one version invalid for the other
+pub const PROTOCOL_LABEL: &[u8] = b"stark101/v2";
+
+// protocol choices that do not belong to the statement
+// but that prover and verifier must agree on
+#[derive(Clone)]
+pub struct ProofOptions {
+	// absorbed first into the transcript, so that proofs of different
+	// protocols or versions are never valid for one another
+	pub transcript_label: Vec<u8>,
+	pub query_sampling: QuerySampling,
+	// the coset offsets of the fri layers
+	pub offset_schedule: OffsetSchedule,
+	// the most fri layers committed, the last of them sent in the clear
+	// when the foldings stop early. fewer layers make proofs faster but
+	// larger, for experiments trading one for the other
+	pub fri_max_layers: Option<usize>,
+	// the hash function of the merkle trees
+	pub merkle_backend: MerkleBackend,
+	// the consecutive rows of the trace extension over one coset in
+	// each leaf of the trace tree, a power of two that is at most the
+	// trace length. wider leaves make a smaller tree and fewer paths
+	// when the frames of the queries share leaves, see merkle::LeafLayout
+	pub trace_leaf_width: usize,
+	// prover side only, ignored by the verifier
+	pub trace_storage: TraceStorage,
+	// prover side only, directory where the completed phases are saved
+	// so that an interrupted proof of the same statement resumes from them.
+	// proofs running at the same time must not share the directory
+	pub checkpoint_dir: Option<PathBuf>,
+	// the proof is the one of the given index in a batch proving
+	// session, set by prove_session and verify_session
+	pub session: Option<Session>,
+}
+
+// a proof of a batch proving session draws its challenges from a
+// transcript derived from the seed of the session and its index in
+// it, so that no two proofs of the session share their challenges
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Session {
+	pub seed: [u8; 32],
+	pub index: usize,
+}
+
+impl ProofOptions {
+
+	// the options with the merkle backend the prover hashes with, for
+	// a verifier that is not in the process of the prover
+	pub fn resolved(&self) -> Self {
+		Self { merkle_backend: self.merkle_backend.resolve(), ..self.clone() }
+	}
+
+	// the leaves of the trace tree of an extension of the given blowup
+	pub fn trace_layout(&self, blowup_factor: usize) -> LeafLayout {
+		LeafLayout::new(self.trace_leaf_width, blowup_factor)
+	}
+
+	// whether the trace leaf width fits a trace of the given length
+	pub fn check_leaf_width(&self, trace_length: usize) -> Result<(), PublicInputError> {
+		let width = self.trace_leaf_width;
+		match width.is_power_of_two() && width <= trace_length {
+			true => Ok(()),
+			false => Err(PublicInputError::LeafWidth { width, trace_length }),
+		}
+	}
+}
+
+impl Default for ProofOptions {
+	fn default() -> Self {
+		Self {
+			transcript_label: PROTOCOL_LABEL.to_vec(),
+			query_sampling: QuerySampling::default(),
+			offset_schedule: OffsetSchedule::default(),
+			fri_max_layers: None,
+			merkle_backend: MerkleBackend::default(),
+			trace_leaf_width: 1,
+			trace_storage: TraceStorage::default(),
+			checkpoint_dir: None,
+			session: None,
+		}
+	}
+}
+
 #[derive(Clone)]
 pub struct InclusionProof<F: IsField> (
     pub FieldElement<F>,
@@ -40,7 +257,11 @@ pub struct VectorCommitment<F: IsField> {
 #[derive(Clone)]
 pub struct StarkProof<F: IsField> {
 	pub trace_commitment: VectorCommitment<F>,
-	pub composition_commitment: FriCommitment<F>
+	pub composition_commitment: FriCommitment<F>,
+	// the transcript squeezed once every opened evaluation is absorbed,
+	// see proof_binding. none for the proofs encoded before version 3,
+	// verified without it
+	pub binding: Option<[u8; 32]>
 }
 
 impl<F> VectorCommitment<F>
@@ -48,62 +269,436 @@ impl<F> VectorCommitment<F>
         F: IsField + IsFFTField,
         FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
 
-    pub fn new_from_tree(tree: &MerkleTree<Keccak256Backend<F>>) -> Self {
+    pub fn new_from_tree(tree: &Tree<F>) -> Self {
         Self {
-            root: tree.root,
+            root: tree.root(),
             inclusion_proofs: vec![],
         }
     }
 
+    // opens the evaluations at the indices, failing without opening
+    // any of them if an index is not in the domain of the evaluations
     pub fn generate_inclusion_proofs(
         &mut self,
         indices: &[usize],
         poly_eval: &[FieldElement<F>],
-        poly_tree: &MerkleTree<Keccak256Backend<F>>,
-    ) {
+        poly_tree: &Tree<F>,
+    ) -> Result<(), QueryError> {
 
-    self.inclusion_proofs.extend(
-        indices
-            .iter()
-            .map(|i| {
-                InclusionProof(poly_eval[*i].to_owned(), poly_tree.get_proof_by_pos(*i).unwrap())
-            })
-            .collect::<Vec<InclusionProof<F>>>()
-        );
+    let openings = parallel_map(indices, |i| {
+        let position_error = QueryError::Position { position: *i, domain_size: poly_eval.len() };
+        let eval = poly_eval.get(*i).ok_or(position_error.clone())?;
+        let proof = poly_tree.get_proof_by_pos(*i).ok_or(position_error)?;
+        Ok(InclusionProof(eval.to_owned(), proof))
+    });
+    self.inclusion_proofs.extend(openings.into_iter().collect::<Result<Vec<InclusionProof<F>>, QueryError>>()?);
+    Ok(())
     }
 
     pub fn verify_inclusion_proofs(
             &self,
             indices: &[usize],
+            backend: MerkleBackend
         ) -> bool {
-    
-        indices
-            .iter()
-            .zip(&self.inclusion_proofs)
-            .map(|(index, InclusionProof(eval, proof))| {
-                proof.verify::<Keccak256Backend<F>>(
-                    &self.root,
-                    *index,
-                    eval
-                )
-            }).all(|valid| valid)
+
+        self.invalid_inclusion_proof(indices, backend).is_none()
+    }
+
+    // returns the position of the first opening that is missing
+    // or not included in the commitment
+    pub fn invalid_inclusion_proof(
+            &self,
+            indices: &[usize],
+            backend: MerkleBackend
+        ) -> Option<usize> {
+
+        invalid_opening(
+            backend,
+            &self.root,
+            indices,
+            self.inclusion_proofs.len(),
+            |i| Cow::Borrowed(&self.inclusion_proofs[i])
+        )
+    }
+}
+
+// returns the position of the first of the given openings that is
+// missing or not included in the commitment of the given root
+pub fn invalid_opening<'a, F>(
+        backend: MerkleBackend,
+        root: &[u8; 32],
+        indices: &[usize],
+        num_openings: usize,
+        opening: impl Fn(usize) -> Cow<'a, InclusionProof<F>>
+    ) -> Option<usize>
+    where
+        F: IsField + 'a,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    invalid_openings(backend, root, indices, num_openings, opening).next()
+}
+
+// the positions of every one of the given openings that is missing or
+// not included, in order, followed by the position past the indices if
+// there are more openings than indices
+pub fn invalid_openings<'a, 'b, F>(
+        backend: MerkleBackend,
+        root: &'b [u8; 32],
+        indices: &'b [usize],
+        num_openings: usize,
+        opening: impl Fn(usize) -> Cow<'a, InclusionProof<F>> + 'b
+    ) -> impl Iterator<Item = usize> + 'b
+    where
+        'a: 'b,
+        F: IsField + 'a,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    invalid_leaf_openings(backend, root, LeafLayout::new(1, 1), indices, num_openings, opening)
+}
+
+// the same for the openings of a tree of the layout at the given
+// positions, every position of a leaf one after the other, see
+// trace_positions. the leaf is included with the path of its first
+// opening, and all of its openings are invalid if it is not
+pub fn invalid_leaf_openings<'a, 'b, F>(
+        backend: MerkleBackend,
+        root: &'b [u8; 32],
+        layout: LeafLayout,
+        positions: &'b [usize],
+        num_openings: usize,
+        opening: impl Fn(usize) -> Cow<'a, InclusionProof<F>> + 'b
+    ) -> impl Iterator<Item = usize> + 'b
+    where
+        'a: 'b,
+        F: IsField + 'a,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    positions
+        .chunks(layout.width)
+        .enumerate()
+        .flat_map(move |(j, leaf_positions)| {
+            let openings = j * layout.width..j * layout.width + leaf_positions.len();
+            let included = openings.end <= num_openings && {
+                let evals = openings.clone().map(|i| opening(i).0.clone()).collect::<Vec<FieldElement<F>>>();
+                let (leaf, _) = layout.leaf_of(leaf_positions[0]);
+                backend.root_from_leaf(&opening(openings.start).1, leaf, &evals) == *root
+            };
+            openings.filter(move |_| !included)
+        })
+        .chain((positions.len() < num_openings).then_some(positions.len()))
+}
+
+// read access to the parts of a proof checked by the verifier, so
+// that it can verify both decoded proofs and encoded ones in place
+pub trait ProofAccess<F: IsField>: LayerAccess<F> {
+    fn trace_root(&self) -> [u8; 32];
+    fn trace_opening_count(&self) -> usize;
+    fn trace_opening(&self, opening: usize) -> Cow<'_, InclusionProof<F>>;
+    fn binding(&self) -> Option<[u8; 32]>;
+}
+
+impl<F: IsField> LayerAccess<F> for StarkProof<F> {
+
+    fn layer_count(&self) -> usize {
+        self.composition_commitment.len()
+    }
+
+    fn layer_root(&self, layer: usize) -> [u8; 32] {
+        self.composition_commitment[layer].root
+    }
+
+    fn layer(&self, layer: usize) -> Cow<'_, FriLayer<F>> {
+        Cow::Borrowed(&self.composition_commitment[layer])
+    }
+}
+
+impl<F: IsField> ProofAccess<F> for StarkProof<F> {
+
+    fn trace_root(&self) -> [u8; 32] {
+        self.trace_commitment.root
+    }
+
+    fn trace_opening_count(&self) -> usize {
+        self.trace_commitment.inclusion_proofs.len()
+    }
+
+    fn trace_opening(&self, opening: usize) -> Cow<'_, InclusionProof<F>> {
+        Cow::Borrowed(&self.trace_commitment.inclusion_proofs[opening])
+    }
+
+    fn binding(&self) -> Option<[u8; 32]> {
+        self.binding
     }
 }
 
+// absorbs every evaluation opened by a proof, the trace openings then,
+// layer by layer, the fri openings at the queries and where they land,
+// and squeezes the digest binding the proof. prover and verifier take
+// it once the last challenge is drawn, so that transcripts diverging
+// anywhere fail this one check, not only the challenges drawn after
+pub(crate) fn proof_binding<F, L>(
+        trace_evals: impl IntoIterator<Item = FieldElement<F>>,
+        layers: &L,
+        transcript: &mut impl Channel<F>
+    ) -> [u8; 32]
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion,
+        L: LayerAccess<F> + ?Sized {
+
+    transcript.label("opened_evaluation", channel::ELEMENT_BE);
+    for eval in trace_evals {
+        transcript.append_bytes(&fe_to_bytes_be(&eval));
+    }
+    for i in 0..layers.layer_count() {
+        let layer = layers.layer(i);
+        for data in &layer.validation_data {
+            transcript.append_bytes(&fe_to_bytes_be(&data.eval));
+            transcript.append_bytes(&fe_to_bytes_be(&data.sym_eval));
+        }
+        for InclusionProof(eval, _) in &layer.landing_data {
+            transcript.append_bytes(&fe_to_bytes_be(eval));
+        }
+    }
+    transcript.label("proof_binding", channel::SAMPLED_BYTES);
+    transcript.sample_bytes()
+}
+
+// the transcript of a proof before its statement is absorbed, made
+// of the protocol label and, within a session, the session seed and
+// the index of the proof
+pub(crate) fn new_transcript<F>(options: &ProofOptions) -> DefaultTranscript<F>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + ByteConversion {
+
+    let mut transcript = DefaultTranscript::<F>::new(&options.transcript_label);
+    if let Some(Session { seed, index }) = &options.session {
+        transcript.append_bytes(seed);
+        transcript.append_bytes(&(*index as u64).to_be_bytes());
+    }
+    transcript
+}
+
+// why queries cannot be sampled from a domain or opened in it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum QueryError {
+	// no query can be sampled from a domain without points
+	EmptyDomain,
+	// a sample of the transcript that is not a 256 bit integer
+	Sample,
+	// a position outside of the domain of the committed evaluations
+	Position { position: usize, domain_size: usize },
+}
+
+impl fmt::Display for QueryError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			QueryError::EmptyDomain => write!(f, "no query in a domain without points"),
+			QueryError::Sample => write!(f, "transcript sample is not a 256 bit integer"),
+			QueryError::Position { position, domain_size } => write!(f, "position {} outside of a domain of {} points", position, domain_size),
+		}
+	}
+}
+
+impl std::error::Error for QueryError {}
+
 pub fn sample_queries<F>(
         num_queries: usize,
         domain_size: usize,
-        transcript: &mut DefaultTranscript<F>
-    ) -> Vec<usize> 
+        transcript: &mut impl Channel<F>
+    ) -> Result<Vec<usize>, QueryError>
+    where 
+        F: IsField,
+        FieldElement<F>: AsBytes + ByteConversion {
+
+    // sorted, so that the openings of a proof follow the domain order
+    // whatever the order the queries were sampled in
+    let mut query_indices = sampled_queries(num_queries, domain_size, transcript)?;
+    query_indices.sort_unstable();
+    Ok(query_indices)
+}
+
+// the query indices in the order they are sampled, the order of the
+// openings of the proofs of version 1, see legacy
+pub fn sampled_queries<F>(
+        num_queries: usize,
+        domain_size: usize,
+        transcript: &mut impl Channel<F>
+    ) -> Result<Vec<usize>, QueryError>
     where 
         F: IsField,
         FieldElement<F>: AsBytes + ByteConversion {
 
-        (0..num_queries)
+    if domain_size == 0 {
+        return Err(QueryError::EmptyDomain)
+    }
+    (0..num_queries)
         .map(|_| {
-            let query_index = U256::from_bytes_be(&transcript.sample()).unwrap();
+            let query_index = U256::from_bytes_be(&transcript.sample_bytes()).map_err(|_| QueryError::Sample)?;
             let(_, query_index) = query_index.div_rem(&U256::from(domain_size as u64));
-            query_index.limbs[3] as usize
+            Ok(query_index.limbs[3] as usize)
         })
-        .collect::<Vec<usize>>()
-}
\ No newline at end of file
+        .collect::<Result<Vec<usize>, QueryError>>()
+}
+
+// position of the point g^k * x in an lde of blowup_factor points per
+// row of the trace, x being the point at the given position. as g is
+// w^blowup_factor, shifting the trace by k rows rotates its lde by
+// k * blowup_factor positions, and no shifted polynomial is evaluated
+pub fn shifted_position(position: usize, k: usize, blowup_factor: usize, domain_size: usize) -> usize {
+    (position + k * blowup_factor) % domain_size
+}
+
+// positions of the trace lde opened for the queries: every position
+// of the leaves holding the rows of the frame of every query at the
+// given offsets, leaf after leaf, sorted and without repetitions, so
+// that a leaf shared by several queries is opened once. with leaves
+// of one evaluation, the sorted positions of the rows
+pub fn trace_positions(
+        query_indices: &[usize],
+        offsets: &[usize],
+        layout: &LeafLayout,
+        domain_size: usize
+    ) -> Vec<usize> {
+
+    let blowup_factor = layout.blowup_factor;
+    let mut leaves = query_indices
+        .iter()
+        .flat_map(|i| offsets.iter().map(move |k| layout.leaf_of(shifted_position(*i, *k, blowup_factor, domain_size)).0))
+        .collect::<Vec<usize>>();
+    leaves.sort_unstable();
+    leaves.dedup();
+    leaves
+        .into_iter()
+        .flat_map(|leaf| layout.positions(leaf))
+        .collect()
+}
+
+// fewest items worth a thread of their own in parallel_map. extracting
+// a merkle path takes under a microsecond, less than spawning a thread
+const ITEMS_PER_THREAD: usize = 1024;
+
+// maps the items in order, splitting them among the available cores
+// when there are enough of them, unless running single threaded
+pub(crate) fn parallel_map<T, R>(items: &[T], f: impl Fn(&T) -> R + Sync) -> Vec<R>
+    where
+        T: Sync,
+        R: Send {
+
+    let threads = thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(items.len() / ITEMS_PER_THREAD);
+    if threads <= 1 || threads::is_single_threaded() {
+        return items.iter().map(f).collect::<Vec<R>>()
+    }
+
+    let f = &f;
+    let mut results = Vec::with_capacity(items.len());
+    thread::scope(|scope| {
+        let handles = items
+            .chunks(items.len().div_ceil(threads))
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect::<Vec<thread::ScopedJoinHandle<'_, Vec<R>>>>();
+        for handle in handles {
+            results.extend(handle.join().unwrap());
+        }
+    });
+    results
+}
+
+pub fn bytes_to_hex(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>()
+}
+
+// why bytes are not the canonical encoding of a field element
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ElementBytesError {
+	// not the size of an element, in bytes or hex digits
+	Length { expected: usize, found: usize },
+	// an integer not below the modulus
+	NonCanonical,
+	// a character that is not a hex digit
+	Hex,
+}
+
+impl fmt::Display for ElementBytesError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			ElementBytesError::Length { expected, found } => write!(f, "{} bytes for an element of {}", found, expected),
+			ElementBytesError::NonCanonical => write!(f, "not below the modulus"),
+			ElementBytesError::Hex => write!(f, "not a hex string"),
+		}
+	}
+}
+
+impl std::error::Error for ElementBytesError {}
+
+// every encoder, the transcript and the proof formats represent a field
+// element by the big endian bytes of its representative below the
+// modulus. the decoders reject any other integer, which would be the
+// same element with a second encoding
+pub fn fe_to_bytes_be<F: IsField>(element: &FieldElement<F>) -> Vec<u8>
+    where
+        FieldElement<F>: ByteConversion {
+
+    element.to_bytes_be()
+}
+
+pub fn fe_to_bytes_le<F: IsField>(element: &FieldElement<F>) -> Vec<u8>
+    where
+        FieldElement<F>: ByteConversion {
+
+    element.to_bytes_le()
+}
+
+pub fn fe_from_bytes_be<F: IsField>(bytes: &[u8]) -> Result<FieldElement<F>, ElementBytesError>
+    where
+        FieldElement<F>: ByteConversion {
+
+    // big endian encodings of the same length compare as the integers
+    // they encode
+    let largest = (-FieldElement::<F>::one()).to_bytes_be();
+    if bytes.len() != largest.len() {
+        return Err(ElementBytesError::Length { expected: largest.len(), found: bytes.len() })
+    }
+    if bytes > &largest[..] {
+        return Err(ElementBytesError::NonCanonical)
+    }
+    Ok(FieldElement::<F>::from_bytes_be(bytes).unwrap())
+}
+
+pub fn fe_from_bytes_le<F: IsField>(bytes: &[u8]) -> Result<FieldElement<F>, ElementBytesError>
+    where
+        FieldElement<F>: ByteConversion {
+
+    fe_from_bytes_be(&bytes.iter().rev().copied().collect::<Vec<u8>>())
+}
+
+// a field element from at most as many hex digits as its bytes have,
+// with or without a 0x prefix, in either case
+pub fn fe_from_hex<F: IsField>(hex: &str) -> Result<FieldElement<F>, ElementBytesError>
+    where
+        FieldElement<F>: ByteConversion {
+
+    let digits = hex.strip_prefix("0x").unwrap_or(hex).as_bytes();
+    let size = FieldElement::<F>::zero().to_bytes_be().len();
+    if digits.is_empty() || digits.len() > 2 * size {
+        return Err(ElementBytesError::Length { expected: 2 * size, found: digits.len() })
+    }
+    let nibbles = digits
+        .iter()
+        .map(|digit| (*digit as char).to_digit(16).map(|nibble| nibble as u8).ok_or(ElementBytesError::Hex))
+        .collect::<Result<Vec<u8>, ElementBytesError>>()?;
+    // left padded to an even number of digits, then to the size
+    let mut bytes = vec![0_u8; size - nibbles.len().div_ceil(2)];
+    let odd = nibbles.len() % 2;
+    if odd == 1 {
+        bytes.push(nibbles[0]);
+    }
+    bytes.extend(nibbles[odd..].chunks(2).map(|pair| pair[0] << 4 | pair[1]));
+    fe_from_bytes_be(&bytes)
+}
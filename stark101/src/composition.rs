@@ -0,0 +1,60 @@
+// the composition of the constraint quotients at a single point, the
+// one formula both the prover, at every point of the lde domain, and
+// the verifier, at every query, derive from the constraints of the
+// air. they only differ in where the values come from: the prover
+// reads the rows of the frame from the extension of the trace and the
+// denominators from its key, the verifier reads the rows from the trace
+// openings and computes the denominators at the query
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+
+use crate::air::TransitionConstraint;
+use crate::ct;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the value at a point x of the trace t, of the constraints and of the
+// denominators of their quotients
+pub struct PointValues<R, T> {
+    // the trace k rows after x, t(g^k x), k a rotation of a frame row
+    pub row: R,
+    // the interpolant of the boundary constraints at x
+    pub boundary_interpolant: FE,
+    // the inverse of the boundary zerofier at x
+    pub boundary_zerofier_inv: FE,
+    // the exemptions of transition j over the zerofier of the trace
+    // domain at x, the factor turning the constraint into its quotient
+    pub transition_factor: T,
+}
+
+// the boundary quotient (t(x) - I(x)) / Z(x) times a plus the quotients
+// of the transitions times their coefficients b. the arithmetic is the
+// one of ct, as the prover computes it on witness values
+pub fn evaluate_at<R, T>(
+        transitions: &[TransitionConstraint<F>],
+        trace_length: usize,
+        values: PointValues<R, T>,
+        a: &FE,
+        b: &[FE]
+    ) -> FE
+    where
+        R: Fn(usize) -> FE,
+        T: Fn(usize) -> FE {
+
+    let PointValues { row, boundary_interpolant, boundary_zerofier_inv, transition_factor } = values;
+    // boundary constraints, enforced by a single quotient of the trace
+    // minus the boundary interpolant over the boundary zerofier
+    let boundary_quotient = ct::mul(&ct::sub(&row(0), &boundary_interpolant), &boundary_zerofier_inv);
+    // trace transition constraints over the frame starting at the point
+    transitions
+        .iter()
+        .zip(b)
+        .enumerate()
+        .fold(ct::mul(a, &boundary_quotient), |eval, (j, (transition, b))| {
+            let frame = transition.frame(trace_length, &row);
+            ct::add(&eval, &ct::mul(&ct::mul(b, &(transition.evaluate)(&frame)), &transition_factor(j)))
+        })
+}
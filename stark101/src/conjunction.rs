@@ -0,0 +1,262 @@
+// a single proof that two statements hold, possibly of different airs
+// and trace lengths. both traces are committed over the same lde
+// domain, so the statements must agree on the lde size and on the
+// number of queries. the transcript absorbs both statements in order,
+// then the shape of the proof, the composition degree bound being the
+// larger of the two, then both trace roots. the coefficients of the
+// first composition are sampled before the ones of the second, and the
+// sum of the two compositions is proven low degree by a single fri, at
+// the same queries as both trace openings. the openings of both traces
+// are absorbed before the binding is squeezed, see common::proof_binding
+use std::borrow::Cow;
+
+use lambdaworks_crypto::fiat_shamir::is_transcript::IsTranscript;
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement,
+    traits::IsField
+};
+
+use crate::air::{self, Air};
+use crate::channel::{self, Channel};
+use crate::common::{self, IsPublicInput, PublicInput, PublicInputError, ProofOptions, VectorCommitment};
+use crate::ct;
+use crate::domain;
+use crate::fri::{self, FriError, FriLayer, FriOptions};
+use crate::key::ProvingKey;
+use crate::prover;
+use crate::report::VerificationReport;
+use crate::storage::CommittedLde;
+use crate::verifier::{self, TraceOpenings, VerifierContext, VerifyError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[derive(Clone)]
+pub struct ConjunctionProof<F: IsField> {
+    // the trace of the first statement, then the one of the second
+    pub trace_commitments: [VectorCommitment<F>; 2],
+    pub composition_commitment: Vec<FriLayer<F>>,
+    pub binding: [u8; 32],
+}
+
+pub fn prove_conjunction<A: Air<F>, B: Air<F>>(
+        first: (&A, &[FE], PublicInput<F>),
+        second: (&B, &[FE], PublicInput<F>),
+        options: &ProofOptions
+    ) -> ConjunctionProof<F> {
+
+    let (air_1, trace_1, public_input_1) = first;
+    let (air_2, trace_2, public_input_2) = second;
+    if let Err(e) = shared_shape(&public_input_1, &public_input_2) {
+        panic!("invalid public input: {}", e);
+    }
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = public_input_1;
+    let eval_order: usize = 1 << eval_two_power;
+    for air_length in [air_1.trace_length(), air_2.trace_length()] {
+        if let Err(e) = options.check_leaf_width(air_length) {
+            panic!("invalid options: {}", e);
+        }
+    }
+
+    let mut transcript = common::new_transcript(options);
+    prover::absorb_air(air_1, &public_input_1, options, &mut transcript);
+    prover::absorb_air(air_2, &public_input_2, options, &mut transcript);
+    let degree_bound = air_1.composition_degree_bound().max(air_2.composition_degree_bound());
+    prover::absorb_shape(degree_bound, &public_input_1, options, &mut transcript);
+
+    // both keys have the same lde domain, only their trace domains differ
+    let key_1 = ProvingKey::new(air_1, eval_two_power);
+    let key_2 = ProvingKey::new(air_2, eval_two_power);
+    let lde_1 = commit_trace(&key_1, air_1, trace_1, options);
+    let lde_2 = commit_trace(&key_2, air_2, trace_2, options);
+    for lde in [&lde_1, &lde_2] {
+        transcript.label("trace_root", channel::DIGEST);
+        transcript.append_bytes(&lde.root());
+    }
+
+    let (a_1, b_1) = sample_coefficients(air_1, &mut transcript);
+    let (a_2, b_2) = sample_coefficients(air_2, &mut transcript);
+    let comp_evals = prover::composition_evaluations(&key_1, air_1, |i| lde_1.get(i), &a_1, &b_1)
+        .into_iter()
+        .zip(prover::composition_evaluations(&key_2, air_2, |i| lde_2.get(i), &a_2, &b_2))
+        .map(|(c_1, c_2)| c_1 + c_2)
+        .collect::<Vec<FE>>();
+
+    transcript.label("query_index", channel::SAMPLED_INDEX);
+    let query_indices = common::sample_queries(num_queries, eval_order, &mut transcript).expect("the lde domain has points");
+    let trace_commitments = [
+        open_trace(&lde_1, &air_1.frame_offsets(), air_1.trace_length(), eval_order, &query_indices, options),
+        open_trace(&lde_2, &air_2.frame_offsets(), air_2.trace_length(), eval_order, &query_indices, options),
+    ];
+
+    let (composition_commitment, _) = fri::commit_and_fold_resumable(
+        comp_evals,
+        &key_1.lde_domain.offset,
+        degree_bound,
+        query_indices,
+        &FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend },
+        &mut transcript,
+        None,
+        true
+    );
+    let binding = common::proof_binding(opened_evaluations(&trace_commitments), composition_commitment.as_slice(), &mut transcript);
+
+    ConjunctionProof { trace_commitments, composition_commitment, binding }
+}
+
+pub fn verify_conjunction<A: Air<F>, B: Air<F>>(
+        first: (&A, PublicInput<F>),
+        second: (&B, PublicInput<F>),
+        proof: &ConjunctionProof<F>,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError> {
+
+    let (air_1, public_input_1) = first;
+    let (air_2, public_input_2) = second;
+    let mut report = VerificationReport::new();
+    for public_input in [&public_input_1, &public_input_2] {
+        public_input.validate().map_err(VerifyError::PublicInput)?;
+    }
+    shared_shape(&public_input_1, &public_input_2).map_err(VerifyError::PublicInput)?;
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = public_input_1;
+    let eval_order: usize = 1 << eval_two_power;
+    assert_eq!(air_1.trace_length(), 1 << public_input_1.1);
+    assert_eq!(air_2.trace_length(), 1 << public_input_2.1);
+    for air_length in [air_1.trace_length(), air_2.trace_length()] {
+        options.check_leaf_width(air_length).map_err(VerifyError::PublicInput)?;
+    }
+
+    let context_1 = VerifierContext::new(air_1, &public_input_1, options);
+    let context_2 = VerifierContext::new(air_2, &public_input_2, options);
+    let max_openings = context_1.limits.max_openings;
+    if num_queries > max_openings {
+        return Err(VerifyError::PublicInput(PublicInputError::TooManyQueries { num_queries, max: max_openings }))
+    }
+
+    // the same operations as the prover, in the same order
+    let mut transcript = common::new_transcript(options);
+    for (context, parameters, public_input) in [
+        (&context_1, air_1.parameters(), &public_input_1),
+        (&context_2, air_2.parameters(), &public_input_2),
+    ] {
+        transcript.append_bytes(&context.key_digest);
+        public_input.absorb_into(&mut transcript);
+        for parameter in parameters {
+            transcript.append_bytes(&common::fe_to_bytes_be(&parameter));
+        }
+    }
+    transcript.append_bytes(&[options.query_sampling as u8]);
+    let degree_bound = air_1.composition_degree_bound().max(air_2.composition_degree_bound());
+    let (schedule, _) = fri::committed_schedule(degree_bound, eval_order, options.fri_max_layers).map_err(VerifyError::Fri)?;
+    transcript.append_bytes(&degree_bound.to_be_bytes());
+    transcript.append_bytes(&schedule.len().to_be_bytes());
+    transcript.append_bytes(&num_queries.to_be_bytes());
+    for commitment in &proof.trace_commitments {
+        transcript.append_bytes(&commitment.root);
+    }
+
+    let (a_1, b_1) = sample_coefficients(air_1, &mut transcript);
+    let (a_2, b_2) = sample_coefficients(air_2, &mut transcript);
+    let query_indices = common::sample_queries(num_queries, eval_order, &mut transcript).expect("the lde domain has points");
+
+    // the lde domain, and so the points of the queries, are the same
+    // for both statements
+    let key = &context_1.key;
+    let queries = domain::coset_points(&key.offset, &key.w, eval_order, &query_indices);
+    let [commitment_1, commitment_2] = &proof.trace_commitments;
+    let trace_1 = TraceOpenings::new(
+        &context_1, &public_input_1, commitment_1.root, commitment_1.inclusion_proofs.len(),
+        |i| Cow::Borrowed(&commitment_1.inclusion_proofs[i]), &query_indices
+    );
+    let trace_2 = TraceOpenings::new(
+        &context_2, &public_input_2, commitment_2.root, commitment_2.inclusion_proofs.len(),
+        |i| Cow::Borrowed(&commitment_2.inclusion_proofs[i]), &query_indices
+    );
+    trace_1.check(&queries, &mut report)?;
+    trace_2.check(&queries, &mut report)?;
+
+    let comp_poly_query_evals = trace_1.compositions(air_1, &queries, &a_1, &b_1)
+        .into_iter()
+        .zip(trace_2.compositions(air_2, &queries, &a_2, &b_2))
+        .map(|(c_1, c_2)| Some(c_1? + c_2?))
+        .collect::<Vec<Option<FE>>>();
+    let first_layer = proof.composition_commitment
+        .first()
+        .ok_or(VerifyError::Fri(FriError::LayerCount { expected: schedule.len(), received: 0 }))?;
+    verifier::check_composition_openings(first_layer, &comp_poly_query_evals, &mut report)?;
+
+    fri::decommit_and_fold_with_mismatch(
+        proof.composition_commitment.as_slice(),
+        &eval_order,
+        &key.offset,
+        degree_bound,
+        &query_indices,
+        &FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend },
+        &mut transcript,
+        &mut report.mismatch
+    ).map_err(VerifyError::Fri)?;
+
+    let binding = common::proof_binding(opened_evaluations(&proof.trace_commitments), proof.composition_commitment.as_slice(), &mut transcript);
+    verifier::check_binding(Some(proof.binding), binding, false, &mut report)
+}
+
+// the statements are proven over the same lde domain, at the same queries
+fn shared_shape(first: &PublicInput<F>, second: &PublicInput<F>) -> Result<(), PublicInputError> {
+    let PublicInput(_, _, eval_two_power_1, num_queries_1, ..) = *first;
+    let PublicInput(_, _, eval_two_power_2, num_queries_2, ..) = *second;
+    match eval_two_power_1 == eval_two_power_2 && num_queries_1 == num_queries_2 {
+        true => Ok(()),
+        false => Err(PublicInputError::Conjunction),
+    }
+}
+
+fn commit_trace<A: Air<F>>(key: &ProvingKey, air: &A, trace: &[FE], options: &ProofOptions) -> CommittedLde<F> {
+    assert_eq!(trace.len(), air.trace_length());
+    if cfg!(debug_assertions) {
+        if let Err(e) = air::check_trace(trace, &air.boundary_constraints(), &air.transition_constraints()) {
+            panic!("inconsistent trace: {}", e);
+        }
+    }
+    let poly = ct::interpolate(&key.trace_domain, trace);
+    let layout = options.trace_layout(key.lde_domain.size / air.trace_length());
+    CommittedLde::commit(&poly, air.trace_length(), &key.lde_domain, &options.trace_storage, options.merkle_backend, &layout, ct::evaluate)
+}
+
+// the rows of the frames of the queries, at their positions in the lde
+fn open_trace(
+        lde: &CommittedLde<F>,
+        frame_offsets: &[usize],
+        trace_length: usize,
+        eval_order: usize,
+        query_indices: &[usize],
+        options: &ProofOptions
+    ) -> VectorCommitment<F> {
+
+    let layout = options.trace_layout(eval_order / trace_length);
+    let positions = common::trace_positions(query_indices, frame_offsets, &layout, eval_order);
+    VectorCommitment::<F> {
+        root: lde.root(),
+        inclusion_proofs: common::parallel_map(&positions, |i| lde.open(*i, &layout)),
+    }
+}
+
+// the evaluations opened in the first trace, then in the second
+fn opened_evaluations(trace_commitments: &[VectorCommitment<F>; 2]) -> impl Iterator<Item = FE> + '_ {
+    trace_commitments
+        .iter()
+        .flat_map(|commitment| commitment.inclusion_proofs.iter().map(|opening| opening.0))
+}
+
+// one coefficient for the boundary constraints of the air and one for
+// each of its transition constraints
+fn sample_coefficients<A: Air<F>>(air: &A, transcript: &mut impl Channel<F>) -> (FE, Vec<FE>) {
+    transcript.label("boundary_coefficient", channel::SAMPLED_ELEMENT);
+    let a = transcript.sample_field_element();
+    transcript.label("transition_coefficient", channel::SAMPLED_ELEMENT);
+    let b = air.transition_constraints()
+        .iter()
+        .map(|_| transcript.sample_field_element())
+        .collect::<Vec<FE>>();
+    (a, b)
+}
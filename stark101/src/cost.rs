@@ -0,0 +1,348 @@
+// the work of the verifier and the size of the proof for a statement,
+// computed from its shape alone, without proving it, e.g. to budget
+// the gas of an on-chain verifier before settling on the parameters.
+// the counts are upper bounds: a proof opens a row shared by several
+// queries once, and the queries sampled twice are opened twice anyway
+use std::fmt;
+
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+use crate::air::Air;
+use crate::common::{ProofOptions, PublicInput};
+use crate::fri::{self, QuerySampling};
+
+type F = Stark252PrimeField;
+
+// bytes of a field element, a merkle node and a length in the
+// encoding of a proof, see encoding
+const ELEMENT_SIZE: usize = 32;
+const NODE_SIZE: usize = 32;
+const LEN_SIZE: usize = 4;
+// magic, version and binding of an encoded proof
+const HEADER_SIZE: usize = 5 + 32;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerifierCost {
+    // size of the encoded proof
+    pub proof_bytes: usize,
+    pub trace_openings: usize,
+    pub fri_layers: usize,
+    // openings of the fri layers, each query opening a point and its
+    // symmetric one, plus the landings of per layer sampling
+    pub fri_openings: usize,
+    // hash invocations checking the merkle paths, one for the
+    // leaf and one per node of the path
+    pub merkle_hashes: usize,
+    // challenges and query indices sampled from the transcript, each
+    // a keccak256 invocation over the absorbed bytes
+    pub transcript_samples: usize,
+    // field multiplications and inversions of the verifier, besides
+    // the ones of evaluating the transition constraints themselves,
+    // which only the air knows
+    pub field_multiplications: usize,
+    pub field_inversions: usize,
+}
+
+// the cost of verifying a proof of the statement with the options
+pub fn estimate_verifier_cost<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions
+    ) -> VerifierCost {
+
+    let PublicInput(_, interp_two_power, eval_two_power, num_queries, ..) = *public_input;
+    let eval_order = 1_usize << eval_two_power;
+    let (schedule, remainder_bound) = fri::committed_schedule(air.composition_degree_bound(), eval_order, options.fri_max_layers)
+        .expect("the fri parameters of the statement are valid");
+    let num_layers = schedule.len();
+    let per_layer = options.query_sampling == QuerySampling::PerLayer;
+    // an opening of the element and the path of a tree of the height
+    let opening_bytes = |height: usize| ELEMENT_SIZE + LEN_SIZE + height * NODE_SIZE;
+
+    let mut cost = VerifierCost {
+        proof_bytes: HEADER_SIZE + NODE_SIZE + LEN_SIZE + LEN_SIZE,
+        fri_layers: num_layers,
+        ..VerifierCost::default()
+    };
+
+    // trace openings, every query opening the leaves of the rows of its
+    // frame, each with every evaluation of the leaf under a single path
+    let width = options.trace_leaf_width;
+    let trace_height = eval_two_power - width.trailing_zeros() as usize;
+    let trace_leaves = (num_queries * air.frame_offsets().len()).min(eval_order / width);
+    cost.trace_openings = trace_leaves * width;
+    cost.proof_bytes += trace_leaves * (opening_bytes(trace_height) + (width - 1) * opening_bytes(0));
+    cost.merkle_hashes += trace_leaves * (trace_height + 1);
+
+    // composition at each query: the query point from the squarings of
+    // the generator, the boundary quotient and one quotient per
+    // transition, with the power of the point to n/p and a term for
+    // each row of its zerofier and each exemption
+    let boundary_points = air.boundary_constraints().constraints.len();
+    let transitions = air.transition_constraints();
+    let per_query = eval_two_power
+        + 2 * boundary_points + 1
+        + transitions
+            .iter()
+            .map(|t| {
+                let rows = t.rows.len().min(t.period - t.rows.len());
+                interp_two_power.saturating_sub(t.period.trailing_zeros() as usize) + rows + t.exemptions.len() + 2
+            })
+            .sum::<usize>();
+    cost.field_multiplications += eval_two_power + num_queries * per_query;
+    // the point of every opened row, checked against its query
+    cost.field_multiplications += num_queries * air.frame_offsets().len() * (eval_two_power + 1);
+    // the denominators of every query, of the boundary and of each
+    // transition, inverted all at once
+    cost.field_multiplications += num_queries * (1 + transitions.len()) * 3;
+    cost.field_inversions += 1;
+
+    // fri layers, the layer i over a domain of 2^(eval_two_power - i)
+    // points. with per layer sampling the last layer is only landed on
+    for layer in 0..num_layers {
+        let height = eval_two_power - layer;
+        let queries = if per_layer && layer + 1 == num_layers && layer > 0 { 0 } else { num_queries };
+        let landings = if per_layer && layer > 0 { num_queries } else { 0 };
+        let openings = 2 * queries + landings;
+        cost.fri_openings += openings;
+        cost.proof_bytes += NODE_SIZE + 2 * LEN_SIZE + openings * opening_bytes(height);
+        cost.merkle_hashes += openings * (height + 1);
+
+        // the inverses of the query points, recomputed on every layer
+        // sampling its own
+        if layer == 0 || per_layer {
+            cost.field_multiplications += height + queries * (height + 3);
+            cost.field_inversions += 1;
+        }
+        // folding into the next layer
+        if layer + 1 < num_layers {
+            cost.field_multiplications += queries * 4;
+        }
+    }
+
+    // the remainder sent after the layers, evaluated at the point of
+    // every value of the last layer, one multiplication per coefficient
+    if let Some(remainder_bound) = remainder_bound {
+        let height = eval_two_power - (num_layers - 1);
+        cost.proof_bytes += LEN_SIZE + remainder_bound * ELEMENT_SIZE;
+        cost.field_multiplications += height + num_queries * (height + remainder_bound);
+    }
+
+    // constraint coefficients, queries, fri betas and the binding
+    cost.transcript_samples = 1 + transitions.len() + num_queries + (num_layers - 1) + 1;
+    if per_layer {
+        cost.transcript_samples += num_queries * num_layers.saturating_sub(2);
+    }
+
+    cost
+}
+
+// the share of a fri layer in the encoded proof
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LayerSize {
+    // height of the merkle tree of the layer
+    pub height: usize,
+    // the layer is of degree less than this bound
+    pub degree_bound: usize,
+    pub openings: usize,
+    pub bytes: usize,
+}
+
+// another setting of one option of the proof, the others unchanged.
+// only the query sampling is an option of this crate, the others are
+// layouts the encoding does not implement, sized for comparison
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alternative {
+    QuerySampling(QuerySampling),
+    // the point and its symmetric one a query opens on a layer stored
+    // in one leaf, under a single path
+    SiblingLeaves,
+    // folding by 2^k points at once, the points folded together
+    // stored in one leaf
+    FoldingFactor(usize),
+    // the top k levels of every tree sent once, as a cap of 2^k nodes
+    // in place of the root, the paths stopping under it
+    MerkleCap(usize),
+}
+
+impl fmt::Display for Alternative {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Alternative::QuerySampling(QuerySampling::Folded) => write!(f, "folded queries"),
+            Alternative::QuerySampling(QuerySampling::PerLayer) => write!(f, "queries per layer"),
+            Alternative::SiblingLeaves => write!(f, "sibling points in one leaf"),
+            Alternative::FoldingFactor(k) => write!(f, "folding factor {}", 1 << k),
+            Alternative::MerkleCap(k) => write!(f, "merkle cap of {} nodes", 1 << k),
+        }
+    }
+}
+
+// the size of the encoded proof by part, and the size it would have
+// with each alternative
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SizeReport {
+    // magic, version, trace root and lengths
+    pub header_bytes: usize,
+    pub trace_bytes: usize,
+    pub layers: Vec<LayerSize>,
+    pub alternatives: Vec<(Alternative, usize)>,
+}
+
+impl SizeReport {
+    pub fn proof_bytes(&self) -> usize {
+        self.header_bytes + self.trace_bytes + self.layers.iter().map(|layer| layer.bytes).sum::<usize>()
+    }
+}
+
+impl fmt::Display for SizeReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proof_bytes = self.proof_bytes();
+        writeln!(f, "{:<34} {:>10} bytes", "Header", self.header_bytes)?;
+        writeln!(f, "{:<34} {:>10} bytes", "Trace openings", self.trace_bytes)?;
+        for (i, layer) in self.layers.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<34} {:>10} bytes  {} openings of height {}, degree below {}",
+                format!("FRI layer {}", i),
+                layer.bytes,
+                layer.openings,
+                layer.height,
+                layer.degree_bound
+            )?;
+        }
+        write!(f, "{:<34} {:>10} bytes", "Total", proof_bytes)?;
+        for (alternative, bytes) in &self.alternatives {
+            let change = 100.0 * (*bytes as f64 - proof_bytes as f64) / proof_bytes as f64;
+            write!(f, "\n{:<34} {:>10} bytes  {:+.1}%", format!("With {}", alternative), bytes, change)?;
+        }
+        Ok(())
+    }
+}
+
+// a layout of the openings of a proof
+#[derive(Clone, Copy)]
+struct Layout {
+    query_sampling: QuerySampling,
+    // log2 of the points folded together
+    folding: usize,
+    // the points a query opens on a layer in one leaf
+    sibling_leaves: bool,
+    // log2 of the nodes of the merkle caps, 0 for the root alone
+    cap: usize,
+    // log2 of the trace rows in a leaf, see ProofOptions
+    trace_width: usize,
+}
+
+// the size of the encoded proof of the statement by part, and how the
+// query sampling, the layout of the fri leaves, the folding factor and
+// merkle caps would change it, computed from the shape of the statement
+// alone. the sizes are upper bounds, as for estimate_verifier_cost
+pub fn proof_size_report<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions
+    ) -> SizeReport {
+
+    let current = Layout {
+        query_sampling: options.query_sampling,
+        folding: 1,
+        sibling_leaves: false,
+        cap: 0,
+        trace_width: options.trace_leaf_width.trailing_zeros() as usize,
+    };
+    let (trace_bytes, layers) = layout_size(air, public_input, current);
+
+    let other_sampling = match options.query_sampling {
+        QuerySampling::Folded => QuerySampling::PerLayer,
+        QuerySampling::PerLayer => QuerySampling::Folded,
+    };
+    let alternatives = [
+            Alternative::QuerySampling(other_sampling),
+            Alternative::SiblingLeaves,
+            Alternative::FoldingFactor(2),
+            Alternative::FoldingFactor(3),
+            Alternative::MerkleCap(2),
+            Alternative::MerkleCap(4),
+        ]
+        .into_iter()
+        .map(|alternative| {
+            let layout = match alternative {
+                Alternative::QuerySampling(query_sampling) => Layout { query_sampling, ..current },
+                Alternative::SiblingLeaves => Layout { sibling_leaves: true, ..current },
+                Alternative::FoldingFactor(folding) => Layout { folding, sibling_leaves: true, ..current },
+                Alternative::MerkleCap(cap) => Layout { cap, ..current },
+            };
+            let (trace_bytes, layers) = layout_size(air, public_input, layout);
+            let bytes = HEADER_BYTES + trace_bytes + layers.iter().map(|layer| layer.bytes).sum::<usize>();
+            (alternative, bytes)
+        })
+        .collect::<Vec<(Alternative, usize)>>();
+
+    SizeReport { header_bytes: HEADER_BYTES, trace_bytes, layers, alternatives }
+}
+
+// magic and version, trace root, and the numbers of trace openings
+// and of fri layers
+const HEADER_BYTES: usize = HEADER_SIZE + NODE_SIZE + LEN_SIZE + LEN_SIZE;
+
+// the bytes of the trace openings and of each fri layer in the layout
+fn layout_size<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        layout: Layout
+    ) -> (usize, Vec<LayerSize>) {
+
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = *public_input;
+    let eval_order = 1_usize << eval_two_power;
+    let per_layer = layout.query_sampling == QuerySampling::PerLayer;
+    // an opening of 2^width elements and its path in a tree of the
+    // height, whose cap, sent in place of the root, has 2^cap(height)
+    // nodes
+    let cap = |height: usize| layout.cap.min(height);
+    let opening_bytes = |height: usize, width: usize| {
+        (ELEMENT_SIZE << width) + LEN_SIZE + (height - width - cap(height - width)) * NODE_SIZE
+    };
+
+    // the trace root is in the header, the rest of its cap is not. the
+    // rows of a trace leaf are opened one by one, the first with the path
+    let trace_width = layout.trace_width;
+    let trace_leaves = (num_queries * air.frame_offsets().len()).min(eval_order >> trace_width);
+    let trace_bytes = trace_leaves * (opening_bytes(eval_two_power, trace_width) + ((1 << trace_width) - 1) * LEN_SIZE)
+        + ((1 << cap(eval_two_power - trace_width)) - 1) * NODE_SIZE;
+
+    // the foldings by 2 of the composition, grouped by the folding
+    // factor, the last group folding what is left
+    let degree_bound = air.composition_degree_bound();
+    let foldings = fri::number_of_foldings(degree_bound, eval_order).expect("the fri parameters of the statement are valid");
+    let mut folds = (0..foldings)
+        .step_by(layout.folding)
+        .map(|i| layout.folding.min(foldings - i))
+        .collect::<Vec<usize>>();
+    // the last layer is only opened at a point and its symmetric one
+    folds.push(1);
+
+    let mut height = eval_two_power;
+    let mut layers = Vec::<LayerSize>::with_capacity(folds.len());
+    for (layer, &fold) in folds.iter().enumerate() {
+        let last = layer + 1 == folds.len();
+        let queries = if per_layer && last && layer > 0 { 0 } else { num_queries };
+        let landings = if per_layer && layer > 0 { num_queries } else { 0 };
+        // the points of a query, and its landing, share a leaf, or each
+        // have one
+        let (openings, width) = if layout.sibling_leaves {
+            (queries + landings, fold)
+        } else {
+            (2 * queries + landings, 0)
+        };
+        let root_bytes = (1 << cap(height - width)) * NODE_SIZE;
+        layers.push(LayerSize {
+            height,
+            degree_bound: degree_bound.div_ceil(1 << (eval_two_power - height)),
+            openings,
+            bytes: root_bytes + 2 * LEN_SIZE + openings * opening_bytes(height, width),
+        });
+        height -= fold;
+    }
+
+    (trace_bytes, layers)
+}
@@ -0,0 +1,240 @@
+// arithmetic on the values the prover derives from the witness. with
+// the constant-time feature it is implemented on the montgomery limbs
+// of the stark252 field without branches or memory accesses depending
+// on the values, for provers whose threat model includes timing side
+// channels, and otherwise it is the one of lambdaworks, whose additions
+// and reductions branch on the values. the proofs are the same
+//
+// the prover routes through it the interpolation and extension of the
+// trace, the composition of the constraint quotients and the trace
+// builders of the statements with private inputs. the constraints of
+// an air are closures over field elements and keep the arithmetic of
+// lambdaworks, as do the fri foldings and the extension of the trace
+// stored on disk
+use lambdaworks_math::fft::cpu::bit_reversing;
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use crate::domain::EvaluationDomain;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+pub use imp::{add, sub, mul, square, select, from_u64};
+
+// the evaluations of the polynomial over the domain
+pub fn evaluate(domain: &EvaluationDomain<F>, poly: &Polynomial<FE>) -> Vec<FE> {
+    let coefficients = poly.coefficients();
+    assert!(coefficients.len() <= domain.size, "a polynomial of {} coefficients over {} points", coefficients.len(), domain.size);
+    let mut evals = Vec::<FE>::with_capacity(domain.size);
+    let mut power = FE::one();
+    for coefficient in coefficients {
+        evals.push(mul(coefficient, &power));
+        power *= domain.offset;
+    }
+    evals.resize(domain.size, FE::zero());
+    fft(&mut evals, &domain.twiddles()[..domain.size / 2]);
+    evals
+}
+
+// the polynomial of the evaluations over the domain
+pub fn interpolate(domain: &EvaluationDomain<F>, evals: &[FE]) -> Polynomial<FE> {
+    assert_eq!(evals.len(), domain.size);
+    let mut coefficients = evals.to_vec();
+    fft(&mut coefficients, domain.inverse_twiddles());
+    let mut factor = *domain.size_inv();
+    for coefficient in coefficients.iter_mut() {
+        *coefficient = mul(coefficient, &factor);
+        factor *= domain.offset_inv();
+    }
+    Polynomial::new(&coefficients)
+}
+
+// in place radix 2 fft with the bit reversed twiddles, in natural
+// order. the positions are those of the domain, not of the values
+fn fft(values: &mut [FE], twiddles: &[FE]) {
+    let mut group_count = 1;
+    let mut group_size = values.len();
+    while group_count < values.len() {
+        for (group, w) in twiddles.iter().enumerate().take(group_count) {
+            let first = group * group_size;
+            for i in first..first + group_size / 2 {
+                let wi = mul(w, &values[i + group_size / 2]);
+                let (y0, y1) = (add(&values[i], &wi), sub(&values[i], &wi));
+                values[i] = y0;
+                values[i + group_size / 2] = y1;
+            }
+        }
+        group_count *= 2;
+        group_size /= 2;
+    }
+    bit_reversing::in_place_bit_reverse_permute(values);
+}
+
+#[cfg(not(feature = "constant-time"))]
+mod imp {
+    use super::FE;
+
+    pub fn add(a: &FE, b: &FE) -> FE {
+        a + b
+    }
+
+    pub fn sub(a: &FE, b: &FE) -> FE {
+        a - b
+    }
+
+    pub fn mul(a: &FE, b: &FE) -> FE {
+        a * b
+    }
+
+    pub fn square(a: &FE) -> FE {
+        a.square()
+    }
+
+    // a if choice is true, b otherwise
+    pub fn select(choice: bool, a: &FE, b: &FE) -> FE {
+        if choice { *a } else { *b }
+    }
+
+    pub fn from_u64(value: u64) -> FE {
+        FE::from(value)
+    }
+}
+
+// the limbs of an element in montgomery form, least significant first,
+// lambdaworks keeping them most significant first
+#[cfg(feature = "constant-time")]
+mod imp {
+    use lambdaworks_math::unsigned_integer::element::U256;
+
+    use super::{F as Stark252PrimeField, FE};
+
+    type Limbs = [u64; 4];
+
+    // p = 2^251 + 17 * 2^192 + 1
+    const MODULUS: Limbs = [1, 0, 0, 0x0800_0000_0000_0011];
+    // -1 / p mod 2^64, p being 1 mod 2^64
+    const MU: u64 = u64::MAX;
+    // 2^512 mod p
+    const R2: U256 = Stark252PrimeField::R2;
+
+    fn limbs(a: &FE) -> Limbs {
+        let limbs = a.value().limbs;
+        [limbs[3], limbs[2], limbs[1], limbs[0]]
+    }
+
+    fn element(limbs: Limbs) -> FE {
+        FE::from_raw(U256 { limbs: [limbs[3], limbs[2], limbs[1], limbs[0]] })
+    }
+
+    // a + b + carry, and the carry out
+    fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+        let sum = a as u128 + b as u128 + carry as u128;
+        (sum as u64, (sum >> 64) as u64)
+    }
+
+    // a - b - borrow, and the borrow out
+    fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+        let difference = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+        (difference as u64, (difference >> 127) as u64)
+    }
+
+    // a + b * c + carry, and the carry out
+    fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+        let sum = a as u128 + b as u128 * c as u128 + carry as u128;
+        (sum as u64, (sum >> 64) as u64)
+    }
+
+    // all ones if the bit is one, zero otherwise
+    fn mask(bit: u64) -> u64 {
+        0_u64.wrapping_sub(bit)
+    }
+
+    // a, or a - p when the subtraction does not borrow from the high limb
+    fn reduce(a: Limbs, high: u64) -> Limbs {
+        let mut reduced = [0_u64; 4];
+        let mut borrow = 0;
+        for i in 0..4 {
+            (reduced[i], borrow) = sbb(a[i], MODULUS[i], borrow);
+        }
+        let (_, borrow) = sbb(high, 0, borrow);
+        let keep = mask(borrow);
+        let mut result = [0_u64; 4];
+        for i in 0..4 {
+            result[i] = (a[i] & keep) | (reduced[i] & !keep);
+        }
+        result
+    }
+
+    pub fn add(a: &FE, b: &FE) -> FE {
+        let (a, b) = (limbs(a), limbs(b));
+        let mut sum = [0_u64; 4];
+        let mut carry = 0;
+        for i in 0..4 {
+            (sum[i], carry) = adc(a[i], b[i], carry);
+        }
+        element(reduce(sum, carry))
+    }
+
+    pub fn sub(a: &FE, b: &FE) -> FE {
+        let (a, b) = (limbs(a), limbs(b));
+        let mut difference = [0_u64; 4];
+        let mut borrow = 0;
+        for i in 0..4 {
+            (difference[i], borrow) = sbb(a[i], b[i], borrow);
+        }
+        // adds p back when the subtraction borrowed
+        let modulus = mask(borrow);
+        let mut carry = 0;
+        for i in 0..4 {
+            (difference[i], carry) = adc(difference[i], MODULUS[i] & modulus, carry);
+        }
+        element(difference)
+    }
+
+    // montgomery multiplication by coarsely integrated operand scanning,
+    // with the final subtraction of the modulus masked
+    pub fn mul(a: &FE, b: &FE) -> FE {
+        let (a, b) = (limbs(a), limbs(b));
+        let mut t = [0_u64; 6];
+        for bi in b {
+            let mut carry = 0;
+            for j in 0..4 {
+                (t[j], carry) = mac(t[j], a[j], bi, carry);
+            }
+            (t[4], t[5]) = adc(t[4], carry, 0);
+
+            let m = t[0].wrapping_mul(MU);
+            let (_, mut carry) = mac(t[0], m, MODULUS[0], 0);
+            for j in 1..4 {
+                (t[j - 1], carry) = mac(t[j], m, MODULUS[j], carry);
+            }
+            (t[3], carry) = adc(t[4], carry, 0);
+            t[4] = t[5] + carry;
+        }
+        element(reduce([t[0], t[1], t[2], t[3]], t[4]))
+    }
+
+    pub fn square(a: &FE) -> FE {
+        mul(a, a)
+    }
+
+    // the montgomery form of the value, value * 2^512 / 2^256
+    pub fn from_u64(value: u64) -> FE {
+        mul(&FE::from_raw(U256::from_u64(value)), &FE::from_raw(R2))
+    }
+
+    // a if choice is true, b otherwise
+    pub fn select(choice: bool, a: &FE, b: &FE) -> FE {
+        let (a, b) = (limbs(a), limbs(b));
+        let choice = mask(choice as u64);
+        let mut result = [0_u64; 4];
+        for i in 0..4 {
+            result[i] = (a[i] & choice) | (b[i] & !choice);
+        }
+        element(result)
+    }
+}
@@ -0,0 +1,142 @@
+use lambdaworks_math::fft::cpu::{bit_reversing, fft, ops, roots_of_unity};
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::{IsField, IsFFTField, RootsConfig}
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+// the coset offset * <w> of a power of two size, with the fft
+// twiddle factors precomputed so that evaluating and interpolating
+// over the same domain many times, possibly across proofs and
+// threads, does not recompute the roots of unity
+#[derive(Clone, Debug)]
+pub struct EvaluationDomain<F: IsFFTField> {
+    pub size: usize,
+    pub offset: FieldElement<F>,
+    twiddles: Vec<FieldElement<F>>,
+    inverse_twiddles: Vec<FieldElement<F>>,
+    offset_inv: FieldElement<F>,
+    size_inv: FieldElement<F>,
+}
+
+impl<F: IsFFTField> EvaluationDomain<F> {
+
+    pub fn new(size: usize, offset: &FieldElement<F>) -> Self {
+        assert!(size.is_power_of_two(), "domain size {} is not a power of two", size);
+        let order = size.trailing_zeros() as u64;
+        Self {
+            size,
+            offset: offset.clone(),
+            twiddles: roots_of_unity::get_twiddles::<F>(order, RootsConfig::BitReverse).unwrap(),
+            inverse_twiddles: roots_of_unity::get_twiddles::<F>(order, RootsConfig::BitReverseInversed).unwrap(),
+            offset_inv: offset.inv().unwrap(),
+            size_inv: FieldElement::<F>::from(size as u64).inv().unwrap(),
+        }
+    }
+
+    // evaluates the polynomial at offset * w^i for i in 0..size. as with
+    // the lambdaworks fft, a polynomial with more coefficients than the
+    // domain size is evaluated over a larger domain
+    pub fn evaluate(&self, poly: &Polynomial<FieldElement<F>>) -> Vec<FieldElement<F>> {
+        if poly.coeff_len() > self.size {
+            return Polynomial::evaluate_offset_fft::<F>(poly, 1, Some(self.size), &self.offset).unwrap()
+        }
+
+        let mut evals = Vec::with_capacity(self.size);
+        coset_fft(poly.coefficients(), &self.offset, self.size, &self.twiddles, &mut evals);
+        evals
+    }
+
+    // the bit reversed roots of unity of the domain, the inverse ones,
+    // and the inverses of the offset and of the size
+    pub(crate) fn twiddles(&self) -> &[FieldElement<F>] {
+        &self.twiddles
+    }
+
+    pub(crate) fn inverse_twiddles(&self) -> &[FieldElement<F>] {
+        &self.inverse_twiddles
+    }
+
+    pub(crate) fn offset_inv(&self) -> &FieldElement<F> {
+        &self.offset_inv
+    }
+
+    pub(crate) fn size_inv(&self) -> &FieldElement<F> {
+        &self.size_inv
+    }
+
+    // interpolates the evaluations at offset * w^i for i in 0..size
+    pub fn interpolate(&self, evals: &[FieldElement<F>]) -> Polynomial<FieldElement<F>> {
+        assert_eq!(evals.len(), self.size);
+        let coeffs = ops::fft(evals, &self.inverse_twiddles).unwrap();
+        Polynomial::new(&coeffs)
+            .scale_coeffs(&self.size_inv)
+            .scale(&self.offset_inv)
+    }
+}
+
+// evaluates the polynomial with the given coefficients over the coset
+// offset * w^i for i in 0..size into the buffer, reusing its allocation,
+// with the bit reversed twiddles of the size. the twiddles of a size are the first half of the ones of twice
+// the size, so a longer twiddle slice can be given
+pub(crate) fn coset_fft<F: IsFFTField>(
+        coefficients: &[FieldElement<F>],
+        offset: &FieldElement<F>,
+        size: usize,
+        twiddles: &[FieldElement<F>],
+        evals: &mut Vec<FieldElement<F>>
+    ) {
+
+    assert!(coefficients.len() <= size);
+    evals.clear();
+    evals.extend(
+        coefficients
+            .iter()
+            .scan(FieldElement::<F>::one(), |power, coefficient| {
+                let scaled = coefficient * &*power;
+                *power = &*power * offset;
+                Some(scaled)
+            })
+    );
+    evals.resize(size, FieldElement::<F>::zero());
+    fft::in_place_nr_2radix_fft(evals, &twiddles[..size / 2]);
+    bit_reversing::in_place_bit_reverse_permute(evals);
+}
+
+// the points offset * w^i of the coset of the given size at the given
+// indices, w generating its subgroup. w^i is the product of the powers
+// w^(2^b) of the bits of i, so that all the points share the log2(size)
+// squarings of w instead of each exponentiation doing its own
+pub fn coset_points<F: IsField>(
+        offset: &FieldElement<F>,
+        w: &FieldElement<F>,
+        size: usize,
+        indices: &[usize]
+    ) -> Vec<FieldElement<F>> {
+
+    let bit_powers = (0..size.trailing_zeros())
+        .scan(w.clone(), |power, _| {
+            let current = power.clone();
+            *power = power.square();
+            Some(current)
+        })
+        .collect::<Vec<FieldElement<F>>>();
+    indices
+        .iter()
+        .map(|i| {
+            bit_powers
+                .iter()
+                .enumerate()
+                .filter(|(b, _)| (i >> b) & 1 == 1)
+                .fold(offset.clone(), |point, (_, power)| point * power)
+        })
+        .collect::<Vec<FieldElement<F>>>()
+}
+
+// the inverses of non zero values with a single field inversion and
+// three multiplications per value, montgomery's batch inversion
+pub fn batch_inverses<F: IsField>(values: &[FieldElement<F>]) -> Vec<FieldElement<F>> {
+    let mut inverses = values.to_vec();
+    FieldElement::<F>::inplace_batch_inverse(&mut inverses).expect("no inverse of zero");
+    inverses
+}
@@ -0,0 +1,799 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::fmt;
+
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+use lambdaworks_math::unsigned_integer::element::U256;
+use lambdaworks_crypto::merkle_tree::proof::Proof;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use crate::common::{self, InclusionProof, ProofAccess, PublicInput, StarkProof, VectorCommitment};
+use crate::fri::{FriLayer, LayerAccess, ValidationData};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// binary proof format, all integers are big endian
+//
+//   magic "S101" | version u8 | binding [32]
+//   trace root [32] | u32 n | n * opening
+//   u32 m | m * (layer root [32] | u32 q | q * (opening | opening) | u32 l | l * opening)
+//   [u32 r | r * coefficient [32]]
+//
+// where an opening is a field element [32] followed by its merkle
+// path as u32 k | k * node [32]. the trace openings are sorted by
+// position, each row opened once, and the fri openings follow the
+// queries, which are sampled in sorted order. the coefficients of the
+// remainder of the last layer follow the layers only when there is
+// one, see fri::FriOptions::max_layers. proofs of version 2 are the
+// same without the binding, see common::proof_binding, and a proof
+// without one is still encoded in it. proofs of versions 1 and 2 are
+// read by legacy only
+const MAGIC: &[u8; 4] = b"S101";
+pub(crate) const VERSION: u8 = 3;
+pub(crate) const UNBOUND_VERSION: u8 = 2;
+const ELEMENT_SIZE: usize = 32;
+const NODE_SIZE: usize = 32;
+
+// public input format, all integers are big endian
+//
+//   modulus [32] | u32 interp_two_power | u32 eval_two_power
+//   u32 num_queries | public value [32] | public value [32]
+//   [witness commitment [32]]
+//
+// the witness commitment is only present for the statements that have one
+const PUBLIC_INPUT_SIZE: usize = 32 + 3 * 4 + 2 * ELEMENT_SIZE;
+
+// compact proof format, for calldata, all integers are big endian
+//
+//   magic "S10C" | version u8 | binding [32]
+//   trace root [32] | u32 n | n * opening
+//   u8 m | m * (layer root [32] | u32 q | q * (opening | opening) | u32 l | l * opening)
+//   [u32 r | r * coefficient [32]]
+//
+// where an opening is a field element [32] followed by its merkle
+// path as u8 k | k * node, a node being either 0x00 | node [32], or
+// 0x01 | u16 i for the i-th node sent in full in the same tree. the
+// openings of a tree close to each other share the upper nodes of
+// their paths, and every fri query shares the top sibling with the
+// others on its half of the layer, so most of them are sent once.
+// the encoder references a node whenever it can, but a decoder
+// accepts a node sent in full twice, this format is not canonical.
+// version 1 has no binding, like version 2 of the binary format
+const COMPACT_MAGIC: &[u8; 4] = b"S10C";
+const COMPACT_VERSION: u8 = 2;
+const UNBOUND_COMPACT_VERSION: u8 = 1;
+const NEW_NODE: u8 = 0;
+const SEEN_NODE: u8 = 1;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeError {
+    // the bytes do not start with the proof magic
+    Magic,
+    Version(u8),
+    // a length or element goes past the end of the bytes
+    UnexpectedEnd { offset: usize },
+    TrailingBytes { offset: usize },
+    // the composition commitment has no fri layer
+    NoLayers,
+    // in strict mode, a field element not below the modulus
+    NonCanonicalElement { offset: usize },
+    // in strict mode, a merkle path longer than the height of its tree
+    PathTooLong { offset: usize },
+    // in a compact proof, a merkle node that is neither new nor one
+    // already seen in its tree
+    NodeReference { offset: usize },
+    // in a proof of a previous version, openings that do not follow
+    // the queries sampled for it, see legacy
+    Layout { version: u8 },
+    // a size past the named limit of DecodeLimits, found before
+    // reading what it is the size of
+    Limit { limit: &'static str, offset: usize },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Magic => write!(f, "not an encoded proof"),
+            DecodeError::Version(v) => write!(f, "unsupported proof version {}", v),
+            DecodeError::UnexpectedEnd { offset } => write!(f, "proof truncated at byte {}", offset),
+            DecodeError::TrailingBytes { offset } => write!(f, "unexpected bytes after the proof at byte {}", offset),
+            DecodeError::NoLayers => write!(f, "proof has no fri layer"),
+            DecodeError::NonCanonicalElement { offset } => write!(f, "non canonical field element at byte {}", offset),
+            DecodeError::PathTooLong { offset } => write!(f, "merkle path longer than its tree at byte {}", offset),
+            DecodeError::NodeReference { offset } => write!(f, "invalid merkle node reference at byte {}", offset),
+            DecodeError::Layout { version } => write!(f, "openings do not follow the layout of proof version {}", version),
+            DecodeError::Limit { limit, offset } => write!(f, "{} exceeded at byte {}", limit, offset),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// how much of an encoded proof is checked when parsing it
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DecodeMode {
+    // only that every length fits in the bytes and nothing follows
+    // the proof, anything else is left to the verifier
+    #[default]
+    Lenient,
+    // additionally that every field element is canonical, i.e., below
+    // the modulus, and that no merkle path is longer than the tree it
+    // opens for an lde domain of 2^eval_two_power points, halving
+    // with each fri layer. this way a proof has a single encoding
+    Strict { eval_two_power: usize },
+}
+
+impl DecodeMode {
+
+    // the longest merkle path accepted in the given fri layer, the
+    // trace lde having the height of the first one
+    fn max_path_len(&self, layer: usize) -> usize {
+        match self {
+            DecodeMode::Lenient => usize::MAX,
+            DecodeMode::Strict { eval_two_power } => eval_two_power.saturating_sub(layer),
+        }
+    }
+}
+
+// upper bounds on the sizes of an untrusted proof, checked while parsing
+// it before anything it holds is hashed or decoded, so that a proof
+// crafted with huge lengths is rejected at the cost of reading them.
+// the defaults are far above the sizes of the proofs of this crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DecodeLimits {
+    // the length of the encoded proof
+    pub max_bytes: usize,
+    pub max_layers: usize,
+    // the number of openings of the trace or of one fri layer, i.e., of
+    // queries times the rows of a frame for the trace
+    pub max_openings: usize,
+    // the number of nodes of a merkle path
+    pub max_path_len: usize,
+    // the two powers of the trace and lde sizes of a public input
+    pub max_two_power: usize,
+}
+
+impl Default for DecodeLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 64 << 20,
+            max_layers: 64,
+            max_openings: 4096,
+            max_path_len: 64,
+            max_two_power: common::MAX_EVAL_TWO_POWER,
+        }
+    }
+}
+
+impl DecodeLimits {
+
+    fn check_size(&self, bytes: &[u8]) -> Result<(), DecodeError> {
+        match bytes.len() > self.max_bytes {
+            true => Err(DecodeError::Limit { limit: "max_bytes", offset: self.max_bytes }),
+            false => Ok(()),
+        }
+    }
+}
+
+pub fn encode_proof(proof: &StarkProof<F>) -> Vec<u8> {
+    let mut bytes = Vec::<u8>::new();
+    bytes.extend_from_slice(MAGIC);
+    push_binding(&mut bytes, proof.binding, VERSION, UNBOUND_VERSION);
+
+    let StarkProof { trace_commitment, composition_commitment, .. } = proof;
+    bytes.extend_from_slice(&trace_commitment.root);
+    push_len(&mut bytes, trace_commitment.inclusion_proofs.len());
+    for InclusionProof(eval, path) in &trace_commitment.inclusion_proofs {
+        push_opening(&mut bytes, eval, path);
+    }
+
+    push_len(&mut bytes, composition_commitment.len());
+    for FriLayer { root, validation_data, landing_data, .. } in composition_commitment {
+        bytes.extend_from_slice(root);
+        push_len(&mut bytes, validation_data.len());
+        for ValidationData { eval, proof, sym_eval, sym_proof } in validation_data {
+            push_opening(&mut bytes, eval, proof);
+            push_opening(&mut bytes, sym_eval, sym_proof);
+        }
+        push_len(&mut bytes, landing_data.len());
+        for InclusionProof(eval, path) in landing_data {
+            push_opening(&mut bytes, eval, path);
+        }
+    }
+    push_remainder(&mut bytes, composition_commitment);
+
+    bytes
+}
+
+pub fn decode_proof(bytes: &[u8]) -> Result<StarkProof<F>, DecodeError> {
+    decode_proof_with(bytes, DecodeMode::Lenient)
+}
+
+pub fn decode_proof_with(bytes: &[u8], mode: DecodeMode) -> Result<StarkProof<F>, DecodeError> {
+    decode_proof_with_limits(bytes, mode, &DecodeLimits::default())
+}
+
+pub fn decode_proof_with_limits(bytes: &[u8], mode: DecodeMode, limits: &DecodeLimits) -> Result<StarkProof<F>, DecodeError> {
+    ProofView::parse_with_limits(bytes, mode, limits).map(|view| view.to_proof())
+}
+
+// the version of the format of an encoded proof, whether this crate
+// decodes it or not
+pub fn proof_version(bytes: &[u8]) -> Result<u8, DecodeError> {
+    let mut reader = Reader::new(bytes, DecodeMode::Lenient, DecodeLimits::default());
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(DecodeError::Magic)
+    }
+    Ok(reader.take(1)?[0])
+}
+
+pub fn encode_public_input(public_input: &PublicInput<F>) -> Vec<u8> {
+    let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, witness_commitment) = public_input;
+    let mut bytes = Vec::<u8>::with_capacity(PUBLIC_INPUT_SIZE + ELEMENT_SIZE);
+    bytes.extend_from_slice(&modulus.to_bytes_be());
+    push_len(&mut bytes, *interp_two_power);
+    push_len(&mut bytes, *eval_two_power);
+    push_len(&mut bytes, *num_queries);
+    bytes.extend_from_slice(&common::fe_to_bytes_be(value_0));
+    bytes.extend_from_slice(&common::fe_to_bytes_be(value_1));
+    if let Some(witness_commitment) = witness_commitment {
+        bytes.extend_from_slice(&common::fe_to_bytes_be(witness_commitment));
+    }
+    bytes
+}
+
+impl PublicInput<F> {
+
+    // keccak256 digest of the encoded public input, a succinct
+    // reference to the statement for logs and on-chain anchors
+    pub fn digest(&self) -> [u8; 32] {
+        DefaultTranscript::<F>::new(&encode_public_input(self)).state()
+    }
+}
+
+// decodes a public input, always strictly: the public values must be
+// canonical. whether it is a valid statement is checked by validate
+pub fn decode_public_input(bytes: &[u8]) -> Result<PublicInput<F>, DecodeError> {
+    decode_public_input_with_limits(bytes, &DecodeLimits::default())
+}
+
+// the same, the two powers of the domain sizes bounded by the limits
+pub fn decode_public_input_with_limits(bytes: &[u8], limits: &DecodeLimits) -> Result<PublicInput<F>, DecodeError> {
+    let mut reader = Reader::new(bytes, DecodeMode::Strict { eval_two_power: 0 }, *limits);
+    let modulus = U256::from_bytes_be(reader.take(32)?).unwrap();
+    let interp_two_power = reader.count(limits.max_two_power, "max_two_power")?;
+    let eval_two_power = reader.count(limits.max_two_power, "max_two_power")?;
+    let num_queries = reader.len()?;
+    let value_0 = reader.element()?;
+    let value_1 = reader.element()?;
+    let witness_commitment = match reader.position {
+        end if end == bytes.len() => None,
+        _ => Some(reader.element()?),
+    };
+    if reader.position != bytes.len() {
+        return Err(DecodeError::TrailingBytes { offset: reader.position })
+    }
+    Ok(PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, witness_commitment))
+}
+
+pub fn encode_proof_compact(proof: &StarkProof<F>) -> Vec<u8> {
+    let mut bytes = Vec::<u8>::new();
+    bytes.extend_from_slice(COMPACT_MAGIC);
+    push_binding(&mut bytes, proof.binding, COMPACT_VERSION, UNBOUND_COMPACT_VERSION);
+
+    let StarkProof { trace_commitment, composition_commitment, .. } = proof;
+    bytes.extend_from_slice(&trace_commitment.root);
+    push_len(&mut bytes, trace_commitment.inclusion_proofs.len());
+    let mut nodes = NodeTable::default();
+    for InclusionProof(eval, path) in &trace_commitment.inclusion_proofs {
+        nodes.push_opening(&mut bytes, eval, path);
+    }
+
+    bytes.push(composition_commitment.len() as u8);
+    for FriLayer { root, validation_data, landing_data, .. } in composition_commitment {
+        let mut nodes = NodeTable::default();
+        bytes.extend_from_slice(root);
+        push_len(&mut bytes, validation_data.len());
+        for ValidationData { eval, proof, sym_eval, sym_proof } in validation_data {
+            nodes.push_opening(&mut bytes, eval, proof);
+            nodes.push_opening(&mut bytes, sym_eval, sym_proof);
+        }
+        push_len(&mut bytes, landing_data.len());
+        for InclusionProof(eval, path) in landing_data {
+            nodes.push_opening(&mut bytes, eval, path);
+        }
+    }
+    push_remainder(&mut bytes, composition_commitment);
+
+    bytes
+}
+
+// decodes a compact proof, always strictly on the field elements. the
+// paths are checked by the verifier like the ones of any other proof
+pub fn decode_proof_compact(bytes: &[u8]) -> Result<StarkProof<F>, DecodeError> {
+    let limits = DecodeLimits::default();
+    limits.check_size(bytes)?;
+    let mut reader = Reader::new(bytes, DecodeMode::Strict { eval_two_power: 0 }, limits);
+    if reader.take(COMPACT_MAGIC.len())? != COMPACT_MAGIC {
+        return Err(DecodeError::Magic)
+    }
+    let binding = match reader.take(1)?[0] {
+        COMPACT_VERSION => Some(reader.node()?),
+        UNBOUND_COMPACT_VERSION => None,
+        version => return Err(DecodeError::Version(version)),
+    };
+
+    let trace_root = reader.node()?;
+    let mut nodes = Vec::<[u8; 32]>::new();
+    let count = reader.count(limits.max_openings, "max_openings")?;
+    let inclusion_proofs = (0..count)
+        .map(|_| reader.compact_opening(&mut nodes))
+        .collect::<Result<Vec<InclusionProof<F>>, DecodeError>>()?;
+
+    let position = reader.position;
+    let num_layers = reader.take(1)?[0] as usize;
+    if num_layers == 0 {
+        return Err(DecodeError::NoLayers)
+    }
+    if num_layers > limits.max_layers {
+        return Err(DecodeError::Limit { limit: "max_layers", offset: position })
+    }
+    let mut composition_commitment = (0..num_layers)
+        .map(|_| {
+            let mut nodes = Vec::<[u8; 32]>::new();
+            let root = reader.node()?;
+            let num_queries = reader.count(limits.max_openings, "max_openings")?;
+            let validation_data = (0..num_queries)
+                .map(|_| {
+                    let InclusionProof(eval, proof) = reader.compact_opening(&mut nodes)?;
+                    let InclusionProof(sym_eval, sym_proof) = reader.compact_opening(&mut nodes)?;
+                    Ok(ValidationData { eval, proof, sym_eval, sym_proof })
+                })
+                .collect::<Result<Vec<ValidationData<F>>, DecodeError>>()?;
+            let count = reader.count(limits.max_openings, "max_openings")?;
+            let landing_data = (0..count)
+                .map(|_| reader.compact_opening(&mut nodes))
+                .collect::<Result<Vec<InclusionProof<F>>, DecodeError>>()?;
+            Ok(FriLayer { root, validation_data, landing_data, remainder: vec![] })
+        })
+        .collect::<Result<Vec<FriLayer<F>>, DecodeError>>()?;
+    composition_commitment.last_mut().unwrap().remainder = reader
+        .remainder()?
+        .into_iter()
+        .map(|position| FE::from_bytes_be(&bytes[position..position + ELEMENT_SIZE]).unwrap())
+        .collect::<Vec<FE>>();
+
+    if reader.position != bytes.len() {
+        return Err(DecodeError::TrailingBytes { offset: reader.position })
+    }
+
+    Ok(StarkProof {
+        trace_commitment: VectorCommitment { root: trace_root, inclusion_proofs },
+        composition_commitment,
+        binding,
+    })
+}
+
+// the version of the format, the one without a binding when the proof
+// has none
+fn push_binding(bytes: &mut Vec<u8>, binding: Option<[u8; 32]>, version: u8, unbound_version: u8) {
+    match binding {
+        Some(binding) => {
+            bytes.push(version);
+            bytes.extend_from_slice(&binding);
+        },
+        None => bytes.push(unbound_version),
+    }
+}
+
+fn push_len(bytes: &mut Vec<u8>, len: usize) {
+    bytes.extend_from_slice(&(len as u32).to_be_bytes());
+}
+
+// the remainder of the last layer, absent when empty so that the
+// proofs folded down to a constant are encoded as before
+fn push_remainder(bytes: &mut Vec<u8>, composition_commitment: &[FriLayer<F>]) {
+    let Some(FriLayer { remainder, .. }) = composition_commitment.last().filter(|layer| !layer.remainder.is_empty()) else {
+        return
+    };
+    push_len(bytes, remainder.len());
+    for coefficient in remainder {
+        bytes.extend_from_slice(&common::fe_to_bytes_be(coefficient));
+    }
+}
+
+fn push_opening(bytes: &mut Vec<u8>, eval: &FE, path: &Proof<[u8; 32]>) {
+    bytes.extend_from_slice(&common::fe_to_bytes_be(eval));
+    push_len(bytes, path.merkle_path.len());
+    for node in &path.merkle_path {
+        bytes.extend_from_slice(node);
+    }
+}
+
+// the nodes of a tree sent in full so far in a compact proof, by the
+// order in which they were sent
+#[derive(Default)]
+struct NodeTable {
+    sent: HashMap<[u8; 32], usize>,
+    count: usize,
+}
+
+impl NodeTable {
+
+    fn push_opening(&mut self, bytes: &mut Vec<u8>, eval: &FE, path: &Proof<[u8; 32]>) {
+        bytes.extend_from_slice(&common::fe_to_bytes_be(eval));
+        bytes.push(path.merkle_path.len() as u8);
+        for node in &path.merkle_path {
+            match self.sent.get(node).and_then(|i| u16::try_from(*i).ok()) {
+                Some(i) => {
+                    bytes.push(SEEN_NODE);
+                    bytes.extend_from_slice(&i.to_be_bytes());
+                },
+                None => {
+                    bytes.push(NEW_NODE);
+                    bytes.extend_from_slice(node);
+                    self.sent.entry(*node).or_insert(self.count);
+                    self.count += 1;
+                },
+            }
+        }
+    }
+}
+
+// offsets of the openings of a fri layer
+struct LayerOffsets {
+    root: usize,
+    validation: Vec<usize>,
+    landing: Vec<usize>,
+}
+
+// an encoded proof read in place. parsing checks the encoding as
+// required by the decode mode and records where each opening starts,
+// field elements and merkle paths are decoded when accessed
+pub struct ProofView<'a> {
+    bytes: &'a [u8],
+    binding: Option<usize>,
+    trace_root: usize,
+    trace_openings: Vec<usize>,
+    layers: Vec<LayerOffsets>,
+    // offsets of the coefficients of the remainder
+    remainder: Vec<usize>,
+}
+
+impl<'a> ProofView<'a> {
+
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, DecodeError> {
+        Self::parse_with(bytes, DecodeMode::Lenient)
+    }
+
+    pub fn parse_with(bytes: &'a [u8], mode: DecodeMode) -> Result<Self, DecodeError> {
+        Self::parse_with_limits(bytes, mode, &DecodeLimits::default())
+    }
+
+    pub fn parse_with_limits(bytes: &'a [u8], mode: DecodeMode, limits: &DecodeLimits) -> Result<Self, DecodeError> {
+        Self::parse_version(bytes, mode, limits, &[VERSION])
+    }
+
+    // parses a proof of one of the given versions, the wire format of
+    // the previous ones being the same as the current one without the
+    // binding
+    pub(crate) fn parse_version(bytes: &'a [u8], mode: DecodeMode, limits: &DecodeLimits, accepted: &[u8]) -> Result<Self, DecodeError> {
+        limits.check_size(bytes)?;
+        let mut reader = Reader::new(bytes, mode, *limits);
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(DecodeError::Magic)
+        }
+        let version = reader.take(1)?[0];
+        if !accepted.contains(&version) {
+            return Err(DecodeError::Version(version))
+        }
+
+        let binding = match version >= VERSION {
+            true => {
+                let position = reader.position;
+                reader.take(NODE_SIZE)?;
+                Some(position)
+            },
+            false => None,
+        };
+        let trace_root = reader.position;
+        reader.take(NODE_SIZE)?;
+        let trace_openings = reader.openings(0)?;
+
+        let num_layers = reader.count(limits.max_layers, "max_layers")?;
+        if num_layers == 0 {
+            return Err(DecodeError::NoLayers)
+        }
+        let layers = (0..num_layers)
+            .map(|layer| {
+                let root = reader.position;
+                reader.take(NODE_SIZE)?;
+                let num_queries = reader.count(limits.max_openings, "max_openings")?;
+                let validation = (0..num_queries)
+                    .map(|_| {
+                        let position = reader.opening(layer)?;
+                        reader.opening(layer)?;
+                        Ok(position)
+                    })
+                    .collect::<Result<Vec<usize>, DecodeError>>()?;
+                let landing = reader.openings(layer)?;
+                Ok(LayerOffsets { root, validation, landing })
+            })
+            .collect::<Result<Vec<LayerOffsets>, DecodeError>>()?;
+        let remainder = reader.remainder()?;
+
+        if reader.position != bytes.len() {
+            return Err(DecodeError::TrailingBytes { offset: reader.position })
+        }
+
+        Ok(Self { bytes, binding, trace_root, trace_openings, layers, remainder })
+    }
+
+    // decodes the whole proof
+    pub fn to_proof(&self) -> StarkProof<F> {
+        StarkProof {
+            trace_commitment: VectorCommitment {
+                root: self.trace_root(),
+                inclusion_proofs: (0..self.trace_opening_count())
+                    .map(|i| self.trace_opening(i).into_owned())
+                    .collect::<Vec<InclusionProof<F>>>(),
+            },
+            composition_commitment: (0..self.layer_count())
+                .map(|i| self.layer(i).into_owned())
+                .collect::<Vec<FriLayer<F>>>(),
+            binding: self.binding(),
+        }
+    }
+
+    fn node(&self, position: usize) -> [u8; 32] {
+        let mut node = [0_u8; 32];
+        node.copy_from_slice(&self.bytes[position..position + NODE_SIZE]);
+        node
+    }
+
+    // decodes the opening at a position checked by parse, returning
+    // it with the position that follows it
+    fn opening(&self, position: usize) -> (InclusionProof<F>, usize) {
+        let eval = FE::from_bytes_be(&self.bytes[position..position + ELEMENT_SIZE]).unwrap();
+        let mut position = position + ELEMENT_SIZE;
+        let path_len = read_len(self.bytes, position);
+        position += 4;
+        let merkle_path = (0..path_len)
+            .map(|k| self.node(position + k * NODE_SIZE))
+            .collect::<Vec<[u8; 32]>>();
+        (InclusionProof(eval, Proof { merkle_path }), position + path_len * NODE_SIZE)
+    }
+}
+
+impl LayerAccess<F> for ProofView<'_> {
+
+    fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    fn layer_root(&self, layer: usize) -> [u8; 32] {
+        self.node(self.layers[layer].root)
+    }
+
+    fn layer(&self, layer: usize) -> Cow<'_, FriLayer<F>> {
+        let LayerOffsets { root, validation, landing } = &self.layers[layer];
+        Cow::Owned(FriLayer {
+            root: self.node(*root),
+            validation_data: validation
+                .iter()
+                .map(|position| {
+                    let (InclusionProof(eval, proof), position) = self.opening(*position);
+                    let (InclusionProof(sym_eval, sym_proof), _) = self.opening(position);
+                    ValidationData { eval, proof, sym_eval, sym_proof }
+                })
+                .collect::<Vec<ValidationData<F>>>(),
+            landing_data: landing
+                .iter()
+                .map(|position| self.opening(*position).0)
+                .collect::<Vec<InclusionProof<F>>>(),
+            remainder: match layer + 1 == self.layers.len() {
+                true => self.remainder
+                    .iter()
+                    .map(|position| FE::from_bytes_be(&self.bytes[*position..*position + ELEMENT_SIZE]).unwrap())
+                    .collect::<Vec<FE>>(),
+                false => vec![],
+            },
+        })
+    }
+}
+
+impl ProofAccess<F> for ProofView<'_> {
+
+    fn trace_root(&self) -> [u8; 32] {
+        self.node(self.trace_root)
+    }
+
+    fn trace_opening_count(&self) -> usize {
+        self.trace_openings.len()
+    }
+
+    fn trace_opening(&self, opening: usize) -> Cow<'_, InclusionProof<F>> {
+        Cow::Owned(self.opening(self.trace_openings[opening]).0)
+    }
+
+    fn binding(&self) -> Option<[u8; 32]> {
+        self.binding.map(|position| self.node(position))
+    }
+}
+
+fn read_len(bytes: &[u8], position: usize) -> usize {
+    let mut len = [0_u8; 4];
+    len.copy_from_slice(&bytes[position..position + 4]);
+    u32::from_be_bytes(len) as usize
+}
+
+// bounds checked cursor used while parsing
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    mode: DecodeMode,
+    limits: DecodeLimits,
+}
+
+impl<'a> Reader<'a> {
+
+    fn new(bytes: &'a [u8], mode: DecodeMode, limits: DecodeLimits) -> Self {
+        Self { bytes, position: 0, mode, limits }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let end = self.position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DecodeError::UnexpectedEnd { offset: self.position })?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    fn len(&mut self) -> Result<usize, DecodeError> {
+        let position = self.position;
+        self.take(4)?;
+        Ok(read_len(self.bytes, position))
+    }
+
+    // a length no larger than the given limit
+    fn count(&mut self, max: usize, limit: &'static str) -> Result<usize, DecodeError> {
+        let position = self.position;
+        match self.len()? {
+            len if len > max => Err(DecodeError::Limit { limit, offset: position }),
+            len => Ok(len),
+        }
+    }
+
+    // skips a field element, checking it is canonical in strict mode
+    fn skip_element(&mut self) -> Result<&'a [u8], DecodeError> {
+        let position = self.position;
+        let element = self.take(ELEMENT_SIZE)?;
+        if self.mode != DecodeMode::Lenient && common::fe_from_bytes_be::<F>(element).is_err() {
+            return Err(DecodeError::NonCanonicalElement { offset: position })
+        }
+        Ok(element)
+    }
+
+    fn element(&mut self) -> Result<FE, DecodeError> {
+        self.skip_element().map(|element| FE::from_bytes_be(element).unwrap())
+    }
+
+    // skips an opening of the given fri layer, returning the
+    // position where it starts
+    fn opening(&mut self, layer: usize) -> Result<usize, DecodeError> {
+        let position = self.position;
+        self.skip_element()?;
+        let path_position = self.position;
+        let path_len = self.count(self.limits.max_path_len, "max_path_len")?;
+        if path_len > self.mode.max_path_len(layer) {
+            return Err(DecodeError::PathTooLong { offset: path_position })
+        }
+        let path_size = path_len
+            .checked_mul(NODE_SIZE)
+            .ok_or(DecodeError::UnexpectedEnd { offset: self.position })?;
+        self.take(path_size)?;
+        Ok(position)
+    }
+
+    fn openings(&mut self, layer: usize) -> Result<Vec<usize>, DecodeError> {
+        let count = self.count(self.limits.max_openings, "max_openings")?;
+        (0..count)
+            .map(|_| self.opening(layer))
+            .collect::<Result<Vec<usize>, DecodeError>>()
+    }
+
+    // skips the remainder after the layers, if any bytes are left,
+    // returning the positions of its coefficients. an empty remainder
+    // is not encoded, its length would be a second encoding of the proof
+    fn remainder(&mut self) -> Result<Vec<usize>, DecodeError> {
+        if self.position == self.bytes.len() {
+            return Ok(vec![])
+        }
+        let position = self.position;
+        let count = self.len()?;
+        if count == 0 {
+            return Err(DecodeError::TrailingBytes { offset: position })
+        }
+        (0..count)
+            .map(|_| {
+                let position = self.position;
+                self.skip_element()?;
+                Ok(position)
+            })
+            .collect::<Result<Vec<usize>, DecodeError>>()
+    }
+
+    fn node(&mut self) -> Result<[u8; 32], DecodeError> {
+        let mut node = [0_u8; 32];
+        node.copy_from_slice(self.take(NODE_SIZE)?);
+        Ok(node)
+    }
+
+    // decodes an opening of a compact proof, resolving the references
+    // to the nodes already sent in its tree
+    fn compact_opening(&mut self, nodes: &mut Vec<[u8; 32]>) -> Result<InclusionProof<F>, DecodeError> {
+        let eval = self.element()?;
+        let position = self.position;
+        let path_len = self.take(1)?[0] as usize;
+        if path_len > self.limits.max_path_len {
+            return Err(DecodeError::Limit { limit: "max_path_len", offset: position })
+        }
+        let merkle_path = (0..path_len)
+            .map(|_| {
+                let position = self.position;
+                match self.take(1)?[0] {
+                    NEW_NODE => {
+                        let node = self.node()?;
+                        nodes.push(node);
+                        Ok(node)
+                    },
+                    SEEN_NODE => {
+                        let i = self.take(2)?;
+                        nodes
+                            .get(u16::from_be_bytes([i[0], i[1]]) as usize)
+                            .copied()
+                            .ok_or(DecodeError::NodeReference { offset: position })
+                    },
+                    _ => Err(DecodeError::NodeReference { offset: position }),
+                }
+            })
+            .collect::<Result<Vec<[u8; 32]>, DecodeError>>()?;
+        Ok(InclusionProof(eval, Proof { merkle_path }))
+    }
+}
+
+// a proof file mapped in memory, to be verified in place
+#[cfg(feature = "mmap")]
+pub struct MappedProof {
+    mmap: memmap2::Mmap,
+}
+
+#[cfg(feature = "mmap")]
+impl MappedProof {
+
+    pub fn open(path: &std::path::Path) -> std::io::Result<Self> {
+        let file = std::fs::File::open(path)?;
+        // safety: the proof file must not be modified while it is mapped
+        let mmap = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { mmap })
+    }
+
+    pub fn view(&self) -> Result<ProofView<'_>, DecodeError> {
+        self.view_with(DecodeMode::Lenient)
+    }
+
+    pub fn view_with(&self, mode: DecodeMode) -> Result<ProofView<'_>, DecodeError> {
+        ProofView::parse_with(&self.mmap, mode)
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.mmap
+    }
+}
@@ -0,0 +1,413 @@
+// a self-describing cbor envelope around an encoded proof, so that
+// systems handling proofs of several protocols can recognize and route
+// it without parsing the proof. the envelope is the tag 55799 of self
+// described cbor around a map of integer keys, in this order:
+//
+//   1  protocol, the text "stark101"
+//   2  version of the proof format of the payload, see encoding
+//   3  options digest, the keccak256 digest of the proof options the
+//      verifier must use, see options_digest
+//   4  payload, the encoded proof as a byte string
+//   5  metadata, optional, a map of integer keys, in this order:
+//        1  prover version, the version of this crate that made the proof
+//        2  options digest of the prover
+//        3  creation time, in seconds since the unix epoch
+//        4  tag, free-form text
+//   6  statement, optional, the identifier of the statement of the
+//      proof among the ones a deployment verifies, see registry
+//
+// with the shortest encoding of every length, so that an envelope is
+// canonical cbor. it is not a cose structure, as nothing is signed.
+// the metadata is not bound to the proof either, the verifier only
+// reports it and anyone handling the envelope can change it
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use crate::air::Air;
+use crate::common::{ProofOptions, PublicInput, Session, StarkProof};
+use crate::encoding::{self, DecodeError, DecodeMode};
+use crate::fri::{OffsetSchedule, QuerySampling};
+use crate::merkle::MerkleBackend;
+use crate::report::VerificationReport;
+use crate::verifier::{self, VerifyError};
+
+type F = Stark252PrimeField;
+
+pub const PROTOCOL: &str = "stark101";
+
+const SELF_DESCRIBED_CBOR: u64 = 55799;
+const UNSIGNED: u8 = 0;
+const BYTE_STRING: u8 = 2;
+const TEXT_STRING: u8 = 3;
+const MAP: u8 = 5;
+const TAG: u8 = 6;
+
+const PROTOCOL_KEY: u64 = 1;
+const VERSION_KEY: u64 = 2;
+const OPTIONS_KEY: u64 = 3;
+const PAYLOAD_KEY: u64 = 4;
+const METADATA_KEY: u64 = 5;
+const STATEMENT_KEY: u64 = 6;
+
+const PROVER_VERSION_KEY: u64 = 1;
+const PROVER_OPTIONS_KEY: u64 = 2;
+const CREATED_AT_KEY: u64 = 3;
+const TAG_KEY: u64 = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvelopeError {
+    // the bytes are not an envelope of the expected shape, the offset
+    // being the one of the first unexpected item
+    Malformed { offset: usize },
+    // an envelope of a proof of another protocol
+    Protocol(String),
+    // a proof format this crate does not decode
+    Version(u64),
+    // the proof was made with other options than the given ones
+    Options,
+    // the payload is not a valid encoded proof
+    Payload(DecodeError),
+}
+
+impl fmt::Display for EnvelopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvelopeError::Malformed { offset } => write!(f, "malformed envelope at byte {}", offset),
+            EnvelopeError::Protocol(protocol) => write!(f, "envelope of a {} proof", protocol),
+            EnvelopeError::Version(v) => write!(f, "unsupported proof version {}", v),
+            EnvelopeError::Options => write!(f, "proof made with other options"),
+            EnvelopeError::Payload(e) => write!(f, "malformed payload: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for EnvelopeError {}
+
+// the options the verifier depends on: the transcript label, the query
+// sampling, the offset schedule unless the squared offsets, the most
+// fri layers if limited, the resolved merkle backend unless keccak256,
+// the trace leaf width unless 1, and the session of the proof, as
+//
+//   u32 label length | label | u8 sampling | [u8 schedule]
+//   [u8 3 | u64 max layers]
+//   [u8 4 | u8 merkle backend]
+//   [u8 5 | u64 trace leaf width]
+//   u8 0, or u8 1 | seed [32] | u64 index
+pub fn options_digest(options: &ProofOptions) -> [u8; 32] {
+    let mut hasher = DefaultTranscript::<F>::new(&(options.transcript_label.len() as u32).to_be_bytes());
+    hasher.append_bytes(&options.transcript_label);
+    hasher.append_bytes(&[match options.query_sampling {
+        QuerySampling::Folded => 0,
+        QuerySampling::PerLayer => 1,
+    }]);
+    match options.offset_schedule {
+        OffsetSchedule::Squared => {},
+        OffsetSchedule::Fixed => hasher.append_bytes(&[1]),
+        OffsetSchedule::Sampled => hasher.append_bytes(&[2]),
+    }
+    if let Some(max_layers) = options.fri_max_layers {
+        hasher.append_bytes(&[3]);
+        hasher.append_bytes(&(max_layers as u64).to_be_bytes());
+    }
+    match options.merkle_backend.resolve() {
+        MerkleBackend::Keccak256 | MerkleBackend::Auto => {},
+        #[cfg(feature = "blake3")]
+        MerkleBackend::Blake3 => hasher.append_bytes(&[4, 1]),
+    }
+    if options.trace_leaf_width != 1 {
+        hasher.append_bytes(&[5]);
+        hasher.append_bytes(&(options.trace_leaf_width as u64).to_be_bytes());
+    }
+    match &options.session {
+        None => hasher.append_bytes(&[0]),
+        Some(Session { seed, index }) => {
+            hasher.append_bytes(&[1]);
+            hasher.append_bytes(seed);
+            hasher.append_bytes(&(*index as u64).to_be_bytes());
+        },
+    }
+    hasher.state()
+}
+
+// where a proof comes from, for operators tracing which prover build
+// made it. the options digest is the one of the options of the prover,
+// which may differ from the ones the proof is verified with
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofMetadata {
+    pub prover_version: String,
+    pub options_digest: [u8; 32],
+    // seconds since the unix epoch
+    pub created_at: u64,
+    pub tag: String,
+}
+
+impl ProofMetadata {
+
+    // the metadata of a proof made now by this build, with the options
+    pub fn new(options: &ProofOptions, tag: &str) -> Self {
+        Self {
+            prover_version: env!("CARGO_PKG_VERSION").to_string(),
+            options_digest: options_digest(options),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_secs()),
+            tag: tag.to_string(),
+        }
+    }
+}
+
+// an envelope read in place
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope<'a> {
+    pub version: u64,
+    pub options_digest: [u8; 32],
+    pub payload: &'a [u8],
+    pub metadata: Option<ProofMetadata>,
+    pub statement: Option<&'a str>,
+}
+
+impl<'a> Envelope<'a> {
+
+    pub fn parse(bytes: &'a [u8]) -> Result<Self, EnvelopeError> {
+        let mut reader = Reader { bytes, position: 0 };
+        reader.expect(TAG, SELF_DESCRIBED_CBOR)?;
+        let position = reader.position;
+        let num_items = reader.item(MAP)?;
+        if !(4..=6).contains(&num_items) {
+            return Err(EnvelopeError::Malformed { offset: position })
+        }
+
+        reader.expect(UNSIGNED, PROTOCOL_KEY)?;
+        let protocol = reader.string(TEXT_STRING)?;
+        if protocol != PROTOCOL.as_bytes() {
+            return Err(EnvelopeError::Protocol(String::from_utf8_lossy(protocol).into_owned()))
+        }
+        reader.expect(UNSIGNED, VERSION_KEY)?;
+        let version = reader.item(UNSIGNED)?;
+        reader.expect(UNSIGNED, OPTIONS_KEY)?;
+        let options_digest = reader.digest()?;
+        reader.expect(UNSIGNED, PAYLOAD_KEY)?;
+        let payload = reader.string(BYTE_STRING)?;
+        // the optional items, each at most once and in the order of
+        // their keys
+        let mut metadata = None;
+        let mut statement = None;
+        let mut last_key = PAYLOAD_KEY;
+        for _ in 4..num_items {
+            let position = reader.position;
+            let key = reader.item(UNSIGNED)?;
+            match key {
+                METADATA_KEY if key > last_key => metadata = Some(reader.metadata()?),
+                STATEMENT_KEY if key > last_key => statement = Some(reader.text()?),
+                _ => return Err(EnvelopeError::Malformed { offset: position }),
+            }
+            last_key = key;
+        }
+
+        if reader.position != bytes.len() {
+            return Err(EnvelopeError::Malformed { offset: reader.position })
+        }
+        Ok(Self { version, options_digest, payload, metadata, statement })
+    }
+
+    // decodes the proof, if it was made with the given options
+    pub fn proof(&self, options: &ProofOptions) -> Result<StarkProof<F>, EnvelopeError> {
+        self.check(options)?;
+        encoding::decode_proof(self.payload).map_err(EnvelopeError::Payload)
+    }
+
+    // verifies the proof in place, if it was made with the given
+    // options, reporting the metadata of the envelope with the outcome
+    pub fn verify_with_report<A: Air<F>>(
+            &self,
+            air: &A,
+            public_input: PublicInput<F>,
+            mode: DecodeMode,
+            options: &ProofOptions
+        ) -> VerificationReport {
+
+        let mut report = match self.check(options) {
+            Ok(()) => verifier::verify_encoded_with_report(air, public_input, self.payload, mode, options),
+            Err(e) => VerificationReport { result: Err(VerifyError::Envelope(e)), ..Default::default() },
+        };
+        report.metadata = self.metadata.clone();
+        report
+    }
+
+    // whether the proof was made with the given options, in the
+    // current format
+    pub(crate) fn check(&self, options: &ProofOptions) -> Result<(), EnvelopeError> {
+        if self.version != encoding::VERSION as u64 {
+            return Err(EnvelopeError::Version(self.version))
+        }
+        if self.options_digest != options_digest(options) {
+            return Err(EnvelopeError::Options)
+        }
+        Ok(())
+    }
+}
+
+// whether the bytes start as an envelope, to tell it from a bare
+// encoded proof before parsing either
+pub fn is_envelope(bytes: &[u8]) -> bool {
+    let mut head = Vec::<u8>::new();
+    push_head(&mut head, TAG, SELF_DESCRIBED_CBOR);
+    bytes.starts_with(&head)
+}
+
+pub fn seal(proof: &StarkProof<F>, options: &ProofOptions) -> Vec<u8> {
+    seal_envelope(proof, options, None, None)
+}
+
+// seals the proof with metadata, which the verifier does not check
+pub fn seal_with_metadata(proof: &StarkProof<F>, options: &ProofOptions, metadata: &ProofMetadata) -> Vec<u8> {
+    seal_envelope(proof, options, Some(metadata), None)
+}
+
+// seals the proof of the registered statement of the given identifier,
+// see registry
+pub fn seal_statement(proof: &StarkProof<F>, options: &ProofOptions, statement: &str, metadata: Option<&ProofMetadata>) -> Vec<u8> {
+    seal_envelope(proof, options, metadata, Some(statement))
+}
+
+fn seal_envelope(proof: &StarkProof<F>, options: &ProofOptions, metadata: Option<&ProofMetadata>, statement: Option<&str>) -> Vec<u8> {
+    let payload = encoding::encode_proof(proof);
+    let mut bytes = Vec::<u8>::with_capacity(payload.len() + 64);
+    push_head(&mut bytes, TAG, SELF_DESCRIBED_CBOR);
+    push_head(&mut bytes, MAP, 4 + metadata.is_some() as u64 + statement.is_some() as u64);
+    push_head(&mut bytes, UNSIGNED, PROTOCOL_KEY);
+    push_string(&mut bytes, TEXT_STRING, PROTOCOL.as_bytes());
+    push_head(&mut bytes, UNSIGNED, VERSION_KEY);
+    // the version of the payload, without a binding for a proof with none
+    let version = encoding::proof_version(&payload).expect("the payload is an encoded proof");
+    push_head(&mut bytes, UNSIGNED, version as u64);
+    push_head(&mut bytes, UNSIGNED, OPTIONS_KEY);
+    push_string(&mut bytes, BYTE_STRING, &options_digest(options));
+    push_head(&mut bytes, UNSIGNED, PAYLOAD_KEY);
+    push_string(&mut bytes, BYTE_STRING, &payload);
+    if let Some(ProofMetadata { prover_version, options_digest, created_at, tag }) = metadata {
+        push_head(&mut bytes, UNSIGNED, METADATA_KEY);
+        push_head(&mut bytes, MAP, 4);
+        push_head(&mut bytes, UNSIGNED, PROVER_VERSION_KEY);
+        push_string(&mut bytes, TEXT_STRING, prover_version.as_bytes());
+        push_head(&mut bytes, UNSIGNED, PROVER_OPTIONS_KEY);
+        push_string(&mut bytes, BYTE_STRING, options_digest);
+        push_head(&mut bytes, UNSIGNED, CREATED_AT_KEY);
+        push_head(&mut bytes, UNSIGNED, *created_at);
+        push_head(&mut bytes, UNSIGNED, TAG_KEY);
+        push_string(&mut bytes, TEXT_STRING, tag.as_bytes());
+    }
+    if let Some(statement) = statement {
+        push_head(&mut bytes, UNSIGNED, STATEMENT_KEY);
+        push_string(&mut bytes, TEXT_STRING, statement.as_bytes());
+    }
+    bytes
+}
+
+// the head of a cbor item, its major type followed by its argument in
+// the fewest bytes
+fn push_head(bytes: &mut Vec<u8>, major: u8, argument: u64) {
+    let major = major << 5;
+    match argument {
+        0..=23 => bytes.push(major | argument as u8),
+        24..=0xff => bytes.extend_from_slice(&[major | 24, argument as u8]),
+        0x100..=0xffff => {
+            bytes.push(major | 25);
+            bytes.extend_from_slice(&(argument as u16).to_be_bytes());
+        },
+        0x10000..=0xffff_ffff => {
+            bytes.push(major | 26);
+            bytes.extend_from_slice(&(argument as u32).to_be_bytes());
+        },
+        _ => {
+            bytes.push(major | 27);
+            bytes.extend_from_slice(&argument.to_be_bytes());
+        },
+    }
+}
+
+fn push_string(bytes: &mut Vec<u8>, major: u8, string: &[u8]) {
+    push_head(bytes, major, string.len() as u64);
+    bytes.extend_from_slice(string);
+}
+
+// bounds checked cursor used while parsing
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+
+    fn take(&mut self, len: usize, start: usize) -> Result<&'a [u8], EnvelopeError> {
+        let end = self.position
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(EnvelopeError::Malformed { offset: start })?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    // the argument of an item of the major type, which must be in
+    // the shortest encoding
+    fn item(&mut self, major: u8) -> Result<u64, EnvelopeError> {
+        let start = self.position;
+        let malformed = EnvelopeError::Malformed { offset: start };
+        let initial = self.take(1, start)?[0];
+        if initial >> 5 != major {
+            return Err(malformed)
+        }
+        let (argument, shortest) = match initial & 0x1f {
+            additional @ 0..=23 => (additional as u64, 0),
+            24 => (self.take(1, start)?[0] as u64, 24),
+            25 => (u16::from_be_bytes(self.take(2, start)?.try_into().unwrap()) as u64, 0x100),
+            26 => (u32::from_be_bytes(self.take(4, start)?.try_into().unwrap()) as u64, 0x10000),
+            27 => (u64::from_be_bytes(self.take(8, start)?.try_into().unwrap()), 0x1_0000_0000),
+            _ => return Err(malformed),
+        };
+        if argument < shortest {
+            return Err(malformed)
+        }
+        Ok(argument)
+    }
+
+    fn expect(&mut self, major: u8, argument: u64) -> Result<(), EnvelopeError> {
+        let start = self.position;
+        match self.item(major)? {
+            found if found == argument => Ok(()),
+            _ => Err(EnvelopeError::Malformed { offset: start }),
+        }
+    }
+
+    fn string(&mut self, major: u8) -> Result<&'a [u8], EnvelopeError> {
+        let start = self.position;
+        let len = usize::try_from(self.item(major)?).map_err(|_| EnvelopeError::Malformed { offset: start })?;
+        self.take(len, start)
+    }
+
+    fn text(&mut self) -> Result<&'a str, EnvelopeError> {
+        let start = self.position;
+        let text = self.string(TEXT_STRING)?;
+        std::str::from_utf8(text).map_err(|_| EnvelopeError::Malformed { offset: start })
+    }
+
+    fn digest(&mut self) -> Result<[u8; 32], EnvelopeError> {
+        let start = self.position;
+        <[u8; 32]>::try_from(self.string(BYTE_STRING)?).map_err(|_| EnvelopeError::Malformed { offset: start })
+    }
+
+    fn metadata(&mut self) -> Result<ProofMetadata, EnvelopeError> {
+        self.expect(MAP, 4)?;
+        self.expect(UNSIGNED, PROVER_VERSION_KEY)?;
+        let prover_version = self.text()?.to_string();
+        self.expect(UNSIGNED, PROVER_OPTIONS_KEY)?;
+        let options_digest = self.digest()?;
+        self.expect(UNSIGNED, CREATED_AT_KEY)?;
+        let created_at = self.item(UNSIGNED)?;
+        self.expect(UNSIGNED, TAG_KEY)?;
+        let tag = self.text()?.to_string();
+        Ok(ProofMetadata { prover_version, options_digest, created_at, tag })
+    }
+}
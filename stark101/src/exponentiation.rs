@@ -0,0 +1,213 @@
+// an exponentiation: knowledge of a base x such that y = x^e, for a
+// public power y and a public exponent e. the power is computed by
+// square and multiply from the most significant bit of e, each step
+// taking a block of 4 rows of the single trace column:
+//
+//   row 0    the power of x by the bits of e before this step
+//   row 1    the bit of the step, the selector of the multiplication
+//   row 2    the base x, the same on every step
+//   row 3    the bits of e up to this step, the last one being e
+//
+// the step squares the power and multiplies it by x when its bit is
+// one, i.e. by bit * (x - 1) + 1. the bits are private like the base,
+// so they are constrained to be boolean and to recompose the exponent
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::{
+        Stark252PrimeField,
+        MontgomeryConfigStark252PrimeField
+    },
+    fields::montgomery_backed_prime_fields::IsModulus,
+    element::FieldElement
+};
+
+use crate::air::{Air, BoundaryConstraint, BoundaryConstraints, TransitionConstraint};
+use crate::common::PublicInput;
+use crate::expr::Expr;
+use crate::ct;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// rows of the trace of one step of square and multiply
+pub const EXPONENTIATION_BLOCK_ROWS: usize = 4;
+
+// the statement that the power is the exponent-th power of some base,
+// checked with an lde of 2^blowup_log2 times the trace. the exponent
+// is not zero, and as the step has degree 4 the blowup must be 8 or more
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ExponentiationInput {
+    pub exponent: u64,
+    pub power: FE,
+    pub blowup_log2: usize,
+    pub num_queries: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExponentiationError {
+    // the base raised to the exponent is not the power
+    NotTheBase,
+    // the public input is not the one of an exponentiation
+    NotAnExponentiation,
+}
+
+impl std::fmt::Display for ExponentiationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExponentiationError::NotTheBase => write!(f, "the base raised to the exponent is not the power"),
+            ExponentiationError::NotAnExponentiation => write!(f, "not the public input of an exponentiation"),
+        }
+    }
+}
+
+impl std::error::Error for ExponentiationError {}
+
+impl ExponentiationInput {
+
+    // one step per bit of the exponent
+    pub fn steps(&self) -> usize {
+        assert_ne!(self.exponent, 0, "an exponentiation by a non zero exponent");
+        (u64::BITS - self.exponent.leading_zeros()) as usize
+    }
+
+    // the steps and the row of the result, followed by zero padding
+    pub fn trace_length(&self) -> usize {
+        (EXPONENTIATION_BLOCK_ROWS * self.steps() + 1).next_power_of_two()
+    }
+
+    // the generic public input of the statement, its public values
+    // being the power and the exponent
+    pub fn public_input(&self) -> PublicInput<F> {
+        let interp_two_power = self.trace_length().trailing_zeros() as usize;
+        PublicInput(
+            MontgomeryConfigStark252PrimeField::MODULUS,
+            interp_two_power,
+            interp_two_power + self.blowup_log2,
+            self.num_queries,
+            self.power,
+            FE::from(self.exponent),
+            None,
+        )
+    }
+
+    pub fn from_public_input(public_input: &PublicInput<F>) -> Result<Self, ExponentiationError> {
+        let PublicInput(_, interp_two_power, eval_two_power, num_queries, power, exponent, witness_commitment) = public_input;
+        // the exponent fits in the least significant limb
+        let limbs = exponent.representative().limbs;
+        if witness_commitment.is_some() || *eval_two_power < *interp_two_power || limbs[..3] != [0; 3] || limbs[3] == 0 {
+            return Err(ExponentiationError::NotAnExponentiation)
+        }
+        let input = Self {
+            exponent: limbs[3],
+            power: *power,
+            blowup_log2: eval_two_power - interp_two_power,
+            num_queries: *num_queries,
+        };
+        if input.trace_length() != 1 << interp_two_power {
+            return Err(ExponentiationError::NotAnExponentiation)
+        }
+        Ok(input)
+    }
+
+    pub fn air(&self) -> Exponentiation {
+        Exponentiation {
+            trace_length: self.trace_length(),
+            steps: self.steps(),
+            exponent: self.exponent,
+            power: self.power,
+        }
+    }
+
+    // the trace raising the base to the exponent
+    pub fn trace(&self, base: &FE) -> Result<Vec<FE>, ExponentiationError> {
+        let steps = self.steps();
+        let mut trace = Vec::<FE>::with_capacity(self.trace_length());
+        let mut power = FE::one();
+        let mut bits = 0_u64;
+        for step in 0..steps {
+            let bit = (self.exponent >> (steps - 1 - step)) & 1;
+            bits = 2 * bits + bit;
+            trace.extend([power, FE::from(bit), *base, FE::from(bits)]);
+            power = ct::mul(&ct::square(&power), &ct::select(bit == 1, base, &FE::one()));
+        }
+        if power != self.power {
+            return Err(ExponentiationError::NotTheBase)
+        }
+        trace.push(power);
+        trace.resize(self.trace_length(), FE::zero());
+        Ok(trace)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Exponentiation {
+    pub trace_length: usize,
+    pub steps: usize,
+    pub exponent: u64,
+    pub power: FE,
+}
+
+impl Exponentiation {
+
+    // the exemptions of a constraint holding on the given row of the
+    // blocks of the steps for which applies is true
+    fn exemptions(&self, row: usize, applies: impl Fn(usize) -> bool) -> Vec<usize> {
+        (0..self.trace_length)
+            .filter(|r| r % EXPONENTIATION_BLOCK_ROWS != row || r / EXPONENTIATION_BLOCK_ROWS >= self.steps || !applies(r / EXPONENTIATION_BLOCK_ROWS))
+            .collect::<Vec<usize>>()
+    }
+}
+
+impl Air<F> for Exponentiation {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "exponentiation".to_string()
+    }
+
+    // the power starts from one and ends at the public power, the
+    // bits recompose the public exponent
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        let last = EXPONENTIATION_BLOCK_ROWS * (self.steps - 1);
+        BoundaryConstraints::new(vec![
+            BoundaryConstraint { step: 0, value: FE::one() },
+            BoundaryConstraint { step: last + 3, value: FE::from(self.exponent) },
+            BoundaryConstraint { step: last + EXPONENTIATION_BLOCK_ROWS, value: self.power },
+        ])
+    }
+
+    // the frames of a step start on its first row and reach the
+    // first row of the next step
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let steps = self.steps;
+        vec![
+            // square and multiply
+            TransitionConstraint::from_expr(
+                &self.exemptions(0, |_| true),
+                Expr::row(4) - Expr::col(0).square() * (Expr::next(0) * (Expr::row(2) - FE::one()) + FE::one())
+            ),
+            // the bits are boolean
+            TransitionConstraint::from_expr(
+                &self.exemptions(1, |_| true),
+                Expr::col(0) * (Expr::col(0) - FE::one())
+            ),
+            // the base is the same on every step
+            TransitionConstraint::from_expr(
+                &self.exemptions(2, |step| step + 1 < steps),
+                Expr::row(4) - Expr::col(0)
+            ),
+            // the bits up to a step are twice the ones before plus its
+            // bit, from the bit of the first step
+            TransitionConstraint::from_expr(
+                &self.exemptions(1, |step| step == 0),
+                Expr::row(2) - Expr::col(0)
+            ),
+            TransitionConstraint::from_expr(
+                &self.exemptions(3, |step| step + 1 < steps),
+                Expr::row(4) - Expr::col(0) * FE::from(2_u64) - Expr::row(2)
+            ),
+        ]
+    }
+}
@@ -0,0 +1,147 @@
+// polynomial expressions over the rows of the trace, to write a
+// transition constraint once, declaratively:
+//
+//     let transition = Expr::row(2) - Expr::next(0).square() - Expr::col(0).square();
+//     TransitionConstraint::from_expr(&exemptions, transition)
+//
+// the rows read, the frame size and the degree of the constraint are
+// derived from the expression, and the same expression is evaluated
+// by the prover on the frames of the lde domain and by the verifier
+// on the frames opened at the queries
+use std::ops::{Add, Mul, Neg, Sub};
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+
+#[derive(Clone, Debug)]
+pub enum Expr<F: IsField> {
+    // the trace k rows after the current one
+    Row(usize),
+    Constant(FieldElement<F>),
+    Sum(Box<Expr<F>>, Box<Expr<F>>),
+    Difference(Box<Expr<F>>, Box<Expr<F>>),
+    Product(Box<Expr<F>>, Box<Expr<F>>),
+    Negation(Box<Expr<F>>),
+    Power(Box<Expr<F>>, u64),
+}
+
+impl<F: IsField> Expr<F> {
+
+    // the column on the current row. the trace has a single column,
+    // the one of index 0
+    pub fn col(column: usize) -> Self {
+        assert_eq!(column, 0, "the trace has a single column");
+        Expr::Row(0)
+    }
+
+    // the column on the next row
+    pub fn next(column: usize) -> Self {
+        assert_eq!(column, 0, "the trace has a single column");
+        Expr::Row(1)
+    }
+
+    // the trace k rows after the current one, for frames wider than
+    // two rows
+    pub fn row(k: usize) -> Self {
+        Expr::Row(k)
+    }
+
+    pub fn constant(value: FieldElement<F>) -> Self {
+        Expr::Constant(value)
+    }
+
+    pub fn pow(self, exponent: u64) -> Self {
+        Expr::Power(Box::new(self), exponent)
+    }
+
+    pub fn square(self) -> Self {
+        self.pow(2)
+    }
+
+    // degree of the expression as a polynomial in the rows, an upper
+    // bound when terms cancel
+    pub fn degree(&self) -> usize {
+        match self {
+            Expr::Row(_) => 1,
+            Expr::Constant(_) => 0,
+            Expr::Sum(lhs, rhs) | Expr::Difference(lhs, rhs) => lhs.degree().max(rhs.degree()),
+            Expr::Product(lhs, rhs) => lhs.degree() + rhs.degree(),
+            Expr::Negation(inner) => inner.degree(),
+            Expr::Power(base, exponent) => base.degree() * *exponent as usize,
+        }
+    }
+
+    // the rows read by the expression, sorted without repetitions
+    pub fn offsets(&self) -> Vec<usize> {
+        let mut offsets = Vec::new();
+        self.collect_offsets(&mut offsets);
+        offsets.sort_unstable();
+        offsets.dedup();
+        offsets
+    }
+
+    fn collect_offsets(&self, offsets: &mut Vec<usize>) {
+        match self {
+            Expr::Row(k) => offsets.push(*k),
+            Expr::Constant(_) => {},
+            Expr::Sum(lhs, rhs) | Expr::Difference(lhs, rhs) | Expr::Product(lhs, rhs) => {
+                lhs.collect_offsets(offsets);
+                rhs.collect_offsets(offsets);
+            },
+            Expr::Negation(inner) | Expr::Power(inner, _) => inner.collect_offsets(offsets),
+        }
+    }
+
+    // evaluates the expression on a frame of consecutive rows
+    pub fn evaluate(&self, frame: &[FieldElement<F>]) -> FieldElement<F> {
+        match self {
+            Expr::Row(k) => frame[*k].clone(),
+            Expr::Constant(value) => value.clone(),
+            Expr::Sum(lhs, rhs) => lhs.evaluate(frame) + rhs.evaluate(frame),
+            Expr::Difference(lhs, rhs) => lhs.evaluate(frame) - rhs.evaluate(frame),
+            Expr::Product(lhs, rhs) => lhs.evaluate(frame) * rhs.evaluate(frame),
+            Expr::Negation(inner) => -inner.evaluate(frame),
+            Expr::Power(base, exponent) => base.evaluate(frame).pow(*exponent),
+        }
+    }
+}
+
+impl<F: IsField> From<FieldElement<F>> for Expr<F> {
+    fn from(value: FieldElement<F>) -> Self {
+        Expr::Constant(value)
+    }
+}
+
+impl<F: IsField, R: Into<Expr<F>>> Add<R> for Expr<F> {
+    type Output = Expr<F>;
+
+    fn add(self, rhs: R) -> Expr<F> {
+        Expr::Sum(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<F: IsField, R: Into<Expr<F>>> Sub<R> for Expr<F> {
+    type Output = Expr<F>;
+
+    fn sub(self, rhs: R) -> Expr<F> {
+        Expr::Difference(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<F: IsField, R: Into<Expr<F>>> Mul<R> for Expr<F> {
+    type Output = Expr<F>;
+
+    fn mul(self, rhs: R) -> Expr<F> {
+        Expr::Product(Box::new(self), Box::new(rhs.into()))
+    }
+}
+
+impl<F: IsField> Neg for Expr<F> {
+    type Output = Expr<F>;
+
+    fn neg(self) -> Expr<F> {
+        Expr::Negation(Box::new(self))
+    }
+}
@@ -2,25 +2,72 @@ use lambdaworks_math::field::traits::IsPrimeField;
 use lambdaworks_math::traits::{AsBytes, ByteConversion};
 use lambdaworks_math::field::{
     element::FieldElement,
-    traits::{IsField, IsFFTField}
+    traits::{IsField, IsFFTField, RootsConfig}
 };
 use lambdaworks_math::polynomial::Polynomial;
-use lambdaworks_crypto::merkle_tree::{
-    merkle::MerkleTree,
-    backends::types::Keccak256Backend, 
-    proof::Proof
-};
+use lambdaworks_math::fft::cpu::roots_of_unity;
+use lambdaworks_crypto::merkle_tree::proof::Proof;
 use lambdaworks_crypto::fiat_shamir::{
     is_transcript::IsTranscript,
     default_transcript::DefaultTranscript
 };
 
-use crate::poly;
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::channel::{self, Channel};
+use crate::domain;
+use crate::common::{self, InclusionProof};
+use crate::merkle::{MerkleBackend, Tree};
+use crate::report::Mismatch;
 
 pub type FriCommitment<F> = Vec<FriLayer<F>>;
 
+// evaluations of a layer polynomial together with their merkle tree
+type CommittedLayer<F> = (Vec<FieldElement<F>>, Tree<F>);
+
+// coefficients of the polynomial of the last layer, see FriOptions::max_layers
+type Remainder<F> = Vec<FieldElement<F>>;
+
+// keeps the evaluations of the committed layers across prover runs
+pub(crate) trait LayerStore<F: IsField> {
+    fn load_layer(&self, layer: usize) -> Option<Vec<FieldElement<F>>>;
+    fn save_layer(&self, layer: usize, evals: &[FieldElement<F>]);
+}
+
+// state reused by the commit phase from one proof to the next: the
+// fft twiddles of the first layer. the folded layers are evaluated
+// from the previous layer, so they need no fft nor coefficient buffer
+pub struct FriWorkspace<F: IsFFTField> {
+    twiddles: Vec<FieldElement<F>>,
+}
+
+impl<F: IsFFTField> FriWorkspace<F> {
+
+    pub fn new() -> Self {
+        Self {
+            twiddles: vec![],
+        }
+    }
+
+    // makes the twiddles cover a domain of the given size
+    fn reserve_twiddles(&mut self, size: usize) {
+        if self.twiddles.len() < size / 2 {
+            let order = size.trailing_zeros() as u64;
+            self.twiddles = roots_of_unity::get_twiddles::<F>(order, RootsConfig::BitReverse).unwrap();
+        }
+    }
+}
+
+impl<F: IsFFTField> Default for FriWorkspace<F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[derive(Clone)]
 pub struct ValidationData<F: IsField> {
+    pub eval: FieldElement<F>,
     pub proof: Proof<[u8; 32]>,
     pub sym_eval: FieldElement<F>,
     pub sym_proof: Proof<[u8; 32]>,
@@ -30,175 +77,933 @@ pub struct ValidationData<F: IsField> {
 pub struct FriLayer<F: IsField> {
     pub root: [u8; 32],
     pub validation_data: Vec<ValidationData<F>>,
+    // openings at the squares of the previous layer queries,
+    // only present when queries are sampled per layer
+    pub landing_data: Vec<InclusionProof<F>>,
+    // the coefficients of the polynomial of the last layer, sent in the
+    // clear when the layers stop before the constant one, see max_layers
+    pub remainder: Vec<FieldElement<F>>,
+}
+
+// how the query indices of each layer are obtained
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QuerySampling {
+    // the first layer indices folded down to each layer domain
+    #[default]
+    Folded,
+    // fresh indices sampled from the transcript for each layer
+    PerLayer,
+}
+
+// how the coset offset of each layer follows from the one before it.
+// the folding of a layer over offset * <w> is a value at index j of the
+// squared coset offset^2 * <w^2>, taken as the one at index j of the
+// coset of the next layer: that of the folded polynomial scaled to it,
+// of the same degree, so that no layer is evaluated again
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OffsetSchedule {
+    // the squared offset, the coset the folding lands on
+    #[default]
+    Squared,
+    // the offset of the first layer on every layer
+    Fixed,
+    // an offset sampled from the transcript after each beta
+    Sampled,
+}
+
+#[derive(Clone)]
+pub struct FriOptions {
+    pub num_queries: usize,
+    pub query_sampling: QuerySampling,
+    pub offset_schedule: OffsetSchedule,
+    // at most this many layers are committed, at least the first one.
+    // when the foldings stop before the constant layer, the polynomial
+    // of the last layer is sent instead, smaller proofs made faster
+    pub max_layers: Option<usize>,
+    pub merkle_backend: MerkleBackend,
 }
 
+// read access to committed fri layers, so that they can be checked
+// both decoded and in place in an encoded proof
+pub trait LayerAccess<F: IsField> {
+    fn layer_count(&self) -> usize;
+    fn layer_root(&self, layer: usize) -> [u8; 32];
+    fn layer(&self, layer: usize) -> Cow<'_, FriLayer<F>>;
+}
+
+impl<F: IsField> LayerAccess<F> for [FriLayer<F>] {
+
+    fn layer_count(&self) -> usize {
+        self.len()
+    }
+
+    fn layer_root(&self, layer: usize) -> [u8; 32] {
+        self[layer].root
+    }
+
+    fn layer(&self, layer: usize) -> Cow<'_, FriLayer<F>> {
+        Cow::Borrowed(&self[layer])
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FriError {
+    // the commitment does not have one layer per folding
+    LayerCount { expected: usize, received: usize },
+    // a layer does not open one value per query
+    QueryCount { layer: usize },
+    // an opening is not included in its layer commitment
+    Inclusion { layer: usize, query: usize },
+    // the folding of the previous layer does not land on the opened value
+    Folding { layer: usize, query: usize },
+    // the last layer is not constant
+    NotConstant { query: usize },
+    // the remainder does not have one coefficient per degree below the
+    // bound of the last layer, none if the last layer is the constant one
+    RemainderSize { expected: usize, received: usize },
+    // the last layer does not evaluate to the remainder
+    Remainder { query: usize },
+    // the degree bound is zero, or the domain is not a power of two of
+    // at least two elements
+    Parameters { degree_bound: usize, domain_size: usize },
+}
+
+impl FriError {
+
+    pub fn layer(&self) -> Option<usize> {
+        match self {
+            FriError::LayerCount { .. } | FriError::NotConstant { .. } => None,
+            FriError::RemainderSize { .. } | FriError::Remainder { .. } => None,
+            FriError::Parameters { .. } => None,
+            FriError::QueryCount { layer } => Some(*layer),
+            FriError::Inclusion { layer, .. } => Some(*layer),
+            FriError::Folding { layer, .. } => Some(*layer),
+        }
+    }
+
+    pub fn query(&self) -> Option<usize> {
+        match self {
+            FriError::LayerCount { .. } | FriError::QueryCount { .. } => None,
+            FriError::RemainderSize { .. } | FriError::Parameters { .. } => None,
+            FriError::Inclusion { query, .. } => Some(*query),
+            FriError::Folding { query, .. } => Some(*query),
+            FriError::NotConstant { query } => Some(*query),
+            FriError::Remainder { query } => Some(*query),
+        }
+    }
+}
+
+impl fmt::Display for FriError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FriError::LayerCount { expected, received } => write!(f, "expected {} fri layers, received {}", expected, received),
+            FriError::QueryCount { layer } => write!(f, "wrong number of openings in fri layer {}", layer),
+            FriError::Inclusion { layer, query } => write!(f, "fri layer {} opening not included at query {}", layer, query),
+            FriError::Folding { layer, query } => write!(f, "fri layer {} inconsistent with its folding at query {}", layer, query),
+            FriError::NotConstant { query } => write!(f, "last fri layer not constant at query {}", query),
+            FriError::RemainderSize { expected, received } => write!(f, "expected {} remainder coefficients, received {}", expected, received),
+            FriError::Remainder { query } => write!(f, "last fri layer inconsistent with the remainder at query {}", query),
+            FriError::Parameters { degree_bound, domain_size } => write!(f, "no fri of degree bound {} over a domain of {} points", degree_bound, domain_size),
+        }
+    }
+}
+
+impl std::error::Error for FriError {}
+
+#[derive(Clone)]
+pub struct LowDegreeProof<F: IsField> {
+    pub commitment: FriCommitment<F>,
+}
+
+// commits to a polynomial of degree less than degree_bound and opens
+// it at the queries. the number of layers follows from the declared
+// degree bound, the one the verifier checks against
+#[allow(clippy::too_many_arguments)]
 pub fn commit_and_fold<F>(
         polynomial: &Polynomial<FieldElement<F>>,
-        mut domain_size: usize,
+        domain_size: usize,
         offset: &FieldElement<F>,
+        degree_bound: usize,
         query_indices: Vec<usize>,
-        transcript: &mut DefaultTranscript<F>
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>,
+        workspace: &mut FriWorkspace<F>
     ) -> Vec<FriLayer<F>>
     where
         F: IsField + IsFFTField + IsPrimeField,
         FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
 
-    let mut polynomial = polynomial.clone();
-    let mut offset = offset.clone();
-    let number_of_foldings = (usize::BITS - polynomial.degree().leading_zeros()) as usize;
-    let mut fri_layers = Vec::<FriLayer<F>>::with_capacity(number_of_foldings + 1);
+    workspace.reserve_twiddles(domain_size);
+    let mut evaluations = Vec::with_capacity(domain_size);
+    domain::coset_fft(polynomial.coefficients(), offset, domain_size, &workspace.twiddles, &mut evaluations);
+    commit_and_fold_evaluations(evaluations, offset, degree_bound, query_indices, options, transcript)
+}
 
-    // commit to evaluations
-    let (eval, tree) = commit(&polynomial, domain_size, &offset);
-    transcript.append_bytes(&tree.root);
-
-    // Generate inclusion proofs, validation data and append to layer
-    fri_layers.push(
-        FriLayer {
-            root: tree.root,
-            validation_data: query_indices.iter().map(|i| { 
-                let idx = i.to_owned();
-                let sym_idx = (idx + domain_size / 2) % domain_size;
-        
-                ValidationData {
-                    proof: tree.get_proof_by_pos(idx).unwrap(),
-                    sym_eval: eval[sym_idx].to_owned(),
-                    sym_proof: tree.get_proof_by_pos(sym_idx).unwrap()
-                }
-            })
-            .collect::<Vec<ValidationData<F>>>()
-        }
+// same as commit_and_fold, from the evaluations of the polynomial over
+// the coset of size evaluations.len() shifted by offset, which are the
+// first layer as they are, with no interpolation
+pub fn commit_and_fold_evaluations<F>(
+        evaluations: Vec<FieldElement<F>>,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        query_indices: Vec<usize>,
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>
+    ) -> Vec<FriLayer<F>>
+    where
+        F: IsField + IsFFTField + IsPrimeField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    commit_and_fold_resumable(evaluations, offset, degree_bound, query_indices, options, transcript, None, false).0
+}
+
+// same as commit_and_fold_evaluations, saving the evaluations of every
+// folded layer to the store and reusing the ones it already holds. a
+// reused layer is only rehashed, its root is absorbed as if just
+// committed. also returns the betas sampled for the foldings. with
+// check_degrees, debug builds interpolate every layer and panic if it
+// is not within its degree bound, which only the honest prover asks
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn commit_and_fold_resumable<F>(
+        evaluations: Vec<FieldElement<F>>,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        query_indices: Vec<usize>,
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>,
+        store: Option<&dyn LayerStore<F>>,
+        check_degrees: bool
+    ) -> (Vec<FriLayer<F>>, Vec<FieldElement<F>>)
+    where
+        F: IsField + IsFFTField + IsPrimeField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    let domain_size = evaluations.len();
+    let (schedule, remainder_bound) = committed_schedule(degree_bound, domain_size, options.max_layers).expect("the fri parameters of the statement are valid");
+    let degree_bounds = (check_degrees && cfg!(debug_assertions)).then_some(schedule.as_slice());
+    let (committed_layers, betas, remainder) = commit_phase(
+        evaluations,
+        offset,
+        schedule.len() - 1,
+        remainder_bound,
+        options.offset_schedule,
+        options.merkle_backend,
+        transcript,
+        store,
+        degree_bounds
+    );
+
+    let layer_indices = layer_query_indices(
+        &query_indices,
+        domain_size,
+        committed_layers.len(),
+        options,
+        transcript
+    );
+
+    (query_phase(&committed_layers, &layer_indices, options.query_sampling, remainder), betas)
+}
+
+pub fn decommit_and_fold<F>(
+        layers: &[FriLayer<F>],
+        domain_size: &usize,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        query_indices: &[usize],
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>
+    ) -> Result<(), FriError>
+    where
+        F: IsField + IsFFTField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    decommit_and_fold_with_mismatch(layers, domain_size, offset, degree_bound, query_indices, options, transcript, &mut None)
+}
+
+// same as decommit_and_fold, additionally recording the expected and
+// received values of the failed check in debug builds
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn decommit_and_fold_with_mismatch<F, L>(
+        layers: &L,
+        domain_size: &usize,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        query_indices: &[usize],
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>,
+        mismatch: &mut Option<Mismatch>
+    ) -> Result<(), FriError>
+    where
+        F: IsField + IsFFTField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send,
+        L: LayerAccess<F> + ?Sized {
+
+    // the number of layers is fixed by the degree bound, not by the prover
+    let (schedule, remainder_bound) = committed_schedule(degree_bound, *domain_size, options.max_layers)?;
+    if layers.layer_count() != schedule.len() {
+        return Err(FriError::LayerCount { expected: schedule.len(), received: layers.layer_count() })
+    }
+
+    let (betas, offsets, remainder) = replay_commitments(layers, offset, options.offset_schedule, transcript);
+    check_remainder_size(&remainder, remainder_bound)?;
+    let layer_indices = layer_query_indices(query_indices, *domain_size, layers.layer_count(), options, transcript);
+
+    check_layers(layers, &betas, *domain_size, &offsets, &layer_indices, options.query_sampling, options.merkle_backend, mismatch)?;
+    check_remainder(layers, &remainder, *domain_size, &offsets, &layer_indices, options.query_sampling, mismatch)
+}
+
+// proves that the evaluations, taken over the coset of size
+// evaluations.len() shifted by offset, belong to a polynomial
+// of degree less than degree_bound. the proof is independent
+// of any other protocol, it uses its own transcript
+pub fn prove_low_degree<F>(
+        evaluations: &[FieldElement<F>],
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        options: &FriOptions
+    ) -> Result<LowDegreeProof<F>, FriError>
+    where
+        F: IsField + IsFFTField + IsPrimeField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    let domain_size = evaluations.len();
+    let mut transcript = low_degree_transcript(domain_size, offset, degree_bound, options);
+
+    // the evaluations are the first layer, nothing is interpolated
+    let (schedule, remainder_bound) = committed_schedule(degree_bound, domain_size, options.max_layers)?;
+    let (committed_layers, _, remainder) = commit_phase(
+        evaluations.to_vec(),
+        offset,
+        schedule.len() - 1,
+        remainder_bound,
+        options.offset_schedule,
+        options.merkle_backend,
+        &mut transcript,
+        None,
+        None
     );
 
-    // recursive foldings
-    for _ in 1..=number_of_foldings {
+    // queries are sampled only after all layers are committed
+    let query_indices = common::sample_queries(options.num_queries, domain_size, &mut transcript).expect("the first layer has points");
+    let layer_indices = layer_query_indices(
+        &query_indices,
+        domain_size,
+        committed_layers.len(),
+        options,
+        &mut transcript
+    );
+
+    Ok(LowDegreeProof {
+        commitment: query_phase(&committed_layers, &layer_indices, options.query_sampling, remainder)
+    })
+}
+
+// verifies a proof generated by prove_low_degree for the same
+// domain, degree bound and options
+pub fn verify_low_degree<F>(
+        proof: &LowDegreeProof<F>,
+        domain_size: usize,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        options: &FriOptions
+    ) -> Result<(), FriError>
+    where
+        F: IsField + IsFFTField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    let LowDegreeProof{commitment} = proof;
+    let mut transcript = low_degree_transcript(domain_size, offset, degree_bound, options);
+
+    // the number of layers is fixed by the degree bound, not by the prover
+    let (schedule, remainder_bound) = committed_schedule(degree_bound, domain_size, options.max_layers)?;
+    if commitment.len() != schedule.len() {
+        return Err(FriError::LayerCount { expected: schedule.len(), received: commitment.len() })
+    }
+
+    let (betas, offsets, remainder) = replay_commitments(commitment.as_slice(), offset, options.offset_schedule, &mut transcript);
+    check_remainder_size(&remainder, remainder_bound)?;
+    let query_indices = common::sample_queries(options.num_queries, domain_size, &mut transcript).expect("the first layer has points");
+    let layer_indices = layer_query_indices(
+        &query_indices,
+        domain_size,
+        commitment.len(),
+        options,
+        &mut transcript
+    );
+
+    check_layers(commitment.as_slice(), &betas, domain_size, &offsets, &layer_indices, options.query_sampling, options.merkle_backend, &mut None)?;
+    check_remainder(commitment.as_slice(), &remainder, domain_size, &offsets, &layer_indices, options.query_sampling, &mut None)
+}
+
+// number of foldings that reduce a polynomial of degree less than
+// degree_bound to a constant. the domain is never folded below two
+// elements since merkle trees of a single leaf cannot be built
+pub(crate) fn number_of_foldings(degree_bound: usize, domain_size: usize) -> Result<usize, FriError> {
+    let degree = degree_bound.checked_sub(1);
+    let halvings = (domain_size.trailing_zeros() as usize).checked_sub(1).filter(|_| domain_size.is_power_of_two());
+    match (degree, halvings) {
+        (Some(degree), Some(halvings)) => Ok(((usize::BITS - degree.leading_zeros()) as usize).min(halvings)),
+        _ => Err(FriError::Parameters { degree_bound, domain_size }),
+    }
+}
+
+// the degree bound of every layer, from the one of the first layer: a
+// folding of a polynomial of degree less than d is of degree less than
+// d/2 rounded up, down to the constants of the last layer
+pub fn degree_schedule(degree_bound: usize, domain_size: usize) -> Result<Vec<usize>, FriError> {
+    Ok((0..=number_of_foldings(degree_bound, domain_size)?)
+        .map(|layer| degree_bound.div_ceil(1 << layer))
+        .collect::<Vec<usize>>())
+}
+
+// the degree bounds of the committed layers, at most max_layers of
+// them, with the degree bound of the remainder when the layers stop
+// before the constant one
+pub(crate) fn committed_schedule(
+        degree_bound: usize,
+        domain_size: usize,
+        max_layers: Option<usize>
+    ) -> Result<(Vec<usize>, Option<usize>), FriError> {
+
+    let mut schedule = degree_schedule(degree_bound, domain_size)?;
+    let num_layers = max_layers.map_or(schedule.len(), |max_layers| max_layers.clamp(1, schedule.len()));
+    let remainder_bound = (num_layers < schedule.len()).then(|| schedule[num_layers - 1]);
+    schedule.truncate(num_layers);
+    Ok((schedule, remainder_bound))
+}
+
+fn low_degree_transcript<F>(
+        domain_size: usize,
+        offset: &FieldElement<F>,
+        degree_bound: usize,
+        options: &FriOptions
+    ) -> DefaultTranscript<F>
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    let mut transcript = DefaultTranscript::<F>::new(&[]);
+    transcript.append_bytes(&domain_size.to_be_bytes());
+    transcript.append_bytes(&common::fe_to_bytes_be(offset));
+    transcript.append_bytes(&degree_bound.to_be_bytes());
+    transcript.append_bytes(&options.num_queries.to_be_bytes());
+    transcript.append_bytes(&[options.query_sampling as u8]);
+    // absorbed only when not the default, so that the proofs of the
+    // squared offsets are unchanged
+    if options.offset_schedule != OffsetSchedule::Squared {
+        transcript.append_bytes(&[options.offset_schedule as u8]);
+    }
+    if let Some(max_layers) = options.max_layers {
+        transcript.append_bytes(&max_layers.to_be_bytes());
+    }
+    let merkle_backend = options.merkle_backend.resolve();
+    if merkle_backend != MerkleBackend::Keccak256 {
+        transcript.append_bytes(&[merkle_backend as u8]);
+    }
+    transcript
+}
+
+// the offset of the coset of the layer after the one over offset * <w>,
+// the first layer being over first * <w>
+fn next_offset<F>(
+        offset_schedule: OffsetSchedule,
+        offset: &FieldElement<F>,
+        first: &FieldElement<F>,
+        transcript: &mut impl Channel<F>
+    ) -> FieldElement<F>
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
+
+    match offset_schedule {
+        OffsetSchedule::Squared => offset.square(),
+        OffsetSchedule::Fixed => first.clone(),
+        OffsetSchedule::Sampled => {
+            transcript.label("fri_layer_offset", channel::SAMPLED_ELEMENT);
+            transcript.sample_field_element()
+        },
+    }
+}
+
+// commits to the evaluations of the polynomial and of each of its
+// foldings, appending every root to the transcript. given the degree
+// bound of a remainder, the last layer is also interpolated and its
+// coefficients appended. returns the committed layers with the betas
+// of the foldings and the remainder. given the degree bounds of the
+// layers, checks every layer against its own
+#[allow(clippy::too_many_arguments)]
+fn commit_phase<F>(
+        evaluations: Vec<FieldElement<F>>,
+        first_offset: &FieldElement<F>,
+        number_of_foldings: usize,
+        remainder_bound: Option<usize>,
+        offset_schedule: OffsetSchedule,
+        merkle_backend: MerkleBackend,
+        transcript: &mut impl Channel<F>,
+        store: Option<&dyn LayerStore<F>>,
+        degree_bounds: Option<&[usize]>
+    ) -> (Vec<CommittedLayer<F>>, Vec<FieldElement<F>>, Remainder<F>)
+    where
+        F: IsField + IsFFTField + IsPrimeField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    let mut domain_size = evaluations.len();
+    let mut offset = first_offset.clone();
+    let mut committed_layers = Vec::<CommittedLayer<F>>::with_capacity(number_of_foldings + 1);
+    let mut betas = Vec::<FieldElement<F>>::with_capacity(number_of_foldings);
+    let check_degree = |layer: usize, eval: &[FieldElement<F>], offset: &FieldElement<F>| {
+        if let Some(degree_bounds) = degree_bounds {
+            let degree = Polynomial::interpolate_offset_fft(eval, offset).unwrap().degree();
+            assert!(
+                degree < degree_bounds[layer],
+                "fri layer {} has degree {}, not below its degree bound {}",
+                layer, degree, degree_bounds[layer]
+            );
+        }
+    };
+
+    // commit to evaluations
+    check_degree(0, &evaluations, &offset);
+    let tree = merkle_backend.build(&evaluations);
+    transcript.label("fri_layer_root", channel::DIGEST);
+    transcript.append_bytes(&tree.root());
+    committed_layers.push((evaluations, tree));
+
+    // recursive foldings, each layer is evaluated from the evaluations
+    // of the previous one without interpolating it
+    for layer in 1..=number_of_foldings {
+        transcript.label("fri_beta", channel::SAMPLED_ELEMENT);
         let beta = transcript.sample_field_element();
 
-        (polynomial, domain_size, offset) = fold(polynomial, domain_size, offset, beta);
+        let (previous_eval, _) = &committed_layers[layer - 1];
+        let eval = stored_or(store, layer, domain_size / 2, || fold_evaluations(previous_eval, &offset, &beta));
+        domain_size /= 2;
+        offset = next_offset(offset_schedule, &offset, first_offset, transcript);
+        check_degree(layer, &eval, &offset);
+
+        let tree = merkle_backend.build(&eval);
+        transcript.label("fri_layer_root", channel::DIGEST);
+        transcript.append_bytes(&tree.root());
+        committed_layers.push((eval, tree));
+        betas.push(beta);
+    }
+
+    // the coefficients of the last layer over its coset, padded with
+    // zeros up to its degree bound
+    let mut remainder = Remainder::<F>::new();
+    if let Some(remainder_bound) = remainder_bound {
+        let (last_eval, _) = committed_layers.last().unwrap();
+        remainder = Polynomial::interpolate_offset_fft(last_eval, &offset).unwrap().coefficients().to_vec();
+        remainder.resize(remainder_bound, FieldElement::<F>::zero());
+        transcript.label("fri_remainder", channel::ELEMENT_BE);
+        for coefficient in &remainder {
+            transcript.append_bytes(&common::fe_to_bytes_be(coefficient));
+        }
+    }
+
+    (committed_layers, betas, remainder)
+}
 
-        let (eval, tree) = commit(&polynomial, domain_size, &offset);
-        transcript.append_bytes(&tree.root);
+// returns the query indices of every layer. with per layer
+// sampling, the indices of the inner layers are sampled from
+// the transcript and the last layer is only reached by landings,
+// the first layer being queried at the given indices
+fn layer_query_indices<'a, F>(
+        query_indices: &'a [usize],
+        domain_size: usize,
+        num_layers: usize,
+        options: &FriOptions,
+        transcript: &mut impl Channel<F>
+    ) -> Vec<Cow<'a, [usize]>>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + ByteConversion {
+
+    match options.query_sampling {
+        QuerySampling::Folded => (0..num_layers)
+            .map(|i| {
+                query_indices
+                    .iter()
+                    .map(|idx| idx % (domain_size >> i))
+                    .collect::<Vec<usize>>()
+                    .into()
+            })
+            .collect::<Vec<Cow<[usize]>>>(),
+        QuerySampling::PerLayer => {
+            let mut layer_indices = vec![Cow::Borrowed(query_indices)];
+            transcript.label("fri_layer_query_index", channel::SAMPLED_INDEX);
+            for i in 1..num_layers.saturating_sub(1) {
+                // no layer is folded below two points
+                layer_indices.push(
+                    common::sample_queries(options.num_queries, domain_size >> i, transcript).expect("the layer has points").into()
+                );
+            }
+            if num_layers > 1 {
+                layer_indices.push(Cow::Borrowed(&[]));
+            }
+            layer_indices
+        }
+    }
+}
+
+// generates inclusion proofs of every committed layer at its
+// query indices and at their symmetric indices, the remainder
+// going with the last layer
+fn query_phase<F>(
+        committed_layers: &[CommittedLayer<F>],
+        layer_indices: &[Cow<[usize]>],
+        query_sampling: QuerySampling,
+        remainder: Remainder<F>
+    ) -> Vec<FriLayer<F>>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + Sync + Send {
+
+    let mut layers = committed_layers
+        .iter()
+        .enumerate()
+        .map(|(i, (eval, tree))| {
+            let domain_size = eval.len();
+            let landing_indices = match query_sampling {
+                QuerySampling::PerLayer if i > 0 => layer_indices[i - 1].as_ref(),
+                _ => &[],
+            };
 
-        // append layer
-        fri_layers.push(
             FriLayer {
-                root: tree.root,
-                validation_data: query_indices.iter().map(|i| { 
-                    let idx = i.to_owned() % domain_size;
+                root: tree.root(),
+                validation_data: common::parallel_map(&layer_indices[i], |idx| {
                     let sym_idx = (idx + domain_size / 2) % domain_size;
-        
+
                     ValidationData {
-                        proof: tree.get_proof_by_pos(idx).unwrap(),
+                        eval: eval[*idx].to_owned(),
+                        proof: tree.get_proof_by_pos(*idx).unwrap(),
                         sym_eval: eval[sym_idx].to_owned(),
                         sym_proof: tree.get_proof_by_pos(sym_idx).unwrap()
                     }
-                })
-                .collect::<Vec<ValidationData<F>>>()
+                }),
+                landing_data: common::parallel_map(landing_indices, |idx| {
+                    let idx = idx % domain_size;
+                    InclusionProof(eval[idx].to_owned(), tree.get_proof_by_pos(idx).unwrap())
+                }),
+                remainder: vec![],
             }
-        );
+        })
+        .collect::<Vec<FriLayer<F>>>();
+    if let Some(last) = layers.last_mut() {
+        last.remainder = remainder;
     }
+    layers
+}
 
-    fri_layers
+// absorbs the layer roots and the remainder into the transcript in
+// the same order as the commit phase and returns the sampled folding
+// challenges, with the coset offset of every layer and the remainder
+fn replay_commitments<F, L>(
+        layers: &L,
+        first_offset: &FieldElement<F>,
+        offset_schedule: OffsetSchedule,
+        transcript: &mut impl Channel<F>
+    ) -> (Vec<FieldElement<F>>, Vec<FieldElement<F>>, Remainder<F>)
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion,
+        L: LayerAccess<F> + ?Sized {
+
+    transcript.append_bytes(&layers.layer_root(0));
+
+    let mut betas = Vec::<FieldElement<F>>::with_capacity(layers.layer_count());
+    let mut offsets = vec![first_offset.clone()];
+    for i in 1..layers.layer_count() {
+        betas.push(transcript.sample_field_element());
+        offsets.push(next_offset(offset_schedule, &offsets[i - 1], first_offset, transcript));
+        transcript.append_bytes(&layers.layer_root(i));
+    }
+    // only the remainder of the last layer is copied, not its openings
+    let remainder = match layers.layer(layers.layer_count() - 1) {
+        Cow::Borrowed(layer) => layer.remainder.clone(),
+        Cow::Owned(layer) => layer.remainder,
+    };
+    for coefficient in &remainder {
+        transcript.append_bytes(&common::fe_to_bytes_be(coefficient));
+    }
+    (betas, offsets, remainder)
 }
 
-pub fn decommit_and_fold<F>(
-        layers: &[FriLayer<F>],
-        domain_size: &usize,
-        query_indices: &[usize],
-        queries: &[FieldElement<F>],
-        query_evals: &[FieldElement<F>],
-        transcript: &mut DefaultTranscript<F>
-    ) -> bool
+// verifies the inclusion proofs of every layer and the consistency
+// between the evaluations of consecutive layers
+#[allow(clippy::too_many_arguments)]
+fn check_layers<F, L>(
+        layers: &L,
+        betas: &[FieldElement<F>],
+        domain_size: usize,
+        offsets: &[FieldElement<F>],
+        layer_indices: &[Cow<[usize]>],
+        query_sampling: QuerySampling,
+        merkle_backend: MerkleBackend,
+        mismatch: &mut Option<Mismatch>
+    ) -> Result<(), FriError>
     where
         F: IsField + IsFFTField,
-        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send,
+        L: LayerAccess<F> + ?Sized {
 
-    let mut domain_size = domain_size.to_owned();
-    let mut queries = queries.to_owned();
-    let mut query_evals = query_evals.to_owned();
-    let mut sym_evals = query_evals.clone();
+    let num_layers = layers.layer_count();
+    let mut domain_size = domain_size;
+    let mut w = F::get_primitive_root_of_unity(domain_size.trailing_zeros() as u64).unwrap();
 
-    // commit to evaluations
-    let FriLayer{root, validation_data} = &layers[0];
-    transcript.append_bytes(root);
+    // evaluations of the current layer obtained by folding the previous one
+    let mut folded_evals = Vec::<FieldElement<F>>::new();
+    // inverses of the query points of the current layer
+    let mut query_invs = Vec::<FieldElement<F>>::new();
+    let two_inv = FieldElement::<F>::from(2_u64).inv().unwrap();
 
-    // verify first layer inclusion proofs and get next layer queries
-    let num_queries = query_indices.len();
-    for i in 0..num_queries {
-        let idx = query_indices[i];
-        let sym_idx = (idx + domain_size / 2) % domain_size;
-        let eval = &query_evals[i];
-        let ValidationData{proof, sym_eval, sym_proof} = &validation_data[i];
-        sym_evals[i] = sym_eval.clone();
+    for i in 0..num_layers {
+        let layer = layers.layer(i);
+        let FriLayer{root, validation_data, landing_data, ..} = layer.as_ref();
 
-        if !proof.verify::<Keccak256Backend<F>>(root, idx, eval) || !sym_proof.verify::<Keccak256Backend<F>>(root, sym_idx, sym_eval) {
-            return false            
+        if validation_data.len() != layer_indices[i].len() {
+            return Err(FriError::QueryCount { layer: i })
         }
-    };
 
-    // recursive foldings
-    for layer in layers.iter().skip(1) {
-        let beta = transcript.sample_field_element();
-        
-        domain_size /= 2;
-        
-        let FriLayer{root, validation_data} = layer;
-        transcript.append_bytes(root);
+        // values of the layer reached by the queries of the previous one
+        let layer_evals = if query_sampling == QuerySampling::PerLayer && i > 0 {
+            if landing_data.len() != folded_evals.len() {
+                return Err(FriError::QueryCount { layer: i })
+            }
+            for (j, InclusionProof(eval, proof)) in landing_data.iter().enumerate() {
+                let idx = layer_indices[i - 1][j] % domain_size;
+                if !merkle_backend.verify(proof, root, idx, eval) {
+                    record_root_mismatch(mismatch, merkle_backend, root, proof, idx, eval);
+                    return Err(FriError::Inclusion { layer: i, query: j })
+                }
+            }
+            landing_data
+                .iter()
+                .map(|InclusionProof(eval, _)| eval)
+                .collect::<Vec<&FieldElement<F>>>()
+        } else {
+            validation_data
+                .iter()
+                .map(|data| &data.eval)
+                .collect::<Vec<&FieldElement<F>>>()
+        };
 
-        for i in 0..num_queries {
-            query_evals[i] = curr_layer_query_evals(&queries[i], &query_evals[i], &sym_evals[i], &beta);
-            queries[i] = queries[i].square();
+        // the folding of the previous layer must land on the opened values
+        if i > 0 {
+            if let Some(j) = (0..layer_evals.len()).find(|j| *layer_evals[*j] != folded_evals[*j]) {
+                record_mismatch(mismatch, &folded_evals[j], layer_evals[j]);
+                return Err(FriError::Folding { layer: i, query: j })
+            }
+        }
 
-            let idx = query_indices[i] % domain_size;
+        // check if all queries to the last polynomial are equal, unless
+        // it is sent as a remainder
+        if i + 1 == num_layers && layer.remainder.is_empty() {
+            if let Some(j) = (1..layer_evals.len()).find(|j| layer_evals[*j] != layer_evals[0]) {
+                record_mismatch(mismatch, layer_evals[0], layer_evals[j]);
+                return Err(FriError::NotConstant { query: j })
+            }
+        }
+
+        // only the inverses of the query points enter the foldings,
+        // all of them computed with a single inversion
+        if i == 0 || query_sampling == QuerySampling::PerLayer {
+            query_invs = domain::batch_inverses(&domain::coset_points(&offsets[i], &w, domain_size, &layer_indices[i]));
+        }
+
+        folded_evals.clear();
+        for (j, idx) in layer_indices[i].iter().enumerate() {
             let sym_idx = (idx + domain_size / 2) % domain_size;
-            let eval = &query_evals[i];
-            let ValidationData{proof, sym_eval, sym_proof} = &validation_data[i];
-            sym_evals[i] = sym_eval.clone();
+            let ValidationData{eval, proof, sym_eval, sym_proof} = &validation_data[j];
+
+            if !merkle_backend.verify(proof, root, *idx, eval) {
+                record_root_mismatch(mismatch, merkle_backend, root, proof, *idx, eval);
+                return Err(FriError::Inclusion { layer: i, query: j })
+            }
+            if !merkle_backend.verify(sym_proof, root, sym_idx, sym_eval) {
+                record_root_mismatch(mismatch, merkle_backend, root, sym_proof, sym_idx, sym_eval);
+                return Err(FriError::Inclusion { layer: i, query: j })
+            }
 
-            if !proof.verify::<Keccak256Backend<F>>(root, idx, eval) || !sym_proof.verify::<Keccak256Backend<F>>(root, sym_idx, sym_eval) {
-                return false            
+            // get next layer queries
+            if i + 1 < num_layers {
+                folded_evals.push(fold_at_inverse(&query_invs[j], eval, sym_eval, &betas[i], &two_inv));
+                query_invs[j] = query_invs[j].square();
             }
         }
+
+        // the squared queries are over the squared coset, moved to the
+        // coset of the next layer when it is another one
+        if i + 1 < num_layers && offsets[i + 1] != offsets[i].square() {
+            let shift_inv = offsets[i].square() * offsets[i + 1].inv().unwrap();
+            for query_inv in query_invs.iter_mut() {
+                *query_inv = &*query_inv * &shift_inv;
+            }
+        }
+
+        domain_size /= 2;
+        w = w.square();
     };
 
-    // check if all queries to the last polynomial are equal
-    if !query_evals.iter().all(|q| q == query_evals.first().unwrap()) {
-        return false
-    }
+    Ok(())
+}
+
+// the remainder has one coefficient per degree below the bound of the
+// last layer, or none when the last layer is the constant one
+fn check_remainder_size<F: IsField>(
+        remainder: &[FieldElement<F>],
+        remainder_bound: Option<usize>
+    ) -> Result<(), FriError> {
 
-    true
+    let expected = remainder_bound.unwrap_or(0);
+    if remainder.len() != expected {
+        return Err(FriError::RemainderSize { expected, received: remainder.len() })
+    }
+    Ok(())
 }
 
-fn commit<F>(
-        polynomial: &Polynomial<FieldElement<F>>,
+// checks that the values of the last layer, once checked by
+// check_layers, are the evaluations of the remainder if there is one
+fn check_remainder<F, L>(
+        layers: &L,
+        remainder: &[FieldElement<F>],
         domain_size: usize,
-        offset: &FieldElement<F>
-    ) -> (Vec<FieldElement<F>>, MerkleTree<Keccak256Backend<F>>)
+        offsets: &[FieldElement<F>],
+        layer_indices: &[Cow<[usize]>],
+        query_sampling: QuerySampling,
+        mismatch: &mut Option<Mismatch>
+    ) -> Result<(), FriError>
     where
         F: IsField + IsFFTField,
-        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+        FieldElement<F>: ByteConversion,
+        L: LayerAccess<F> + ?Sized {
+
+    if remainder.is_empty() {
+        return Ok(())
+    }
+
+    // the values of the last layer, opened at the queries or, with per
+    // layer sampling, landed on
+    let last = layers.layer_count() - 1;
+    let domain_size = domain_size >> last;
+    let w = F::get_primitive_root_of_unity(domain_size.trailing_zeros() as u64).unwrap();
+    let layer = layers.layer(last);
+    let (indices, values) = if query_sampling == QuerySampling::PerLayer && last > 0 {
+        (
+            layer_indices[last - 1]
+                .iter()
+                .map(|idx| idx % domain_size)
+                .collect::<Vec<usize>>()
+                .into(),
+            layer.landing_data
+                .iter()
+                .map(|InclusionProof(eval, _)| eval)
+                .collect::<Vec<&FieldElement<F>>>()
+        )
+    } else {
+        (
+            Cow::Borrowed(layer_indices[last].as_ref()),
+            layer.validation_data
+                .iter()
+                .map(|data| &data.eval)
+                .collect::<Vec<&FieldElement<F>>>()
+        )
+    };
+
+    let polynomial = Polynomial::new(remainder);
+    let points = domain::coset_points(&offsets[last], &w, domain_size, &indices);
+    for (j, (point, value)) in points.iter().zip(&values).enumerate() {
+        let expected = polynomial.evaluate(point);
+        if expected != **value {
+            record_mismatch(mismatch, &expected, value);
+            return Err(FriError::Remainder { query: j })
+        }
+    }
+    Ok(())
+}
+
+fn record_mismatch<F>(
+        mismatch: &mut Option<Mismatch>,
+        expected: &FieldElement<F>,
+        received: &FieldElement<F>
+    )
+    where
+        F: IsField,
+        FieldElement<F>: ByteConversion {
 
-    let eval = Polynomial::evaluate_offset_fft::<F>(
-        polynomial,
-        1, 
-        Some(domain_size),
-        offset
-    ).unwrap();
-    
-    let tree = MerkleTree::<Keccak256Backend<F>>::build(&eval);
+    if cfg!(debug_assertions) {
+        *mismatch = Some(Mismatch {
+            expected: common::bytes_to_hex(&common::fe_to_bytes_be(expected)),
+            received: common::bytes_to_hex(&common::fe_to_bytes_be(received)),
+        });
+    }
+}
+
+fn record_root_mismatch<F>(
+        mismatch: &mut Option<Mismatch>,
+        merkle_backend: MerkleBackend,
+        root: &[u8; 32],
+        proof: &Proof<[u8; 32]>,
+        index: usize,
+        eval: &FieldElement<F>
+    )
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + Sync + Send {
 
-    (eval, tree)
+    if cfg!(debug_assertions) {
+        *mismatch = Some(Mismatch {
+            expected: common::bytes_to_hex(root),
+            received: common::bytes_to_hex(&merkle_backend.root_from_path(proof, index, eval)),
+        });
+    }
 }
 
-fn fold<F: IsField>(
-        polynomial: Polynomial<FieldElement<F>>,
+// the layer evaluations held by the store, if it has them for this
+// domain, or else the computed ones once saved
+fn stored_or<F: IsField>(
+        store: Option<&dyn LayerStore<F>>,
+        layer: usize,
         domain_size: usize,
-        offset: FieldElement<F>,
-        beta: FieldElement<F>
-    ) -> (Polynomial<FieldElement<F>>, usize, FieldElement<F>) {
-    (poly::fold_polynomial(&polynomial, &beta),
-    domain_size / 2,
-    offset.square())
+        compute: impl FnOnce() -> Vec<FieldElement<F>>
+    ) -> Vec<FieldElement<F>> {
+
+    let Some(store) = store else {
+        return compute()
+    };
+    match store.load_layer(layer).filter(|eval| eval.len() == domain_size) {
+        Some(eval) => eval,
+        None => {
+            let eval = compute();
+            store.save_layer(layer, &eval);
+            eval
+        }
+    }
+}
+
+// evaluations of the folding of a polynomial over the squared coset,
+// from its evaluations over the coset offset * <w>. the points j and
+// j + n/2 of the coset are x and -x, and the folding at x^2 is
+// (f(x) + f(-x)) / 2 + beta * (f(x) - f(-x)) / (2 * x)
+pub(crate) fn fold_evaluations<F: IsFFTField>(
+        eval: &[FieldElement<F>],
+        offset: &FieldElement<F>,
+        beta: &FieldElement<F>
+    ) -> Vec<FieldElement<F>> {
+
+    let half = eval.len() / 2;
+    let w_inv = F::get_primitive_root_of_unity(eval.len().trailing_zeros() as u64).unwrap().inv().unwrap();
+    let two_inv = FieldElement::<F>::from(2_u64).inv().unwrap();
+    let (positive, negative) = eval.split_at(half);
+    positive
+        .iter()
+        .zip(negative)
+        .scan(offset.inv().unwrap() * &two_inv, |x_inv_half, (f_x, f_minus_x)| {
+            let folded = (f_x + f_minus_x) * &two_inv + beta * (f_x - f_minus_x) * &*x_inv_half;
+            *x_inv_half = &*x_inv_half * &w_inv;
+            Some(folded)
+        })
+        .collect::<Vec<FieldElement<F>>>()
 }
 
 pub fn curr_layer_query_evals<F: IsField>(
@@ -207,7 +1012,69 @@ pub fn curr_layer_query_evals<F: IsField>(
         sym_eval: &FieldElement<F>,
         beta: &FieldElement<F>,
     ) -> FieldElement<F> {
-    let query_inv = query.inv().unwrap();
     let two_inv = FieldElement::<F>::from(2_u64).inv().unwrap();
+    fold_at_inverse(&query.inv().unwrap(), eval, sym_eval, beta, &two_inv)
+}
+
+// the folding of curr_layer_query_evals from the inverse of the query,
+// for the verifier to invert all of its queries at once
+fn fold_at_inverse<F: IsField>(
+        query_inv: &FieldElement<F>,
+        eval: &FieldElement<F>,
+        sym_eval: &FieldElement<F>,
+        beta: &FieldElement<F>,
+        two_inv: &FieldElement<F>
+    ) -> FieldElement<F> {
+
     ((eval + sym_eval) + beta * (eval - sym_eval) * query_inv) * two_inv
-}
\ No newline at end of file
+}
+
+// the folding with beta at x^r of a polynomial f, from the evaluations
+// of f over the r points x * z^j, z being a primitive r-th root of unity,
+// with r a power of two. folding by r = 2^k is k foldings by 2 with the
+// betas beta, beta^2, beta^4, ..., each one by curr_layer_query_evals:
+// the points j and j + r/2 are y and -y, folded at y^2. for r = 2 the
+// evaluations are f(x) and f(-x), and the folding is the one checked
+// by the verifier of this crate on every layer
+pub fn fold_step<F: IsFFTField>(
+        x: &FieldElement<F>,
+        evals: &[FieldElement<F>],
+        beta: &FieldElement<F>
+    ) -> FieldElement<F> {
+
+    assert!(evals.len() >= 2 && evals.len().is_power_of_two(), "cannot fold {} evaluations", evals.len());
+    let mut root = F::get_primitive_root_of_unity(evals.len().trailing_zeros() as u64).unwrap();
+    let mut point = x.clone();
+    let mut beta = beta.clone();
+    let mut evals = evals.to_vec();
+    while evals.len() > 1 {
+        let (positive, negative) = evals.split_at(evals.len() / 2);
+        evals = positive
+            .iter()
+            .zip(negative)
+            .scan(point.clone(), |y, (f_y, f_minus_y)| {
+                let folded = curr_layer_query_evals(y, f_y, f_minus_y, &beta);
+                *y = &*y * &root;
+                Some(folded)
+            })
+            .collect::<Vec<FieldElement<F>>>();
+        point = point.square();
+        root = root.square();
+        beta = beta.square();
+    }
+    evals.remove(0)
+}
+
+// whether the evaluation of the next layer at x^r is the folding with
+// beta of the evaluations of the current layer over x * z^j, see
+// fold_step. a verifier of another language or crate checking this
+// relation checks the same folding as this crate
+pub fn verify_fold_step<F: IsFFTField>(
+        x: &FieldElement<F>,
+        evals: &[FieldElement<F>],
+        beta: &FieldElement<F>,
+        expected_next: &FieldElement<F>
+    ) -> bool {
+
+    fold_step(x, evals, beta) == *expected_next
+}
@@ -0,0 +1,348 @@
+use lambdaworks_math::field::{
+    traits::{IsFFTField, IsPrimeField},
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+use lambdaworks_math::traits::ByteConversion;
+use lambdaworks_math::unsigned_integer::element::U256;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use crate::air::Air;
+use crate::common::{self, ProofOptions};
+use crate::domain::{self, EvaluationDomain};
+use crate::fri::OffsetSchedule;
+use crate::merkle::MerkleBackend;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the shape of a statement, everything the precomputed context depends
+// on: the trace length, the boundary steps and, for each transition
+// constraint, its frame size, origin, rows and exempted steps. the
+// boundary values and the transition constraint itself are free to change
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Shape {
+    trace_length: usize,
+    boundary_steps: Vec<usize>,
+    transitions: Vec<TransitionShape>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TransitionShape {
+    frame_size: usize,
+    origin: usize,
+    period: usize,
+    rows: Vec<usize>,
+    exemptions: Vec<usize>,
+}
+
+impl Shape {
+
+    fn of<A: Air<F>>(air: &A) -> Self {
+        let trace_length = air.trace_length();
+        Self {
+            trace_length,
+            boundary_steps: air.boundary_constraints().constraints
+                .iter()
+                .map(|c| c.step)
+                .collect::<Vec<usize>>(),
+            transitions: air.transition_constraints()
+                .into_iter()
+                .map(|t| {
+                    assert!(t.period <= trace_length, "a period of {} steps in a trace of {} rows", t.period, trace_length);
+                    TransitionShape { frame_size: t.frame_size, origin: t.origin, period: t.period, rows: t.rows, exemptions: t.exemptions }
+                })
+                .collect::<Vec<TransitionShape>>(),
+        }
+    }
+}
+
+// the zerofier of the steps of the rows of a transition, exemptions
+// included, in closed form: the product of x^(n/p) - g^(r n/p) over its
+// rows r of the blocks of p steps, or x^n - 1 over the same product on
+// the other rows when these are fewer, the case of a constraint holding
+// on every step. the factor turning the constraint into its quotient
+// is the product over its exemptions over this zerofier
+#[derive(Clone)]
+pub(crate) struct RowsZerofier {
+    // n / p, x^(n/p) taking the same value on the steps of a row
+    power: usize,
+    // g^(r n/p) for each row r, or each other row
+    points: Vec<FE>,
+    complement: bool,
+}
+
+impl RowsZerofier {
+
+    fn new(g: &FE, trace_length: usize, transition: &TransitionShape) -> Self {
+        let TransitionShape { period, rows, .. } = transition;
+        let complement = 2 * rows.len() > *period;
+        let rows = match complement {
+            true => (0..*period).filter(|r| !rows.contains(r)).collect::<Vec<usize>>(),
+            false => rows.clone(),
+        };
+        let power = trace_length / period;
+        Self {
+            power,
+            points: powers(&g.pow(power), &rows),
+            complement,
+        }
+    }
+
+    fn product(&self, x: &FE) -> FE {
+        let y = x.pow(self.power);
+        self.points
+            .iter()
+            .fold(FE::one(), |product, p| product * (y - p))
+    }
+
+    // the numerator of the factor at x, from the points of the exemptions
+    pub(crate) fn numerator(&self, x: &FE, exemption_points: &[FE]) -> FE {
+        match self.complement {
+            true => vanishing(x, exemption_points) * self.product(x),
+            false => vanishing(x, exemption_points),
+        }
+    }
+
+    // the denominator of the factor at x, that depends on x^(n/p) only,
+    // one value over each coset of the subgroup of order n/p
+    pub(crate) fn denominator(&self, x: &FE, trace_length: usize) -> FE {
+        match self.complement {
+            true => x.pow(trace_length) - FE::one(),
+            false => self.product(x),
+        }
+    }
+}
+
+// prover context computed once for a statement shape and an lde size,
+// then reused by every proof of a statement with the same shape
+pub struct ProvingKey {
+    shape: Shape,
+    pub(crate) trace_domain: EvaluationDomain<F>,
+    pub(crate) lde_domain: EvaluationDomain<F>,
+    // generator of the trace domain
+    pub(crate) g: FE,
+    // 1 / Z_B(x) over the lde domain, Z_B vanishing on the boundary steps
+    pub(crate) boundary_zerofier_inv: Vec<FE>,
+    // the factor turning each transition constraint into its quotient
+    // over the lde domain, exemptions(x) / (x^n - 1) for a constraint
+    // holding on every step, see RowsZerofier
+    pub(crate) transition_factors: Vec<Vec<FE>>,
+}
+
+impl ProvingKey {
+
+    pub fn new<A: Air<F>>(air: &A, eval_two_power: usize) -> Self {
+        let shape = Shape::of(air);
+        let trace_length = shape.trace_length;
+        assert!(trace_length.is_power_of_two(), "trace length {} is not a power of two", trace_length);
+        let interp_two_power = trace_length.trailing_zeros() as u64;
+        let lde_size = 1_usize << eval_two_power;
+        assert!(lde_size > trace_length, "the lde domain must be larger than the trace");
+
+        let trace_domain = EvaluationDomain::new(trace_length, &FE::one());
+        let lde_domain = EvaluationDomain::new(lde_size, &FE::from(2_u64));
+        let g = F::get_primitive_root_of_unity(interp_two_power).unwrap();
+        let w = F::get_primitive_root_of_unity(eval_two_power as u64).unwrap();
+
+        // points of the lde domain, offset * w^i
+        let points = (0..lde_size)
+            .scan(lde_domain.offset, |x, _| {
+                let current = *x;
+                *x *= w;
+                Some(current)
+            })
+            .collect::<Vec<FE>>();
+
+        let boundary_points = powers(&g, &shape.boundary_steps);
+        let boundary_zerofier_inv = domain::batch_inverses(
+            &points
+                .iter()
+                .map(|x| vanishing(x, &boundary_points))
+                .collect::<Vec<FE>>()
+        );
+
+        // the denominators of a transition repeat over the lde domain,
+        // x^(n/p) taking one value on each of the blowup * p cosets of
+        // the subgroup of order n/p, so that they are inverted once per
+        // coset, and the numerators have a term for each exemption
+        let transition_factors = shape.transitions
+            .iter()
+            .map(|transition| {
+                let zerofier = RowsZerofier::new(&g, trace_length, transition);
+                let cosets = lde_size / zerofier.power;
+                let denominator_invs = domain::batch_inverses(
+                    &points[..cosets]
+                        .iter()
+                        .map(|x| zerofier.denominator(x, trace_length))
+                        .collect::<Vec<FE>>()
+                );
+                let exemption_points = powers(&g, &transition.exemptions);
+                points
+                    .iter()
+                    .enumerate()
+                    .map(|(i, x)| zerofier.numerator(x, &exemption_points) * denominator_invs[i % cosets])
+                    .collect::<Vec<FE>>()
+            })
+            .collect::<Vec<Vec<FE>>>();
+
+        Self {
+            shape,
+            trace_domain,
+            lde_domain,
+            g,
+            boundary_zerofier_inv,
+            transition_factors,
+        }
+    }
+
+    // whether the key was computed for the shape of the statement
+    pub fn fits<A: Air<F>>(&self, air: &A, eval_two_power: usize) -> bool {
+        self.lde_domain.size == 1 << eval_two_power && self.shape == Shape::of(air)
+    }
+}
+
+// verifier context computed once for a statement shape and an lde size
+#[derive(Clone)]
+pub struct VerifyingKey {
+    identifier: String,
+    shape: Shape,
+    // generators of the trace and lde domains
+    pub(crate) g: FE,
+    pub(crate) w: FE,
+    pub(crate) offset: FE,
+    // g^step for each boundary step
+    pub(crate) boundary_points: Vec<FE>,
+    // g^step for each exempted step, one list for each transition
+    pub(crate) exemption_points: Vec<Vec<FE>>,
+    // the zerofier of the rows of each transition
+    pub(crate) zerofiers: Vec<RowsZerofier>,
+    eval_two_power: usize,
+}
+
+impl VerifyingKey {
+
+    pub fn new<A: Air<F>>(air: &A, eval_two_power: usize) -> Self {
+        let shape = Shape::of(air);
+        assert!(shape.trace_length.is_power_of_two(), "trace length {} is not a power of two", shape.trace_length);
+        let g = F::get_primitive_root_of_unity(shape.trace_length.trailing_zeros() as u64).unwrap();
+        let w = F::get_primitive_root_of_unity(eval_two_power as u64).unwrap();
+        let boundary_points = powers(&g, &shape.boundary_steps);
+        let exemption_points = shape.transitions
+            .iter()
+            .map(|transition| powers(&g, &transition.exemptions))
+            .collect::<Vec<Vec<FE>>>();
+        let zerofiers = shape.transitions
+            .iter()
+            .map(|transition| RowsZerofier::new(&g, shape.trace_length, transition))
+            .collect::<Vec<RowsZerofier>>();
+
+        Self {
+            identifier: air.identifier(),
+            shape,
+            g,
+            w,
+            offset: FE::from(2_u64),
+            boundary_points,
+            exemption_points,
+            zerofiers,
+            eval_two_power,
+        }
+    }
+
+    // whether the key was computed for the air and the shape of the statement
+    pub fn fits<A: Air<F>>(&self, air: &A, eval_two_power: usize) -> bool {
+        self.eval_two_power == eval_two_power && self.identifier == air.identifier() && self.shape == Shape::of(air)
+    }
+
+    // digest of everything a proof is bound to besides the public input
+    // and the constraints: the field, the air and the shape of its
+    // statement, the lde and the options of the verifier, as
+    //
+    //   modulus [32] | u32 k | identifier [k] | u64 trace length
+    //   u32 b | b * u64 boundary step
+    //   u32 t | t * (u64 frame size | u32 e | e * u64 exempted step)
+    //   u64 eval_two_power | lde offset [32]
+    //   u32 l | transcript label [l] | u8 query sampling
+    //   u8 offset schedule, unless the squared offsets
+    //   u64 fri max layers, if bounded
+    //   u8 merkle backend, unless keccak256
+    //   u64 trace leaf width, unless 1
+    //   t * u64 frame origin, if a frame reaches backwards
+    //   t * (u64 period | u32 r | r * u64 row), if a constraint holds
+    //   on some rows of blocks of steps only
+    //
+    // absorbed first into the transcript, so that a proof does not
+    // verify against another configuration. the session of a proof is
+    // absorbed with the label, before it. the statements have no
+    // preprocessed columns, so there is no preprocessed root to bind
+    pub fn digest(&self, options: &ProofOptions) -> [u8; 32] {
+        let modulus = F::modulus_minus_one() + U256::from_u64(1);
+        let mut hasher = DefaultTranscript::<F>::new(&modulus.to_bytes_be());
+        append_len(&mut hasher, self.identifier.len());
+        hasher.append_bytes(self.identifier.as_bytes());
+        hasher.append_bytes(&(self.shape.trace_length as u64).to_be_bytes());
+        append_steps(&mut hasher, &self.shape.boundary_steps);
+        append_len(&mut hasher, self.shape.transitions.len());
+        for transition in &self.shape.transitions {
+            hasher.append_bytes(&(transition.frame_size as u64).to_be_bytes());
+            append_steps(&mut hasher, &transition.exemptions);
+        }
+        hasher.append_bytes(&(self.eval_two_power as u64).to_be_bytes());
+        hasher.append_bytes(&common::fe_to_bytes_be(&self.offset));
+        append_len(&mut hasher, options.transcript_label.len());
+        hasher.append_bytes(&options.transcript_label);
+        hasher.append_bytes(&[options.query_sampling as u8]);
+        if options.offset_schedule != OffsetSchedule::Squared {
+            hasher.append_bytes(&[options.offset_schedule as u8]);
+        }
+        if let Some(max_layers) = options.fri_max_layers {
+            hasher.append_bytes(&(max_layers as u64).to_be_bytes());
+        }
+        let merkle_backend = options.merkle_backend.resolve();
+        if merkle_backend != MerkleBackend::Keccak256 {
+            hasher.append_bytes(&[merkle_backend as u8]);
+        }
+        if options.trace_leaf_width != 1 {
+            hasher.append_bytes(&(options.trace_leaf_width as u64).to_be_bytes());
+        }
+        if self.shape.transitions.iter().any(|transition| transition.origin > 0) {
+            for transition in &self.shape.transitions {
+                hasher.append_bytes(&(transition.origin as u64).to_be_bytes());
+            }
+        }
+        if self.shape.transitions.iter().any(|transition| transition.period > 1) {
+            for transition in &self.shape.transitions {
+                hasher.append_bytes(&(transition.period as u64).to_be_bytes());
+                append_steps(&mut hasher, &transition.rows);
+            }
+        }
+        hasher.state()
+    }
+}
+
+fn append_len(hasher: &mut DefaultTranscript<F>, len: usize) {
+    hasher.append_bytes(&(len as u32).to_be_bytes());
+}
+
+fn append_steps(hasher: &mut DefaultTranscript<F>, steps: &[usize]) {
+    append_len(hasher, steps.len());
+    for step in steps {
+        hasher.append_bytes(&(*step as u64).to_be_bytes());
+    }
+}
+
+// the product of (x - p) over the points
+pub(crate) fn vanishing(x: &FE, points: &[FE]) -> FE {
+    points
+        .iter()
+        .fold(FE::one(), |product, p| product * (x - p))
+}
+
+fn powers(g: &FE, exponents: &[usize]) -> Vec<FE> {
+    exponents
+        .iter()
+        .map(|e| g.pow(*e))
+        .collect::<Vec<FE>>()
+}
+
@@ -0,0 +1,189 @@
+// proofs of the previous version of the encoding, so that verifiers
+// keep accepting them while the provers are upgraded. version 1 has
+// the wire format of the current version, but the openings follow
+// the queries in the order they are sampled rather than sorted, and
+// the trace openings are, for each query, every row of its frame
+//
+//   q * (row 0 | row 1 | ... | row frame_size - 1)
+//
+// repeated when queries share a row. a proof of version 1 is decoded
+// and its openings rearranged in the layout of the current version,
+// for which the queries are sampled again from the transcript.
+//
+// versions 1 and 2 have no binding, see common::proof_binding, so a
+// proof of the current version stripped of its binding reads as one
+// of them. they are only verified by the entry points of this module,
+// which a verifier calls explicitly to accept unbound proofs
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+use lambdaworks_crypto::fiat_shamir::is_transcript::IsTranscript;
+
+use crate::air::Air;
+use crate::common::{self, InclusionProof, PublicInput, ProofAccess, ProofOptions, StarkProof};
+use crate::encoding::{self, DecodeError, DecodeLimits, DecodeMode, ProofView};
+use crate::fri::{self, QuerySampling};
+use crate::merkle::LeafLayout;
+use crate::prover;
+use crate::verifier::{self, VerifierContext, VerifyError};
+
+type F = Stark252PrimeField;
+
+pub const VERSION: u8 = 1;
+
+// decodes a proof of version 1 of the given statement, in the layout
+// of the current version. the public input must be valid
+pub fn decode_proof<A: Air<F>>(
+        bytes: &[u8],
+        mode: DecodeMode,
+        limits: &DecodeLimits,
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions
+    ) -> Result<StarkProof<F>, DecodeError> {
+
+    let proof = ProofView::parse_version(bytes, mode, limits, &[VERSION])?.to_proof();
+    upgrade(air, public_input, proof, options)
+}
+
+// verifies a proof with or without a binding, a binding present being
+// checked as by verifier::verify
+pub fn verify<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    let context = VerifierContext::new(air, &public_input, options).accepting_unbound();
+    verifier::report_until(&context, air, public_input, stark_proof, None).result
+}
+
+// verifies an encoded proof of any version, dispatching on its version
+// byte: one of version 2 or of the current version is read in place,
+// one of version 1 is decoded in the current layout. the public input
+// must be valid
+pub fn verify_encoded<A: Air<F>>(
+        air: &A,
+        public_input: PublicInput<F>,
+        bytes: &[u8],
+        mode: DecodeMode,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError> {
+
+    let limits = DecodeLimits::default();
+    verifier::report_encoded(
+        || match encoding::proof_version(bytes)? {
+            encoding::VERSION | encoding::UNBOUND_VERSION => {
+                let view = ProofView::parse_version(bytes, mode, &limits, &[encoding::VERSION, encoding::UNBOUND_VERSION])?;
+                Ok(Box::new(view) as Box<dyn ProofAccess<F>>)
+            },
+            VERSION => Ok(Box::new(decode_proof(bytes, mode, &limits, air, &public_input, options)?)),
+            version => Err(DecodeError::Version(version)),
+        },
+        |proof| {
+            let context = VerifierContext::new(air, &public_input, options).accepting_unbound();
+            verifier::report_until(&context, air, public_input.clone(), proof, None)
+        }
+    ).result
+}
+
+fn upgrade<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        mut proof: StarkProof<F>,
+        options: &ProofOptions
+    ) -> Result<StarkProof<F>, DecodeError> {
+
+    let PublicInput(_, interp_two_power, eval_two_power, num_queries, ..) = *public_input;
+    let eval_order: usize = 1 << eval_two_power;
+    let blowup_factor: usize = 1 << (eval_two_power - interp_two_power);
+    let layout = DecodeError::Layout { version: VERSION };
+
+    // an lde too small for the composition is no statement of any
+    // version, left to the verifier to reject
+    if air.composition_degree_bound() > eval_order / 2 {
+        return Ok(proof)
+    }
+
+    // the transcript of the verifier up to the sampling of the queries
+    let mut transcript = common::new_transcript(options);
+    prover::absorb_statement(air, public_input, options, &mut transcript);
+    transcript.append_bytes(&proof.trace_commitment.root);
+    for _ in 0..=air.transition_constraints().len() {
+        transcript.sample_field_element();
+    }
+    let query_indices = common::sampled_queries(num_queries, eval_order, &mut transcript).expect("the lde domain has points");
+
+    // the trace openings of the rows the current version opens
+    // of a frame, wrapped around the trace when it reaches backwards
+    let frame_offsets = air.frame_offsets();
+    let openings = &proof.trace_commitment.inclusion_proofs;
+    if openings.len() != num_queries * frame_offsets.len() {
+        return Err(layout)
+    }
+    let opened_rows = query_indices
+        .iter()
+        .flat_map(|q| frame_offsets.iter().map(move |k| common::shifted_position(*q, *k, blowup_factor, eval_order)))
+        .collect::<Vec<usize>>();
+    // the trees of version 1 have a leaf for each evaluation
+    let sorted_queries = sorted(&query_indices);
+    proof.trace_commitment.inclusion_proofs = common::trace_positions(&sorted_queries, &frame_offsets, &LeafLayout::new(1, blowup_factor), eval_order)
+        .iter()
+        .map(|position| {
+            let i = opened_rows.iter().position(|row| row == position).ok_or(layout.clone())?;
+            Ok(openings[i].clone())
+        })
+        .collect::<Result<Vec<InclusionProof<F>>, DecodeError>>()?;
+
+    // the queries of every layer in the order they are sampled, with
+    // per layer sampling those of the inner layers are sampled once
+    // the layers are committed
+    let num_layers = proof.composition_commitment.len();
+    let mut layer_queries = vec![query_indices];
+    if options.query_sampling == QuerySampling::PerLayer {
+        transcript.append_bytes(&proof.composition_commitment[0].root);
+        for layer in &proof.composition_commitment[1..] {
+            transcript.sample_field_element();
+            transcript.append_bytes(&layer.root);
+        }
+        for i in 1..num_layers.saturating_sub(1) {
+            layer_queries.push(common::sampled_queries(num_queries, eval_order >> i, &mut transcript).expect("the layer has points"));
+        }
+    }
+
+    // folded queries keep the order of the first layer
+    let order = |i: usize| match options.query_sampling {
+        QuerySampling::Folded => layer_queries.first(),
+        QuerySampling::PerLayer => layer_queries.get(i),
+    };
+    for (i, fri::FriLayer { validation_data, landing_data, .. }) in proof.composition_commitment.iter_mut().enumerate() {
+        if !validation_data.is_empty() {
+            *validation_data = rearranged(validation_data, order(i)).ok_or(layout.clone())?;
+        }
+        if i > 0 && !landing_data.is_empty() {
+            *landing_data = rearranged(landing_data, order(i - 1)).ok_or(layout.clone())?;
+        }
+    }
+
+    Ok(proof)
+}
+
+fn sorted(indices: &[usize]) -> Vec<usize> {
+    let mut indices = indices.to_owned();
+    indices.sort_unstable();
+    indices
+}
+
+// the openings of the queries in sampled order, in sorted order. the
+// openings of a repeated query are the same, any of them is taken
+fn rearranged<T: Clone>(openings: &[T], query_indices: Option<&Vec<usize>>) -> Option<Vec<T>> {
+    let query_indices = query_indices.filter(|query_indices| query_indices.len() == openings.len())?;
+    let mut order = (0..query_indices.len()).collect::<Vec<usize>>();
+    order.sort_by_key(|j| query_indices[*j]);
+    Some(order
+        .iter()
+        .map(|j| openings[*j].clone())
+        .collect::<Vec<T>>())
+}
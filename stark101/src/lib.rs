@@ -1,5 +1,39 @@
 pub mod poly;
+pub mod domain;
+pub mod key;
+pub mod air;
+pub mod witness;
+pub mod expr;
+pub mod composition;
 pub mod common;
 pub mod fri;
 pub mod prover;
-pub mod verifier;
\ No newline at end of file
+pub mod verifier;
+pub mod report;
+pub mod metrics;
+pub mod threads;
+pub mod storage;
+pub mod merkle;
+mod checkpoint;
+pub mod encoding;
+pub mod channel;
+pub mod observer;
+pub mod pipeline;
+pub mod malicious;
+pub mod range_check;
+pub mod membership;
+pub mod exponentiation;
+pub mod trace;
+pub mod cost;
+pub mod anchor;
+#[cfg(feature = "borsh")]
+pub mod borsh;
+pub mod envelope;
+pub mod registry;
+pub mod stream;
+pub mod conjunction;
+pub mod output;
+#[cfg(feature = "transcript-log")]
+pub mod spec;
+pub mod legacy;
+pub mod ct;
@@ -7,11 +7,22 @@ use lambdaworks_math::field::{
     element::FieldElement
 };
 
-mod poly;
-mod common;
-mod fri;
-mod prover;
-mod verifier;
+use lambdaworks_crypto::fiat_shamir::{
+    is_transcript::IsTranscript,
+    default_transcript::DefaultTranscript
+};
+
+use std::error::Error;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use stark101::{air, common, cost, encoding, envelope, merkle, prover, registry, report, threads, verifier};
+use stark101::common::IsPublicInput;
+use stark101::observer::{ProverEvent, ProverObserver};
+use stark101::witness::Witness;
+#[cfg(feature = "mmap")]
+use stark101::storage;
 
 // the stark252 field has 2-adicity of 192, i.e., the largest
 // multiplicative subgroup whose order is a power of two has order 2^192
@@ -25,17 +36,318 @@ const INTERP_TWO_POWER: usize = 10;
 const EVAL_TWO_POWER: usize = 13;
 // number of queries in FRI
 const NUM_QUERIES: usize = 10;
+// hash chain of 2^16 steps by default
+const HASH_CHAIN_TWO_POWER: usize = 16;
+
+const USAGE: &str = "usage: stark101 [hash-chain [--steps-log2 N] [--trace-dir DIR] [--checkpoint-dir DIR] [--proof-out FILE [--tag TAG]] [--public-input-out FILE]]
+       stark101 tutorial
+       stark101 demo (--seed SEED | --witness HEX)
+       stark101 bench [--trace-log2 N] [--json | --explain] [--single-threaded] [--merkle-backend (keccak256 | blake3 | auto)]
+       stark101 verify [--json] (fibonacci-square | hash-chain) PUBLIC_INPUT_FILE PROOF_FILE
+       stark101 verify [--json] PUBLIC_INPUT_FILE SEALED_PROOF_FILE
+       stark101 spec [--per-layer]    with the transcript-log feature";
 
 
 fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    match args.first().map(String::as_str) {
+        None => fibonacci_square(),
+        Some("tutorial") if args.len() == 1 => tutorial(),
+        Some("demo") => match &args[1..] {
+            [flag, seed] if flag == "--seed" => demo(&Witness::from_seed(seed), &format!("derived from the seed \"{}\"", seed)),
+            [flag, hex] if flag == "--witness" => match Witness::from_hex(hex) {
+                Ok(witness) => demo(&witness, "given"),
+                Err(e) => {
+                    eprintln!("invalid witness: {}\n{}", e, USAGE);
+                    std::process::exit(2);
+                }
+            },
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("hash-chain") => match parse_hash_chain_args(&args[1..]) {
+            Ok(args) => hash_chain(&args),
+            Err(e) => {
+                eprintln!("{}\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("bench") => match parse_bench_args(&args[1..]) {
+            Ok(args) if args.single_threaded => threads::single_threaded(|| bench(&args)),
+            Ok(args) => bench(&args),
+            Err(e) => {
+                eprintln!("{}\n{}", e, USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some("verify") => {
+            let (json, args) = match &args[1..] {
+                [flag, args @ ..] if flag == "--json" => (true, args),
+                args => (false, args),
+            };
+            match args {
+                [statement, public_input, proof] => verify_command(statement, Path::new(public_input), Path::new(proof), json),
+                [public_input, proof] => verify_sealed_command(Path::new(public_input), Path::new(proof), json),
+                _ => {
+                    eprintln!("{}", USAGE);
+                    std::process::exit(2);
+                }
+            }
+        },
+        #[cfg(feature = "transcript-log")]
+        Some("spec") => match &args[1..] {
+            [] => spec_command(stark101::fri::QuerySampling::Folded),
+            [flag] if flag == "--per-layer" => spec_command(stark101::fri::QuerySampling::PerLayer),
+            _ => {
+                eprintln!("{}", USAGE);
+                std::process::exit(2);
+            }
+        },
+        Some(_) => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    }
+}
+
+struct HashChainArgs {
+    steps_log2: usize,
+    options: common::ProofOptions,
+    // files the encoded proof and public input are written to
+    proof_out: Option<PathBuf>,
+    public_input_out: Option<PathBuf>,
+    // seals the proof written out in an envelope naming its statement,
+    // with this tag in its metadata
+    tag: Option<String>,
+}
+
+fn parse_hash_chain_args(args: &[String]) -> Result<HashChainArgs, String> {
+    let mut parsed = HashChainArgs {
+        steps_log2: HASH_CHAIN_TWO_POWER,
+        options: common::ProofOptions::default(),
+        proof_out: None,
+        public_input_out: None,
+        tag: None,
+    };
+
+    for pair in args.chunks(2) {
+        match pair {
+            [flag, value] if flag == "--steps-log2" => {
+                parsed.steps_log2 = match value.parse::<usize>() {
+                    Ok(steps_log2 @ 2..=28) => steps_log2,
+                    Ok(steps_log2) => return Err(format!("--steps-log2 must be between 2 and 28, got {}", steps_log2)),
+                    Err(_) => return Err(format!("invalid number of steps: {}", value)),
+                };
+            },
+            [flag, value] if flag == "--proof-out" => {
+                parsed.proof_out = Some(value.into());
+            },
+            [flag, value] if flag == "--public-input-out" => {
+                parsed.public_input_out = Some(value.into());
+            },
+            [flag, value] if flag == "--tag" => {
+                parsed.tag = Some(value.clone());
+            },
+            #[cfg(feature = "mmap")]
+            [flag, value] if flag == "--trace-dir" => {
+                parsed.options.trace_storage = storage::TraceStorage::Disk(value.into());
+            },
+            #[cfg(not(feature = "mmap"))]
+            [flag, _] if flag == "--trace-dir" => {
+                return Err("--trace-dir requires the mmap feature".to_string());
+            },
+            [flag, value] if flag == "--checkpoint-dir" => {
+                parsed.options.checkpoint_dir = Some(value.into());
+            },
+            _ => return Err(format!("unexpected arguments: {}", pair.join(" "))),
+        }
+    }
+    if parsed.tag.is_some() && parsed.proof_out.is_none() {
+        return Err("--tag requires --proof-out".to_string());
+    }
+
+    Ok(parsed)
+}
+
+struct BenchArgs {
+    trace_log2: usize,
+    json: bool,
+    // the proof size by part and under other options
+    explain: bool,
+    // proves and verifies on one thread, see threads
+    single_threaded: bool,
+    merkle_backend: merkle::MerkleBackend,
+}
+
+fn parse_bench_args(args: &[String]) -> Result<BenchArgs, String> {
+    let mut parsed = BenchArgs {
+        trace_log2: HASH_CHAIN_TWO_POWER,
+        json: false,
+        explain: false,
+        single_threaded: false,
+        merkle_backend: merkle::MerkleBackend::default(),
+    };
+
+    let mut args = args.iter();
+    while let Some(flag) = args.next() {
+        match flag.as_str() {
+            "--json" => parsed.json = true,
+            "--explain" => parsed.explain = true,
+            "--single-threaded" => parsed.single_threaded = true,
+            "--merkle-backend" => {
+                let value = args.next().ok_or("--merkle-backend needs a value")?;
+                parsed.merkle_backend = match merkle::MerkleBackend::from_name(value) {
+                    Some(backend) => backend,
+                    None if value == "blake3" => return Err("--merkle-backend blake3 requires the blake3 feature".to_string()),
+                    None => return Err(format!("unknown merkle backend: {}", value)),
+                };
+            },
+            "--trace-log2" => {
+                let value = args.next().ok_or("--trace-log2 needs a value")?;
+                parsed.trace_log2 = match value.parse::<usize>() {
+                    Ok(trace_log2 @ 2..=28) => trace_log2,
+                    Ok(trace_log2) => return Err(format!("--trace-log2 must be between 2 and 28, got {}", trace_log2)),
+                    Err(_) => return Err(format!("invalid trace size: {}", value)),
+                };
+            },
+            _ => return Err(format!("unexpected argument: {}", flag)),
+        }
+    }
+    if parsed.json && parsed.explain {
+        return Err("--explain is not part of the json output".to_string());
+    }
+
+    Ok(parsed)
+}
+
+// version of the json output of the bench command, changed only when
+// a field is removed or changes meaning
+const BENCH_SCHEMA: u32 = 1;
+
+// prints the protocol specification as json, recorded while proving
+// a hash chain of 2^6 steps with the default parameters of the demo
+#[cfg(feature = "transcript-log")]
+fn spec_command(query_sampling: stark101::fri::QuerySampling) {
+    let trace_length = 1 << 6;
+    let seed = FE::from(3141592_u64);
+    let trace = air::HashChain::trace(&seed, trace_length);
+    let digest = trace[trace_length - 1];
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        6,
+        6 + EVAL_TWO_POWER - INTERP_TWO_POWER,
+        NUM_QUERIES,
+        seed,
+        digest,
+        None,
+    );
+    let air = air::HashChain { trace_length, seed, digest };
+    let options = common::ProofOptions { query_sampling, ..Default::default() };
+    println!("{}", stark101::spec::protocol_spec(&air, &trace, public_input, &options).to_json());
+}
+
+// proves and verifies the hash chain statement with a trace of
+// 2^trace_log2 rows, reporting the time and memory of each prover
+// phase, the proof size and the verification time, after the time
+// each merkle backend takes to build a tree of the trace size
+fn bench(args: &BenchArgs) {
+    let trace_length = 1 << args.trace_log2;
+    let blowup_log2 = EVAL_TWO_POWER - INTERP_TWO_POWER;
+    let seed = FE::from(3141592_u64);
+    let trace = air::HashChain::trace(&seed, trace_length);
+    let digest = trace[trace_length - 1];
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        args.trace_log2,
+        args.trace_log2 + blowup_log2,
+        NUM_QUERIES,
+        seed,
+        digest,
+        None,
+    );
+    let air = air::HashChain { trace_length, seed, digest };
+    let options = common::ProofOptions { merkle_backend: args.merkle_backend, ..Default::default() };
+    let timings = merkle::benchmark_backends(trace_length);
+
+    let (proof, metrics) = prover::prove_with_metrics(&air, &trace, public_input.clone(), &options);
+    let proof_bytes = encoding::encode_proof(&proof).len();
+    let start = Instant::now();
+    let result = verifier::verify(&air, public_input.clone(), &proof, &options);
+    let verification = start.elapsed();
+    if let Err(e) = result {
+        eprintln!("the proof could not be verified: {}", e);
+        std::process::exit(1);
+    }
+
+    if !args.json {
+        println!("Hash chain of 2^{} steps", args.trace_log2);
+        println!("Merkle trees of 2^{} leaves:", args.trace_log2);
+        for timing in &timings {
+            println!("  {:<10} {:>10.2?}  {:.0} leaves/s", timing.backend.name(), timing.duration, timing.leaves_per_second());
+        }
+        println!("Merkle backend: {}", options.merkle_backend.resolve().name());
+        println!("{}", metrics);
+        println!("Proof size: {} bytes", proof_bytes);
+        println!("Verification time: {:.2?}", verification);
+        if args.explain {
+            println!("{}", cost::proof_size_report(&air, &public_input, &options));
+        }
+        return
+    }
+
+    let optional = |bytes: Option<usize>| bytes.map_or("null".to_string(), |bytes| bytes.to_string());
+    let phases = metrics.phases
+        .iter()
+        .map(|p| format!(
+            "{{\"phase\": \"{}\", \"seconds\": {:.6}, \"peak_allocated_bytes\": {}, \"peak_rss_bytes\": {}}}",
+            p.phase.name(),
+            p.duration.as_secs_f64(),
+            optional(p.peak_allocated_bytes),
+            optional(p.peak_rss_bytes)
+        ))
+        .collect::<Vec<String>>();
+    let merkle_backends = timings
+        .iter()
+        .map(|timing| format!("{{\"backend\": \"{}\", \"seconds\": {:.6}, \"leaves_per_second\": {:.0}}}", timing.backend.name(), timing.duration.as_secs_f64(), timing.leaves_per_second()))
+        .collect::<Vec<String>>();
+    let target_features = metrics.backend.target_features
+        .iter()
+        .map(|feature| format!("\"{}\"", feature))
+        .collect::<Vec<String>>();
+
+    println!("{{");
+    println!("  \"schema\": {},", BENCH_SCHEMA);
+    println!("  \"version\": \"{}\",", env!("CARGO_PKG_VERSION"));
+    println!("  \"statement\": \"hash-chain\",");
+    println!("  \"trace_log2\": {},", args.trace_log2);
+    println!("  \"blowup_log2\": {},", blowup_log2);
+    println!("  \"num_queries\": {},", NUM_QUERIES);
+    println!("  \"backend\": {{\"parallel\": {}, \"threads\": {}, \"target_features\": [{}]}},", metrics.backend.parallel, metrics.backend.threads, target_features.join(", "));
+    println!("  \"merkle_backend\": \"{}\",", options.merkle_backend.resolve().name());
+    println!("  \"merkle_backends\": [\n    {}\n  ],", merkle_backends.join(",\n    "));
+    println!("  \"phases\": [\n    {}\n  ],", phases.join(",\n    "));
+    println!("  \"prove_seconds\": {:.6},", metrics.total_duration().as_secs_f64());
+    println!("  \"verify_seconds\": {:.6},", verification.as_secs_f64());
+    println!("  \"proof_bytes\": {},", proof_bytes);
+    println!("  \"peak_allocated_bytes\": {},", optional(metrics.peak_allocated_bytes()));
+    println!("  \"peak_rss_bytes\": {}", optional(metrics.peak_rss_bytes()));
+    println!("}}");
+}
+
+// proves and verifies the fibonacci square statement, then
+// shows that a tampered proof is rejected
+fn fibonacci_square() {
 
     // public input //
     // field properties
     let modulus = FConfig::MODULUS;
 
-    // trace properties
+    // trace properties, a(1022) being reached from the witness
+    // of prover::generate_proof
     let fib_squared_0 = FE::one();
-    let fib_squared_1022 = FE::from_hex_unchecked("6A317721EF632FF24FB815C9BBD4D4582BC7E21A43CFBDD89A8B8F0BDA68252");
+    let fib_squared_1022 = air::FibonacciSquare::fib_squared_1022(&fib_squared_0, Witness::<F>::example().value());
 
     let public_input = common::PublicInput(
         modulus,
@@ -44,24 +356,480 @@ fn main() {
         NUM_QUERIES,
         fib_squared_0,
         fib_squared_1022,
+        None,
     );
 
+    let options = common::ProofOptions::default();
+
     // generate valid proof
-    let proof = prover::generate_proof(public_input.clone());
+    let proof = prover::generate_proof(public_input.clone(), &options);
 
     // simulate invalid proof
     let mut invalid_proof = proof.clone();
     invalid_proof.trace_commitment.root[0] += 1;
 
-    if verifier::verify_proof(public_input.clone(), proof) {
-        println!("Valid Proof: successfully verified.");
-    } else {
-        println!("Valid Proof: could not be verified.");
+    match verifier::verify_proof(&public_input, &proof, &options) {
+        Ok(()) => println!("Valid Proof: successfully verified."),
+        Err(e) => println!("Valid Proof: could not be verified ({}).", e),
     }
 
-    if verifier::verify_proof(public_input.clone(), invalid_proof) {
+    let report = verifier::verify_proof_with_report(&public_input, &invalid_proof, &options);
+    if report.is_valid() {
         println!("Invalid Proof: successfully verified.");
     } else {
-        println!("Invalid Proof: could not be verified.");
+        println!("Invalid Proof: could not be verified ({}).", report);
+    }
+}
+
+// walks through the parts of stark 101 on the fibonacci square
+// statement, printing the objects computed by the prover in each
+// part and pausing between them when run from a terminal
+fn tutorial() {
+    let fib_squared_0 = FE::one();
+    let witness = Witness::<F>::example();
+    let fib_squared_1022 = air::FibonacciSquare::fib_squared_1022(&fib_squared_0, witness.value());
+    let trace_length = 1 << INTERP_TWO_POWER;
+
+    println!("STARK 101: proving knowledge of x such that the sequence");
+    println!("  a(0) = 1, a(1) = x, a(n+2) = a(n)^2 + a(n+1)^2");
+    println!("reaches a(1022) = 0x{}", fib_squared_1022.representative().to_hex());
+
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        INTERP_TWO_POWER,
+        EVAL_TWO_POWER,
+        NUM_QUERIES,
+        fib_squared_0,
+        fib_squared_1022,
+        None,
+    );
+    let air = air::FibonacciSquare { trace_length, fib_squared_0, fib_squared_1022 };
+    let trace = air::FibonacciSquare::trace(&fib_squared_0, witness.value(), trace_length);
+    let options = common::ProofOptions::default();
+
+    let mut tutorial = Tutorial { pause: std::io::stdin().is_terminal() };
+    tutorial.pause();
+    let proof = prover::prove_observed(&air, &trace, public_input.clone(), &options, &mut tutorial);
+
+    println!();
+    println!("The verifier replays the transcript from the commitments and checks the openings.");
+    match verifier::verify(&air, public_input, &proof, &options) {
+        Ok(()) => println!("Proof verified."),
+        Err(e) => println!("Proof rejected: {}.", e),
+    }
+}
+
+// proves the fibonacci square statement for the witness, derived from
+// a seed or given, printing the public element the sequence reaches, so
+// that an exercise is handed out as its seed and checked against that
+// value
+fn demo(witness: &Witness<F>, origin: &str) {
+    let fib_squared_0 = FE::one();
+    let trace_length = 1 << INTERP_TWO_POWER;
+    let trace = air::FibonacciSquare::trace(&fib_squared_0, witness.value(), trace_length);
+    let fib_squared_1022 = air::FibonacciSquare::fib_squared_1022(&fib_squared_0, witness.value());
+
+    println!("witness x {}", origin);
+    println!("  a(0) = 1, a(1) = x, a(n+2) = a(n)^2 + a(n+1)^2");
+    println!("public statement: a(1022) = {}", hex(&fib_squared_1022));
+
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        INTERP_TWO_POWER,
+        EVAL_TWO_POWER,
+        NUM_QUERIES,
+        fib_squared_0,
+        fib_squared_1022,
+        None,
+    );
+    println!("statement digest {}", common::bytes_to_hex(&public_input.digest()));
+    let air = air::FibonacciSquare { trace_length, fib_squared_0, fib_squared_1022 };
+    let options = common::ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    match verifier::verify(&air, public_input, &proof, &options) {
+        Ok(()) => println!("Proof verified."),
+        Err(e) => {
+            println!("Proof rejected: {}.", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// prints the objects of the prover as they are computed
+struct Tutorial {
+    // wait for enter between the parts, only from a terminal
+    pause: bool,
+}
+
+impl Tutorial {
+
+    fn pause(&self) {
+        if self.pause {
+            print!("\n[press enter to continue] ");
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+            let _ = std::io::stdin().read_line(&mut String::new());
+        }
+    }
+}
+
+fn hex(element: &FE) -> String {
+    format!("0x{}", element.representative().to_hex())
+}
+
+impl ProverObserver<F> for Tutorial {
+
+    fn observe(&mut self, event: ProverEvent<'_, F>) {
+        match event {
+            ProverEvent::Trace { trace, polynomial } => {
+                println!("\n== Part 1: Trace and Low Degree Extension ==");
+                println!("trace of {} rows, starting with", trace.len());
+                for (i, row) in trace.iter().take(5).enumerate() {
+                    println!("  a({}) = {}", i, hex(row));
+                }
+                println!("interpolated over the subgroup of order {} by f of degree {}", trace.len(), polynomial.degree());
+            },
+            ProverEvent::TraceCommitment { lde_size, root } => {
+                println!("f evaluated over a coset of order {}, committed with merkle root", lde_size);
+                println!("  {}", common::bytes_to_hex(&root));
+                self.pause();
+            },
+            ProverEvent::Composition { boundary_coefficient, transition_coefficients, evaluations, degree_bound } => {
+                println!("\n== Part 2: Polynomial Constraints ==");
+                println!("boundary quotient taken with coefficient alpha_0 = {}", hex(boundary_coefficient));
+                for (i, coefficient) in transition_coefficients.iter().enumerate() {
+                    println!("transition quotient {} taken with coefficient alpha_{} = {}", i, i + 1, hex(coefficient));
+                }
+                println!("composition polynomial CP evaluated over the {} points of the lde domain, of degree below {}", evaluations.len(), degree_bound);
+                self.pause();
+            },
+            ProverEvent::Queries { indices } => {
+                println!("\n== Part 3: FRI Commitments ==");
+                println!("queries of the lde domain sampled from the transcript: {:?}", indices);
+            },
+            ProverEvent::FriLayers { roots, betas } => {
+                println!("CP committed, then folded {} times", betas.len());
+                println!("  layer 0 root {}", common::bytes_to_hex(&roots[0]));
+                for (i, (root, beta)) in roots[1..].iter().zip(betas).enumerate() {
+                    println!("  layer {} root {} folded with beta = {}", i + 1, common::bytes_to_hex(root), hex(beta));
+                }
+                self.pause();
+            },
+        }
+    }
+}
+
+// proves and verifies a hash chain of 2^steps_log2 steps,
+// reporting the time taken by each. if an output file is given,
+// the proof is written to it and verified from the file
+fn hash_chain(args: &HashChainArgs) {
+    let HashChainArgs { steps_log2, options, proof_out, public_input_out, tag } = args;
+    let steps_log2 = *steps_log2;
+    let trace_length = 1 << steps_log2;
+    let seed = FE::from(3141592_u64);
+    let trace = air::HashChain::trace(&seed, trace_length);
+    let digest = trace[trace_length - 1];
+
+    let public_input = common::PublicInput(
+        FConfig::MODULUS,
+        steps_log2,
+        steps_log2 + EVAL_TWO_POWER - INTERP_TWO_POWER,
+        NUM_QUERIES,
+        seed,
+        digest,
+        None,
+    );
+    let air = air::HashChain { trace_length, seed, digest };
+
+    println!("Hash chain of 2^{} steps", steps_log2);
+    println!("Statement: {}", public_input);
+    println!("Statement digest: {}", common::bytes_to_hex(&public_input.digest()));
+
+    if let Some(path) = public_input_out {
+        if let Err(e) = std::fs::write(path, encoding::encode_public_input(&public_input)) {
+            eprintln!("could not write {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+
+    let (proof, metrics) = prover::prove_with_metrics(&air, &trace, public_input.clone(), options);
+    println!("{}", metrics);
+
+    let start = Instant::now();
+    let result = match proof_out {
+        None => verifier::verify(&air, public_input, &proof, options).map_err(Box::from),
+        Some(path) => {
+            let bytes = match tag {
+                None => encoding::encode_proof(&proof),
+                Some(tag) => envelope::seal_statement(&proof, options, &hash_chain_statement(steps_log2), Some(&envelope::ProofMetadata::new(options, tag))),
+            };
+            if let Err(e) = std::fs::write(path, &bytes) {
+                eprintln!("could not write {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            println!("Proof size: {} bytes", bytes.len());
+            verify_file(&air, public_input, path, options)
+                .and_then(|report| report.result.map_err(Box::from))
+        },
+    };
+    println!("Verification time: {:.2?}", start.elapsed());
+
+    match result {
+        Ok(()) => println!("Valid Proof: successfully verified."),
+        Err(e) => println!("Valid Proof: could not be verified ({}).", e),
+    }
+}
+
+// identifier of the hash chain of 2^steps_log2 steps in the registry
+// of the cli
+fn hash_chain_statement(steps_log2: usize) -> String {
+    format!("hash-chain/{}", steps_log2)
+}
+
+// the statements the cli verifies sealed proofs of: the fibonacci
+// square of the default command and the hash chains of every length of
+// the hash-chain command, with the lde sizes these commands prove them with
+fn registry() -> registry::Registry {
+    let options = common::ProofOptions::default();
+    let shape = |interp_two_power: usize| common::PublicInput(
+        FConfig::MODULUS,
+        interp_two_power,
+        interp_two_power + EVAL_TWO_POWER - INTERP_TWO_POWER,
+        NUM_QUERIES,
+        FE::zero(),
+        FE::zero(),
+        None,
+    );
+
+    let mut registry = registry::Registry::new();
+    let fibonacci_square = verifier::StarkVerifier::fibonacci_square(options.clone());
+    let air = air::FibonacciSquare { trace_length: 1 << INTERP_TWO_POWER, fib_squared_0: FE::one(), fib_squared_1022: FE::zero() };
+    registry.register("fibonacci-square", fibonacci_square, registry::key_digest(&air, &shape(INTERP_TWO_POWER), &options));
+    for steps_log2 in 2..=28 {
+        let air = air::HashChain { trace_length: 1 << steps_log2, seed: FE::zero(), digest: FE::zero() };
+        let key_digest = registry::key_digest(&air, &shape(steps_log2), &options);
+        registry.register(&hash_chain_statement(steps_log2), verifier::StarkVerifier::hash_chain(options.clone()), key_digest);
+    }
+    registry
+}
+
+// verifies a sealed proof of one of the statements of the registry of
+// the cli, the one its envelope names, printing the outcome of each
+// check of the verifier, or the report as json. exits with status 1 if
+// the proof is rejected
+fn verify_sealed_command(public_input_path: &Path, proof_path: &Path, json: bool) {
+    let read = |path: &Path| std::fs::read(path).unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    let public_input_bytes = read(public_input_path);
+    let proof_bytes = read(proof_path);
+
+    let start = Instant::now();
+    let report = registry().verify_with_report(&public_input_bytes, &proof_bytes);
+    let elapsed = start.elapsed();
+    // a proof of another statement fails on the public input too
+    let public_input_valid = encoding::decode_public_input(&public_input_bytes).is_ok_and(|public_input| public_input.validate().is_ok())
+        && !matches!(report.result, Err(verifier::VerifyError::Statement(_)));
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        let statement = envelope::Envelope::parse(&proof_bytes).ok().and_then(|envelope| envelope.statement.map(str::to_string));
+        println!("Verifying {} proof {}", statement.as_deref().unwrap_or("sealed"), proof_path.display());
+        print_report(&report, &public_input_bytes, public_input_valid, elapsed, &Style::detect());
+    }
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
+
+// verifies an encoded proof in place, mapping the file in memory
+// when the mmap feature is enabled, either bare or sealed in an
+// envelope. a proof that cannot be decoded is reported as such, only
+// io errors are returned
+fn verify_file<A: air::Air<F>>(
+        air: &A,
+        public_input: common::PublicInput<F>,
+        path: &Path,
+        options: &common::ProofOptions
+    ) -> Result<report::VerificationReport, Box<dyn Error>> {
+
+    // a proof read from outside is held to its single valid encoding
+    let common::PublicInput(_, _, eval_two_power, ..) = public_input;
+    let mode = encoding::DecodeMode::Strict { eval_two_power };
+
+    #[cfg(feature = "mmap")]
+    let file = encoding::MappedProof::open(path)?;
+    #[cfg(feature = "mmap")]
+    let bytes = file.bytes();
+
+    #[cfg(not(feature = "mmap"))]
+    let bytes = &std::fs::read(path)?;
+
+    if envelope::is_envelope(bytes) {
+        return Ok(match envelope::Envelope::parse(bytes) {
+            Ok(envelope) => envelope.verify_with_report(air, public_input, mode, options),
+            Err(e) => rejected(verifier::VerifyError::Envelope(e)),
+        })
+    }
+    Ok(verifier::verify_encoded_with_report(air, public_input, bytes, mode, options))
+}
+
+fn rejected(error: verifier::VerifyError) -> report::VerificationReport {
+    report::VerificationReport { result: Err(error), ..Default::default() }
+}
+
+// verifies a proof of one of the statements from its encoded public
+// input and proof, printing the outcome of each check of the verifier,
+// or the report as json. exits with status 1 if the proof is rejected
+fn verify_command(statement: &str, public_input_path: &Path, proof_path: &Path, json: bool) {
+    let read = |path: &Path| std::fs::read(path).unwrap_or_else(|e| {
+        eprintln!("could not read {}: {}", path.display(), e);
+        std::process::exit(1);
+    });
+    let public_input_bytes = read(public_input_path);
+    let options = common::ProofOptions::default();
+
+    let start = Instant::now();
+    let (report, public_input_valid) = match encoding::decode_public_input(&public_input_bytes) {
+        Err(e) => (rejected(verifier::VerifyError::Decode(e)), false),
+        // the air is built from the public input, which must be valid first
+        Ok(public_input) => match public_input.validate() {
+            Err(e) => (rejected(verifier::VerifyError::PublicInput(e)), false),
+            Ok(()) => {
+                let common::PublicInput(_, interp_two_power, _, _, first, last, _) = public_input;
+                let trace_length = 1 << interp_two_power;
+                let result = match statement {
+                    "fibonacci-square" => {
+                        let air = air::FibonacciSquare { trace_length, fib_squared_0: first, fib_squared_1022: last };
+                        verify_file(&air, public_input, proof_path, &options)
+                    },
+                    "hash-chain" => {
+                        let air = air::HashChain { trace_length, seed: first, digest: last };
+                        verify_file(&air, public_input, proof_path, &options)
+                    },
+                    _ => {
+                        eprintln!("unknown statement: {}\n{}", statement, USAGE);
+                        std::process::exit(2);
+                    },
+                };
+                let report = result.unwrap_or_else(|e| {
+                    eprintln!("could not read {}: {}", proof_path.display(), e);
+                    std::process::exit(1);
+                });
+                (report, true)
+            },
+        },
+    };
+    let elapsed = start.elapsed();
+
+    if json {
+        println!("{}", report.to_json());
+    } else {
+        println!("Verifying {} proof {}", statement, proof_path.display());
+        print_report(&report, &public_input_bytes, public_input_valid, elapsed, &Style::detect());
+    }
+    if !report.is_valid() {
+        std::process::exit(1);
+    }
+}
+
+// ansi styling of the report, only when writing to a terminal
+// and NO_COLOR is not set
+struct Style {
+    color: bool,
+}
+
+impl Style {
+
+    fn detect() -> Self {
+        Self {
+            color: std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+        }
+    }
+
+    fn paint(&self, code: &str, text: &str) -> String {
+        if self.color {
+            format!("\x1b[{}m{}\x1b[0m", code, text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    fn status(&self, status: report::CheckStatus) -> String {
+        match status {
+            report::CheckStatus::Passed => self.paint("32", "  ok  "),
+            report::CheckStatus::Failed => self.paint("1;31", " FAIL "),
+            report::CheckStatus::NotReached => self.paint("2", "  --  "),
+        }
+    }
+}
+
+// one line per check of the verifier, with the number of queries or
+// fri layers that passed it, the failing one highlighted. the public
+// input is checked before the proof is decoded
+fn print_report(
+        report: &report::VerificationReport,
+        public_input_bytes: &[u8],
+        public_input_valid: bool,
+        elapsed: std::time::Duration,
+        style: &Style
+    ) {
+
+    use report::{CheckStatus, Phase};
+
+    let status = |phase: Phase| match (public_input_valid, phase) {
+        (true, Phase::PublicInput) => CheckStatus::Passed,
+        (true, _) => report.status(phase),
+        (false, Phase::PublicInput) => CheckStatus::Failed,
+        (false, _) => CheckStatus::NotReached,
+    };
+    // the queries of a failed check before the failing one did pass
+    let passed = |phase: Phase, total: usize, unit: &str| match status(phase) {
+        CheckStatus::Passed => format!("{}/{} {}", total, total, unit),
+        CheckStatus::Failed => format!("{}/{} {}", report.query().unwrap_or(0), total, unit),
+        CheckStatus::NotReached => "not reached".to_string(),
+    };
+    // the degree bounds of the layers, from the composition down to the
+    // constants of the last layer
+    let degrees = match (report.degree_bounds.first(), report.degree_bounds.last()) {
+        (Some(first), Some(last)) => format!(", degree bound {} down to {}", first, last),
+        _ => String::new(),
+    };
+    let layers = match status(Phase::Fri) {
+        CheckStatus::Passed => format!("{}/{} layers{}", report.num_layers, report.num_layers, degrees),
+        CheckStatus::Failed => match report.layer() {
+            Some(layer) => format!("{}/{} layers{}", layer, report.num_layers, degrees),
+            None => format!("last layer of {}{}", report.num_layers, degrees),
+        },
+        CheckStatus::NotReached => "not reached".to_string(),
+    };
+
+    let digest = DefaultTranscript::<F>::new(public_input_bytes).state();
+    let checks = [
+        (Phase::PublicInput, "public input", format!("keccak256 {}", common::bytes_to_hex(&digest))),
+        (Phase::Decoding, "proof decoding", String::new()),
+        (Phase::TraceOpenings, "trace openings", passed(Phase::TraceOpenings, report.num_queries, "queries")),
+        (Phase::CompositionConsistency, "composition", passed(Phase::CompositionConsistency, report.num_queries, "queries")),
+        (Phase::Fri, "fri", layers),
+    ];
+    for (phase, name, detail) in checks {
+        let status = status(phase);
+        let line = format!("{:<16}{}", name, detail);
+        let line = if status == CheckStatus::Failed { style.paint("1", line.trim_end()) } else { line.trim_end().to_string() };
+        println!("[{}] {}", style.status(status), line);
+    }
+
+    if let Some(root) = report.trace_root {
+        println!("trace root      {}", common::bytes_to_hex(&root));
+    }
+    if let Some(metadata) = &report.metadata {
+        println!("prover          stark101 {}, tag \"{}\", created at {} (unix time)", metadata.prover_version, metadata.tag, metadata.created_at);
+        println!("prover options  {}", common::bytes_to_hex(&metadata.options_digest));
+    }
+    println!("total time      {:.2?}", elapsed);
+    match &report.result {
+        Ok(()) => println!("{}", style.paint("32", "Proof verified.")),
+        Err(_) => println!("{}", style.paint("1;31", &format!("Proof rejected: {}.", report))),
     }
-}
\ No newline at end of file
+}
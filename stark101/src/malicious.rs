@@ -0,0 +1,134 @@
+// provers that deliberately cheat in one specific way each, so that
+// the verifier can be shown to catch every attack by the check meant
+// for it. apart from the attack, each follows the honest prover step
+// by step, with the same transcript
+use std::cell::RefCell;
+
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+
+use crate::air::Air;
+use crate::common::{ProofOptions, PublicInput, StarkProof};
+use crate::fri::{self, LayerStore, OffsetSchedule};
+use crate::key::ProvingKey;
+use crate::pipeline;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Attack {
+    // proves a trace of a wrong witness whose boundary rows are
+    // overwritten with the values claimed by the public input. the
+    // transitions into and out of those rows then fail, so the
+    // composition is not of low degree and fri rejects it
+    ForgedBoundary,
+    // commits to the given fri layer folded with a beta of the prover's
+    // choosing instead of the sampled one
+    InconsistentFolding { layer: usize },
+    // sends the commitment and openings of the previous fri layer in
+    // place of the ones of the given layer
+    ReusedCommitment { layer: usize },
+}
+
+// the beta a dishonest prover folds with
+const CHOSEN_BETA: u64 = 101;
+
+// proves the statement from the trace, carrying out the attack
+pub fn prove_dishonestly<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions,
+        attack: Attack
+    ) -> StarkProof<F> {
+
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = public_input;
+    let key = ProvingKey::new(air, eval_two_power);
+    let mut transcript = pipeline::start_transcript(air, &public_input, options);
+
+    let mut trace = trace.to_vec();
+    if attack == Attack::ForgedBoundary {
+        for constraint in &air.boundary_constraints().constraints {
+            trace[constraint.step] = constraint.value;
+        }
+    }
+
+    let trace_poly = pipeline::interpolate_trace(&key, &trace);
+    let trace_lde = pipeline::extend_trace(&key, &trace_poly);
+    let trace_tree = pipeline::commit_evaluations(&key, &trace_lde, options, &mut transcript);
+    let composition = pipeline::build_composition(&key, air, &trace_lde, &mut transcript);
+    let query_indices = pipeline::sample_queries(&public_input, &mut transcript);
+    let trace_commitment = pipeline::open_trace(&key, air, &trace_lde, &trace_tree, &query_indices, options);
+
+    let store = match attack {
+        Attack::InconsistentFolding { layer } => Some(MisfoldingStore::new(layer, key.lde_domain.offset, options.offset_schedule, &composition.evaluations)),
+        _ => None,
+    };
+    let (mut composition_commitment, _) = fri::commit_and_fold_resumable(
+        composition.evaluations,
+        &key.lde_domain.offset,
+        air.composition_degree_bound(),
+        query_indices,
+        &fri::FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend },
+        &mut transcript,
+        store.as_ref().map(|store| store as &dyn LayerStore<F>),
+        false
+    );
+
+    if let Attack::ReusedCommitment { layer } = attack {
+        assert!(0 < layer && layer < composition_commitment.len(), "no fri layer {} to reuse a commitment for", layer);
+        composition_commitment[layer] = composition_commitment[layer - 1].clone();
+    }
+
+    pipeline::bind_proof(trace_commitment, composition_commitment, &mut transcript)
+}
+
+// hands fri its own evaluations for the attacked layer: the previous
+// layer, the composition or a folding seen through save_layer, folded
+// with the chosen beta
+struct MisfoldingStore {
+    layer: usize,
+    lde_offset: FE,
+    offset_schedule: OffsetSchedule,
+    previous: RefCell<Vec<FE>>,
+}
+
+impl MisfoldingStore {
+
+    // the offsets sampled by the transcript are not seen by the store
+    fn new(layer: usize, lde_offset: FE, offset_schedule: OffsetSchedule, composition: &[FE]) -> Self {
+        assert!(layer > 0, "the first fri layer is not a folding");
+        assert!(offset_schedule != OffsetSchedule::Sampled, "no misfolding with sampled offsets");
+        Self {
+            layer,
+            lde_offset,
+            offset_schedule,
+            previous: RefCell::new(composition.to_vec()),
+        }
+    }
+}
+
+impl LayerStore<F> for MisfoldingStore {
+
+    fn load_layer(&self, layer: usize) -> Option<Vec<FE>> {
+        if layer != self.layer {
+            return None
+        }
+        // the domain of the previous layer is the lde coset squared
+        // layer - 1 times, or the lde coset itself with fixed offsets
+        let offset = match self.offset_schedule {
+            OffsetSchedule::Fixed => self.lde_offset,
+            _ => (1..layer).fold(self.lde_offset, |offset, _| offset.square()),
+        };
+        Some(fri::fold_evaluations(&self.previous.borrow(), &offset, &FE::from(CHOSEN_BETA)))
+    }
+
+    fn save_layer(&self, layer: usize, evals: &[FE]) {
+        if layer + 1 == self.layer {
+            *self.previous.borrow_mut() = evals.to_vec();
+        }
+    }
+}
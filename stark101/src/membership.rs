@@ -0,0 +1,255 @@
+// a membership proof: knowledge of a leaf and of its authentication
+// path in a merkle tree of public root and depth, the leaf, its index
+// and the path being private. every level of the path takes a block of
+// 16 rows of the single trace column, hashing the node with its sibling:
+//
+//   row 0        zero, the capacity of the hash
+//   rows 1, 2    the left and right children, one of them the node
+//                output by the block before
+//   rows 3..16   the rounds of the hash, the last row being the parent
+//
+// the blocks past the path hash the root with zero, and so on, so that
+// the rounds and the links hold on the same rows of every block
+//
+// the hash is an unbalanced feistel network over a state of three rows,
+// each round adding the cube of the two rows before it to a third one,
+// not poseidon: its rounds would need round constants varying with the
+// row, i.e. periodic columns, and a state wider than the single column
+// of this crate. it only illustrates the statement and must not be
+// relied on for security
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::{
+        Stark252PrimeField,
+        MontgomeryConfigStark252PrimeField
+    },
+    fields::montgomery_backed_prime_fields::IsModulus,
+    element::FieldElement
+};
+
+use crate::air::{Air, BoundaryConstraint, BoundaryConstraints, TransitionConstraint};
+use crate::common::PublicInput;
+use crate::expr::Expr;
+use crate::ct;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// rows of the trace hashing one level of the path
+pub const MEMBERSHIP_BLOCK_ROWS: usize = 16;
+
+pub const COMPRESSION_ROUND_CONSTANT: u64 = 11;
+
+// the rounds of the hash, from the capacity and the two children
+const COMPRESSION_ROUNDS: usize = MEMBERSHIP_BLOCK_ROWS - 3;
+
+// the rows of the block hashing a left and a right child
+fn compression_rows(left: &FE, right: &FE) -> Vec<FE> {
+    let round_constant = FE::from(COMPRESSION_ROUND_CONSTANT);
+    let mut rows = vec![FE::zero(), *left, *right];
+    for k in 0..COMPRESSION_ROUNDS {
+        let sum = ct::add(&ct::add(&rows[k + 1], &rows[k + 2]), &round_constant);
+        rows.push(ct::add(&rows[k], &ct::mul(&ct::square(&sum), &sum)));
+    }
+    rows
+}
+
+// the parent of two nodes of the tree
+pub fn compress(left: &FE, right: &FE) -> FE {
+    *compression_rows(left, right).last().unwrap()
+}
+
+// the levels of the tree, from the leaves to the root, whose number
+// must be a power of two
+fn tree_levels(leaves: &[FE]) -> Vec<Vec<FE>> {
+    assert!(leaves.len().is_power_of_two(), "a merkle tree of a power of two leaves");
+    let mut levels = vec![leaves.to_vec()];
+    while levels.last().unwrap().len() > 1 {
+        let parents = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| compress(&pair[0], &pair[1]))
+            .collect::<Vec<FE>>();
+        levels.push(parents);
+    }
+    levels
+}
+
+pub fn merkle_root(leaves: &[FE]) -> FE {
+    tree_levels(leaves).pop().unwrap()[0]
+}
+
+// the siblings of the leaf at index and of its ancestors, from the
+// leaf up to the children of the root
+pub fn authentication_path(leaves: &[FE], index: usize) -> Vec<FE> {
+    let levels = tree_levels(leaves);
+    levels[..levels.len() - 1]
+        .iter()
+        .enumerate()
+        .map(|(level, nodes)| nodes[(index >> level) ^ 1])
+        .collect::<Vec<FE>>()
+}
+
+// the statement that a leaf of the tree of the given root and depth
+// is known, checked with an lde of 2^blowup_log2 times the trace. the
+// rounds are cubic and hold on 13 of the 16 rows of each block, so the
+// composition has over twice the degree of the trace and the blowup
+// must be 8 or more
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MembershipInput {
+    pub root: FE,
+    pub depth: usize,
+    pub blowup_log2: usize,
+    pub num_queries: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MembershipError {
+    // the path does not have one sibling per level
+    PathLength { depth: usize, siblings: usize },
+    // the index is not the one of a leaf of the tree
+    IndexOutOfRange { index: usize },
+    // the path leads to another root
+    NotTheRoot,
+    // the public input is not the one of a membership proof
+    NotAMembership,
+}
+
+impl std::fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MembershipError::PathLength { depth, siblings } => write!(f, "a path of {} siblings in a tree of depth {}", siblings, depth),
+            MembershipError::IndexOutOfRange { index } => write!(f, "no leaf at index {}", index),
+            MembershipError::NotTheRoot => write!(f, "the path does not lead to the root"),
+            MembershipError::NotAMembership => write!(f, "not the public input of a membership proof"),
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {}
+
+impl MembershipInput {
+
+    // the levels of the path followed by the padding blocks
+    pub fn trace_length(&self) -> usize {
+        (MEMBERSHIP_BLOCK_ROWS * self.depth).next_power_of_two()
+    }
+
+    // the generic public input of the statement, its public values
+    // being the root and the depth
+    pub fn public_input(&self) -> PublicInput<F> {
+        let interp_two_power = self.trace_length().trailing_zeros() as usize;
+        PublicInput(
+            MontgomeryConfigStark252PrimeField::MODULUS,
+            interp_two_power,
+            interp_two_power + self.blowup_log2,
+            self.num_queries,
+            self.root,
+            FE::from(self.depth as u64),
+            None,
+        )
+    }
+
+    pub fn from_public_input(public_input: &PublicInput<F>) -> Result<Self, MembershipError> {
+        let PublicInput(_, interp_two_power, eval_two_power, num_queries, root, depth, witness_commitment) = public_input;
+        if witness_commitment.is_some() || *eval_two_power < *interp_two_power {
+            return Err(MembershipError::NotAMembership)
+        }
+        // the depths whose trace has the length of the public input
+        let trace_length = 1_usize.checked_shl(*interp_two_power as u32).ok_or(MembershipError::NotAMembership)?;
+        let depth = (trace_length / (2 * MEMBERSHIP_BLOCK_ROWS) + 1..=trace_length / MEMBERSHIP_BLOCK_ROWS)
+            .find(|candidate| FE::from(*candidate as u64) == *depth)
+            .ok_or(MembershipError::NotAMembership)?;
+        Ok(Self {
+            root: *root,
+            depth,
+            blowup_log2: eval_two_power - interp_two_power,
+            num_queries: *num_queries,
+        })
+    }
+
+    pub fn air(&self) -> Membership {
+        Membership {
+            trace_length: self.trace_length(),
+            depth: self.depth,
+            root: self.root,
+        }
+    }
+
+    // the trace hashing the leaf with the siblings of the path, up to
+    // the root. bit j of the index tells whether the node of level j
+    // is the right child
+    pub fn trace(&self, leaf: &FE, index: usize, siblings: &[FE]) -> Result<Vec<FE>, MembershipError> {
+        if siblings.len() != self.depth {
+            return Err(MembershipError::PathLength { depth: self.depth, siblings: siblings.len() })
+        }
+        if index >> self.depth != 0 {
+            return Err(MembershipError::IndexOutOfRange { index })
+        }
+        let mut trace = Vec::<FE>::with_capacity(self.trace_length());
+        let mut node = *leaf;
+        for (level, sibling) in siblings.iter().enumerate() {
+            // the node is the right child without branching on the index
+            let right_child = (index >> level) & 1 == 1;
+            let rows = compression_rows(
+                &ct::select(right_child, sibling, &node),
+                &ct::select(right_child, &node, sibling)
+            );
+            node = *rows.last().unwrap();
+            trace.extend(rows);
+        }
+        if node != self.root {
+            return Err(MembershipError::NotTheRoot)
+        }
+        while trace.len() < self.trace_length() {
+            let rows = compression_rows(&node, &FE::zero());
+            node = *rows.last().unwrap();
+            trace.extend(rows);
+        }
+        Ok(trace)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Membership {
+    pub trace_length: usize,
+    pub depth: usize,
+    pub root: FE,
+}
+
+impl Air<F> for Membership {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "membership".to_string()
+    }
+
+    // the capacity of every block is zero and the last level outputs the root
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        let mut constraints = (0..self.depth)
+            .map(|level| BoundaryConstraint { step: level * MEMBERSHIP_BLOCK_ROWS, value: FE::zero() })
+            .collect::<Vec<BoundaryConstraint<F>>>();
+        constraints.push(BoundaryConstraint { step: self.depth * MEMBERSHIP_BLOCK_ROWS - 1, value: self.root });
+        BoundaryConstraints::new(constraints)
+    }
+
+    // the rounds of the hash on the first rows of each block, and the
+    // output of each block but the last one being one of the children
+    // hashed by the next one
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let round_rows = (0..COMPRESSION_ROUNDS).collect::<Vec<usize>>();
+        vec![
+            TransitionConstraint::from_expr(
+                &[],
+                Expr::row(3) - Expr::col(0) - (Expr::next(0) + Expr::row(2) + FE::from(COMPRESSION_ROUND_CONSTANT)).pow(3)
+            ).on_rows(MEMBERSHIP_BLOCK_ROWS, &round_rows),
+            TransitionConstraint::from_expr(
+                &[self.trace_length - 1],
+                (Expr::row(2) - Expr::col(0)) * (Expr::row(3) - Expr::col(0))
+            ).on_rows(MEMBERSHIP_BLOCK_ROWS, &[MEMBERSHIP_BLOCK_ROWS - 1]),
+        ]
+    }
+}
@@ -0,0 +1,374 @@
+// the hash function of the merkle trees committing to the trace and to
+// the fri layers. keccak256 keeps the paths checkable on the evm, which
+// blake3 gives up for leaf hashing several times faster on large
+// extensions, where the prover spends much of its commit time. both
+// sides must use the same backend: it is part of the proof options,
+// absorbed into the transcript with the verifying key digest, so that
+// a proof is only valid for the backend it was made with.
+//
+// a leaf of the trace tree holds one evaluation of the extension, or
+// several consecutive rows of the trace over one coset, see LeafLayout
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    traits::IsField
+};
+use lambdaworks_math::traits::AsBytes;
+use lambdaworks_crypto::merkle_tree::{
+    merkle::MerkleTree,
+    backends::types::{BatchKeccak256Backend, Keccak256Backend},
+    proof::Proof,
+    traits::IsMerkleTreeBackend
+};
+#[cfg(feature = "blake3")]
+use lambdaworks_crypto::merkle_tree::backends::{
+    field_element::FieldElementBackend,
+    field_element_vector::FieldElementVectorBackend
+};
+
+use crate::common::InclusionProof;
+
+#[cfg(feature = "blake3")]
+pub type Blake3Backend<F> = FieldElementBackend<F, blake3::Hasher, 32>;
+#[cfg(feature = "blake3")]
+pub type BatchBlake3Backend<F> = FieldElementVectorBackend<F, blake3::Hasher, 32>;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MerkleBackend {
+    #[default]
+    Keccak256,
+    #[cfg(feature = "blake3")]
+    Blake3,
+    // the fastest backend of this build on this machine, see
+    // fastest_backend, for proofs checked by a verifier in the same
+    // process. any other verifier is given the resolved options, see
+    // ProofOptions::resolved. the other backends are always used as set
+    Auto,
+}
+
+// the backends of this build, in order of preference between equally
+// fast ones
+pub const BACKENDS: &[MerkleBackend] = &[
+    MerkleBackend::Keccak256,
+    #[cfg(feature = "blake3")]
+    MerkleBackend::Blake3,
+];
+
+impl MerkleBackend {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MerkleBackend::Keccak256 => "keccak256",
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => "blake3",
+            MerkleBackend::Auto => "auto",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        BACKENDS
+            .iter()
+            .chain([&MerkleBackend::Auto])
+            .find(|backend| backend.name() == name)
+            .copied()
+    }
+
+    // the backend the trees are hashed with, never auto
+    pub fn resolve(&self) -> Self {
+        match self {
+            MerkleBackend::Auto => fastest_backend(),
+            backend => *backend,
+        }
+    }
+
+    pub fn hash_data<F>(&self, eval: &FieldElement<F>) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        match self {
+            MerkleBackend::Keccak256 => Keccak256Backend::<F>::hash_data(eval),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => Blake3Backend::<F>::hash_data(eval),
+            MerkleBackend::Auto => self.resolve().hash_data(eval),
+        }
+    }
+
+    pub fn hash_new_parent<F>(&self, left: &[u8; 32], right: &[u8; 32]) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        match self {
+            MerkleBackend::Keccak256 => Keccak256Backend::<F>::hash_new_parent(left, right),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => Blake3Backend::<F>::hash_new_parent(left, right),
+            MerkleBackend::Auto => self.resolve().hash_new_parent::<F>(left, right),
+        }
+    }
+
+    pub fn build<F>(&self, evals: &[FieldElement<F>]) -> Tree<F>
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        match self {
+            MerkleBackend::Keccak256 => Tree::Keccak256(MerkleTree::build(evals)),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => Tree::Blake3(MerkleTree::build(evals)),
+            MerkleBackend::Auto => self.resolve().build(evals),
+        }
+    }
+
+    // the node of a leaf of the given evaluations, the hash of their
+    // bytes one after the other. the node of a leaf of one evaluation
+    // is the one of the evaluation
+    pub fn hash_leaf<F>(&self, evals: &[FieldElement<F>]) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        match self {
+            MerkleBackend::Keccak256 => BatchKeccak256Backend::<F>::hash_data(&evals.to_vec()),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => BatchBlake3Backend::<F>::hash_data(&evals.to_vec()),
+            MerkleBackend::Auto => self.resolve().hash_leaf(evals),
+        }
+    }
+
+    // the tree of the evaluations packed into the leaves of the layout,
+    // the tree of the evaluations themselves for leaves of one
+    pub fn build_packed<F>(&self, evals: &[FieldElement<F>], layout: &LeafLayout) -> Tree<F>
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        if layout.width == 1 {
+            return self.build(evals)
+        }
+        let leaves = (0..evals.len() / layout.width)
+            .map(|leaf| layout.positions(leaf).map(|position| evals[position].clone()).collect())
+            .collect::<Vec<Vec<FieldElement<F>>>>();
+        match self {
+            MerkleBackend::Keccak256 => Tree::Keccak256Leaves(MerkleTree::build(&leaves)),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => Tree::Blake3Leaves(MerkleTree::build(&leaves)),
+            MerkleBackend::Auto => self.resolve().build_packed(evals, layout),
+        }
+    }
+
+    // whether the path opens the evaluation at the index of the tree
+    // of the given root
+    pub fn verify<F>(&self, proof: &Proof<[u8; 32]>, root: &[u8; 32], index: usize, eval: &FieldElement<F>) -> bool
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        match self {
+            MerkleBackend::Keccak256 => proof.verify::<Keccak256Backend<F>>(root, index, eval),
+            #[cfg(feature = "blake3")]
+            MerkleBackend::Blake3 => proof.verify::<Blake3Backend<F>>(root, index, eval),
+            MerkleBackend::Auto => self.resolve().verify(proof, root, index, eval),
+        }
+    }
+
+    // recomputes the merkle root reached by the path of an opening
+    pub fn root_from_path<F>(&self, proof: &Proof<[u8; 32]>, index: usize, eval: &FieldElement<F>) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        self.root_from_node::<F>(proof, index, self.hash_data(eval))
+    }
+
+    // recomputes the merkle root reached by the path of the leaf of the
+    // given index holding the evaluations
+    pub fn root_from_leaf<F>(&self, proof: &Proof<[u8; 32]>, index: usize, evals: &[FieldElement<F>]) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        self.root_from_node::<F>(proof, index, self.hash_leaf(evals))
+    }
+
+    fn root_from_node<F>(&self, proof: &Proof<[u8; 32]>, mut index: usize, leaf: [u8; 32]) -> [u8; 32]
+        where
+            F: IsField,
+            FieldElement<F>: AsBytes + Sync + Send {
+
+        proof.merkle_path
+            .iter()
+            .fold(leaf, |node, sibling| {
+                let parent = if index.is_multiple_of(2) {
+                    self.hash_new_parent::<F>(&node, sibling)
+                } else {
+                    self.hash_new_parent::<F>(sibling, &node)
+                };
+                index >>= 1;
+                parent
+            })
+    }
+}
+
+// a lambdaworks merkle tree over the evaluations, of either backend,
+// with one evaluation or several in each leaf
+#[derive(Clone)]
+pub enum Tree<F>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + Sync + Send {
+
+    Keccak256(MerkleTree<Keccak256Backend<F>>),
+    #[cfg(feature = "blake3")]
+    Blake3(MerkleTree<Blake3Backend<F>>),
+    Keccak256Leaves(MerkleTree<BatchKeccak256Backend<F>>),
+    #[cfg(feature = "blake3")]
+    Blake3Leaves(MerkleTree<BatchBlake3Backend<F>>),
+}
+
+impl<F> Tree<F>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + Sync + Send {
+
+    pub fn root(&self) -> [u8; 32] {
+        match self {
+            Tree::Keccak256(tree) => tree.root,
+            #[cfg(feature = "blake3")]
+            Tree::Blake3(tree) => tree.root,
+            Tree::Keccak256Leaves(tree) => tree.root,
+            #[cfg(feature = "blake3")]
+            Tree::Blake3Leaves(tree) => tree.root,
+        }
+    }
+
+    // the path of the leaf of the given index
+    pub fn get_proof_by_pos(&self, pos: usize) -> Option<Proof<[u8; 32]>> {
+        match self {
+            Tree::Keccak256(tree) => tree.get_proof_by_pos(pos),
+            #[cfg(feature = "blake3")]
+            Tree::Blake3(tree) => tree.get_proof_by_pos(pos),
+            Tree::Keccak256Leaves(tree) => tree.get_proof_by_pos(pos),
+            #[cfg(feature = "blake3")]
+            Tree::Blake3Leaves(tree) => tree.get_proof_by_pos(pos),
+        }
+    }
+
+    // the opening of the evaluation at the position of a tree of the
+    // layout. the path of a leaf is sent with its first evaluation, the
+    // other evaluations of the leaf have an empty path
+    pub fn open(&self, layout: &LeafLayout, position: usize, eval: FieldElement<F>) -> Option<InclusionProof<F>> {
+        let (leaf, offset) = layout.leaf_of(position);
+        let path = match offset {
+            0 => self.get_proof_by_pos(leaf)?,
+            _ => Proof { merkle_path: vec![] },
+        };
+        Some(InclusionProof(eval, path))
+    }
+}
+
+// how the trace extension is packed into leaves. the extension over
+// blowup_factor * n points is the union of blowup_factor cosets of the
+// trace domain, the position c + blowup_factor * r holding the row r
+// of the trace over the coset c. the leaf c + blowup_factor * i holds
+// the width rows from i * width of the coset c, so that the rows of
+// the frame of a query, rotations of the trace, share a leaf unless
+// they straddle two. with a width of 1 the leaves are the positions.
+// every evaluation of a leaf is opened, in the order of the rows
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LeafLayout {
+    pub width: usize,
+    pub blowup_factor: usize,
+}
+
+impl LeafLayout {
+
+    pub fn new(width: usize, blowup_factor: usize) -> Self {
+        Self { width, blowup_factor }
+    }
+
+    // the leaf holding the position and the offset of the position in it
+    pub fn leaf_of(&self, position: usize) -> (usize, usize) {
+        let (coset, row) = (position % self.blowup_factor, position / self.blowup_factor);
+        (coset + self.blowup_factor * (row / self.width), row % self.width)
+    }
+
+    // the positions held by the leaf, in order
+    pub fn positions(&self, leaf: usize) -> impl Iterator<Item = usize> {
+        let (coset, first_row) = (leaf % self.blowup_factor, leaf / self.blowup_factor * self.width);
+        let blowup_factor = self.blowup_factor;
+        (first_row..first_row + self.width).map(move |row| coset + blowup_factor * row)
+    }
+
+    // the rank of the position when the positions are ordered by leaf,
+    // then by offset, the order of the trace openings
+    pub fn rank(&self, position: usize) -> usize {
+        let (leaf, offset) = self.leaf_of(position);
+        leaf * self.width + offset
+    }
+
+    // the height of the tree of an extension of the given size
+    pub fn height(&self, domain_size: usize) -> usize {
+        (domain_size / self.width).trailing_zeros() as usize
+    }
+}
+
+// leaves of the trees built by fastest_backend, enough for the hashing
+// to outweigh the allocations, and the runs of each backend
+const BENCHMARK_LEAVES: usize = 1 << 12;
+const BENCHMARK_RUNS: usize = 3;
+
+// the time a backend took to build a tree of the given leaves
+#[derive(Clone, Copy, Debug)]
+pub struct BackendTiming {
+    pub backend: MerkleBackend,
+    pub leaves: usize,
+    pub duration: Duration,
+}
+
+impl BackendTiming {
+
+    pub fn leaves_per_second(&self) -> f64 {
+        self.leaves as f64 / self.duration.as_secs_f64().max(f64::MIN_POSITIVE)
+    }
+}
+
+// builds a tree of the given number of leaves with each backend of
+// this build, in the order of BACKENDS. the fastest of a few runs is
+// kept, leaving out the warm up of the caches
+pub fn benchmark_backends(leaves: usize) -> Vec<BackendTiming> {
+    let evals = (0..leaves as u64)
+        .map(FieldElement::<Stark252PrimeField>::from)
+        .collect::<Vec<FieldElement<Stark252PrimeField>>>();
+    BACKENDS
+        .iter()
+        .map(|backend| {
+            let duration = (0..BENCHMARK_RUNS)
+                .map(|_| {
+                    let start = Instant::now();
+                    std::hint::black_box(backend.build(&evals).root());
+                    start.elapsed()
+                })
+                .min()
+                .unwrap_or_default();
+            BackendTiming { backend: *backend, leaves, duration }
+        })
+        .collect()
+}
+
+// the backend of this build building trees the fastest on this
+// machine, measured once per process so that a prover and a verifier
+// in the same process resolve auto to the same backend
+pub fn fastest_backend() -> MerkleBackend {
+    static FASTEST: OnceLock<MerkleBackend> = OnceLock::new();
+    *FASTEST.get_or_init(|| match BACKENDS {
+        [backend] => *backend,
+        _ => benchmark_backends(BENCHMARK_LEAVES)
+            .iter()
+            .min_by_key(|timing| timing.duration)
+            .map_or(MerkleBackend::default(), |timing| timing.backend),
+    })
+}
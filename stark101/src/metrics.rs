@@ -0,0 +1,303 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::threads;
+
+// the three parts of the stark 101 prover
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverPhase {
+    // trace interpolation, low degree extension and commitment
+    TraceCommitment,
+    // boundary and transition quotients and their composition
+    Constraints,
+    // fri commitment of the composition polynomial and trace openings
+    Fri,
+}
+
+impl ProverPhase {
+
+    // name of the phase in machine readable output, kept stable
+    // across versions
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProverPhase::TraceCommitment => "trace_commitment",
+            ProverPhase::Constraints => "constraints",
+            ProverPhase::Fri => "fri",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PhaseMetrics {
+    pub phase: ProverPhase,
+    pub duration: Duration,
+    // peak heap usage during the phase, only available with
+    // the alloc-tracking feature
+    pub peak_allocated_bytes: Option<usize>,
+    // peak resident set size of the process during the phase,
+    // only available on linux
+    pub peak_rss_bytes: Option<usize>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ProverMetrics {
+    pub phases: Vec<PhaseMetrics>,
+    pub backend: Backend,
+}
+
+// cpu features that speed up the field arithmetic and the hashing
+const TARGET_FEATURES: [&str; 4] = ["bmi2", "adx", "avx2", "avx512f"];
+
+// how the arithmetic and hashing of the prover run in this build
+#[derive(Clone, Debug, Default)]
+pub struct Backend {
+    // whether merkle trees are hashed with rayon, with the parallel
+    // feature and outside of threads::single_threaded
+    pub parallel: bool,
+    pub threads: usize,
+    // cpu features the build was compiled for
+    pub target_features: Vec<&'static str>,
+    // cpu features of this machine the build was not compiled for,
+    // enabled with RUSTFLAGS="-C target-cpu=native"
+    pub unused_target_features: Vec<&'static str>,
+}
+
+impl Backend {
+
+    pub fn current() -> Self {
+        let (target_features, unused_target_features) = TARGET_FEATURES
+            .iter()
+            .filter(|feature| detected(feature))
+            .partition::<Vec<&'static str>, _>(|feature| compiled(feature));
+        Self {
+            parallel: cfg!(feature = "parallel") && !threads::is_single_threaded(),
+            threads: match threads::is_single_threaded() {
+                true => 1,
+                false => std::thread::available_parallelism().map_or(1, |n| n.get()),
+            },
+            target_features,
+            unused_target_features,
+        }
+    }
+}
+
+// whether the build was compiled for the cpu feature
+fn compiled(feature: &str) -> bool {
+    match feature {
+        "bmi2" => cfg!(target_feature = "bmi2"),
+        "adx" => cfg!(target_feature = "adx"),
+        "avx2" => cfg!(target_feature = "avx2"),
+        "avx512f" => cfg!(target_feature = "avx512f"),
+        _ => false,
+    }
+}
+
+// whether this machine has the cpu feature
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+fn detected(feature: &str) -> bool {
+    match feature {
+        "bmi2" => std::is_x86_feature_detected!("bmi2"),
+        "adx" => std::is_x86_feature_detected!("adx"),
+        "avx2" => std::is_x86_feature_detected!("avx2"),
+        "avx512f" => std::is_x86_feature_detected!("avx512f"),
+        _ => false,
+    }
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64")))]
+fn detected(feature: &str) -> bool {
+    compiled(feature)
+}
+
+impl fmt::Display for Backend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "generic montgomery field arithmetic")?;
+        match self.target_features.as_slice() {
+            [] => write!(f, ", no cpu extension")?,
+            features => write!(f, " with {}", features.join(", "))?,
+        }
+        if self.parallel {
+            write!(f, ", parallel hashing on {} thread{}", self.threads, if self.threads == 1 { "" } else { "s" })?;
+        } else {
+            write!(f, ", serial hashing")?;
+        }
+        if !self.unused_target_features.is_empty() {
+            write!(f, " ({} not enabled in this build)", self.unused_target_features.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+impl ProverMetrics {
+
+    pub fn total_duration(&self) -> Duration {
+        self.phases
+            .iter()
+            .map(|p| p.duration)
+            .sum()
+    }
+
+    pub fn peak_allocated_bytes(&self) -> Option<usize> {
+        self.phases
+            .iter()
+            .filter_map(|p| p.peak_allocated_bytes)
+            .max()
+    }
+
+    pub fn peak_rss_bytes(&self) -> Option<usize> {
+        self.phases
+            .iter()
+            .filter_map(|p| p.peak_rss_bytes)
+            .max()
+    }
+}
+
+// measures the prover phases one after the other
+pub(crate) struct PhaseRecorder {
+    metrics: ProverMetrics,
+    current: Option<(ProverPhase, Instant)>,
+}
+
+impl PhaseRecorder {
+
+    pub(crate) fn new() -> Self {
+        Self {
+            metrics: ProverMetrics {
+                phases: vec![],
+                backend: Backend::current(),
+            },
+            current: None,
+        }
+    }
+
+    // ends the current phase, if any, and starts measuring the given one
+    pub(crate) fn start(&mut self, phase: ProverPhase) {
+        self.end();
+        alloc::reset_peak();
+        rss::reset_peak();
+        self.current = Some((phase, Instant::now()));
+    }
+
+    pub(crate) fn finish(mut self) -> ProverMetrics {
+        self.end();
+        self.metrics
+    }
+
+    fn end(&mut self) {
+        if let Some((phase, start)) = self.current.take() {
+            self.metrics.phases.push(PhaseMetrics {
+                phase,
+                duration: start.elapsed(),
+                peak_allocated_bytes: alloc::peak(),
+                peak_rss_bytes: rss::peak(),
+            });
+        }
+    }
+}
+
+impl fmt::Display for ProverMetrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for p in &self.phases {
+            write!(f, "{:<16} {:>10.2?}", format!("{:?}", p.phase), p.duration)?;
+            if let Some(bytes) = p.peak_allocated_bytes {
+                write!(f, "  peak heap {}", format_bytes(bytes))?;
+            }
+            if let Some(bytes) = p.peak_rss_bytes {
+                write!(f, "  peak rss {}", format_bytes(bytes))?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "{:<16} {:>10.2?}", "Total", self.total_duration())?;
+        write!(f, "{:<16} {}", "Backend", self.backend)
+    }
+}
+
+fn format_bytes(bytes: usize) -> String {
+    format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+// global allocator counting the bytes in use and their peak,
+// installed only when the alloc-tracking feature is enabled
+#[cfg(feature = "alloc-tracking")]
+pub mod alloc {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+    static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+    pub struct TrackingAllocator;
+
+    unsafe impl GlobalAlloc for TrackingAllocator {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let ptr = System.alloc(layout);
+            if !ptr.is_null() {
+                let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+                PEAK.fetch_max(allocated, Ordering::Relaxed);
+            }
+            ptr
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout);
+            ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        }
+
+        unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+            let new_ptr = System.realloc(ptr, layout, new_size);
+            if !new_ptr.is_null() {
+                if new_size > layout.size() {
+                    let grown = new_size - layout.size();
+                    let allocated = ALLOCATED.fetch_add(grown, Ordering::Relaxed) + grown;
+                    PEAK.fetch_max(allocated, Ordering::Relaxed);
+                } else {
+                    ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+                }
+            }
+            new_ptr
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: TrackingAllocator = TrackingAllocator;
+
+    // the peak restarts from the bytes currently in use
+    pub fn reset_peak() {
+        PEAK.store(ALLOCATED.load(Ordering::Relaxed), Ordering::Relaxed);
+    }
+
+    pub fn peak() -> Option<usize> {
+        Some(PEAK.load(Ordering::Relaxed))
+    }
+}
+
+#[cfg(not(feature = "alloc-tracking"))]
+pub mod alloc {
+
+    pub fn reset_peak() {}
+
+    pub fn peak() -> Option<usize> {
+        None
+    }
+}
+
+// peak resident set size as tracked by the linux kernel
+mod rss {
+
+    // the kernel high water mark is reset by writing 5 to clear_refs,
+    // if that is not permitted the peak is the one of the whole process
+    pub fn reset_peak() {
+        if cfg!(target_os = "linux") {
+            let _ = std::fs::write("/proc/self/clear_refs", "5");
+        }
+    }
+
+    pub fn peak() -> Option<usize> {
+        let status = std::fs::read_to_string("/proc/self/status").ok()?;
+        status
+            .lines()
+            .find_map(|line| line.strip_prefix("VmHWM:"))
+            .and_then(|value| value.trim().trim_end_matches("kB").trim().parse::<usize>().ok())
+            .map(|kb| kb * 1024)
+    }
+}
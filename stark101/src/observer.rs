@@ -0,0 +1,53 @@
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+// an intermediate object of the prover, handed to its observer as
+// soon as it is computed, in the order of the parts of stark 101
+pub enum ProverEvent<'a, F: IsField> {
+    // part 1: the trace and the polynomial interpolating it over
+    // the trace domain
+    Trace {
+        trace: &'a [FieldElement<F>],
+        polynomial: &'a Polynomial<FieldElement<F>>,
+    },
+    // part 1: the root of the evaluations of the trace polynomial
+    // over the lde domain
+    TraceCommitment {
+        lde_size: usize,
+        root: [u8; 32],
+    },
+    // part 2: the composition of the constraints with the random
+    // coefficient of the boundary constraints and the ones of the
+    // transition constraints
+    Composition {
+        boundary_coefficient: &'a FieldElement<F>,
+        transition_coefficients: &'a [FieldElement<F>],
+        // over the lde domain, never interpolated by the prover
+        evaluations: &'a [FieldElement<F>],
+        degree_bound: usize,
+    },
+    // part 3: the queries of the lde domain opened in the proof
+    Queries {
+        indices: &'a [usize],
+    },
+    // part 3: the root of each fri layer and the random betas folding
+    // each of them into the next one
+    FriLayers {
+        roots: &'a [[u8; 32]],
+        betas: &'a [FieldElement<F>],
+    },
+}
+
+// receives the intermediate objects of a proof, for instance to print
+// them while the prover runs
+pub trait ProverObserver<F: IsField> {
+    fn observe(&mut self, event: ProverEvent<'_, F>);
+}
+
+// the observer of the plain prover, ignoring every event
+impl<F: IsField> ProverObserver<F> for () {
+    fn observe(&mut self, _event: ProverEvent<'_, F>) {}
+}
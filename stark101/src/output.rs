@@ -0,0 +1,63 @@
+// the outputs of a computation, read from the trace at the output steps
+// of its air and exported by the prover with the proof. the verifier
+// checks the proof against the statement claiming these values, i.e.
+// the air with one boundary constraint for each output, see
+// air::BoundaryClaims. a proof with any other outputs is rejected,
+// so the caller gets the results of the computation, not only its
+// validity. the output steps must not be constrained by the air itself
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+
+use crate::air::{Air, BoundaryClaims, BoundaryConstraint};
+use crate::common::{PublicInput, PublicInputError, ProofOptions, StarkProof};
+use crate::prover;
+use crate::verifier::{self, VerifyError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// proves the statement together with the values of the trace at its
+// output steps, returned in the order of the steps
+pub fn prove_with_outputs<A: Air<F> + Clone>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> (StarkProof<F>, Vec<FE>) {
+
+    let outputs = air.output_steps()
+        .iter()
+        .map(|step| trace[*step])
+        .collect::<Vec<FE>>();
+    let proof = prover::prove(&claiming(air, &outputs), trace, public_input, options);
+    (proof, outputs)
+}
+
+// verifies the proof of the statement with the given outputs, one for
+// each output step of the air
+pub fn verify_with_outputs<A: Air<F> + Clone>(
+        air: &A,
+        public_input: PublicInput<F>,
+        proof: &StarkProof<F>,
+        outputs: &[FE],
+        options: &ProofOptions
+    ) -> Result<(), VerifyError> {
+
+    let expected = air.output_steps().len();
+    if outputs.len() != expected {
+        return Err(VerifyError::PublicInput(PublicInputError::OutputCount { expected, received: outputs.len() }))
+    }
+    verifier::verify(&claiming(air, outputs), public_input, proof, options)
+}
+
+// the statement of the air, claiming the outputs at its output steps
+pub fn claiming<A: Air<F> + Clone>(air: &A, outputs: &[FE]) -> BoundaryClaims<F, A> {
+    let claims = air.output_steps()
+        .into_iter()
+        .zip(outputs)
+        .map(|(step, value)| BoundaryConstraint { step, value: *value })
+        .collect::<Vec<BoundaryConstraint<F>>>();
+    BoundaryClaims { inner: air.clone(), claims }
+}
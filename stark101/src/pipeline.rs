@@ -0,0 +1,196 @@
+// the steps of the prover, each returning what it computes, so that
+// the protocol can be run one step at a time from a repl or a notebook
+// instead of through generate_proof alone. run in order on the same
+// transcript, they give the same proof as prover::prove:
+//
+//     let air = FibonacciSquare { trace_length: 1024, fib_squared_0, fib_squared_1022 };
+//     let key = ProvingKey::new(&air, 13);
+//     let mut transcript = pipeline::start_transcript(&air, &public_input, &options);
+//     let trace = pipeline::build_trace(&Witness::example(), 1024);
+//     let trace_poly = pipeline::interpolate_trace(&key, &trace);
+//     let trace_lde = pipeline::extend_trace(&key, &trace_poly);
+//     let trace_tree = pipeline::commit_evaluations(&key, &trace_lde, &options, &mut transcript);
+//     let composition = pipeline::build_composition(&key, &air, &trace_lde, &mut transcript);
+//     let queries = pipeline::sample_queries(&public_input, &mut transcript);
+//     let trace_commitment = pipeline::open_trace(&key, &air, &trace_lde, &trace_tree, &queries, &options);
+//     let fri_layers = pipeline::fri_commit(&key, &air, &composition.evaluations, queries, &public_input, &options, &mut transcript);
+//     let proof = pipeline::bind_proof(trace_commitment, fri_layers, &mut transcript);
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+use lambdaworks_math::polynomial::Polynomial;
+use lambdaworks_crypto::fiat_shamir::default_transcript::DefaultTranscript;
+
+use crate::air::{self, Air};
+use crate::channel::{self, Channel};
+use crate::common::{self, ProofOptions, PublicInput, StarkProof, VectorCommitment};
+use crate::ct;
+use crate::fri::{self, FriCommitment};
+use crate::key::ProvingKey;
+use crate::merkle::Tree;
+use crate::prover;
+use crate::witness::Witness;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the evaluations of the composition polynomial over the lde domain
+// with the random coefficients it was built from
+#[derive(Clone, Debug)]
+pub struct Composition {
+    pub boundary_coefficient: FE,
+    pub transition_coefficients: Vec<FE>,
+    pub evaluations: Vec<FE>,
+}
+
+// the fibonacci square trace of stark 101, starting from 1 and the witness
+pub fn build_trace(witness: &Witness<F>, trace_length: usize) -> Vec<FE> {
+    air::FibonacciSquare::trace(&FE::one(), witness.value(), trace_length)
+}
+
+// a transcript holding the public input and the shape of the proof,
+// ready for the trace commitment
+pub fn start_transcript<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions
+    ) -> DefaultTranscript<F> {
+
+    let mut transcript = common::new_transcript(options);
+    prover::absorb_statement(air, public_input, options, &mut transcript);
+    transcript
+}
+
+// the polynomial of degree less than the trace length taking the
+// values of the trace over the trace domain
+pub fn interpolate_trace(key: &ProvingKey, trace: &[FE]) -> Polynomial<FE> {
+    ct::interpolate(&key.trace_domain, trace)
+}
+
+// the evaluations of the trace polynomial over the lde domain
+pub fn extend_trace(key: &ProvingKey, trace_poly: &Polynomial<FE>) -> Vec<FE> {
+    ct::evaluate(&key.lde_domain, trace_poly)
+}
+
+// the evaluations of the trace polynomial shifted by k rows, t(g^k x),
+// over the lde domain: the extension of the trace rotated by k rows
+pub fn shift_trace(key: &ProvingKey, trace_lde: &[FE], k: usize) -> Vec<FE> {
+    let lde_size = key.lde_domain.size;
+    let blowup_factor = lde_size / key.trace_domain.size;
+    (0..lde_size)
+        .map(|i| trace_lde[common::shifted_position(i, k, blowup_factor, lde_size)])
+        .collect::<Vec<FE>>()
+}
+
+// commits to the evaluations over the lde domain with a merkle tree of
+// the backend and the trace leaves of the options, appending its root
+// to the transcript
+pub fn commit_evaluations(
+        key: &ProvingKey,
+        evaluations: &[FE],
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>
+    ) -> Tree<F> {
+
+    let layout = options.trace_layout(key.lde_domain.size / key.trace_domain.size);
+    let tree = options.merkle_backend.build_packed(evaluations, &layout);
+    transcript.label("trace_root", channel::DIGEST);
+    transcript.append_bytes(&tree.root());
+    tree
+}
+
+// samples one coefficient for the boundary constraints and one for
+// each transition constraint, and combines the constraint quotients
+// of the trace polynomial with them, pointwise over the extension of
+// the trace
+pub fn build_composition<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        trace_lde: &[FE],
+        transcript: &mut impl Channel<F>
+    ) -> Composition {
+
+    transcript.label("boundary_coefficient", channel::SAMPLED_ELEMENT);
+    let boundary_coefficient = transcript.sample_field_element();
+    transcript.label("transition_coefficient", channel::SAMPLED_ELEMENT);
+    let transition_coefficients = air.transition_constraints()
+        .iter()
+        .map(|_| transcript.sample_field_element())
+        .collect::<Vec<FE>>();
+    let evaluations = prover::composition_evaluations(key, air, |i| trace_lde[i], &boundary_coefficient, &transition_coefficients);
+    Composition {
+        boundary_coefficient,
+        transition_coefficients,
+        evaluations,
+    }
+}
+
+// the queries of the lde domain, sorted
+pub fn sample_queries(public_input: &PublicInput<F>, transcript: &mut impl Channel<F>) -> Vec<usize> {
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = *public_input;
+    transcript.label("query_index", channel::SAMPLED_INDEX);
+    common::sample_queries(num_queries, 1 << eval_two_power, transcript).expect("the lde domain has points")
+}
+
+// the trace commitment with the openings of the rows of the frame
+// of every query read by the constraints
+pub fn open_trace<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        trace_lde: &[FE],
+        trace_tree: &Tree<F>,
+        query_indices: &[usize],
+        options: &ProofOptions
+    ) -> VectorCommitment<F> {
+
+    let lde_size = key.lde_domain.size;
+    let layout = options.trace_layout(lde_size / air.trace_length());
+    let positions = common::trace_positions(query_indices, &air.frame_offsets(), &layout, lde_size);
+    let mut commitment = VectorCommitment::new_from_tree(trace_tree);
+    commitment.inclusion_proofs = common::parallel_map(&positions, |position| {
+        trace_tree
+            .open(&layout, *position, trace_lde[*position])
+            .expect("the positions of the frames are in the lde domain")
+    });
+    commitment
+}
+
+// commits to the evaluations of the composition polynomial and its
+// fri foldings, and opens every layer at the queries
+pub fn fri_commit<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        composition: &[FE],
+        query_indices: Vec<usize>,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>
+    ) -> FriCommitment<F> {
+
+    let PublicInput(_, _, _, num_queries, ..) = *public_input;
+    fri::commit_and_fold_evaluations(
+        composition.to_vec(),
+        &key.lde_domain.offset,
+        air.composition_degree_bound(),
+        query_indices,
+        &fri::FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend },
+        transcript
+    )
+}
+
+// absorbs the evaluations opened by the proof and completes it with
+// the digest squeezed from the transcript
+pub fn bind_proof(
+        trace_commitment: VectorCommitment<F>,
+        composition_commitment: FriCommitment<F>,
+        transcript: &mut impl Channel<F>
+    ) -> StarkProof<F> {
+
+    let binding = common::proof_binding(
+        trace_commitment.inclusion_proofs.iter().map(|opening| opening.0),
+        composition_commitment.as_slice(),
+        transcript
+    );
+    StarkProof { trace_commitment, composition_commitment, binding: Some(binding) }
+}
@@ -2,7 +2,9 @@ use lambdaworks_math::field::{
     element::FieldElement,
     traits::{IsField, IsFFTField}
 };
-use lambdaworks_math::polynomial::{self, Polynomial};
+use lambdaworks_math::polynomial::Polynomial;
+
+use crate::domain::EvaluationDomain;
 
 // performs polynomial division in evaluation form.
 // the obtained polynomial is the actual division if and
@@ -10,47 +12,68 @@ use lambdaworks_math::polynomial::{self, Polynomial};
 pub fn polynomial_division<F: IsField + IsFFTField>(
         num: &Polynomial<FieldElement<F>>,
         den: &Polynomial<FieldElement<F>>,
-        domain_size: usize,
-        offset: &FieldElement<F>
+        domain: &EvaluationDomain<F>
     ) -> Polynomial<FieldElement<F>> {
 
-    let num_eval = Polynomial::evaluate_offset_fft::<F>(
-        num, 1, Some(domain_size), offset
-    ).unwrap();
+    let num_eval = domain.evaluate(num);
 
-    let den_eval = Polynomial::evaluate_offset_fft::<F>(
-        den, 1, Some(domain_size), offset
-    ).unwrap();
+    let den_eval = domain.evaluate(den);
 
     let poly_eval = num_eval
         .iter()
         .zip(den_eval.iter())
         .map(|(n, d)| n / d)
         .collect::<Vec<FieldElement<F>>>();
-    
-    Polynomial::interpolate_offset_fft::<F>(
-        &poly_eval, offset
-    ).unwrap()
+
+    let quotient = domain.interpolate(&poly_eval);
+    #[cfg(feature = "poly-check")]
+    check_division(num, den, domain, &quotient);
+    quotient
+}
+
+// the result of a multiplication or a power of degree at least the
+// size of the domain, which a polynomial in evaluation form over the
+// domain cannot represent
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DomainSizeError {
+    DomainTooSmall { degree: u64, size: usize },
+}
+
+impl std::fmt::Display for DomainSizeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DomainSizeError::DomainTooSmall { degree, size } => write!(f, "a result of degree {} does not fit in a domain of {} points", degree, size),
+        }
+    }
+}
+
+impl std::error::Error for DomainSizeError {}
+
+// a domain of the size fits polynomials of degree less than the size
+fn check_fits(degree: Option<u64>, size: usize) -> Result<(), DomainSizeError> {
+    match degree {
+        Some(degree) if degree < size as u64 => Ok(()),
+        degree => Err(DomainSizeError::DomainTooSmall { degree: degree.unwrap_or(u64::MAX), size }),
+    }
 }
 
 // performs polynomial multiplication in evaluation form.
-// the obtained polynomial is the actual multiplication if
-// and only if the degree of the multiplication fits in the
-// domain size
+// the degree of the product, the sum of the degrees of the
+// factors, must fit in the domain size
 pub fn polynomial_multiplication<F: IsField + IsFFTField>(
         factors: &[&Polynomial<FieldElement<F>>],
-        domain_size: usize,
-        offset: &FieldElement<F>
-    ) -> Polynomial<FieldElement<F>> {
+        domain: &EvaluationDomain<F>
+    ) -> Result<Polynomial<FieldElement<F>>, DomainSizeError> {
 
-    let mut product_eval = Polynomial::evaluate_offset_fft::<F>(
-        factors[0], 1, Some(domain_size), offset
-    ).unwrap();
+    let degree = factors
+        .iter()
+        .try_fold(0_u64, |degree, factor| degree.checked_add(factor.degree() as u64));
+    check_fits(degree, domain.size)?;
+
+    let mut product_eval = domain.evaluate(factors[0]);
 
     for factor in factors.iter().skip(1) {
-        let evaluations = Polynomial::evaluate_offset_fft::<F>(
-            factor, 1, Some(domain_size), offset
-        ).unwrap();
+        let evaluations = domain.evaluate(factor);
         product_eval = product_eval
             .iter()
             .zip(evaluations)
@@ -58,34 +81,140 @@ pub fn polynomial_multiplication<F: IsField + IsFFTField>(
             .collect::<Vec<FieldElement<F>>>();
     }
 
-    Polynomial::interpolate_offset_fft::<F>(
-        &product_eval, offset
-    ).unwrap()
+    let product = domain.interpolate(&product_eval);
+    #[cfg(feature = "poly-check")]
+    check_multiplication(factors, domain, &product);
+    Ok(product)
 }
 
 // performs polynomial power in evaluation form.
-// the obtained polynomial is the actual power if
-// and only if the degree of the power fits in the
-// domain size
+// the degree of the power, the degree of the polynomial
+// times the exponent, must fit in the domain size
 pub fn polynomial_power<F: IsField + IsFFTField>(
         poly: &Polynomial<FieldElement<F>>,
         power: u64,
-        domain_size: usize,
-        offset: &FieldElement<F>
-    ) -> Polynomial<FieldElement<F>> {
+        domain: &EvaluationDomain<F>
+    ) -> Result<Polynomial<FieldElement<F>>, DomainSizeError> {
 
-    let evaluations = Polynomial::evaluate_offset_fft::<F>(
-        poly, 1, Some(domain_size), offset
-    ).unwrap();
+    check_fits((poly.degree() as u64).checked_mul(power), domain.size)?;
+
+    let evaluations = domain.evaluate(poly);
 
     let power_eval = evaluations
             .iter()
             .map(|eval| eval.pow(power))
             .collect::<Vec<FieldElement<F>>>();
 
-    Polynomial::interpolate_offset_fft::<F>(
-        &power_eval, offset
-    ).unwrap()
+    let result = domain.interpolate(&power_eval);
+    #[cfg(feature = "poly-check")]
+    check_power(poly, power, domain, &result);
+    Ok(result)
+}
+
+// with the poly-check feature, the results in evaluation form above
+// are compared with the coefficient form arithmetic of lambdaworks,
+// panicking on a mismatch. products and powers too large for the
+// domain are already errors, a division mismatch is most often a
+// remainder or a numerator too large for the domain. the coefficient
+// form being quadratic, only results of degree up to this bound are
+// checked
+#[cfg(feature = "poly-check")]
+const CHECKED_DEGREE: usize = 512;
+
+#[cfg(feature = "poly-check")]
+fn check_division<F: IsField + IsFFTField>(
+        num: &Polynomial<FieldElement<F>>,
+        den: &Polynomial<FieldElement<F>>,
+        domain: &EvaluationDomain<F>,
+        quotient: &Polynomial<FieldElement<F>>
+    ) {
+
+    if num.degree() > CHECKED_DEGREE || *den == Polynomial::zero() {
+        return;
+    }
+    let (expected, remainder) = num.clone().long_division_with_remainder(den);
+    assert!(
+        remainder == Polynomial::zero(),
+        "polynomial_division: the denominator of degree {} does not divide the numerator of degree {}, \
+        the remainder has degree {}",
+        den.degree(), num.degree(), remainder.degree()
+    );
+    assert!(
+        *quotient == expected,
+        "polynomial_division: the quotient has degree {} but the division over {} points gives another \
+        polynomial, the numerator of degree {} does not fit in the domain",
+        expected.degree(), domain.size, num.degree()
+    );
+}
+
+#[cfg(feature = "poly-check")]
+fn check_multiplication<F: IsField + IsFFTField>(
+        factors: &[&Polynomial<FieldElement<F>>],
+        domain: &EvaluationDomain<F>,
+        product: &Polynomial<FieldElement<F>>
+    ) {
+
+    if factors.iter().map(|factor| factor.degree()).sum::<usize>() > CHECKED_DEGREE {
+        return;
+    }
+    let expected = factors
+        .iter()
+        .skip(1)
+        .fold(factors[0].clone(), |product, factor| product * *factor);
+    assert!(
+        *product == expected,
+        "polynomial_multiplication: the product has degree {} but the domain has {} points, \
+        the product over it aliases",
+        expected.degree(), domain.size
+    );
+}
+
+#[cfg(feature = "poly-check")]
+fn check_power<F: IsField + IsFFTField>(
+        poly: &Polynomial<FieldElement<F>>,
+        power: u64,
+        domain: &EvaluationDomain<F>,
+        result: &Polynomial<FieldElement<F>>
+    ) {
+
+    if power > CHECKED_DEGREE as u64 || poly.degree() * power as usize > CHECKED_DEGREE {
+        return;
+    }
+    let expected = (0..power)
+        .fold(Polynomial::new(&[FieldElement::<F>::one()]), |result, _| result * poly);
+    assert!(
+        *result == expected,
+        "polynomial_power: the power {} has degree {} but the domain has {} points, \
+        the power over it aliases",
+        power, expected.degree(), domain.size
+    );
+}
+
+// evaluates a constraint on a frame of polynomials in evaluation
+// form. the obtained polynomial is the actual constraint polynomial
+// if and only if its degree fits in the domain size
+pub fn frame_evaluation<F: IsField + IsFFTField>(
+        frame: &[Polynomial<FieldElement<F>>],
+        evaluate: fn(&[FieldElement<F>]) -> FieldElement<F>,
+        domain: &EvaluationDomain<F>
+    ) -> Polynomial<FieldElement<F>> {
+
+    let frame_evals = frame
+        .iter()
+        .map(|poly| domain.evaluate(poly))
+        .collect::<Vec<Vec<FieldElement<F>>>>();
+
+    let constraint_eval = (0..domain.size)
+        .map(|i| {
+            let row = frame_evals
+                .iter()
+                .map(|evals| evals[i].clone())
+                .collect::<Vec<FieldElement<F>>>();
+            evaluate(&row)
+        })
+        .collect::<Vec<FieldElement<F>>>();
+
+    domain.interpolate(&constraint_eval)
 }
 
 // performs polynomial folding into a new polynomial of degree
@@ -97,24 +226,28 @@ pub fn fold_polynomial<F>(
 where
     F: IsField,
 {
-    let coef = poly.coefficients();
-    let even_coef: Vec<FieldElement<F>> = coef
-        .iter()
-        .step_by(2)
-        .cloned()
-        .collect();
-
-    // odd coeficients of poly are multiplied by beta
-    let odd_coef_mul_beta: Vec<FieldElement<F>> = coef
-        .iter()
-        .skip(1)
-        .step_by(2)
-        .map(|v| (v.clone()) * beta)
-        .collect();
+    let mut coefficients = poly.coefficients().to_vec();
+    fold_coefficients(&mut coefficients, beta);
+    Polynomial::new(&coefficients)
+}
 
-    let (even_poly, odd_poly) = polynomial::pad_with_zero_coefficients(
-        &Polynomial::new(&even_coef),
-        &Polynomial::new(&odd_coef_mul_beta),
-    );
-    even_poly + odd_poly
-}
\ No newline at end of file
+// folds the polynomial with the given coefficients in place, the
+// even coefficients are added to the odd ones multiplied by beta
+pub fn fold_coefficients<F>(
+    coefficients: &mut Vec<FieldElement<F>>,
+    beta: &FieldElement<F>,
+)
+where
+    F: IsField,
+{
+    let folded_len = coefficients.len().div_ceil(2);
+    for i in 0..folded_len {
+        // coefficient 2i is read before being overwritten, as i <= 2i
+        let even = coefficients[2 * i].clone();
+        coefficients[i] = match coefficients.get(2 * i + 1) {
+            Some(odd) => even + odd * beta,
+            None => even,
+        };
+    }
+    coefficients.truncate(folded_len);
+}
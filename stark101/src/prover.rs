@@ -1,200 +1,482 @@
-use lambdaworks_math::traits::ByteConversion;
+use std::collections::HashMap;
+use std::sync::{Mutex, atomic::{AtomicUsize, Ordering}};
+use std::thread;
+
 use lambdaworks_math::field::{
-    traits::IsFFTField,
     fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
     element::FieldElement
 };
-use lambdaworks_math::polynomial::Polynomial;
-use lambdaworks_crypto::merkle_tree::{
-    merkle::MerkleTree,
-    backends::types::Keccak256Backend
-};
-use lambdaworks_crypto::fiat_shamir::{
-    is_transcript::IsTranscript,
-    default_transcript::DefaultTranscript
-};
 
-use crate::poly;
-use crate::common::{self, PublicInput, VectorCommitment, StarkProof};
+use crate::channel::{self, Channel};
+use crate::key::{ProvingKey, VerifyingKey};
+use crate::air::{self, Air};
+use crate::ct;
+use crate::composition::{self, PointValues};
+use crate::common::{self, IsPublicInput, PublicInput, ProofOptions, Session, VectorCommitment, StarkProof};
 use crate::fri;
+use crate::threads;
+use crate::storage::CommittedLde;
+use crate::checkpoint::{self, Checkpoint};
+use crate::metrics::{PhaseRecorder, ProverMetrics, ProverPhase};
+use crate::observer::{ProverEvent, ProverObserver};
+use crate::witness::Witness;
 
 // the stark252 field has 2-adicity of 192, i.e., the largest
 // multiplicative subgroup whose order is a power of two has order 2^192
 type F = Stark252PrimeField;
 type FE = FieldElement<F>;
 
-pub fn generate_proof(public_input: PublicInput<F>) -> StarkProof<F> {
+pub fn generate_proof(public_input: PublicInput<F>, options: &ProofOptions) -> StarkProof<F> {
+
+    let PublicInput(_, interp_two_power, _, _, fib_squared_0, fib_squared_1022, _) = public_input.clone();
+
+    // define example parameters
+    let witness = Witness::<F>::example();
+    let interp_order: usize = 1 << interp_two_power;
+    let fib_squared = air::FibonacciSquare::trace(&fib_squared_0, witness.value(), interp_order);
+
+    let air = air::FibonacciSquare {
+        trace_length: interp_order,
+        fib_squared_0,
+        fib_squared_1022,
+    };
+
+    prove(&air, &fib_squared, public_input, options)
+}
+
+// proves that the trace satisfies the constraints of the statement.
+// the two field elements of the public input are absorbed into the
+// transcript as given, the air is expected to be built from them
+pub fn prove<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> StarkProof<F> {
+
+    prove_with_metrics(air, trace, public_input, options).0
+}
+
+// same as prove, additionally measuring the time and memory
+// taken by each part of the prover
+pub fn prove_with_metrics<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> (StarkProof<F>, ProverMetrics) {
+
+    let PublicInput(_, _, eval_two_power, ..) = public_input;
+    let key = ProvingKey::new(air, eval_two_power);
+    prove_over(&key, air, trace, public_input, options, &mut common::new_transcript(options), &mut ())
+}
+
+// same as prove, handing every intermediate object of the proof
+// to the observer as soon as it is computed
+pub fn prove_observed<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions,
+        observer: &mut dyn ProverObserver<F>
+    ) -> StarkProof<F> {
+
+    let PublicInput(_, _, eval_two_power, ..) = public_input;
+    let key = ProvingKey::new(air, eval_two_power);
+    prove_over(&key, air, trace, public_input, options, &mut common::new_transcript(options), observer).0
+}
+
+// same as prove, additionally returning every operation applied to
+// the transcript, to be compared with the ones of the verifier
+#[cfg(feature = "transcript-log")]
+pub fn prove_recorded<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> (StarkProof<F>, Vec<crate::channel::TranscriptEvent>) {
+
+    let (proof, transcript) = prove_recording(air, trace, public_input, options);
+    (proof, transcript.events)
+}
+
+// same as prove_recorded, returning the recording channel itself with
+// the labels of the operations
+#[cfg(feature = "transcript-log")]
+pub(crate) fn prove_recording<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> (StarkProof<F>, crate::channel::RecordingChannel<F>) {
+
+    let PublicInput(_, _, eval_two_power, ..) = public_input;
+    let key = ProvingKey::new(air, eval_two_power);
+    let mut transcript = crate::channel::RecordingChannel::new(common::new_transcript(options));
+    let (proof, _) = prove_over(&key, air, trace, public_input, options, &mut transcript, &mut ());
+    (proof, transcript)
+}
+
+// proves with a key computed beforehand for the shape of the statement,
+// so that proofs of statements of the same shape share the precomputation
+pub fn prove_with_key<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> StarkProof<F> {
+
+    prove_over(key, air, trace, public_input, options, &mut common::new_transcript(options), &mut ()).0
+}
+
+// proves every statement with its trace, running at most parallelism
+// proofs at a time. a proving key is computed once for each trace and
+// evaluation size and shared by all the proofs of statements it fits
+pub fn prove_many<A: Air<F> + Sync>(
+        witnesses: &[(A, Vec<FE>, PublicInput<F>)],
+        parallelism: usize,
+        options: &ProofOptions
+    ) -> Vec<StarkProof<F>> {
+
+    prove_batch(witnesses, parallelism, options, None)
+}
+
+// same as prove_many, the proofs forming a session of the given seed
+// in which each proof takes its index among the witnesses. the proofs
+// are verified together by verify_session with the same seed
+pub fn prove_session<A: Air<F> + Sync>(
+        witnesses: &[(A, Vec<FE>, PublicInput<F>)],
+        parallelism: usize,
+        seed: [u8; 32],
+        options: &ProofOptions
+    ) -> Vec<StarkProof<F>> {
+
+    prove_batch(witnesses, parallelism, options, Some(seed))
+}
+
+fn prove_batch<A: Air<F> + Sync>(
+        witnesses: &[(A, Vec<FE>, PublicInput<F>)],
+        parallelism: usize,
+        options: &ProofOptions,
+        session_seed: Option<[u8; 32]>
+    ) -> Vec<StarkProof<F>> {
+
+    let mut keys = HashMap::<(usize, usize), ProvingKey>::new();
+    for (air, _, PublicInput(_, interp_two_power, eval_two_power, ..)) in witnesses {
+        keys
+            .entry((*interp_two_power, *eval_two_power))
+            .or_insert_with(|| ProvingKey::new(air, *eval_two_power));
+    }
+
+    // workers take the next unproven witness until none is left
+    let next = AtomicUsize::new(0);
+    let proofs = Mutex::new((0..witnesses.len()).map(|_| None).collect::<Vec<Option<StarkProof<F>>>>());
+    let worker = || {
+        loop {
+            let i = next.fetch_add(1, Ordering::Relaxed);
+            let Some((air, trace, public_input)) = witnesses.get(i) else {
+                break
+            };
+            let PublicInput(_, interp_two_power, eval_two_power, ..) = public_input;
+            let shared_key = &keys[&(*interp_two_power, *eval_two_power)];
+            let own_key;
+            let key = if shared_key.fits(air, *eval_two_power) {
+                shared_key
+            } else {
+                own_key = ProvingKey::new(air, *eval_two_power);
+                &own_key
+            };
+            let options = ProofOptions {
+                session: session_seed.map(|seed| Session { seed, index: i }),
+                ..options.clone()
+            };
+            let (proof, _) = prove_over(key, air, trace, public_input.clone(), &options, &mut common::new_transcript(&options), &mut ());
+            proofs.lock().unwrap()[i] = Some(proof);
+        }
+    };
+    // inside threads::single_threaded, the proofs are generated one
+    // after the other on the calling thread
+    if threads::is_single_threaded() {
+        worker();
+    } else {
+        thread::scope(|scope| {
+            for _ in 0..parallelism.clamp(1, witnesses.len().max(1)) {
+                scope.spawn(worker);
+            }
+        });
+    }
+
+    proofs
+        .into_inner()
+        .unwrap()
+        .into_iter()
+        .map(Option::unwrap)
+        .collect::<Vec<StarkProof<F>>>()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn prove_over<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>,
+        observer: &mut dyn ProverObserver<F>
+    ) -> (StarkProof<F>, ProverMetrics) {
+
+    let mut recorder = PhaseRecorder::new();
+    recorder.start(ProverPhase::TraceCommitment);
 
     // ===================================
     // ==========|    Part 1:   |=========
     // === Statement, LDE & Commitment ===
     // ===================================
-    // extract public input
-    let PublicInput(
-        modulus,
-        interp_two_power,
-        eval_two_power,
-        num_queries,
-        fib_squared_0,
-        fib_squared_1022
-    ) = public_input;
-
-    // initialize transcript and append all public inputs
-    let mut transcript = DefaultTranscript::<F>::new(&[]);
-    transcript.append_bytes(&modulus.to_bytes_be());
-    transcript.append_bytes(&interp_two_power.to_be_bytes());
-    transcript.append_bytes(&eval_two_power.to_be_bytes());
-    transcript.append_bytes(&num_queries.to_be_bytes());
-    transcript.append_bytes(&fib_squared_0.to_bytes_be());
-    transcript.append_bytes(&fib_squared_1022.to_bytes_be());
+    // append all public inputs and the shape of the proof to the transcript
+    let degree_bound = absorb_statement(air, &public_input, options, transcript);
+    let PublicInput(_, interp_two_power, eval_two_power, num_queries, ..) = public_input;
 
     // define example parameters
-    let one = FE::one();
-    let witness = FE::from(3141592_u64);
     let interp_order: usize = 1 << interp_two_power;
     let eval_order: usize = 1 << eval_two_power;
+    assert_eq!(air.trace_length(), interp_order);
+    assert_eq!(trace.len(), interp_order);
+    assert!(key.fits(air, eval_two_power), "the proving key does not fit the statement");
 
-
-    // define primitive root
-    let g = F::get_primitive_root_of_unity(interp_two_power as u64).unwrap();
-    let g_to_the_1021 = g.pow(1021_u64);
-    let g_to_the_1022 = g * g_to_the_1021;
-    let g_to_the_1023 = g * g_to_the_1022;
     let blowup_factor = (2_usize).pow((eval_two_power - interp_two_power) as u32);
+    if let Err(e) = options.check_leaf_width(interp_order) {
+        panic!("invalid options: {}", e);
+    }
+    let trace_layout = options.trace_layout(blowup_factor);
 
-    // create vec to hold fibonacci square sequence
-    let mut fib_squared = Vec::<FE>::with_capacity(interp_order);
-    fib_squared.push(fib_squared_0);
-    fib_squared.push(witness);
-
-    for i in 2..interp_order-1 {
-        let x = fib_squared[i-2];
-        let y = fib_squared[i-1];
-        fib_squared.push(x.square() + y.square());
+    // in debug builds, check the trace against every constraint before
+    // interpolating it, so an inconsistent witness is reported at the
+    // step where it fails instead of as a non low degree composition
+    let boundary = air.boundary_constraints();
+    let transitions = air.transition_constraints();
+    if cfg!(debug_assertions) {
+        if let Err(e) = air::check_trace(trace, &boundary, &transitions) {
+            panic!("inconsistent trace: {}", e);
+        }
     }
-    fib_squared.push(FE::zero());
 
-    // fft-interpolate the fibonacci square sequence
-    let trace_poly = match Polynomial::interpolate_fft::<F>(&fib_squared) {
-        Ok(p) => p,
-        Err(e) => panic!("{:?}", e),
+    // the saved phases of an interrupted proof are reused only if
+    // they belong to this same statement
+    let checkpoint = options.checkpoint_dir
+        .as_ref()
+        .map(|dir| Checkpoint::open(dir, &transcript.state()));
+
+    // fft-interpolate the trace
+    let interpolate_trace = || ct::interpolate(&key.trace_domain, trace);
+    let trace_poly = match &checkpoint {
+        Some(checkpoint) => checkpoint.polynomial(checkpoint::TRACE, interpolate_trace),
+        None => interpolate_trace(),
     };
+    observer.observe(ProverEvent::Trace { trace, polynomial: &trace_poly });
 
-    // fft-evaluate the fibonacci square sequence over a larger domain
+    // fft-evaluate the trace over a larger domain
     // of size (blow-up factor) * (interpolation domain size)
     // the offset is obtained as an outside not in the interpolation domain
-    let offset = FE::from(2_u64);
-    let trace_poly_eval = Polynomial::evaluate_offset_fft::<F>(
-        &trace_poly, 1, Some(eval_order), &offset
-    ).unwrap();
-
-    // commit to the trace evaluations over the larger domain using a merkle tree
-    let trace_poly_tree = MerkleTree::<Keccak256Backend<F>>::build(&trace_poly_eval);
-    let mut trace_commitment = VectorCommitment::<F>::new_from_tree(&trace_poly_tree);
-    transcript.append_bytes(&trace_poly_tree.root);
+    // and commit to the evaluations using a merkle tree
+    let trace_lde = CommittedLde::commit(
+        &trace_poly, interp_order, &key.lde_domain, &options.trace_storage, options.merkle_backend, &trace_layout, ct::evaluate
+    );
+    let mut trace_commitment = VectorCommitment::<F> {
+        root: trace_lde.root(),
+        inclusion_proofs: vec![],
+    };
+    transcript.label("trace_root", channel::DIGEST);
+    transcript.append_bytes(&trace_commitment.root);
+    observer.observe(ProverEvent::TraceCommitment { lde_size: eval_order, root: trace_commitment.root });
 
     // ===================================
     // =========|    Part 2:   |==========
     // ===== Polynomial Constraints ======
     // ===================================
-    let x = Polynomial::new_monomial(one, 1);
-    let x_to_the_1024 = Polynomial::new_monomial(one, interp_order);
-
-    // initial element constraint
-    let constraint_0_poly = poly::polynomial_division(
-        &(&trace_poly - fib_squared_0),
-        &(&x - one),
-        eval_order,
-        &offset
-    );
-
-    // result element constraint
-    let constraint_1022_poly = poly::polynomial_division(
-        &(&trace_poly - fib_squared_1022),
-        &(&x - g_to_the_1022),
-        eval_order,
-        &offset
-    );
+    recorder.start(ProverPhase::Constraints);
 
-    // trace transition constraint
-    // numerator
-    let trace_poly_scaled_once = trace_poly.scale(&g);
-    let trace_poly_scaled_twice = trace_poly_scaled_once.scale(&g);
-    let trace_poly_squared = poly::polynomial_power(
-        &trace_poly,
-        2_u64,
-        eval_order,
-        &offset
-    );
-    let trace_poly_scaled_once_squared = poly::polynomial_power(
-        &trace_poly_scaled_once,
-        2_u64,
-        eval_order,
-        &offset
-    );
-
-    let numerator = poly::polynomial_multiplication(
-        &[
-            &(trace_poly_scaled_twice - trace_poly_scaled_once_squared - trace_poly_squared),
-            &(&x - g_to_the_1021), 
-            &(&x - g_to_the_1022),
-            &(&x - g_to_the_1023)
-        ],
-        eval_order,
-        &offset
-    );
-    // denominator
-    let denominator = &x_to_the_1024 - one;
-    // polynomial
-    let transition_constraint_poly = poly::polynomial_division(
-        &numerator,
-        &denominator,
-        eval_order,
-        &offset
-    );
-
-    // composition polynomial
+    // composition polynomial, one random coefficient for the boundary
+    // constraints and one for each transition constraint
+    transcript.label("boundary_coefficient", channel::SAMPLED_ELEMENT);
     let a = transcript.sample_field_element();
-    let b = transcript.sample_field_element();
-    let c = transcript.sample_field_element();
-    let comp_poly = a * constraint_0_poly + b * constraint_1022_poly + c * transition_constraint_poly;
+    transcript.label("transition_coefficient", channel::SAMPLED_ELEMENT);
+    let b = transitions
+        .iter()
+        .map(|_| transcript.sample_field_element())
+        .collect::<Vec<FE>>();
+    // the constraints are evaluated over the committed lde, with no
+    // other evaluation of the trace polynomial, in a single pass over
+    // the lde domain. the composition is never interpolated, its
+    // evaluations are the first fri layer
+    let compose = || composition_evaluations(key, air, |i| trace_lde.get(i), &a, &b);
+    let comp_evals = match &checkpoint {
+        Some(checkpoint) => checkpoint.evaluations(checkpoint::COMPOSITION, eval_order, compose),
+        None => compose(),
+    };
+    observer.observe(ProverEvent::Composition {
+        boundary_coefficient: &a,
+        transition_coefficients: &b,
+        evaluations: &comp_evals,
+        degree_bound,
+    });
 
     // ===================================
     // =========|    Part 3:   |==========
     // ========= FRI Commitment ==========
     // ===================================
+    recorder.start(ProverPhase::Fri);
     // get queries evaluations and add to transcript
-    let query_indices = common::sample_queries(num_queries, eval_order, &mut transcript);
-    let aux_indices = [0, blowup_factor, 2 * blowup_factor];
-    let all_indices = query_indices
-        .iter()
-        .map(|i| {
-            aux_indices
-                .iter()
-                .map(|j| (i + j) % eval_order)
-                .collect::<Vec<usize>>()
-    }).collect::<Vec<Vec<usize>>>()
-    .concat();
-
-    trace_commitment.generate_inclusion_proofs(
-        &all_indices,
-        &trace_poly_eval,
-        &trace_poly_tree,
-    );
+    transcript.label("query_index", channel::SAMPLED_INDEX);
+    let query_indices = common::sample_queries(num_queries, eval_order, transcript).expect("the lde domain has points");
+    let trace_positions = common::trace_positions(&query_indices, &air.frame_offsets(), &trace_layout, eval_order);
+    observer.observe(ProverEvent::Queries { indices: &query_indices });
+
+    trace_commitment.inclusion_proofs = common::parallel_map(&trace_positions, |i| trace_lde.open(*i, &trace_layout));
         
     // build fri layers
-    let composition_commitment = fri::commit_and_fold(
-        &comp_poly,
-        eval_order,
-        &offset,
+    let (composition_commitment, betas) = fri::commit_and_fold_resumable(
+        comp_evals,
+        &key.lde_domain.offset,
+        degree_bound,
         query_indices,
-        &mut transcript
+        &fri::FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend },
+        transcript,
+        checkpoint.as_ref().map(|checkpoint| checkpoint as &dyn fri::LayerStore<F>),
+        true
     );
+    let roots = composition_commitment
+        .iter()
+        .map(|layer| layer.root)
+        .collect::<Vec<[u8; 32]>>();
+    observer.observe(ProverEvent::FriLayers { roots: &roots, betas: &betas });
 
+    // every opened evaluation is absorbed once the last challenge is
+    // drawn, the verifier recomputing the digest from its transcript
+    let binding = common::proof_binding(
+        trace_commitment.inclusion_proofs.iter().map(|opening| opening.0),
+        composition_commitment.as_slice(),
+        transcript
+    );
 
-    StarkProof {
+    // the proof is complete, nothing is left to resume
+    if let Some(checkpoint) = checkpoint {
+        checkpoint.clear();
+    }
+
+    let proof = StarkProof {
         trace_commitment,
-        composition_commitment
+        composition_commitment,
+        binding: Some(binding),
+    };
+
+    (proof, recorder.finish())
+
+}
+
+// absorbs the digest of the verifying key, the public input and the
+// parameters of the air, then binds the shape of the proof before any
+// challenge is sampled, so that the prover cannot try other shapes
+// once it sees them. returns the degree bound of the composition
+pub(crate) fn absorb_statement<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>
+    ) -> usize {
+
+    absorb_air(air, public_input, options, transcript);
+    absorb_shape(air.composition_degree_bound(), public_input, options, transcript)
+}
+
+// absorbs the digest of the verifying key, the public input and the
+// parameters of the air
+pub(crate) fn absorb_air<A: Air<F>>(
+        air: &A,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>
+    ) {
+
+    if let Err(e) = public_input.validate() {
+        panic!("invalid public input: {}", e);
+    }
+    let PublicInput(_, _, eval_two_power, ..) = *public_input;
+
+    transcript.label("verifying_key", channel::DIGEST);
+    transcript.append_bytes(&VerifyingKey::new(air, eval_two_power).digest(options));
+    public_input.absorb_into(transcript);
+    transcript.label("air_parameter", channel::ELEMENT_BE);
+    for parameter in air.parameters() {
+        transcript.append_bytes(&common::fe_to_bytes_be(&parameter));
     }
+}
+
+// absorbs the query sampling and the shape of the proof of a composition
+// of the given degree bound over the lde of the public input
+pub(crate) fn absorb_shape(
+        degree_bound: usize,
+        public_input: &PublicInput<F>,
+        options: &ProofOptions,
+        transcript: &mut impl Channel<F>
+    ) -> usize {
 
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = *public_input;
+    transcript.label("query_sampling", channel::U8);
+    transcript.append_bytes(&[options.query_sampling as u8]);
+
+    let eval_order: usize = 1 << eval_two_power;
+    // fri never folds below two points, so the composition only
+    // folds down to a constant if its degree bound is at most half
+    // the lde size
+    assert!(
+        degree_bound <= eval_order / 2,
+        "an lde domain of {} points is too small for a composition of degree bound {}",
+        eval_order,
+        degree_bound
+    );
+    let (schedule, _) = fri::committed_schedule(degree_bound, eval_order, options.fri_max_layers).expect("the fri parameters of the statement are valid");
+    let num_layers = schedule.len();
+    transcript.label("degree_bound", channel::U64_BE);
+    transcript.append_bytes(&degree_bound.to_be_bytes());
+    transcript.label("num_layers", channel::U64_BE);
+    transcript.append_bytes(&num_layers.to_be_bytes());
+    transcript.label("num_queries", channel::U64_BE);
+    transcript.append_bytes(&num_queries.to_be_bytes());
+    degree_bound
+}
+
+// the evaluations over the lde domain of the constraint quotients of
+// the trace polynomial combined with the random coefficients a, for
+// the boundary constraints, and b, one for each transition constraint.
+// the quotients are computed pointwise, the key holding their
+// denominators, from the committed evaluations of the trace
+// polynomial, the one at each point of the lde domain given by
+// trace_evals
+pub(crate) fn composition_evaluations<A: Air<F>>(
+        key: &ProvingKey,
+        air: &A,
+        trace_evals: impl Fn(usize) -> FE,
+        a: &FE,
+        b: &[FE]
+    ) -> Vec<FE> {
+
+    let boundary = air.boundary_constraints();
+    let transitions = air.transition_constraints();
+    let lde_size = key.lde_domain.size;
+    let blowup_factor = lde_size / air.trace_length();
+
+    let boundary_interpolant_evals = key.lde_domain.evaluate(&boundary.interpolant(&key.g));
+
+    (0..lde_size)
+        .map(|i| {
+            let values = PointValues {
+                row: |k: usize| trace_evals(common::shifted_position(i, k, blowup_factor, lde_size)),
+                boundary_interpolant: boundary_interpolant_evals[i],
+                boundary_zerofier_inv: key.boundary_zerofier_inv[i],
+                transition_factor: |j: usize| key.transition_factors[j][i],
+            };
+            composition::evaluate_at(&transitions, air.trace_length(), values, a, b)
+        })
+        .collect::<Vec<FE>>()
 }
@@ -0,0 +1,146 @@
+// a range check: every value of the trace column lies in [0, 2^16).
+// the crate has no lookup argument, which needs random challenges and
+// a second column, so each value is decomposed into its bits instead,
+// over a block of 16 rows of the single trace column: row j of a block
+// holds the value of the j + 1 most significant bits, twice the row
+// before it plus a bit, and the last row of the block is the value.
+// the values are committed with the trace, only their number is public
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::{
+        Stark252PrimeField,
+        MontgomeryConfigStark252PrimeField
+    },
+    fields::montgomery_backed_prime_fields::IsModulus,
+    element::FieldElement
+};
+
+use crate::air::{Air, BoundaryConstraints, TransitionConstraint};
+use crate::common::PublicInput;
+use crate::expr::Expr;
+use crate::ct;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// bits of the range, also the number of rows of each value
+pub const RANGE_CHECK_BITS: usize = 16;
+
+// the statement that 2^values_log2 values are in range, checked with
+// an lde of 2^blowup_log2 times the trace. the bit constraint on the
+// first row of each block holds on few steps, so its quotient has
+// almost twice the degree of the trace and the blowup must be 4 or more
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeCheckInput {
+    pub values_log2: usize,
+    pub blowup_log2: usize,
+    pub num_queries: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RangeCheckError {
+    // a value does not fit in the range bits
+    OutOfRange { index: usize, value: u64 },
+    // the public input is not the one of a range check
+    NotARangeCheck,
+}
+
+impl std::fmt::Display for RangeCheckError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeCheckError::OutOfRange { index, value } => write!(f, "value {} at index {} is not below 2^{}", value, index, RANGE_CHECK_BITS),
+            RangeCheckError::NotARangeCheck => write!(f, "not the public input of a {} bit range check", RANGE_CHECK_BITS),
+        }
+    }
+}
+
+impl std::error::Error for RangeCheckError {}
+
+impl RangeCheckInput {
+
+    pub fn trace_length(&self) -> usize {
+        RANGE_CHECK_BITS << self.values_log2
+    }
+
+    // the generic public input of the statement, its first public
+    // value being the number of bits of the range
+    pub fn public_input(&self) -> PublicInput<F> {
+        let interp_two_power = self.trace_length().trailing_zeros() as usize;
+        PublicInput(
+            MontgomeryConfigStark252PrimeField::MODULUS,
+            interp_two_power,
+            interp_two_power + self.blowup_log2,
+            self.num_queries,
+            FE::from(RANGE_CHECK_BITS as u64),
+            FE::zero(),
+            None,
+        )
+    }
+
+    pub fn from_public_input(public_input: &PublicInput<F>) -> Result<Self, RangeCheckError> {
+        let PublicInput(_, interp_two_power, eval_two_power, num_queries, bits, zero, witness_commitment) = public_input;
+        let block_log2 = RANGE_CHECK_BITS.trailing_zeros() as usize;
+        if *bits != FE::from(RANGE_CHECK_BITS as u64) || *zero != FE::zero() || witness_commitment.is_some()
+            || *interp_two_power < block_log2 || *eval_two_power < *interp_two_power {
+            return Err(RangeCheckError::NotARangeCheck)
+        }
+        Ok(Self {
+            values_log2: interp_two_power - block_log2,
+            blowup_log2: eval_two_power - interp_two_power,
+            num_queries: *num_queries,
+        })
+    }
+
+    pub fn air(&self) -> RangeCheck {
+        RangeCheck { trace_length: self.trace_length() }
+    }
+
+    // the trace decomposing the values, of which there must be
+    // 2^values_log2
+    pub fn trace(&self, values: &[u64]) -> Result<Vec<FE>, RangeCheckError> {
+        assert_eq!(values.len(), 1 << self.values_log2, "a range check of 2^{} values", self.values_log2);
+        // every value is read before any is reported
+        if values.iter().fold(0, |high_bits, value| high_bits | value >> RANGE_CHECK_BITS) != 0 {
+            let (index, value) = values.iter().enumerate().find(|(_, value)| **value >> RANGE_CHECK_BITS != 0).unwrap();
+            return Err(RangeCheckError::OutOfRange { index, value: *value })
+        }
+        Ok(values
+            .iter()
+            .flat_map(|value| (0..RANGE_CHECK_BITS).rev().map(move |shift| ct::from_u64(value >> shift)))
+            .collect::<Vec<FE>>())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RangeCheck {
+    pub trace_length: usize,
+}
+
+impl Air<F> for RangeCheck {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "range-check".to_string()
+    }
+
+    // the values are private
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        BoundaryConstraints::new(vec![])
+    }
+
+    // each row of a block is twice the previous one plus a bit, but on
+    // the first row of the block, which is a bit itself. both hold on
+    // rows of the blocks, the zerofier of the first over the last rows
+    // being (x^n - 1) / (x^(n/16) - g^(15n/16)) and the one of the
+    // second x^(n/16) - 1
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let not_block_last_rows = (0..RANGE_CHECK_BITS - 1).collect::<Vec<usize>>();
+        let bit = Expr::next(0) - Expr::col(0) * FE::from(2_u64);
+        vec![
+            TransitionConstraint::from_expr(&[], bit.clone() * (bit - FE::one())).on_rows(RANGE_CHECK_BITS, &not_block_last_rows),
+            TransitionConstraint::from_expr(&[], Expr::col(0) * (Expr::col(0) - FE::one())).on_rows(RANGE_CHECK_BITS, &[0]),
+        ]
+    }
+}
@@ -0,0 +1,144 @@
+// the statements a single deployment verifies, each under an identifier
+// of its choosing, carried by the envelope of its proofs, see envelope.
+// a statement is registered with the verifier building its air from the
+// public input, the options of its proofs and the digest of its
+// verifying key. a proof is checked against the statement its envelope
+// names only, and rejected if its public input gives that statement
+// another verifying key than the registered one. registering a new
+// statement leaves the keys of the others as they are
+use std::collections::BTreeMap;
+use std::fmt;
+
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+use crate::air::Air;
+use crate::common::{ProofOptions, PublicInput};
+use crate::envelope::Envelope;
+use crate::key::VerifyingKey;
+use crate::report::VerificationReport;
+use crate::verifier::{StarkVerifier, VerifyError};
+
+type F = Stark252PrimeField;
+
+// why a proof is not one of a registered statement
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StatementError {
+    // the envelope of the proof names no statement
+    Unnamed,
+    Unknown(String),
+    // the public input describes the statement with another verifying
+    // key than the registered one, e.g. for another lde size
+    Key,
+}
+
+impl fmt::Display for StatementError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatementError::Unnamed => write!(f, "the envelope names no statement"),
+            StatementError::Unknown(statement) => write!(f, "no statement {} is registered", statement),
+            StatementError::Key => write!(f, "verifying key differs from the registered one"),
+        }
+    }
+}
+
+impl std::error::Error for StatementError {}
+
+// the digest a statement is registered with, the one of the verifying
+// key of its air for the lde of the public input, see VerifyingKey::digest
+pub fn key_digest<A: Air<F>>(air: &A, public_input: &PublicInput<F>, options: &ProofOptions) -> [u8; 32] {
+    let PublicInput(_, _, eval_two_power, ..) = *public_input;
+    VerifyingKey::new(air, eval_two_power).digest(options)
+}
+
+// a registered verifier, whatever the air it builds
+trait Statement: Send + Sync {
+    fn options(&self) -> &ProofOptions;
+    fn verify(&self, public_input: &[u8], proof: &[u8], key_digest: &[u8; 32]) -> VerificationReport;
+}
+
+impl<A: Air<F>> Statement for StarkVerifier<A> {
+
+    fn options(&self) -> &ProofOptions {
+        StarkVerifier::options(self)
+    }
+
+    fn verify(&self, public_input: &[u8], proof: &[u8], key_digest: &[u8; 32]) -> VerificationReport {
+        let (air, public_input) = match self.statement(public_input) {
+            Ok(statement) => statement,
+            Err(e) => return rejected(e),
+        };
+        if self::key_digest(&air, &public_input, self.options()) != *key_digest {
+            return rejected(VerifyError::Statement(StatementError::Key))
+        }
+        self.verify_statement(&air, public_input, proof)
+    }
+}
+
+struct Registered {
+    verifier: Box<dyn Statement>,
+    key_digest: [u8; 32],
+}
+
+#[derive(Default)]
+pub struct Registry {
+    statements: BTreeMap<String, Registered>,
+}
+
+impl Registry {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // registers the statement of the given identifier, whose proofs are
+    // verified by the verifier, with its options, against the verifying
+    // key of the given digest, see key_digest
+    pub fn register<A: Air<F> + 'static>(&mut self, statement: &str, verifier: StarkVerifier<A>, key_digest: [u8; 32]) {
+        assert!(!self.statements.contains_key(statement), "the statement {} is already registered", statement);
+        self.statements.insert(statement.to_string(), Registered { verifier: Box::new(verifier), key_digest });
+    }
+
+    // the identifiers of the registered statements, in order
+    pub fn statements(&self) -> impl Iterator<Item = &str> {
+        self.statements.keys().map(String::as_str)
+    }
+
+    pub fn key_digest(&self, statement: &str) -> Option<[u8; 32]> {
+        self.statements.get(statement).map(|registered| registered.key_digest)
+    }
+
+    pub fn options(&self, statement: &str) -> Option<&ProofOptions> {
+        self.statements.get(statement).map(|registered| registered.verifier.options())
+    }
+
+    pub fn verify(&self, public_input: &[u8], sealed_proof: &[u8]) -> Result<(), VerifyError> {
+        self.verify_with_report(public_input, sealed_proof).result
+    }
+
+    // verifies a proof sealed in an envelope naming its statement against
+    // the encoded public input, with the options and key registered for
+    // the statement, reporting the metadata of the envelope if any
+    pub fn verify_with_report(&self, public_input: &[u8], sealed_proof: &[u8]) -> VerificationReport {
+        let envelope = match Envelope::parse(sealed_proof) {
+            Ok(envelope) => envelope,
+            Err(e) => return rejected(VerifyError::Envelope(e)),
+        };
+        let registered = match envelope.statement {
+            None => Err(StatementError::Unnamed),
+            Some(statement) => self.statements.get(statement).ok_or(StatementError::Unknown(statement.to_string())),
+        };
+        let mut report = match registered {
+            Err(e) => rejected(VerifyError::Statement(e)),
+            Ok(registered) => match envelope.check(registered.verifier.options()) {
+                Err(e) => rejected(VerifyError::Envelope(e)),
+                Ok(()) => registered.verifier.verify(public_input, envelope.payload, &registered.key_digest),
+            },
+        };
+        report.metadata = envelope.metadata;
+        report
+    }
+}
+
+fn rejected(error: VerifyError) -> VerificationReport {
+    VerificationReport { result: Err(error), ..Default::default() }
+}
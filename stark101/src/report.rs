@@ -0,0 +1,344 @@
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::common;
+use crate::envelope::ProofMetadata;
+use crate::fri::FriError;
+use crate::verifier::VerifyError;
+
+// version of the json of a report, changed only when a field is
+// removed or changes meaning
+pub const REPORT_SCHEMA: u32 = 1;
+
+// stage of the verifier transcript reached when a check failed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptStage {
+    PublicInput,
+    TraceCommitment,
+    ConstraintCoefficients,
+    QuerySampling,
+    FriLayers,
+}
+
+impl TranscriptStage {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            TranscriptStage::PublicInput => "public_input",
+            TranscriptStage::TraceCommitment => "trace_commitment",
+            TranscriptStage::ConstraintCoefficients => "constraint_coefficients",
+            TranscriptStage::QuerySampling => "query_sampling",
+            TranscriptStage::FriLayers => "fri_layers",
+        }
+    }
+}
+
+// part of the protocol whose check failed, in the order the
+// verifier runs them
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Phase {
+    Decoding,
+    PublicInput,
+    TraceOpenings,
+    CompositionConsistency,
+    Fri,
+}
+
+impl Phase {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Decoding => "decoding",
+            Phase::PublicInput => "public_input",
+            Phase::TraceOpenings => "trace_openings",
+            Phase::CompositionConsistency => "composition_consistency",
+            Phase::Fri => "fri",
+        }
+    }
+}
+
+// whether the verifier stops at the first failed check, or goes on and
+// reports every one it finds, to debug proofs of other implementations.
+// an invalid public input or a passed deadline stops it in both modes,
+// and fri stops at its first failed check, each layer following from
+// the previous one
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum VerificationMode {
+    #[default]
+    FailFast,
+    Exhaustive,
+}
+
+impl VerificationMode {
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            VerificationMode::FailFast => "fail_fast",
+            VerificationMode::Exhaustive => "exhaustive",
+        }
+    }
+}
+
+// expected and received values of a failed check, as hex strings
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub expected: String,
+    pub received: String,
+}
+
+// outcome of one of the checks of the verifier
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CheckStatus {
+    Passed,
+    Failed,
+    // an earlier check failed
+    NotReached,
+}
+
+#[derive(Clone, Debug)]
+pub struct VerificationReport {
+    pub result: Result<(), VerifyError>,
+    pub stage: TranscriptStage,
+    // only recorded in debug builds
+    pub mismatch: Option<Mismatch>,
+    // size of the proof checked, once known to the verifier
+    pub num_queries: usize,
+    pub num_layers: usize,
+    // the degree bound of every fri layer, the last one of constants
+    pub degree_bounds: Vec<usize>,
+    pub trace_root: Option<[u8; 32]>,
+    // where the proof comes from, when its envelope says so. reported
+    // only, the verifier checks nothing of it
+    pub metadata: Option<ProofMetadata>,
+    // time spent in each phase the verifier ran, in order, the one it
+    // stopped in included. the public input phase also absorbs the
+    // statement and samples the challenges up to the queries
+    pub durations: Vec<(Phase, Duration)>,
+    pub mode: VerificationMode,
+    // every failed check in the order found, the first one being the
+    // error of the result. only that one in fail fast mode
+    pub failures: Vec<VerifyError>,
+}
+
+impl VerificationReport {
+
+    pub fn new() -> Self {
+        Self {
+            result: Ok(()),
+            stage: TranscriptStage::PublicInput,
+            mismatch: None,
+            num_queries: 0,
+            num_layers: 0,
+            degree_bounds: vec![],
+            trace_root: None,
+            metadata: None,
+            durations: vec![],
+            mode: VerificationMode::FailFast,
+            failures: vec![],
+        }
+    }
+
+    // records the time since the start of the phase, restarting it for
+    // the next one
+    pub(crate) fn record(&mut self, phase: Phase, start: &mut Instant) {
+        let now = Instant::now();
+        self.durations.push((phase, now - *start));
+        *start = now;
+    }
+
+    // records a failed check, returning it when verification stops there
+    pub(crate) fn fail(&mut self, error: VerifyError) -> Result<(), VerifyError> {
+        let stops = self.mode == VerificationMode::FailFast || stops(&error);
+        self.failures.push(error.clone());
+        match stops {
+            true => Err(error),
+            false => Ok(()),
+        }
+    }
+
+    // the phase verification stopped in before running the later ones,
+    // none if it ran them all
+    pub(crate) fn stopped(&self) -> Option<Phase> {
+        match self.mode {
+            VerificationMode::FailFast => self.phase(),
+            VerificationMode::Exhaustive => self.failures.last().filter(|e| stops(e)).map(phase),
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.result.is_ok()
+    }
+
+    pub fn phase(&self) -> Option<Phase> {
+        self.result.as_ref().err().map(phase)
+    }
+
+    // whether the check of the given phase passed, failed, or was
+    // not run because an earlier one failed
+    pub fn status(&self, phase: Phase) -> CheckStatus {
+        let failed = self.failures.iter().chain(self.result.as_ref().err()).any(|e| self::phase(e) == phase);
+        match self.stopped() {
+            _ if failed => CheckStatus::Failed,
+            Some(stopped) if stopped < phase => CheckStatus::NotReached,
+            _ => CheckStatus::Passed,
+        }
+    }
+
+    // fri layer of the failed check, if it happened in fri
+    pub fn layer(&self) -> Option<usize> {
+        self.result.as_ref().err().and_then(layer)
+    }
+
+    // index, among the sampled queries, of the failed check
+    pub fn query(&self) -> Option<usize> {
+        self.result.as_ref().err().and_then(query)
+    }
+
+    // name of the check that failed, one for each kind of failure, so
+    // that systems can alert on some of them
+    pub fn failed_check(&self) -> Option<&'static str> {
+        self.result.as_ref().err().map(check)
+    }
+
+    // the report as json, for services and the cli. the fields keep their
+    // names within a schema, an absent value being null, and the
+    // durations are in seconds
+    pub fn to_json(&self) -> String {
+        let optional = |value: Option<String>| value.unwrap_or("null".to_string());
+        let durations = self.durations
+            .iter()
+            .map(|(phase, duration)| format!("\"{}\": {:.6}", phase.name(), duration.as_secs_f64()))
+            .collect::<Vec<String>>();
+        let metadata = self.metadata.as_ref().map(|metadata| format!(
+            "{{\"prover_version\": {}, \"options_digest\": \"{}\", \"created_at\": {}, \"tag\": {}}}",
+            json_string(&metadata.prover_version),
+            common::bytes_to_hex(&metadata.options_digest),
+            metadata.created_at,
+            json_string(&metadata.tag)
+        ));
+
+        let failures = self.failures
+            .iter()
+            .map(|e| format!(
+                "{{\"check\": \"{}\", \"phase\": \"{}\", \"layer\": {}, \"query\": {}, \"error\": {}}}",
+                check(e),
+                phase(e).name(),
+                optional(layer(e).map(|layer| layer.to_string())),
+                optional(query(e).map(|query| query.to_string())),
+                json_string(&e.to_string())
+            ))
+            .collect::<Vec<String>>();
+
+        let mut json = String::new();
+        json.push_str("{\n");
+        json.push_str(&format!("  \"schema\": {},\n", REPORT_SCHEMA));
+        json.push_str(&format!("  \"status\": \"{}\",\n", if self.is_valid() { "verified" } else { "rejected" }));
+        json.push_str(&format!("  \"failed_check\": {},\n", optional(self.failed_check().map(json_string))));
+        json.push_str(&format!("  \"phase\": {},\n", optional(self.phase().map(|phase| json_string(phase.name())))));
+        json.push_str(&format!("  \"stage\": \"{}\",\n", self.stage.name()));
+        json.push_str(&format!("  \"layer\": {},\n", optional(self.layer().map(|layer| layer.to_string()))));
+        json.push_str(&format!("  \"query\": {},\n", optional(self.query().map(|query| query.to_string()))));
+        json.push_str(&format!("  \"error\": {},\n", optional(self.result.as_ref().err().map(|e| json_string(&e.to_string())))));
+        json.push_str(&format!("  \"num_queries\": {},\n", self.num_queries));
+        json.push_str(&format!("  \"num_layers\": {},\n", self.num_layers));
+        json.push_str(&format!("  \"trace_root\": {},\n", optional(self.trace_root.map(|root| json_string(&common::bytes_to_hex(&root))))));
+        json.push_str(&format!("  \"durations\": {{{}}},\n", durations.join(", ")));
+        json.push_str(&format!("  \"mode\": \"{}\",\n", self.mode.name()));
+        json.push_str(&format!("  \"failures\": [{}],\n", failures.join(", ")));
+        json.push_str(&format!("  \"metadata\": {}\n", optional(metadata)));
+        json.push('}');
+        json
+    }
+}
+
+impl Default for VerificationReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for VerificationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Err(e) = &self.result else {
+            return write!(f, "verified")
+        };
+
+        write!(f, "{} during {:?}", e, self.stage)?;
+        if let Some(Mismatch { expected, received }) = &self.mismatch {
+            write!(f, " (expected {}, received {})", expected, received)?;
+        }
+        Ok(())
+    }
+}
+
+fn phase(e: &VerifyError) -> Phase {
+    match e {
+        VerifyError::Decode(_) | VerifyError::Envelope(_) => Phase::Decoding,
+        VerifyError::PublicInput(_) | VerifyError::Statement(_) | VerifyError::Anchor => Phase::PublicInput,
+        VerifyError::TraceInclusion { .. } | VerifyError::TracePosition { .. } => Phase::TraceOpenings,
+        VerifyError::CompositionMismatch { .. } => Phase::CompositionConsistency,
+        VerifyError::Fri(_) | VerifyError::ProofBinding => Phase::Fri,
+        VerifyError::Timeout { phase } => *phase,
+    }
+}
+
+// whether verification cannot go on past the failed check, in either mode
+fn stops(e: &VerifyError) -> bool {
+    phase(e) <= Phase::PublicInput || matches!(e, VerifyError::Timeout { .. })
+}
+
+fn layer(e: &VerifyError) -> Option<usize> {
+    match e {
+        VerifyError::Fri(e) => e.layer(),
+        _ => None,
+    }
+}
+
+fn query(e: &VerifyError) -> Option<usize> {
+    match e {
+        VerifyError::TraceInclusion { query } | VerifyError::TracePosition { query } => Some(*query),
+        VerifyError::CompositionMismatch { query } => Some(*query),
+        VerifyError::Fri(e) => e.query(),
+        VerifyError::Decode(_) | VerifyError::Envelope(_) | VerifyError::PublicInput(_) | VerifyError::Statement(_) | VerifyError::Anchor => None,
+        VerifyError::Timeout { .. } | VerifyError::ProofBinding => None,
+    }
+}
+
+fn check(e: &VerifyError) -> &'static str {
+    match e {
+        VerifyError::Decode(_) => "decode",
+        VerifyError::Envelope(_) => "envelope",
+        VerifyError::Statement(_) => "statement",
+        VerifyError::PublicInput(_) => "public_input",
+        VerifyError::TraceInclusion { .. } => "trace_inclusion",
+        VerifyError::TracePosition { .. } => "trace_position",
+        VerifyError::CompositionMismatch { .. } => "composition_mismatch",
+        VerifyError::Fri(FriError::LayerCount { .. }) => "fri_layer_count",
+        VerifyError::Fri(FriError::QueryCount { .. }) => "fri_query_count",
+        VerifyError::Fri(FriError::Inclusion { .. }) => "fri_inclusion",
+        VerifyError::Fri(FriError::Folding { .. }) => "fri_folding",
+        VerifyError::Fri(FriError::NotConstant { .. }) => "fri_not_constant",
+        VerifyError::Fri(FriError::RemainderSize { .. }) => "fri_remainder_size",
+        VerifyError::Fri(FriError::Remainder { .. }) => "fri_remainder",
+        VerifyError::Fri(FriError::Parameters { .. }) => "fri_parameters",
+        VerifyError::Anchor => "anchor",
+        VerifyError::Timeout { .. } => "timeout",
+        VerifyError::ProofBinding => "proof_binding",
+    }
+}
+
+// a json string, with its quotes, backslashes and control characters escaped
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
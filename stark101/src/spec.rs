@@ -0,0 +1,113 @@
+// the protocol specification generated from the prover itself: the
+// schedule of the operations it applies to the transcript, recorded
+// while proving a statement, each named and with the encoding of its
+// bytes by the label the prover gave it, see Channel::label. other
+// implementations of the verifier follow the schedule to derive the
+// same challenges, and diff it against the one of a new version
+//
+// the schedule is the one of the given statement and options: the
+// number of parameters, of transition coefficients, of queries and
+// of fri layers depend on them
+use lambdaworks_math::field::{
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    element::FieldElement
+};
+
+use crate::air::Air;
+use crate::channel::TranscriptOp;
+use crate::common::{self, ProofOptions, PublicInput, Session};
+use crate::prover;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// version of the json of a specification, changed only when a field
+// is removed or changes meaning
+pub const SPEC_SCHEMA: u32 = 1;
+
+// consecutive operations of the same kind, name and size
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolStep {
+    pub op: TranscriptOp,
+    // empty for an operation the prover did not label
+    pub name: &'static str,
+    pub encoding: &'static str,
+    pub size: usize,
+    pub count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProtocolSpec {
+    // absorbed when the transcript is created, before the steps
+    pub transcript_label: Vec<u8>,
+    // then the seed and the index, as a u64 big endian, of a proof of
+    // a batch proving session
+    pub session: Option<Session>,
+    pub steps: Vec<ProtocolStep>,
+}
+
+// the specification of the protocol, recorded while proving the statement
+pub fn protocol_spec<A: Air<F>>(
+        air: &A,
+        trace: &[FE],
+        public_input: PublicInput<F>,
+        options: &ProofOptions
+    ) -> ProtocolSpec {
+
+    let (_, transcript) = prover::prove_recording(air, trace, public_input, options);
+
+    let mut steps = Vec::<ProtocolStep>::new();
+    for (event, (name, encoding)) in transcript.events.iter().zip(&transcript.labels) {
+        match steps.last_mut() {
+            Some(step) if step.op == event.op && step.name == *name && step.size == event.bytes.len() => step.count += 1,
+            _ => steps.push(ProtocolStep { op: event.op, name, encoding, size: event.bytes.len(), count: 1 }),
+        }
+    }
+
+    ProtocolSpec {
+        transcript_label: options.transcript_label.clone(),
+        session: options.session,
+        steps,
+    }
+}
+
+impl ProtocolSpec {
+
+    // the steps the prover did not label
+    pub fn unlabelled(&self) -> Vec<&ProtocolStep> {
+        self.steps
+            .iter()
+            .filter(|step| step.name.is_empty())
+            .collect::<Vec<&ProtocolStep>>()
+    }
+
+    pub fn to_json(&self) -> String {
+        let session = match &self.session {
+            None => "null".to_string(),
+            Some(Session { seed, index }) => format!("{{\"seed\": \"{}\", \"index\": {}}}", common::bytes_to_hex(seed), index),
+        };
+        let steps = self.steps
+            .iter()
+            .map(|step| format!(
+                "{{\"op\": \"{}\", \"name\": \"{}\", \"encoding\": \"{}\", \"size\": {}, \"count\": {}}}",
+                step.op.label(),
+                step.name,
+                step.encoding,
+                step.size,
+                step.count
+            ))
+            .collect::<Vec<String>>();
+
+        let mut json = String::new();
+        json.push_str("{\n");
+        json.push_str(&format!("  \"schema\": {},\n", SPEC_SCHEMA));
+        json.push_str(&format!("  \"version\": \"{}\",\n", env!("CARGO_PKG_VERSION")));
+        json.push_str("  \"hash\": \"keccak256\",\n");
+        json.push_str("  \"sample\": \"keccak256 of the transcript since the previous sample, byte reversed, from which the transcript restarts\",\n");
+        json.push_str(&format!("  \"transcript_label\": \"{}\",\n", common::bytes_to_hex(&self.transcript_label)));
+        json.push_str(&format!("  \"session\": {},\n", session));
+        json.push_str(&format!("  \"steps\": [\n    {}\n  ]\n", steps.join(",\n    ")));
+        json.push('}');
+        json
+    }
+}
@@ -0,0 +1,268 @@
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::{IsField, IsFFTField}
+};
+use lambdaworks_math::traits::{AsBytes, ByteConversion};
+use lambdaworks_math::polynomial::Polynomial;
+
+use crate::common::InclusionProof;
+use crate::domain::EvaluationDomain;
+use crate::merkle::{LeafLayout, MerkleBackend, Tree};
+
+// where the prover keeps the low degree extension of the trace
+// and its merkle tree. the choice does not change the proof
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum TraceStorage {
+    #[default]
+    Memory,
+    // memory-mapped temporary files in the given directory for the
+    // extension of the trace and its merkle tree only, the extension
+    // computed one coset at a time. the trace polynomial, the
+    // evaluations of the composition polynomial and the first fri
+    // layer stay in memory
+    #[cfg(feature = "mmap")]
+    Disk(std::path::PathBuf),
+}
+
+// evaluates a polynomial over a domain
+pub(crate) type Evaluation<F> = fn(&EvaluationDomain<F>, &Polynomial<FieldElement<F>>) -> Vec<FieldElement<F>>;
+
+// the low degree extension of the trace committed with a merkle tree
+pub(crate) enum CommittedLde<F>
+    where
+        F: IsField,
+        FieldElement<F>: AsBytes + Sync + Send {
+
+    Memory(Vec<FieldElement<F>>, Tree<F>),
+    #[cfg(feature = "mmap")]
+    Disk(disk::MappedColumn<F>, disk::MappedMerkleTree),
+}
+
+impl<F> CommittedLde<F>
+    where
+        F: IsField + IsFFTField,
+        FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+    // evaluates the trace polynomial over the domain and commits
+    // to the evaluations, packed into the leaves of the layout. in
+    // memory, the evaluations are the ones of the given function,
+    // the prover's being the one of ct
+    #[cfg_attr(not(feature = "mmap"), allow(unused_variables))]
+    pub(crate) fn commit(
+            trace_poly: &Polynomial<FieldElement<F>>,
+            trace_length: usize,
+            domain: &EvaluationDomain<F>,
+            storage: &TraceStorage,
+            backend: MerkleBackend,
+            layout: &LeafLayout,
+            evaluate: Evaluation<F>
+        ) -> Self {
+
+        match storage {
+            TraceStorage::Memory => {
+                let evals = evaluate(domain, trace_poly);
+                let tree = backend.build_packed(&evals, layout);
+                CommittedLde::Memory(evals, tree)
+            },
+            #[cfg(feature = "mmap")]
+            TraceStorage::Disk(dir) => {
+                match disk::commit(trace_poly, trace_length, domain.size, &domain.offset, backend, layout, dir) {
+                    Ok((column, tree)) => CommittedLde::Disk(column, tree),
+                    Err(e) => panic!("could not store the trace extension in {}: {}", dir.display(), e),
+                }
+            },
+        }
+    }
+
+    pub(crate) fn root(&self) -> [u8; 32] {
+        match self {
+            CommittedLde::Memory(_, tree) => tree.root(),
+            #[cfg(feature = "mmap")]
+            CommittedLde::Disk(_, tree) => tree.node(0),
+        }
+    }
+
+    // the evaluation at the point of the given index of the domain
+    pub(crate) fn get(&self, index: usize) -> FieldElement<F> {
+        match self {
+            CommittedLde::Memory(evals, _) => evals[index].clone(),
+            #[cfg(feature = "mmap")]
+            CommittedLde::Disk(column, _) => column.get(index),
+        }
+    }
+
+    // the evaluation at the position, with the path of its leaf if it
+    // is the first of the leaf, see Tree::open
+    pub(crate) fn open(&self, position: usize, layout: &LeafLayout) -> InclusionProof<F> {
+        match self {
+            CommittedLde::Memory(evals, tree) => tree.open(layout, position, evals[position].clone()).unwrap(),
+            #[cfg(feature = "mmap")]
+            CommittedLde::Disk(column, tree) => {
+                let path = match layout.leaf_of(position) {
+                    (leaf, 0) => tree.proof(leaf),
+                    _ => lambdaworks_crypto::merkle_tree::proof::Proof { merkle_path: vec![] },
+                };
+                InclusionProof(column.get(position), path)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "mmap")]
+mod disk {
+    use std::io;
+    use std::marker::PhantomData;
+    use std::path::Path;
+
+    use memmap2::MmapMut;
+    use lambdaworks_math::field::{
+        element::FieldElement,
+        traits::{IsField, IsFFTField}
+    };
+    use lambdaworks_math::traits::{AsBytes, ByteConversion};
+    use lambdaworks_math::polynomial::Polynomial;
+    use lambdaworks_crypto::merkle_tree::proof::Proof;
+
+    use crate::merkle::{LeafLayout, MerkleBackend};
+
+    const ELEMENT_SIZE: usize = 32;
+    const NODE_SIZE: usize = 32;
+
+    fn map(dir: &Path, len: usize) -> io::Result<MmapMut> {
+        let file = tempfile::tempfile_in(dir)?;
+        file.set_len(len as u64)?;
+        // safety: the file is unlinked and only reachable through this map
+        unsafe { MmapMut::map_mut(&file) }
+    }
+
+    // field elements stored as 32 big endian bytes each
+    pub(crate) struct MappedColumn<F: IsField> {
+        mmap: MmapMut,
+        field: PhantomData<F>,
+    }
+
+    impl<F> MappedColumn<F>
+        where
+            F: IsField,
+            FieldElement<F>: ByteConversion {
+
+        fn new(dir: &Path, len: usize) -> io::Result<Self> {
+            Ok(Self {
+                mmap: map(dir, len * ELEMENT_SIZE)?,
+                field: PhantomData,
+            })
+        }
+
+        pub(crate) fn get(&self, index: usize) -> FieldElement<F> {
+            let bytes = &self.mmap[index * ELEMENT_SIZE..(index + 1) * ELEMENT_SIZE];
+            FieldElement::<F>::from_bytes_be(bytes).unwrap()
+        }
+
+        fn set(&mut self, index: usize, value: &FieldElement<F>) {
+            self.mmap[index * ELEMENT_SIZE..(index + 1) * ELEMENT_SIZE].copy_from_slice(&value.to_bytes_be());
+        }
+    }
+
+    // merkle tree with the node layout of the lambdaworks one: the
+    // root at 0, the children of node i at 2i + 1 and 2i + 2 and the
+    // leaves at the end, so the paths are the same
+    pub(crate) struct MappedMerkleTree {
+        mmap: MmapMut,
+        leaves: usize,
+    }
+
+    impl MappedMerkleTree {
+
+        fn new(dir: &Path, leaves: usize) -> io::Result<Self> {
+            Ok(Self {
+                mmap: map(dir, (2 * leaves - 1) * NODE_SIZE)?,
+                leaves,
+            })
+        }
+
+        pub(crate) fn node(&self, index: usize) -> [u8; 32] {
+            let mut node = [0_u8; 32];
+            node.copy_from_slice(&self.mmap[index * NODE_SIZE..(index + 1) * NODE_SIZE]);
+            node
+        }
+
+        fn set_node(&mut self, index: usize, node: &[u8; 32]) {
+            self.mmap[index * NODE_SIZE..(index + 1) * NODE_SIZE].copy_from_slice(node);
+        }
+
+        // hashes the inner nodes once every leaf is set, children
+        // always have larger indices than their parent
+        fn build_inner_nodes<F>(&mut self, backend: MerkleBackend)
+            where
+                F: IsField,
+                FieldElement<F>: AsBytes + Sync + Send {
+
+            for index in (0..self.leaves - 1).rev() {
+                let parent = backend.hash_new_parent::<F>(
+                    &self.node(2 * index + 1),
+                    &self.node(2 * index + 2)
+                );
+                self.set_node(index, &parent);
+            }
+        }
+
+        pub(crate) fn proof(&self, index: usize) -> Proof<[u8; 32]> {
+            let mut position = index + self.leaves - 1;
+            let mut merkle_path = Vec::new();
+            while position != 0 {
+                let sibling = if position.is_multiple_of(2) { position - 1 } else { position + 1 };
+                merkle_path.push(self.node(sibling));
+                position = (position - 1) / 2;
+            }
+            Proof { merkle_path }
+        }
+    }
+
+    // the domain of size blowup * trace_length is the union of
+    // blowup cosets of the trace domain: the points with index
+    // k + j * blowup are offset * w^k * g^j. each coset is evaluated
+    // with a trace sized fft and written in place, hashing its leaves,
+    // the consecutive evaluations of a coset being the rows of a leaf
+    pub(crate) fn commit<F>(
+            trace_poly: &Polynomial<FieldElement<F>>,
+            trace_length: usize,
+            domain_size: usize,
+            offset: &FieldElement<F>,
+            backend: MerkleBackend,
+            layout: &LeafLayout,
+            dir: &Path
+        ) -> io::Result<(MappedColumn<F>, MappedMerkleTree)>
+        where
+            F: IsField + IsFFTField,
+            FieldElement<F>: AsBytes + ByteConversion + Sync + Send {
+
+        let blowup_factor = domain_size / trace_length;
+        let w = F::get_primitive_root_of_unity(domain_size.trailing_zeros() as u64).unwrap();
+
+        let mut column = MappedColumn::<F>::new(dir, domain_size)?;
+        let leaves = domain_size / layout.width;
+        let mut tree = MappedMerkleTree::new(dir, leaves)?;
+
+        let mut coset_offset = offset.clone();
+        for k in 0..blowup_factor {
+            let coset_evals = Polynomial::evaluate_offset_fft::<F>(
+                trace_poly, 1, Some(trace_length), &coset_offset
+            ).unwrap();
+            for (j, eval) in coset_evals.iter().enumerate() {
+                column.set(k + j * blowup_factor, eval);
+            }
+            for (i, rows) in coset_evals.chunks(layout.width).enumerate() {
+                let leaf = k + i * blowup_factor;
+                let node = match rows {
+                    [eval] => backend.hash_data(eval),
+                    _ => backend.hash_leaf(rows),
+                };
+                tree.set_node(leaves - 1 + leaf, &node);
+            }
+            coset_offset *= &w;
+        }
+
+        tree.build_inner_nodes::<F>(backend);
+        Ok((column, tree))
+    }
+}
@@ -0,0 +1,106 @@
+// verification of a continuous feed of proofs, e.g. by a rollup node,
+// yielding the result of each proof as soon as it is verified. the
+// pairs of encoded public input and proof are taken from the feed only
+// as fast as the workers verify them and the results are consumed: at
+// most a few times the concurrency of pairs are held at once, whatever
+// the length of the feed. the results come in the order the proofs are
+// verified, each with the index of its pair in the feed
+//
+// the crate has no async runtime, the workers are threads and the
+// stream is an iterator, blocking until the next result is ready
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::threads;
+use crate::verifier::{ProofVerifier, VerifyError};
+
+// an encoded public input and proof, see encoding
+pub type ProofPair = (Vec<u8>, Vec<u8>);
+
+pub struct VerificationStream {
+    source: Source,
+}
+
+enum Source {
+    // the results sent by the workers
+    Workers(Receiver<(usize, Result<(), VerifyError>)>),
+    // inside threads::single_threaded, each pair is verified on the
+    // calling thread when its result is asked for
+    Inline {
+        verifier: Arc<dyn ProofVerifier + Send + Sync>,
+        pairs: Box<dyn Iterator<Item = ProofPair> + Send>,
+        index: usize,
+    },
+}
+
+impl Iterator for VerificationStream {
+    type Item = (usize, Result<(), VerifyError>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.source {
+            Source::Workers(results) => results.recv().ok(),
+            Source::Inline { verifier, pairs, index } => {
+                let (public_input, proof) = pairs.next()?;
+                *index += 1;
+                Some((*index - 1, verifier.verify(&public_input, &proof)))
+            },
+        }
+    }
+}
+
+// verifies the pairs of the feed with at most concurrency proofs at a
+// time. dropping the stream stops the workers once their current proof
+// is verified, without taking more pairs from the feed
+pub fn verify_stream<V, I>(verifier: Arc<V>, pairs: I, concurrency: usize) -> VerificationStream
+    where
+        V: ProofVerifier + Send + Sync + 'static,
+        I: IntoIterator<Item = ProofPair>,
+        I::IntoIter: Send + 'static {
+
+    if threads::is_single_threaded() {
+        return VerificationStream {
+            source: Source::Inline { verifier, pairs: Box::new(pairs.into_iter()), index: 0 },
+        }
+    }
+
+    let concurrency = concurrency.max(1);
+    let (work_sender, work) = mpsc::sync_channel::<(usize, ProofPair)>(concurrency);
+    let (result_sender, results) = mpsc::sync_channel(concurrency);
+
+    // the feed is read by a thread of its own, blocked while every
+    // worker is busy and the queue of pairs is full
+    let pairs = pairs.into_iter();
+    thread::spawn(move || feed(pairs, work_sender));
+
+    let work = Arc::new(Mutex::new(work));
+    for _ in 0..concurrency {
+        let work = Arc::clone(&work);
+        let results = result_sender.clone();
+        let verifier = Arc::clone(&verifier);
+        thread::spawn(move || {
+            loop {
+                // the lock is only held while waiting for the next pair
+                let next = work.lock().unwrap().recv();
+                let Ok((index, (public_input, proof))) = next else {
+                    break
+                };
+                if results.send((index, verifier.verify(&public_input, &proof))).is_err() {
+                    break
+                }
+            }
+        });
+    }
+
+    VerificationStream { source: Source::Workers(results) }
+}
+
+// sends the pairs of the feed with their index, until the feed ends or
+// the workers are gone
+fn feed(pairs: impl Iterator<Item = ProofPair>, work: SyncSender<(usize, ProofPair)>) {
+    for pair in pairs.enumerate() {
+        if work.send(pair).is_err() {
+            break
+        }
+    }
+}
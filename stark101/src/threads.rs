@@ -0,0 +1,48 @@
+// the prover runs on every core by default: parallel_map extracts the
+// openings of the queries on several threads, prove_many runs several
+// proofs at a time and, with the parallel feature, lambdaworks hashes
+// the merkle trees with rayon. single_threaded runs a closure with all
+// of them on a single thread, so that a run is reproducible down to the
+// order of its allocations, for benchmarks and for debugging a failure
+// only seen in multi-threaded runs. the proofs are the same either way
+use std::cell::Cell;
+
+thread_local! {
+    static SINGLE_THREADED: Cell<bool> = const { Cell::new(false) };
+}
+
+// runs the closure with the prover on one thread. with the parallel
+// feature, it runs on the only thread of its own rayon pool, where the
+// hashing of lambdaworks stays
+pub fn single_threaded<R: Send>(f: impl FnOnce() -> R + Send) -> R {
+    #[cfg(feature = "parallel")]
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .expect("cannot start a thread pool of one thread");
+        pool.install(|| flagged(f))
+    }
+    #[cfg(not(feature = "parallel"))]
+    flagged(f)
+}
+
+// whether the caller runs inside single_threaded
+pub fn is_single_threaded() -> bool {
+    SINGLE_THREADED.with(Cell::get)
+}
+
+// sets the flag of the current thread while the closure runs, restoring
+// it even if the closure panics, so that calls nest
+fn flagged<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore(bool);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SINGLE_THREADED.with(|flag| flag.set(self.0));
+        }
+    }
+
+    let _restore = Restore(SINGLE_THREADED.with(|flag| flag.replace(true)));
+    f()
+}
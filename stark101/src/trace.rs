@@ -0,0 +1,90 @@
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+
+use crate::air::{self, Air, TraceError};
+
+// builds the single column of a trace row by row: explicit rows,
+// rows computed from the ones before them, then padding up to the
+// length of the trace. the length is only checked when the trace is
+// built, so that a builder can be filled in any order
+//
+//     let trace = TraceBuilder::new(1024)
+//         .row(FE::one())
+//         .row(witness)
+//         .rows(1021, |rows| rows[rows.len() - 2].square() + rows[rows.len() - 1].square())
+//         .pad(FE::zero())
+//         .build_for(&air)?;
+#[derive(Clone, Debug)]
+pub struct TraceBuilder<F: IsField> {
+    trace_length: usize,
+    rows: Vec<FieldElement<F>>,
+}
+
+impl<F: IsField> TraceBuilder<F> {
+
+    pub fn new(trace_length: usize) -> Self {
+        Self {
+            trace_length,
+            rows: Vec::with_capacity(trace_length),
+        }
+    }
+
+    // number of rows filled so far
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    // appends a row
+    pub fn row(mut self, value: FieldElement<F>) -> Self {
+        self.rows.push(value);
+        self
+    }
+
+    // appends count rows, each computed from all the rows before it
+    pub fn rows(mut self, count: usize, next: impl Fn(&[FieldElement<F>]) -> FieldElement<F>) -> Self {
+        for _ in 0..count {
+            let value = next(&self.rows);
+            self.rows.push(value);
+        }
+        self
+    }
+
+    // overwrites a row already filled
+    pub fn set(mut self, step: usize, value: FieldElement<F>) -> Self {
+        assert!(step < self.rows.len(), "row {} is set before it is filled, {} rows are", step, self.rows.len());
+        self.rows[step] = value;
+        self
+    }
+
+    // fills the remaining rows with the value
+    pub fn pad(mut self, value: FieldElement<F>) -> Self {
+        let trace_length = self.trace_length.max(self.rows.len());
+        self.rows.resize(trace_length, value);
+        self
+    }
+
+    // the trace, if it has exactly the length it was built for
+    pub fn build(self) -> Result<Vec<FieldElement<F>>, TraceError> {
+        if self.rows.len() != self.trace_length {
+            return Err(TraceError::Length { expected: self.trace_length, found: self.rows.len() })
+        }
+        Ok(self.rows)
+    }
+
+    // the trace, if it has the length of the statement and satisfies
+    // its constraints, checked before the prover interpolates it
+    pub fn build_for<A: Air<F>>(self, air: &A) -> Result<Vec<FieldElement<F>>, TraceError> {
+        if self.trace_length != air.trace_length() {
+            return Err(TraceError::Length { expected: air.trace_length(), found: self.rows.len() })
+        }
+        let trace = self.build()?;
+        air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints())?;
+        Ok(trace)
+    }
+}
@@ -1,135 +1,916 @@
-use lambdaworks_math::traits::ByteConversion;
+use std::borrow::Cow;
+use std::fmt;
+use std::time::{Duration, Instant};
+use std::ops::Range;
+
 use lambdaworks_math::field::{
-    traits::IsFFTField,
     fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
     element::FieldElement
 };
-use lambdaworks_crypto::fiat_shamir::{
-    is_transcript::IsTranscript,
-    default_transcript::DefaultTranscript
-};
 
-use crate::common::{self, PublicInput, StarkProof};
+use crate::air::{self, Air};
+use crate::anchor;
+use crate::channel::Channel;
+use crate::composition::{self, PointValues};
+use crate::domain;
+use crate::common::{self, InclusionProof, IsPublicInput, PublicInput, PublicInputError, ProofAccess, ProofOptions, Session, StarkProof};
+use crate::encoding::{self, DecodeError, DecodeLimits, DecodeMode};
+use crate::envelope::EnvelopeError;
 use crate::fri;
+use crate::key::{self, VerifyingKey};
+use crate::merkle::LeafLayout;
+use crate::registry::StatementError;
+use crate::report::{Mismatch, Phase, TranscriptStage, VerificationMode, VerificationReport};
 
 // the stark252 field has 2-adicity of 192, i.e., the largest
 // multiplicative subgroup whose order is a power of two has order 2^192
 type F = Stark252PrimeField;
 type FE = FieldElement<F>;
 
-pub fn verify_proof(public_input: PublicInput<F>, stark_proof: StarkProof<F>) -> bool {
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyError {
+    // the public input or the proof bytes are not a valid encoding
+    Decode(DecodeError),
+    // the envelope of the proof is malformed, or not of a proof this
+    // verifier accepts, see envelope
+    Envelope(EnvelopeError),
+    // the proof is not of a statement registered for it, see registry
+    Statement(StatementError),
+    // the public input does not describe a valid statement
+    PublicInput(PublicInputError),
+    // a trace opening of the given query is not included in the
+    // trace commitment
+    TraceInclusion { query: usize },
+    // a trace opening of the given query is not at the position of a
+    // row of its frame, or its path is not one of the trace tree
+    TracePosition { query: usize },
+    // the composition evaluation opened by fri at the given query
+    // differs from the one recomputed from the trace openings
+    CompositionMismatch { query: usize },
+    // the composition commitment does not pass the low degree test
+    Fri(fri::FriError),
+    // the binding of the proof is not the digest of the transcript of
+    // the verifier, the two sides absorbed or sampled differently, or
+    // the proof has none outside of the legacy entry points
+    ProofBinding,
+    // the statement and commitments of the proof are not the anchored ones
+    Anchor,
+    // the deadline passed by the end of the given phase, the later
+    // phases were not run
+    Timeout { phase: Phase },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Decode(e) => write!(f, "malformed input: {}", e),
+            VerifyError::Envelope(e) => write!(f, "invalid envelope: {}", e),
+            VerifyError::Statement(e) => write!(f, "unregistered statement: {}", e),
+            VerifyError::PublicInput(e) => write!(f, "invalid public input: {}", e),
+            VerifyError::TraceInclusion { query } => write!(f, "trace opening not included in the trace commitment at query {}", query),
+            VerifyError::TracePosition { query } => write!(f, "trace opening not at a row of the frame of query {}", query),
+            VerifyError::CompositionMismatch { query } => write!(f, "composition opening does not match the trace at query {}", query),
+            VerifyError::Fri(e) => write!(f, "composition commitment failed the low degree test: {}", e),
+            VerifyError::ProofBinding => write!(f, "proof binding differs from the transcript of the verifier"),
+            VerifyError::Anchor => write!(f, "proof commitments do not match the anchor"),
+            VerifyError::Timeout { phase } => write!(f, "verification deadline passed in phase {}", phase.name()),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+// verifier of a proof system taking its statement and proof as bytes,
+// so that verifiers of different protocols can be held behind one
+// vtable, e.g. by an ffi layer or a plugin host
+pub trait ProofVerifier {
+    fn verify(&self, public_input: &[u8], proof: &[u8]) -> Result<(), VerifyError>;
+}
+
+// the ProofVerifier of this protocol for the statements whose air is
+// built from the public input. both inputs are decoded strictly, see
+// encoding for their formats, a proof of the current version only.
+// the air is only built for traces of at least the minimum
+// length, below which its constraints are not defined
+pub struct StarkVerifier<A> {
+    air: fn(&PublicInput<F>) -> A,
+    min_trace_length: usize,
+    options: ProofOptions,
+}
+
+impl<A: Air<F>> StarkVerifier<A> {
+
+    pub fn new(air: fn(&PublicInput<F>) -> A, min_trace_length: usize, options: ProofOptions) -> Self {
+        Self {
+            air,
+            min_trace_length,
+            options,
+        }
+    }
+}
+
+impl StarkVerifier<air::FibonacciSquare<F>> {
+
+    // the element of step 1022 is claimed, so the trace has 1024 rows
+    // at least
+    pub fn fibonacci_square(options: ProofOptions) -> Self {
+        Self::new(
+            |PublicInput(_, interp_two_power, _, _, fib_squared_0, fib_squared_1022, _)| air::FibonacciSquare {
+                trace_length: 1 << interp_two_power,
+                fib_squared_0: *fib_squared_0,
+                fib_squared_1022: *fib_squared_1022,
+            },
+            1024,
+            options
+        )
+    }
+}
+
+impl StarkVerifier<air::HashChain<F>> {
+
+    // the seed and the digest are on different rows
+    pub fn hash_chain(options: ProofOptions) -> Self {
+        Self::new(
+            |PublicInput(_, interp_two_power, _, _, seed, digest, _)| air::HashChain {
+                trace_length: 1 << interp_two_power,
+                seed: *seed,
+                digest: *digest,
+            },
+            2,
+            options
+        )
+    }
+}
+
+impl<A: Air<F>> StarkVerifier<A> {
+
+    pub fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    // the public input decoded and validated, with the air built from it
+    pub(crate) fn statement(&self, public_input: &[u8]) -> Result<(A, PublicInput<F>), VerifyError> {
+        let public_input = encoding::decode_public_input(public_input).map_err(VerifyError::Decode)?;
+        // the air is built from the public input, which must be valid first
+        public_input.validate().map_err(VerifyError::PublicInput)?;
+        let trace_length = 1 << public_input.1;
+        if trace_length < self.min_trace_length {
+            return Err(VerifyError::PublicInput(PublicInputError::TraceLength { trace_length, min: self.min_trace_length }))
+        }
+        Ok(((self.air)(&public_input), public_input))
+    }
+
+    // verifies the encoded proof of a statement given by statement
+    pub(crate) fn verify_statement(&self, air: &A, public_input: PublicInput<F>, proof: &[u8]) -> VerificationReport {
+        let PublicInput(_, _, eval_two_power, ..) = public_input;
+        let mode = DecodeMode::Strict { eval_two_power };
+        verify_encoded_with_report(air, public_input, proof, mode, &self.options)
+    }
+}
+
+impl<A: Air<F>> ProofVerifier for StarkVerifier<A> {
+
+    fn verify(&self, public_input: &[u8], proof: &[u8]) -> Result<(), VerifyError> {
+        let (air, public_input) = self.statement(public_input)?;
+        self.verify_statement(&air, public_input, proof).result
+    }
+}
+
+// verifies a proof of the fibonacci square statement of the public
+// input. both are borrowed, so that verifying a batch of proofs does
+// not copy any of them
+pub fn verify_proof(public_input: &PublicInput<F>, stark_proof: &StarkProof<F>, options: &ProofOptions) -> Result<(), VerifyError> {
+    verify_proof_with_report(public_input, stark_proof, options).result
+}
+
+// verifies the proof and reports where verification failed, if it did
+pub fn verify_proof_with_report(public_input: &PublicInput<F>, stark_proof: &StarkProof<F>, options: &ProofOptions) -> VerificationReport {
+    let PublicInput(_, interp_two_power, _, _, fib_squared_0, fib_squared_1022, _) = *public_input;
+    let air = air::FibonacciSquare {
+        trace_length: 1 << interp_two_power,
+        fib_squared_0,
+        fib_squared_1022,
+    };
+    verify_with_report(&air, public_input.clone(), stark_proof, options)
+}
+
+// verifies a proof of the given statement, the air is expected
+// to be built from the public input. the proof is either a decoded
+// one or an encoded one read in place
+pub fn verify<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    verify_with_report(air, public_input, stark_proof, options).result
+}
+
+// verifies an encoded proof of the current version, read in place.
+// the proofs of the previous versions have no binding and are only
+// verified by legacy::verify_encoded. the public input must be valid
+pub fn verify_encoded<A: Air<F>>(
+        air: &A,
+        public_input: PublicInput<F>,
+        bytes: &[u8],
+        mode: DecodeMode,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError> {
+
+    verify_encoded_with_report(air, public_input, bytes, mode, options).result
+}
+
+pub fn verify_encoded_with_report<A: Air<F>>(
+        air: &A,
+        public_input: PublicInput<F>,
+        bytes: &[u8],
+        mode: DecodeMode,
+        options: &ProofOptions
+    ) -> VerificationReport {
+
+    report_encoded(
+        || decode(bytes, mode, &DecodeLimits::default()),
+        |proof| verify_with_report(air, public_input.clone(), proof, options)
+    )
+}
+
+// reads an encoded proof of the current version in place
+fn decode<'a>(bytes: &'a [u8], mode: DecodeMode, limits: &DecodeLimits) -> Result<Box<dyn ProofAccess<F> + 'a>, DecodeError> {
+    Ok(Box::new(encoding::ProofView::parse_with_limits(bytes, mode, limits)?))
+}
+
+// the report of the verification of a decoded proof, the time taken to
+// decode it coming first
+pub(crate) fn report_encoded<'a>(
+        decode: impl FnOnce() -> Result<Box<dyn ProofAccess<F> + 'a>, DecodeError>,
+        verify: impl FnOnce(&dyn ProofAccess<F>) -> VerificationReport
+    ) -> VerificationReport {
+
+    let start = Instant::now();
+    match decode() {
+        Ok(proof) => {
+            let decoding = start.elapsed();
+            let mut report = verify(proof.as_ref());
+            report.durations.insert(0, (Phase::Decoding, decoding));
+            report
+        },
+        Err(e) => VerificationReport {
+            result: Err(VerifyError::Decode(e)),
+            durations: vec![(Phase::Decoding, start.elapsed())],
+            ..Default::default()
+        },
+    }
+}
+
+// verifies a proof whose commitment digest was anchored beforehand,
+// see anchor. the digest is checked first, so that a proof of other
+// commitments is rejected without checking its openings
+pub fn verify_anchored<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions,
+        anchor: &[u8; 32]
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    if anchor::commitment_digest(stark_proof, &public_input) != *anchor {
+        return Err(VerifyError::Anchor)
+    }
+    verify(air, public_input, stark_proof, options)
+}
+
+pub fn verify_with_report<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions
+    ) -> VerificationReport
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    report_until(&VerifierContext::new(air, &public_input, options), air, public_input, stark_proof, None)
+}
+
+// verifies the proof, giving up with VerifyError::Timeout once the
+// deadline has passed, for proofs from untrusted sources that may be
+// crafted to be expensive to reject. the deadline is checked between
+// the phases of verification, each bounded by the sizes of the public
+// input, so that an expired verification returns after at most the
+// phase it was in
+pub fn verify_with_deadline<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions,
+        deadline: Duration
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    let deadline = Instant::now().checked_add(deadline);
+    report_until(&VerifierContext::new(air, &public_input, options), air, public_input, stark_proof, deadline).result
+}
+
+pub(crate) fn report_until<A, P>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        deadline: Option<Instant>
+    ) -> VerificationReport
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    let start = Instant::now();
+    let mut report = VerificationReport::new();
+    report.result = check_proof(context, air, public_input, stark_proof, deadline, &mut report, &mut common::new_transcript(&context.options));
+    // check_proof records every phase it completes but the last, the
+    // rest of the time is spent in the phase it stopped in
+    let recorded = report.durations.iter().map(|(_, duration)| *duration).sum::<Duration>();
+    report.durations.push((report.stopped().unwrap_or(Phase::Fri), start.elapsed().saturating_sub(recorded)));
+    report
+}
+
+// same as verify, additionally returning every operation applied to
+// the transcript, up to the failed check if any
+#[cfg(feature = "transcript-log")]
+pub fn verify_recorded<A, P>(
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions
+    ) -> (Result<(), VerifyError>, Vec<crate::channel::TranscriptEvent>)
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    let context = VerifierContext::new(air, &public_input, options);
+    let mut transcript = crate::channel::RecordingChannel::new(common::new_transcript(options));
+    let result = check_proof(&context, air, public_input, stark_proof, None, &mut VerificationReport::new(), &mut transcript);
+    (result, transcript.events)
+}
+
+// verifies with a key computed beforehand for the shape of the statement,
+// so that verifying many proofs of the same shape shares the precomputation
+pub fn verify_with_key<A, P>(
+        key: &VerifyingKey,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        options: &ProofOptions
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    let context = VerifierContext::from_key(key.clone(), air, options);
+    check_proof(&context, air, public_input, stark_proof, None, &mut VerificationReport::new(), &mut common::new_transcript(options))
+}
+
+// everything the verifier derives from the shape of a statement and the
+// options alone: the verifying key with the domain generators and the
+// boundary and exemption points, the digest of the key and the options
+// absorbed first into the transcript, and g^k for every row k of the
+// frames read by the constraints. computed once, it is reused by every
+// verify_proof_with_context of proofs of statements of that shape
+#[derive(Clone)]
+pub struct VerifierContext {
+    pub(crate) key: VerifyingKey,
+    options: ProofOptions,
+    pub(crate) limits: DecodeLimits,
+    mode: VerificationMode,
+    pub(crate) key_digest: [u8; 32],
+    // whether a proof without a binding is verified, by the legacy
+    // entry points only
+    unbound: bool,
+    frame_offsets: Vec<usize>,
+    frame_points: Vec<FE>,
+}
+
+impl VerifierContext {
+
+    pub fn new<A: Air<F>>(air: &A, public_input: &PublicInput<F>, options: &ProofOptions) -> Self {
+        let PublicInput(_, _, eval_two_power, ..) = *public_input;
+        Self::from_key(VerifyingKey::new(air, eval_two_power), air, options)
+    }
+
+    pub fn from_key<A: Air<F>>(key: VerifyingKey, air: &A, options: &ProofOptions) -> Self {
+        let frame_offsets = air.frame_offsets();
+        let frame_points = frame_offsets
+            .iter()
+            .map(|k| key.g.pow(*k))
+            .collect::<Vec<FE>>();
+        Self {
+            key_digest: key.digest(options),
+            key,
+            options: options.clone(),
+            limits: DecodeLimits::default(),
+            mode: VerificationMode::FailFast,
+            unbound: false,
+            frame_offsets,
+            frame_points,
+        }
+    }
+
+    // whether the context was computed for the air and the shape of
+    // the statement
+    pub fn fits<A: Air<F>>(&self, air: &A, public_input: &PublicInput<F>) -> bool {
+        let PublicInput(_, _, eval_two_power, ..) = *public_input;
+        self.key.fits(air, eval_two_power) && self.frame_offsets == air.frame_offsets()
+    }
+
+    pub fn options(&self) -> &ProofOptions {
+        &self.options
+    }
+
+    // the bounds on the sizes of the proofs verified with the context,
+    // and on the number of queries and domain sizes of their statements
+    pub fn with_limits(mut self, limits: DecodeLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &DecodeLimits {
+        &self.limits
+    }
+
+    // decodes an untrusted public input within the limits of the context
+    pub fn decode_public_input(&self, bytes: &[u8]) -> Result<PublicInput<F>, VerifyError> {
+        encoding::decode_public_input_with_limits(bytes, &self.limits).map_err(VerifyError::Decode)
+    }
+
+    // whether the reports of the context stop at the first failed check
+    // or hold every one found
+    pub fn with_mode(mut self, mode: VerificationMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    pub fn mode(&self) -> VerificationMode {
+        self.mode
+    }
+
+    // a context verifying the proofs without a binding as well, see legacy
+    pub(crate) fn accepting_unbound(mut self) -> Self {
+        self.unbound = true;
+        self
+    }
+}
+
+// verifies a proof of a statement the context fits, with the options
+// of the context
+pub fn verify_proof_with_context<A, P>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    check_proof(context, air, public_input, stark_proof, None, &mut VerificationReport::new(), &mut common::new_transcript(&context.options))
+}
+
+// same as verify_proof_with_context, reporting every failed check when
+// the context is in exhaustive mode
+pub fn verify_proof_with_context_report<A, P>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P
+    ) -> VerificationReport
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    report_until(context, air, public_input, stark_proof, None)
+}
+
+// same as verify_encoded with the options of the context, the proof
+// being parsed within the limits of the context
+pub fn verify_encoded_with_context<A: Air<F>>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        bytes: &[u8],
+        mode: DecodeMode
+    ) -> Result<(), VerifyError> {
+
+    verify_encoded_with_context_report(context, air, public_input, bytes, mode).result
+}
+
+pub fn verify_encoded_with_context_report<A: Air<F>>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        bytes: &[u8],
+        mode: DecodeMode
+    ) -> VerificationReport {
+
+    report_encoded(
+        || decode(bytes, mode, &context.limits),
+        |proof| verify_proof_with_context_report(context, air, public_input.clone(), proof)
+    )
+}
+
+// verifies the proofs of a session generated by prove_session with
+// the same seed, each proof against the statement of its index. the
+// result of each proof is returned in order
+pub fn verify_session<A, P>(
+        statements: &[(A, PublicInput<F>)],
+        proofs: &[P],
+        seed: [u8; 32],
+        options: &ProofOptions
+    ) -> Vec<Result<(), VerifyError>>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> {
+
+    assert_eq!(statements.len(), proofs.len(), "one proof is expected for each statement");
+    statements
+        .iter()
+        .zip(proofs)
+        .enumerate()
+        .map(|(index, ((air, public_input), proof))| {
+            let options = ProofOptions {
+                session: Some(Session { seed, index }),
+                ..options.clone()
+            };
+            verify(air, public_input.clone(), proof, &options)
+        })
+        .collect::<Vec<Result<(), VerifyError>>>()
+}
+
+// runs the checks of the verifier in the mode of the context, every
+// failed one recorded in the report, returning the first
+fn check_proof<A, P>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        deadline: Option<Instant>,
+        report: &mut VerificationReport,
+        transcript: &mut impl Channel<F>
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
+
+    report.mode = context.mode;
+    // the checks return early only where verification stops, the
+    // failures are the ones recorded
+    let _ = run_checks(context, air, public_input, stark_proof, deadline, report, transcript);
+    match report.failures.first() {
+        Some(e) => Err(e.clone()),
+        None => Ok(()),
+    }
+}
+
+fn run_checks<A, P>(
+        context: &VerifierContext,
+        air: &A,
+        public_input: PublicInput<F>,
+        stark_proof: &P,
+        deadline: Option<Instant>,
+        report: &mut VerificationReport,
+        transcript: &mut impl Channel<F>
+    ) -> Result<(), VerifyError>
+    where
+        A: Air<F>,
+        P: ProofAccess<F> + ?Sized {
 
     // ===================================
     // ==========|    Part 1:   |=========
     // === Statement, LDE & Commitment ===
     // ===================================
     // extract public input
-    let PublicInput(
-        modulus,
-        interp_two_power,
-        eval_two_power,
-        num_queries,
-        fib_squared_0,
-        fib_squared_1022
-    ) = public_input;
-
-    let StarkProof {
-        trace_commitment,
-        composition_commitment
-    } = stark_proof;
-
-    // initialize transcript and append all public inputs
-    let mut transcript = DefaultTranscript::<F>::new(&[]);
-    transcript.append_bytes(&modulus.to_bytes_be());
-    transcript.append_bytes(&interp_two_power.to_be_bytes());
-    transcript.append_bytes(&eval_two_power.to_be_bytes());
-    transcript.append_bytes(&num_queries.to_be_bytes());
-    transcript.append_bytes(&fib_squared_0.to_bytes_be());
-    transcript.append_bytes(&fib_squared_1022.to_bytes_be());
+    let mut phase_start = Instant::now();
+    // checked at the end of each phase but the last, before it is recorded
+    let in_time = |phase: Phase| match deadline {
+        Some(deadline) if Instant::now() > deadline => Err(VerifyError::Timeout { phase }),
+        _ => Ok(()),
+    };
+    public_input.validate().or_else(|e| report.fail(VerifyError::PublicInput(e)))?;
+    let PublicInput(_, interp_two_power, eval_two_power, num_queries, ..) = public_input;
+    report.num_queries = num_queries;
 
-    // define example parameters
-    let one = FE::one();
-    let offset = FE::from(2_u64); 
-    let eval_order: usize = 1 << eval_two_power;
+    let VerifierContext { key, options, limits, key_digest, .. } = context;
+    // as many queries are sampled as the statement asks for, bounded
+    // like the openings of the proof
+    if num_queries > limits.max_openings {
+        return report.fail(VerifyError::PublicInput(PublicInputError::TooManyQueries { num_queries, max: limits.max_openings }))
+    }
+    if eval_two_power > limits.max_two_power {
+        return report.fail(VerifyError::PublicInput(PublicInputError::DomainSizes { interp_two_power, eval_two_power }))
+    }
+    options.check_leaf_width(1 << interp_two_power).or_else(|e| report.fail(VerifyError::PublicInput(e)))?;
+    // the air and the context given with the statement must be its own,
+    // the generator of the lde a root of the one of the trace
+    let blowup_factor = 1_usize << (eval_two_power - interp_two_power);
+    if air.trace_length() != 1 << interp_two_power || !context.fits(air, &public_input) || key.w.pow(blowup_factor as u64) != key.g {
+        return report.fail(VerifyError::PublicInput(PublicInputError::Shape))
+    }
 
-    /*
-        TODO: OFFSET IS PUBLIC INPUT
-    */
+    // the configuration first, then all public inputs and the parameters
+    // of the air
+    transcript.append_bytes(key_digest);
+    public_input.absorb_into(transcript);
+    for parameter in air.parameters() {
+        transcript.append_bytes(&common::fe_to_bytes_be(&parameter));
+    }
+    transcript.append_bytes(&[options.query_sampling as u8]);
+
+    let offset = key.offset;
+    let eval_order: usize = 1 << eval_two_power;
 
     // define primitive root
-    let g = F::get_primitive_root_of_unity(interp_two_power as u64).unwrap();
-    let g_to_the_1021 = g.pow(1021_u64);
-    let g_to_the_1022 = g * g_to_the_1021;
-    let g_to_the_1023 = g * g_to_the_1022;
-    let blowup_factor = (2_usize).pow((eval_two_power - interp_two_power) as u32);
+    let transitions = air.transition_constraints();
+    let w = key.w;
 
-    let w = F::get_primitive_root_of_unity(eval_two_power as u64).unwrap();
-    assert_eq!(w.pow(blowup_factor as u64), g);
+    // the shape of the proof is bound before any challenge is sampled
+    let degree_bound = air.composition_degree_bound();
+    let (schedule, _) = match fri::committed_schedule(degree_bound, eval_order, options.fri_max_layers) {
+        Ok(schedule) => schedule,
+        Err(e) => return report.fail(VerifyError::Fri(e)),
+    };
+    let num_layers = schedule.len();
+    transcript.append_bytes(&degree_bound.to_be_bytes());
+    transcript.append_bytes(&num_layers.to_be_bytes());
+    transcript.append_bytes(&num_queries.to_be_bytes());
+    report.num_layers = num_layers;
+    report.degree_bounds = schedule;
 
-    transcript.append_bytes(&trace_commitment.root);
+    report.stage = TranscriptStage::TraceCommitment;
+    let trace_root = stark_proof.trace_root();
+    transcript.append_bytes(&trace_root);
+    report.trace_root = Some(trace_root);
 
     // ===================================
     // =========|    Part 2:   |==========
     // ===== Polynomial Constraints ======
     // ===================================
+    report.stage = TranscriptStage::ConstraintCoefficients;
     let a = transcript.sample_field_element();
-    let b = transcript.sample_field_element();
-    let c = transcript.sample_field_element();
-
-    // get queries evaluations and add to transcript
-    let query_indices = common::sample_queries(num_queries, eval_order, &mut transcript);
-    let aux_indices = [0, blowup_factor, 2 * blowup_factor];
-    let aux_indices_len = aux_indices.len();
-    let all_indices = query_indices
-        .iter()
-        .map(|i| {
-            aux_indices
-                .iter()
-                .map(|j| (i + j) % eval_order)
-                .collect::<Vec<usize>>()
-    }).collect::<Vec<Vec<usize>>>()
-    .concat();
-
-    if !trace_commitment.verify_inclusion_proofs(&all_indices) {
-        return false
-    }
-
-    // compute queries
-    let queries = query_indices
+    let b = transitions
         .iter()
-        .map(|idx| offset * w.pow(idx.to_owned()))
+        .map(|_| transcript.sample_field_element())
         .collect::<Vec<FE>>();
 
-    // compute composition polynomial evaluations
-    let comp_poly_query_evals = queries
-        .iter()
-        .enumerate()
-        .map(|(i, x0)| {
-            let t = (0..aux_indices_len).map(|k| {
-                trace_commitment.inclusion_proofs[aux_indices_len * i + k].0
-            }).collect::<Vec<FE>>();
-            a * (t[0] - fib_squared_0) / (x0 - one) +
-            b * (t[0] - fib_squared_1022) / (x0 - g_to_the_1022) +
-            c * (
-                    (t[2] - t[1].square() - t[0].square()) * 
-                    (x0 - g_to_the_1021) * 
-                    (x0 - g_to_the_1022) * 
-                    (x0 - g_to_the_1023) / 
-                    (x0.pow(1024_u64) - one)
-            )
-        }).collect::<Vec<FE>>();
+    // get queries evaluations and add to transcript
+    report.stage = TranscriptStage::QuerySampling;
+    let query_indices = common::sample_queries(num_queries, eval_order, transcript).expect("the lde domain has points");
+    in_time(Phase::PublicInput).or_else(|e| report.fail(e))?;
+    report.record(Phase::PublicInput, &mut phase_start);
+    let queries = domain::coset_points(&offset, &w, eval_order, &query_indices);
+    let trace = TraceOpenings::new(context, &public_input, trace_root, stark_proof.trace_opening_count(), |i| stark_proof.trace_opening(i), &query_indices);
+    trace.check(&queries, report)?;
+    in_time(Phase::TraceOpenings).or_else(|e| report.fail(e))?;
+    report.record(Phase::TraceOpenings, &mut phase_start);
+
+    let comp_poly_query_evals = trace.compositions(air, &queries, &a, &b);
 
     // ===================================
     // =========|    Part 3:   |==========
     // ======== FRI Decommitment =========
     // ===================================
+    check_composition_openings(&stark_proof.layer(0), &comp_poly_query_evals, report)?;
+    in_time(Phase::CompositionConsistency).or_else(|e| report.fail(e))?;
+    report.record(Phase::CompositionConsistency, &mut phase_start);
+
     // build fri layers
-    fri::decommit_and_fold(
-        &composition_commitment,
+    report.stage = TranscriptStage::FriLayers;
+    let fri_options = fri::FriOptions { num_queries, query_sampling: options.query_sampling, offset_schedule: options.offset_schedule, max_layers: options.fri_max_layers, merkle_backend: options.merkle_backend };
+    let fri = fri::decommit_and_fold_with_mismatch(
+        stark_proof,
         &eval_order,
+        &offset,
+        degree_bound,
         &query_indices,
-        &queries,
-        &comp_poly_query_evals,
-        &mut transcript
-    )
+        &fri_options,
+        transcript,
+        &mut report.mismatch
+    );
+    if let Err(e) = fri {
+        return report.fail(VerifyError::Fri(e))
+    }
+
+    // the transcript up to here is the one of the prover only if both
+    // sides absorbed and sampled the same, which a different binding
+    // tells explicitly. proofs of previous versions have none, verified
+    // by the legacy entry points only. after a failure in exhaustive
+    // mode, the binding differs for the same cause
+    let binding = common::proof_binding(
+        (0..stark_proof.trace_opening_count()).map(|i| stark_proof.trace_opening(i).0),
+        stark_proof,
+        transcript
+    );
+    check_binding(stark_proof.binding(), binding, context.unbound, report)
+}
+
+pub(crate) fn check_binding(
+        received: Option<[u8; 32]>,
+        expected: [u8; 32],
+        unbound: bool,
+        report: &mut VerificationReport
+    ) -> Result<(), VerifyError> {
+
+    match received {
+        None if !unbound => report.fail(VerifyError::ProofBinding),
+        Some(received) if received != expected && report.failures.is_empty() => {
+            report.mismatch.get_or_insert(Mismatch {
+                expected: common::bytes_to_hex(&expected),
+                received: common::bytes_to_hex(&received),
+            });
+            report.fail(VerifyError::ProofBinding)
+        },
+        _ => Ok(()),
+    }
+}
+
+// the openings of one committed trace at the queries, at the positions
+// the verifier expects them for the frame rows of the air
+pub(crate) struct TraceOpenings<'a, O> {
+    context: &'a VerifierContext,
+    root: [u8; 32],
+    num_openings: usize,
+    opening: O,
+    query_indices: &'a [usize],
+    positions: Vec<usize>,
+    layout: LeafLayout,
+    eval_two_power: usize,
+}
+
+impl<'a, 'p, O: Fn(usize) -> Cow<'p, InclusionProof<F>>> TraceOpenings<'a, O> {
+
+    pub(crate) fn new(
+            context: &'a VerifierContext,
+            public_input: &PublicInput<F>,
+            root: [u8; 32],
+            num_openings: usize,
+            opening: O,
+            query_indices: &'a [usize]
+        ) -> Self {
+
+        let PublicInput(_, interp_two_power, eval_two_power, ..) = *public_input;
+        let layout = context.options.trace_layout(1 << (eval_two_power - interp_two_power));
+        let positions = common::trace_positions(query_indices, &context.frame_offsets, &layout, 1 << eval_two_power);
+        Self { context, root, num_openings, opening, query_indices, positions, layout, eval_two_power }
+    }
+
+    // the row k of the frame of a query, k one of the frame offsets, is
+    // opened at this position of the proof
+    fn opening_of(&self, query: usize, k: usize) -> usize {
+        let position = common::shifted_position(query, k, self.layout.blowup_factor, 1 << self.eval_two_power);
+        self.positions
+            .binary_search_by_key(&self.layout.rank(position), |p| self.layout.rank(*p))
+            .unwrap()
+    }
+
+    // the openings of the leaf of the given opening, or the opening
+    // alone if it is past the expected ones
+    fn leaf_openings(&self, i: usize) -> Range<usize> {
+        match self.positions.get(i) {
+            Some(position) => {
+                let (_, offset) = self.layout.leaf_of(*position);
+                i - offset..i - offset + self.layout.width
+            },
+            None => i..i + 1,
+        }
+    }
+
+    // whether the opening, if any, has the path of a leaf of the trace
+    // tree if it is the first of its leaf, and no path otherwise
+    fn has_leaf_height(&self, i: usize) -> bool {
+        let height = match self.layout.leaf_of(self.positions[i]) {
+            (_, 0) => self.layout.height(1 << self.eval_two_power),
+            _ => 0,
+        };
+        i >= self.num_openings || (self.opening)(i).1.merkle_path.len() == height
+    }
+
+    // checks that every opening is at the position of its row and is
+    // included in the commitment, given the points of the queries.
+    // every failed query is reported
+    pub(crate) fn check(&self, queries: &[FE], report: &mut VerificationReport) -> Result<(), VerifyError> {
+        let VerifierContext { key, frame_offsets, frame_points, .. } = self.context;
+        let (query_indices, num_openings, eval_two_power) = (self.query_indices, self.num_openings, self.eval_two_power);
+        let position_points = domain::coset_points(&key.offset, &key.w, 1 << eval_two_power, &self.positions);
+
+        // the row k of the frame of a query x is the one of the point g^k * x
+        // of the lde, and the path of its leaf has the height of the trace
+        // tree, so that it is the path of a single leaf whatever the bits
+        // of its position. the other openings of the leaf have no path
+        let misplaced = query_indices.iter().zip(queries).enumerate().filter(|(_, (q, x))| {
+            frame_offsets.iter().zip(frame_points).any(|(k, g_k)| {
+                let i = self.opening_of(**q, *k);
+                position_points[i] != g_k * *x || !self.leaf_openings(i).all(|j| self.has_leaf_height(j))
+            })
+        });
+        for (query, _) in misplaced {
+            report.fail(VerifyError::TracePosition { query })?;
+        }
+
+        let mut failed_queries = Vec::<usize>::new();
+        let backend = self.context.options.merkle_backend;
+        for i in common::invalid_leaf_openings(backend, &self.root, self.layout, &self.positions, num_openings, &self.opening) {
+            let leaf_openings = self.leaf_openings(i);
+            if cfg!(debug_assertions) && report.mismatch.is_none() && leaf_openings.end <= num_openings && i < self.positions.len() {
+                let evals = leaf_openings.clone().map(|j| (self.opening)(j).0).collect::<Vec<FE>>();
+                let (leaf, _) = self.layout.leaf_of(self.positions[i]);
+                report.mismatch = Some(Mismatch {
+                    expected: common::bytes_to_hex(&self.root),
+                    received: common::bytes_to_hex(&backend.root_from_leaf(&(self.opening)(leaf_openings.start).1, leaf, &evals)),
+                });
+            }
+            // the first query that relies on the leaf of the invalid opening
+            let query = query_indices
+                .iter()
+                .position(|q| frame_offsets.iter().any(|k| leaf_openings.contains(&self.opening_of(*q, *k))))
+                .unwrap_or(query_indices.len());
+            if !failed_queries.contains(&query) {
+                failed_queries.push(query);
+                report.fail(VerifyError::TraceInclusion { query })?;
+            }
+        }
+        Ok(())
+    }
+
+    // the composition of the constraint quotients of the air at each
+    // query, from the opened rows. in exhaustive mode, the queries with
+    // a missing opening have failed already and are left out
+    pub(crate) fn compositions<A: Air<F>>(&self, air: &A, queries: &[FE], a: &FE, b: &[FE]) -> Vec<Option<FE>> {
+        let key = &self.context.key;
+        let boundary_interpolant = air.boundary_constraints().interpolant(&key.g);
+        let transitions = air.transition_constraints();
+
+        // the denominators of the quotients at every query, the boundary
+        // zerofier and the one of each transition, inverted all at once
+        let denominator_invs = domain::batch_inverses(
+            &queries
+                .iter()
+                .flat_map(|x0| {
+                    std::iter::once(key::vanishing(x0, &key.boundary_points))
+                        .chain(key.zerofiers.iter().map(|zerofier| zerofier.denominator(x0, air.trace_length())))
+                })
+                .collect::<Vec<FE>>()
+        );
+        queries
+            .iter()
+            .zip(denominator_invs.chunks(1 + transitions.len()))
+            .zip(self.query_indices)
+            .map(|((x0, invs), q)| {
+                self.context.frame_offsets.iter().all(|k| self.opening_of(*q, *k) < self.num_openings).then(|| {
+                    let values = PointValues {
+                        row: |k: usize| (self.opening)(self.opening_of(*q, k)).0,
+                        boundary_interpolant: boundary_interpolant.evaluate(x0),
+                        boundary_zerofier_inv: invs[0],
+                        transition_factor: |j: usize| key.zerofiers[j].numerator(x0, &key.exemption_points[j]) * invs[1 + j],
+                    };
+                    composition::evaluate_at(&transitions, air.trace_length(), values, a, b)
+                })
+            })
+            .collect::<Vec<Option<FE>>>()
+    }
+}
+
+// the composition evaluations opened by the first fri layer must
+// be the ones recomputed from the trace openings, at the queries
+// they could be recomputed at
+pub(crate) fn check_composition_openings(
+        first_layer: &fri::FriLayer<F>,
+        comp_poly_query_evals: &[Option<FE>],
+        report: &mut VerificationReport
+    ) -> Result<(), VerifyError> {
+
+    let opened_evals = &first_layer.validation_data;
+    if opened_evals.len() != comp_poly_query_evals.len() {
+        return report.fail(VerifyError::CompositionMismatch { query: opened_evals.len().min(comp_poly_query_evals.len()) })
+    }
+
+    let mismatches = opened_evals
+        .iter()
+        .zip(comp_poly_query_evals)
+        .enumerate()
+        .filter_map(|(query, (data, eval))| eval.filter(|eval| data.eval != *eval).map(|eval| (query, eval)));
+    for (query, eval) in mismatches {
+        if cfg!(debug_assertions) && report.mismatch.is_none() {
+            report.mismatch = Some(Mismatch {
+                expected: common::bytes_to_hex(&common::fe_to_bytes_be(&eval)),
+                received: common::bytes_to_hex(&common::fe_to_bytes_be(&opened_evals[query].eval)),
+            });
+        }
+        report.fail(VerifyError::CompositionMismatch { query })?;
+    }
+    Ok(())
 }
@@ -0,0 +1,121 @@
+// the secret input of a statement, the field element only the prover
+// knows, e.g. the x of stark 101 whose fibonacci square sequence
+// reaches the public a(1022). a witness is read from bytes or hex
+// strings in their canonical encoding only, so that the cli, bindings
+// and services built on this crate read the same witness from the same
+// input. its debug output hides the value, to keep it out of logs
+use std::fmt;
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    traits::IsField
+};
+use lambdaworks_math::traits::ByteConversion;
+
+use crate::air;
+use crate::common::{self, ElementBytesError};
+
+#[derive(Clone)]
+pub struct Witness<F: IsField>(FieldElement<F>);
+
+impl<F: IsField> Witness<F> {
+
+    pub fn new(value: FieldElement<F>) -> Self {
+        Self(value)
+    }
+
+    pub fn from_u64(value: u64) -> Self {
+        Self(FieldElement::from(value))
+    }
+
+    // the witness 3141592 of stark 101, the one of the tutorial and of
+    // prover::generate_proof
+    pub fn example() -> Self {
+        Self::from_u64(3141592)
+    }
+
+    pub fn value(&self) -> &FieldElement<F> {
+        &self.0
+    }
+
+    pub fn into_value(self) -> FieldElement<F> {
+        self.0
+    }
+
+    // the public commitment to the witness, see air::CommittedWitness
+    pub fn commitment(&self) -> FieldElement<F> {
+        air::commit_witness(&self.0)
+    }
+}
+
+impl<F: IsField> Witness<F>
+    where
+        FieldElement<F>: ByteConversion {
+
+    // the big endian bytes of an integer below the modulus
+    pub fn from_bytes_be(bytes: &[u8]) -> Result<Self, ElementBytesError> {
+        common::fe_from_bytes_be(bytes).map(Self)
+    }
+
+    // an integer below the modulus in hex, see common::fe_from_hex
+    pub fn from_hex(hex: &str) -> Result<Self, ElementBytesError> {
+        common::fe_from_hex(hex).map(Self)
+    }
+
+    // see air::witness_from_seed
+    pub fn from_seed(seed: &str) -> Self {
+        Self(air::witness_from_seed(seed))
+    }
+
+    pub fn to_bytes_be(&self) -> Vec<u8> {
+        common::fe_to_bytes_be(&self.0)
+    }
+
+    // the canonical bytes in hex, with a 0x prefix, read back by from_hex
+    pub fn to_hex(&self) -> String {
+        format!("0x{}", common::bytes_to_hex(&self.to_bytes_be()))
+    }
+}
+
+impl<F: IsField> From<u64> for Witness<F> {
+    fn from(value: u64) -> Self {
+        Self::from_u64(value)
+    }
+}
+
+impl<F: IsField> PartialEq for Witness<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<F: IsField> Eq for Witness<F> {}
+
+impl<F: IsField> fmt::Debug for Witness<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Witness(..)")
+    }
+}
+
+// a witness is serialized as the string of to_hex and deserialized
+// from any string from_hex accepts
+#[cfg(feature = "serde")]
+impl<F: IsField> serde::Serialize for Witness<F>
+    where
+        FieldElement<F>: ByteConversion {
+
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, F: IsField> serde::Deserialize<'de> for Witness<F>
+    where
+        FieldElement<F>: ByteConversion {
+
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        Self::from_hex(&hex).map_err(serde::de::Error::custom)
+    }
+}
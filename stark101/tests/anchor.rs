@@ -0,0 +1,49 @@
+// proofs checked against the digest of their commitments
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::anchor;
+use stark101::common::ProofOptions;
+use stark101::encoding::{self, ProofView};
+use stark101::verifier::{self, VerifyError};
+use stark101::prover;
+
+mod common;
+
+use common::{seeded_statement, statement};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn anchored_proofs_verify() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let anchor = proof.commitment_digest(&public_input);
+
+    // the digest is the same for an encoded proof read in place
+    let bytes = encoding::encode_proof(&proof);
+    let view = ProofView::parse(&bytes).unwrap();
+    assert_eq!(anchor::commitment_digest(&view, &public_input), anchor);
+    assert_eq!(verifier::verify_anchored(&air, public_input.clone(), &view, &options, &anchor), Ok(()));
+
+    // the openings are not part of the anchor, a proof with a tampered
+    // opening matches it and is rejected by the verifier
+    let mut tampered = proof.clone();
+    tampered.trace_commitment.inclusion_proofs[0].0 += FE::one();
+    assert_eq!(tampered.commitment_digest(&public_input), anchor);
+    assert!(matches!(verifier::verify_anchored(&air, public_input, &tampered, &options, &anchor), Err(VerifyError::TraceInclusion { .. })));
+}
+
+#[test]
+fn proofs_of_other_commitments_do_not_match_the_anchor() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let anchor = prover::prove(&air, &trace, public_input.clone(), &options).commitment_digest(&public_input);
+
+    // a valid proof of another statement
+    let (other_air, other_trace, other_public_input) = seeded_statement(2718281);
+    let other = prover::prove(&other_air, &other_trace, other_public_input.clone(), &options);
+    assert_eq!(verifier::verify(&other_air, other_public_input.clone(), &other, &options), Ok(()));
+    assert_eq!(verifier::verify_anchored(&other_air, other_public_input, &other, &options, &anchor), Err(VerifyError::Anchor));
+}
@@ -0,0 +1,42 @@
+// the auto merkle backend is the fastest one of the build, and the
+// backends set in the options are used as set
+use stark101::common::ProofOptions;
+use stark101::merkle::{self, MerkleBackend};
+use stark101::{envelope, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+#[test]
+fn every_backend_of_the_build_is_measured() {
+    let timings = merkle::benchmark_backends(256);
+    assert_eq!(timings.iter().map(|timing| timing.backend).collect::<Vec<MerkleBackend>>(), merkle::BACKENDS);
+    assert!(timings.iter().all(|timing| timing.leaves == 256 && timing.leaves_per_second() > 0.0));
+
+    let fastest = merkle::fastest_backend();
+    assert!(merkle::BACKENDS.contains(&fastest));
+    assert_eq!(MerkleBackend::Auto.resolve(), fastest);
+    for backend in merkle::BACKENDS {
+        assert_eq!(backend.resolve(), *backend);
+        assert_eq!(MerkleBackend::from_name(backend.name()), Some(*backend));
+    }
+    assert_eq!(MerkleBackend::from_name("auto"), Some(MerkleBackend::Auto));
+    assert_eq!(MerkleBackend::from_name("sha256"), None);
+}
+
+#[test]
+fn auto_proofs_verify_with_the_resolved_options() {
+    let (air, trace, public_input) = statement();
+    let auto = ProofOptions { merkle_backend: MerkleBackend::Auto, ..Default::default() };
+    let resolved = auto.resolved();
+    assert_eq!(resolved.merkle_backend, merkle::fastest_backend());
+    assert_eq!(ProofOptions::default().resolved().merkle_backend, MerkleBackend::Keccak256);
+
+    let proof = prover::prove(&air, &trace, public_input.clone(), &auto);
+    assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &auto), Ok(()));
+    assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &resolved), Ok(()));
+    let resolved_proof = prover::prove(&air, &trace, public_input, &resolved);
+    assert_eq!(proof.trace_commitment.root, resolved_proof.trace_commitment.root);
+    assert_eq!(envelope::options_digest(&auto), envelope::options_digest(&resolved));
+}
@@ -0,0 +1,89 @@
+// the binding of a proof is the transcript squeezed after every opened
+// evaluation, proofs without one are encoded in the previous format and
+// verified by the legacy entry points only
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+use stark101::air::HashChain;
+use stark101::common::{ProofOptions, PublicInput, StarkProof};
+use stark101::encoding::{self, DecodeError, DecodeMode};
+use stark101::legacy;
+use stark101::verifier::{self, VerifyError};
+use stark101::prover;
+
+mod common;
+
+type F = Stark252PrimeField;
+
+// the shared statement with its proof
+fn statement() -> (HashChain<F>, PublicInput<F>, StarkProof<F>) {
+    let (air, trace, public_input) = common::statement();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &ProofOptions::default());
+    (air, public_input, proof)
+}
+
+#[test]
+fn bound_proofs_round_trip() {
+    let (air, public_input, proof) = statement();
+    let options = ProofOptions::default();
+    assert!(proof.binding.is_some());
+
+    let bytes = encoding::encode_proof(&proof);
+    assert_eq!(encoding::proof_version(&bytes), Ok(3));
+    let decoded = encoding::decode_proof(&bytes).unwrap();
+    assert_eq!(decoded.binding, proof.binding);
+    assert_eq!(verifier::verify(&air, public_input.clone(), &decoded, &options), Ok(()));
+
+    let compact = encoding::decode_proof_compact(&encoding::encode_proof_compact(&proof)).unwrap();
+    assert_eq!(compact.binding, proof.binding);
+    assert_eq!(verifier::verify(&air, public_input, &compact, &options), Ok(()));
+}
+
+#[test]
+fn other_bindings_are_rejected() {
+    let (air, public_input, proof) = statement();
+    let mut tampered = proof.clone();
+    tampered.binding.as_mut().unwrap()[0] ^= 1;
+    assert_eq!(
+        verifier::verify(&air, public_input, &tampered, &ProofOptions::default()),
+        Err(VerifyError::ProofBinding)
+    );
+}
+
+#[test]
+fn unbound_proofs_keep_the_previous_format() {
+    let (air, public_input, mut proof) = statement();
+    proof.binding = None;
+
+    let options = ProofOptions::default();
+    let bytes = encoding::encode_proof(&proof);
+    assert_eq!(encoding::proof_version(&bytes), Ok(2));
+    assert_eq!(legacy::verify_encoded(&air, public_input.clone(), &bytes, DecodeMode::Lenient, &options), Ok(()));
+    assert_eq!(legacy::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+
+    let compact = encoding::encode_proof_compact(&proof);
+    assert_eq!(encoding::decode_proof_compact(&compact).map(|decoded| decoded.binding), Ok(None));
+}
+
+#[test]
+fn stripped_bindings_are_rejected() {
+    let (air, public_input, proof) = statement();
+    let options = ProofOptions::default();
+    let mut stripped = proof.clone();
+    stripped.binding = None;
+
+    // a proof re-encoded without its binding is not read as a current one
+    let bytes = encoding::encode_proof(&stripped);
+    assert_eq!(encoding::decode_proof(&bytes).map(|_| ()), Err(DecodeError::Version(2)));
+    assert_eq!(
+        verifier::verify_encoded(&air, public_input.clone(), &bytes, DecodeMode::Lenient, &options),
+        Err(VerifyError::Decode(DecodeError::Version(2)))
+    );
+    assert_eq!(verifier::verify(&air, public_input.clone(), &stripped, &options), Err(VerifyError::ProofBinding));
+
+    // the legacy entry points check a binding when there is one
+    let mut tampered = proof.clone();
+    tampered.binding.as_mut().unwrap()[0] ^= 1;
+    assert_eq!(legacy::verify(&air, public_input.clone(), &tampered, &options), Err(VerifyError::ProofBinding));
+    let bytes = encoding::encode_proof(&proof);
+    assert_eq!(legacy::verify_encoded(&air, public_input, &bytes, DecodeMode::Lenient, &options), Ok(()));
+}
@@ -0,0 +1,38 @@
+// proofs and public inputs encoded with borsh
+use borsh::BorshDeserialize;
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+use stark101::common::{ProofOptions, PublicInput, StarkProof};
+use stark101::{encoding, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+
+#[test]
+fn borsh_proofs_decode_to_the_same_proof() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let proof_bytes = borsh::to_vec(&proof).unwrap();
+    let public_input_bytes = borsh::to_vec(&public_input).unwrap();
+    let decoded = StarkProof::<F>::try_from_slice(&proof_bytes).unwrap();
+    let decoded_public_input = PublicInput::<F>::try_from_slice(&public_input_bytes).unwrap();
+    assert_eq!(encoding::encode_proof(&decoded), encoding::encode_proof(&proof));
+    assert_eq!(encoding::encode_public_input(&decoded_public_input), encoding::encode_public_input(&public_input));
+    assert_eq!(verifier::verify(&air, decoded_public_input, &decoded, &options), Ok(()));
+
+    // the field elements have the big endian bytes of the other encoders
+    assert_eq!(public_input_bytes[32 + 12..32 + 12 + 32], air.seed.to_bytes_be()[..]);
+    assert_eq!(*public_input_bytes.last().unwrap(), 0);
+    assert_eq!(&proof_bytes[..32], &proof.trace_commitment.root);
+
+    // and are canonical
+    let mut non_canonical = public_input_bytes.clone();
+    non_canonical[32 + 12..32 + 12 + 32].copy_from_slice(&[0xff; 32]);
+    assert!(PublicInput::<F>::try_from_slice(&non_canonical).is_err());
+    assert!(StarkProof::<F>::try_from_slice(&proof_bytes[..proof_bytes.len() - 1]).is_err());
+}
@@ -0,0 +1,46 @@
+// intermediate values of a trace claimed in one proof
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::air::{self, Air, BoundaryClaims, BoundaryConstraint, HashChain};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::malicious::{self, Attack};
+use stark101::{prover, verifier};
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the shared hash chain with claims on its rows at the given steps
+fn statement(steps: &[usize]) -> (BoundaryClaims<F, HashChain<F>>, Vec<FE>, PublicInput<F>) {
+    let (inner, trace, public_input) = common::statement();
+    let claims = steps
+        .iter()
+        .map(|step| BoundaryConstraint { step: *step, value: trace[*step] })
+        .collect::<Vec<BoundaryConstraint<F>>>();
+    (BoundaryClaims { inner, claims }, trace, public_input)
+}
+
+#[test]
+fn claimed_values_are_proven_together() {
+    let (air, trace, public_input) = statement(&[10, 30, 50]);
+    assert_eq!(air.boundary_constraints().constraints.len(), 5);
+    assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+
+    // the claims are part of the statement, a proof does not hold for fewer
+    let (fewer, ..) = statement(&[10, 30]);
+    assert!(verifier::verify(&fewer, public_input, &proof, &options).is_err());
+}
+
+#[test]
+fn a_false_claim_is_rejected() {
+    let (mut air, trace, public_input) = statement(&[10, 30, 50]);
+    air.claims[1].value += FE::one();
+    let options = ProofOptions::default();
+    let proof = malicious::prove_dishonestly(&air, &trace, public_input.clone(), &options, Attack::ForgedBoundary);
+    assert!(matches!(verifier::verify(&air, public_input, &proof, &options), Err(verifier::VerifyError::Fri(_))));
+}
@@ -0,0 +1,86 @@
+// helpers shared by the tests, each test using some of them
+#![allow(dead_code)]
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::{Air, BoundaryConstraint, BoundaryConstraints, HashChain, TransitionConstraint};
+use stark101::common::PublicInput;
+use stark101::expr::Expr;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// small deterministic generator of the randomized tests, so that a
+// failing case is reproduced from its seed
+pub struct SplitMix64(pub u64);
+
+impl SplitMix64 {
+
+    pub fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    pub fn below(&mut self, bound: usize) -> usize {
+        (self.next() % bound as u64) as usize
+    }
+}
+
+// the hash chain of 64 rows most tests prove, with its trace
+pub fn statement() -> (HashChain<F>, Vec<FE>, PublicInput<F>) {
+    seeded_statement(3141592)
+}
+
+pub fn seeded_statement(seed: u64) -> (HashChain<F>, Vec<FE>, PublicInput<F>) {
+    sized_statement(seed, 6)
+}
+
+// the hash chain of 2^steps_log2 rows, with a blowup of 8 and 8 queries
+pub fn sized_statement(seed: u64, steps_log2: usize) -> (HashChain<F>, Vec<FE>, PublicInput<F>) {
+    let (seed, trace_length) = (FE::from(seed), 1 << steps_log2);
+    let trace = HashChain::trace(&seed, trace_length);
+    let digest = trace[trace_length - 1];
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, steps_log2, steps_log2 + 3, 8, seed, digest, None);
+    (HashChain { trace_length, seed, digest }, trace, public_input)
+}
+
+// the fibonacci square written backwards, a(n) = a(n-1)^2 + a(n-2)^2,
+// from the first element to the one of step 62
+pub struct BackwardFibonacci {
+    pub trace_length: usize,
+    pub first: FE,
+    pub last: FE,
+}
+
+impl Air<F> for BackwardFibonacci {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "backward-fibonacci".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        BoundaryConstraints::new(vec![
+            BoundaryConstraint { step: 0, value: self.first },
+            BoundaryConstraint { step: self.trace_length - 2, value: self.last },
+        ])
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        let n = self.trace_length;
+        vec![
+            TransitionConstraint::from_expr(&[0, 1, n - 1], Expr::row(2) - Expr::next(0).square() - Expr::col(0).square())
+                .starting_at(2)
+        ]
+    }
+}
@@ -0,0 +1,76 @@
+// the composition the verifier computes at a point, from the trace and
+// the constraints of the air, is the one the prover committed to, over
+// the lde domain and at any point out of it
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField},
+    traits::IsFFTField
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::air::{Air, FibonacciSquare};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::composition::{self, PointValues};
+use stark101::domain::EvaluationDomain;
+use stark101::key::ProvingKey;
+use stark101::pipeline::{self, Composition};
+use stark101::witness::Witness;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const TRACE_LENGTH: usize = 1024;
+const EVAL_TWO_POWER: usize = 12;
+
+fn statement() -> (FibonacciSquare<F>, Polynomial<FE>, Composition) {
+    let witness = Witness::<F>::example();
+    let fib_squared_0 = FE::one();
+    let air = FibonacciSquare { trace_length: TRACE_LENGTH, fib_squared_0, fib_squared_1022: FibonacciSquare::fib_squared_1022(&fib_squared_0, witness.value()) };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, EVAL_TWO_POWER, 4, fib_squared_0, air.fib_squared_1022, None);
+    let key = ProvingKey::new(&air, EVAL_TWO_POWER);
+    let options = ProofOptions::default();
+    let mut transcript = pipeline::start_transcript(&air, &public_input, &options);
+    let trace_poly = pipeline::interpolate_trace(&key, &pipeline::build_trace(&witness, TRACE_LENGTH));
+    let trace_lde = pipeline::extend_trace(&key, &trace_poly);
+    pipeline::commit_evaluations(&key, &trace_lde, &options, &mut transcript);
+    let composition = pipeline::build_composition(&key, &air, &trace_lde, &mut transcript);
+    (air, trace_poly, composition)
+}
+
+// the composition at x from the trace polynomial, as the verifier
+// computes it from the openings of the trace
+fn composition_at(air: &FibonacciSquare<F>, trace_poly: &Polynomial<FE>, composition: &Composition, x: &FE) -> FE {
+    let g = F::get_primitive_root_of_unity(TRACE_LENGTH.trailing_zeros() as u64).unwrap();
+    let boundary = air.boundary_constraints();
+    let transitions = air.transition_constraints();
+    let zerofier_inv = (x.pow(TRACE_LENGTH) - FE::one()).inv().unwrap();
+    let values = PointValues {
+        row: |k: usize| trace_poly.evaluate(&(g.pow(k) * x)),
+        boundary_interpolant: boundary.interpolant(&g).evaluate(x),
+        boundary_zerofier_inv: boundary.evaluate_zerofier(&g, x).inv().unwrap(),
+        transition_factor: |j: usize| transitions[j].evaluate_exemptions(&g, x) * zerofier_inv,
+    };
+    composition::evaluate_at(&transitions, TRACE_LENGTH, values, &composition.boundary_coefficient, &composition.transition_coefficients)
+}
+
+#[test]
+fn the_composition_at_the_lde_points_is_the_committed_one() {
+    let (air, trace_poly, composition) = statement();
+    let offset = FE::from(2_u64);
+    let w = F::get_primitive_root_of_unity(EVAL_TWO_POWER as u64).unwrap();
+    for i in [0, 1, 7, 1000, 4095] {
+        let x = offset * w.pow(i);
+        assert_eq!(composition_at(&air, &trace_poly, &composition, &x), composition.evaluations[i], "point {}", i);
+    }
+}
+
+#[test]
+fn the_composition_out_of_the_lde_domain_is_the_committed_polynomial() {
+    let (air, trace_poly, composition) = statement();
+    let lde_domain = EvaluationDomain::new(1 << EVAL_TWO_POWER, &FE::from(2_u64));
+    let composition_poly = lde_domain.interpolate(&composition.evaluations);
+    for x in [FE::from(3_u64), FE::from(123456789_u64), -FE::from(5_u64)] {
+        assert_eq!(composition_at(&air, &trace_poly, &composition, &x), composition_poly.evaluate(&x));
+    }
+}
@@ -0,0 +1,91 @@
+// one proof of two statements, of traces of different lengths
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::air::HashChain;
+use stark101::common::{ProofOptions, PublicInput, PublicInputError};
+use stark101::conjunction::{self, ConjunctionProof};
+use stark101::fri::FriError;
+use stark101::verifier::VerifyError;
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+struct Statement {
+    air: HashChain<F>,
+    trace: Vec<FE>,
+    public_input: PublicInput<F>,
+}
+
+fn statement(seed: u64, interp_two_power: usize, eval_two_power: usize) -> Statement {
+    let (air, trace, mut public_input) = common::sized_statement(seed, interp_two_power);
+    public_input.2 = eval_two_power;
+    Statement { air, trace, public_input }
+}
+
+fn prove(first: &Statement, second: &Statement, options: &ProofOptions) -> ConjunctionProof<F> {
+    conjunction::prove_conjunction(
+        (&first.air, &first.trace, first.public_input.clone()),
+        (&second.air, &second.trace, second.public_input.clone()),
+        options
+    )
+}
+
+fn verify(first: &Statement, second: &Statement, proof: &ConjunctionProof<F>, options: &ProofOptions) -> Result<(), VerifyError> {
+    conjunction::verify_conjunction(
+        (&first.air, first.public_input.clone()),
+        (&second.air, second.public_input.clone()),
+        proof,
+        options
+    )
+}
+
+#[test]
+fn conjunctions_of_valid_statements_verify() {
+    let options = ProofOptions::default();
+    let first = statement(3141592, 6, 9);
+    let second = statement(2718281, 7, 9);
+    let proof = prove(&first, &second, &options);
+    assert_eq!(verify(&first, &second, &proof, &options), Ok(()));
+
+    // the statements are bound in order
+    assert!(verify(&second, &first, &proof, &options).is_err());
+}
+
+#[test]
+fn conjunctions_with_a_false_statement_are_rejected() {
+    let options = ProofOptions::default();
+    let first = statement(3141592, 6, 9);
+    let second = statement(2718281, 7, 9);
+    let proof = prove(&first, &second, &options);
+
+    // another claimed digest of the second chain
+    let mut claimed = statement(2718281, 7, 9);
+    claimed.air.digest += FE::one();
+    claimed.public_input.5 = claimed.air.digest;
+    assert!(verify(&first, &claimed, &proof, &options).is_err());
+
+    // an opening of the second trace
+    let mut tampered = proof.clone();
+    tampered.trace_commitments[1].inclusion_proofs[0].0 += FE::one();
+    assert!(matches!(verify(&first, &second, &tampered, &options), Err(VerifyError::TraceInclusion { .. })));
+
+    let mut tampered = proof.clone();
+    tampered.composition_commitment.clear();
+    assert!(matches!(verify(&first, &second, &tampered, &options), Err(VerifyError::Fri(FriError::LayerCount { received: 0, .. }))));
+}
+
+#[test]
+fn statements_over_different_lde_domains_are_rejected() {
+    let options = ProofOptions::default();
+    let first = statement(3141592, 6, 9);
+    let second = statement(2718281, 7, 9);
+    let proof = prove(&first, &second, &options);
+
+    let larger = statement(2718281, 7, 10);
+    assert_eq!(
+        verify(&first, &larger, &proof, &options),
+        Err(VerifyError::PublicInput(PublicInputError::Conjunction))
+    );
+}
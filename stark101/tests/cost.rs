@@ -0,0 +1,58 @@
+// the estimated cost of a proof bounds the one of the proofs generated
+use stark101::common::ProofOptions;
+use stark101::cost;
+use stark101::fri::QuerySampling;
+use stark101::{encoding, prover};
+
+mod common;
+
+use common::sized_statement;
+
+#[test]
+fn the_estimated_proof_size_bounds_the_proof() {
+    for (steps_log2, query_sampling) in [(6, QuerySampling::Folded), (8, QuerySampling::PerLayer), (10, QuerySampling::Folded)] {
+        let (air, trace, mut public_input) = sized_statement(3141592, steps_log2);
+        public_input.3 = 16;
+        let options = ProofOptions { query_sampling, ..Default::default() };
+
+        let estimate = cost::estimate_verifier_cost(&air, &public_input, &options);
+        let proof = prover::prove(&air, &trace, public_input, &options);
+        assert_eq!(estimate.fri_layers, proof.composition_commitment.len());
+        assert!(estimate.trace_openings >= proof.trace_commitment.inclusion_proofs.len());
+
+        // exact once the rows opened for several queries are counted once
+        let shared_rows = estimate.trace_openings - proof.trace_commitment.inclusion_proofs.len();
+        let row_bytes = 32 + 4 + 32 * (steps_log2 + 3);
+        assert_eq!(estimate.proof_bytes - shared_rows * row_bytes, encoding::encode_proof(&proof).len());
+    }
+}
+
+#[test]
+fn the_size_report_adds_up_to_the_estimated_size() {
+    let (air, _, mut public_input) = sized_statement(3141592, 8);
+    public_input.3 = 16;
+
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        let estimate = cost::estimate_verifier_cost(&air, &public_input, &options);
+        let report = cost::proof_size_report(&air, &public_input, &options);
+        assert_eq!(report.proof_bytes(), estimate.proof_bytes);
+        assert_eq!(report.layers.len(), estimate.fri_layers);
+        assert_eq!(report.layers.iter().map(|layer| layer.openings).sum::<usize>(), estimate.fri_openings);
+
+        // the other sampling is the size of its own estimate
+        let other = match query_sampling {
+            QuerySampling::Folded => QuerySampling::PerLayer,
+            QuerySampling::PerLayer => QuerySampling::Folded,
+        };
+        let other_estimate = cost::estimate_verifier_cost(&air, &public_input, &ProofOptions { query_sampling: other, ..Default::default() });
+        assert_eq!(report.alternatives[0], (cost::Alternative::QuerySampling(other), other_estimate.proof_bytes));
+
+        // fewer paths, shorter paths and fewer layers shrink the proof
+        let size = |alternative| report.alternatives.iter().find(|(a, _)| *a == alternative).unwrap().1;
+        assert!(size(cost::Alternative::SiblingLeaves) < report.proof_bytes());
+        assert!(size(cost::Alternative::FoldingFactor(2)) < size(cost::Alternative::SiblingLeaves));
+        assert!(size(cost::Alternative::MerkleCap(4)) < size(cost::Alternative::MerkleCap(2)));
+        assert!(size(cost::Alternative::MerkleCap(2)) < report.proof_bytes());
+    }
+}
@@ -0,0 +1,55 @@
+// the constant-time arithmetic of the prover gives the same values
+// as the one of lambdaworks
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::common::ProofOptions;
+use stark101::ct;
+use stark101::domain::EvaluationDomain;
+use stark101::{prover, verifier};
+
+mod common;
+use common::{statement, SplitMix64};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn random_element(rng: &mut SplitMix64) -> FE {
+    // a random element of the field, from four random limbs below 2^256
+    (0..4).fold(FE::zero(), |element, _| element * FE::from(u64::MAX) + FE::from(rng.next()))
+}
+
+#[test]
+fn constant_time_arithmetic_matches_lambdaworks() {
+    let mut rng = SplitMix64(683);
+    let mut elements = vec![FE::zero(), FE::one(), -FE::one(), -FE::from(2_u64), FE::from(u64::MAX)];
+    elements.extend((0..32).map(|_| random_element(&mut rng)));
+
+    for a in &elements {
+        assert_eq!(ct::square(a), a.square());
+        for b in &elements {
+            assert_eq!(ct::add(a, b), a + b);
+            assert_eq!(ct::sub(a, b), a - b);
+            assert_eq!(ct::mul(a, b), a * b);
+            assert_eq!(ct::select(true, a, b), *a);
+            assert_eq!(ct::select(false, a, b), *b);
+        }
+    }
+    for value in [0, 1, 1 << 16, u64::MAX, rng.next()] {
+        assert_eq!(ct::from_u64(value), FE::from(value));
+    }
+
+    let domain = EvaluationDomain::new(64, &FE::from(2_u64));
+    let poly = Polynomial::new(&(0..1 + rng.below(64)).map(|_| random_element(&mut rng)).collect::<Vec<FE>>());
+    let evals = ct::evaluate(&domain, &poly);
+    assert_eq!(evals, domain.evaluate(&poly));
+    assert_eq!(ct::interpolate(&domain, &evals), domain.interpolate(&evals));
+}
+
+#[test]
+fn constant_time_proofs_verify() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(verifier::verify(&air, public_input, &proof, &options), Ok(()));
+}
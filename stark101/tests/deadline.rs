@@ -0,0 +1,34 @@
+// verification given up once a deadline has passed
+use std::time::Duration;
+
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::common::ProofOptions;
+use stark101::prover;
+use stark101::report::Phase;
+use stark101::verifier::{self, VerifyError};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn verification_stops_at_the_deadline() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let in_time = Duration::from_secs(3600);
+    assert_eq!(verifier::verify_with_deadline(&air, public_input.clone(), &proof, &options, in_time), Ok(()));
+    // a deadline already passed stops verification after the first phase
+    let expired = verifier::verify_with_deadline(&air, public_input.clone(), &proof, &options, Duration::ZERO);
+    assert_eq!(expired, Err(VerifyError::Timeout { phase: Phase::PublicInput }));
+
+    // an invalid proof verified in time is rejected as usual
+    let mut tampered = proof.clone();
+    tampered.trace_commitment.inclusion_proofs[0].0 += FE::one();
+    assert!(matches!(verifier::verify_with_deadline(&air, public_input, &tampered, &options, in_time), Err(VerifyError::TraceInclusion { .. })));
+}
@@ -0,0 +1,73 @@
+// the degree bound of every fri layer, halved by each folding down to
+// the constants of the last layer, as the verifier and the size report
+// give it
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::air::Air;
+use stark101::common::ProofOptions;
+use stark101::fri::{FriError, FriOptions, OffsetSchedule, QuerySampling};
+use stark101::merkle::MerkleBackend;
+use stark101::{cost, fri, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn each_folding_halves_the_degree_bound() {
+    assert_eq!(fri::degree_schedule(8, 64), Ok(vec![8, 4, 2, 1]));
+    // odd bounds round up
+    assert_eq!(fri::degree_schedule(5, 64), Ok(vec![5, 3, 2, 1]));
+    assert_eq!(fri::degree_schedule(1, 64), Ok(vec![1]));
+    let schedule = fri::degree_schedule(2048, 8192).unwrap();
+    assert_eq!(schedule.len(), 12);
+    assert_eq!((schedule[0], schedule[11]), (2048, 1));
+}
+
+#[test]
+fn there_is_no_schedule_of_a_zero_degree_bound_or_a_domain_not_halved() {
+    for (degree_bound, domain_size) in [(0, 64), (8, 0), (8, 1), (8, 48), (8, 63)] {
+        assert_eq!(fri::degree_schedule(degree_bound, domain_size), Err(FriError::Parameters { degree_bound, domain_size }));
+    }
+
+    // nor a low degree proof
+    let offset = FE::from(3_u64);
+    let coefficients = (1..=8_u64).map(FE::from).collect::<Vec<FE>>();
+    let evaluations = Polynomial::evaluate_offset_fft(&Polynomial::new(&coefficients), 1, Some(64), &offset).unwrap();
+    let options = FriOptions { num_queries: 8, query_sampling: QuerySampling::Folded, offset_schedule: OffsetSchedule::Squared, max_layers: None, merkle_backend: MerkleBackend::Keccak256 };
+    assert!(matches!(fri::prove_low_degree(&evaluations, &offset, 0, &options), Err(FriError::Parameters { degree_bound: 0, domain_size: 64 })));
+    assert!(matches!(fri::prove_low_degree(&evaluations[..1], &offset, 8, &options), Err(FriError::Parameters { degree_bound: 8, domain_size: 1 })));
+    let proof = fri::prove_low_degree(&evaluations, &offset, 8, &options).unwrap();
+    assert_eq!(fri::verify_low_degree(&proof, 64, &offset, 8, &options), Ok(()));
+    assert_eq!(fri::verify_low_degree(&proof, 64, &offset, 0, &options), Err(FriError::Parameters { degree_bound: 0, domain_size: 64 }));
+    assert_eq!(fri::verify_low_degree(&proof, 63, &offset, 8, &options), Err(FriError::Parameters { degree_bound: 8, domain_size: 63 }));
+}
+
+#[test]
+fn the_verifier_and_the_size_report_give_the_schedule() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let schedule = fri::degree_schedule(air.composition_degree_bound(), 1 << 9).unwrap();
+
+    // the honest prover checks every layer against it in debug builds
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(proof.composition_commitment.len(), schedule.len());
+
+    let report = verifier::verify_with_report(&air, public_input.clone(), &proof, &options);
+    assert!(report.is_valid());
+    assert_eq!(report.degree_bounds, schedule);
+    assert_eq!(report.degree_bounds.last(), Some(&1));
+
+    let sizes = cost::proof_size_report(&air, &public_input, &options);
+    assert_eq!(
+        sizes.layers
+            .iter()
+            .map(|layer| layer.degree_bound)
+            .collect::<Vec<usize>>(),
+        schedule
+    );
+}
@@ -0,0 +1,176 @@
+// differential test of the transcript against fixtures. each fixture
+// holds a statement and the transcript operations expected when proving
+// it, one per line, so that fixtures produced by another implementation
+// of the same protocol can be checked the same way as the ones recorded
+// from this crate. the first divergence of every fixture is reported.
+//
+// run with `cargo test --features differential --test differential`,
+// setting STARK101_BLESS=1 records new fixtures from this crate for a
+// set of random statements
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air;
+use stark101::channel::{self, TranscriptEvent, TranscriptOp};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::fri::QuerySampling;
+use stark101::{prover, verifier};
+
+mod common;
+use common::SplitMix64;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const RANDOM_STATEMENTS: usize = 8;
+
+// a hash chain statement, as written in the header of its fixture
+#[derive(Debug)]
+struct Statement {
+    seed: u64,
+    steps_log2: usize,
+    blowup_log2: usize,
+    num_queries: usize,
+    query_sampling: QuerySampling,
+}
+
+impl Statement {
+
+    fn random(rng: &mut SplitMix64) -> Self {
+        Self {
+            seed: rng.next(),
+            steps_log2: 3 + rng.below(4),
+            // the composition of the cubic hash chain constraint has about
+            // twice the degree of the trace, fri needs a blowup of four
+            blowup_log2: 2 + rng.below(2),
+            num_queries: 1 + rng.below(8),
+            query_sampling: if rng.below(2) == 0 { QuerySampling::Folded } else { QuerySampling::PerLayer },
+        }
+    }
+
+    fn header(&self) -> String {
+        format!(
+            "# hash-chain seed={} steps_log2={} blowup_log2={} queries={} sampling={}",
+            self.seed,
+            self.steps_log2,
+            self.blowup_log2,
+            self.num_queries,
+            match self.query_sampling {
+                QuerySampling::Folded => "folded",
+                QuerySampling::PerLayer => "per-layer",
+            }
+        )
+    }
+
+    fn parse(header: &str) -> Option<Self> {
+        let mut fields = header.strip_prefix("# hash-chain ")?.split(' ');
+        let mut field = |name: &str| fields.next()?.strip_prefix(name)?.strip_prefix('=').map(str::to_string);
+        Some(Self {
+            seed: field("seed")?.parse().ok()?,
+            steps_log2: field("steps_log2")?.parse().ok()?,
+            blowup_log2: field("blowup_log2")?.parse().ok()?,
+            num_queries: field("queries")?.parse().ok()?,
+            query_sampling: match field("sampling")?.as_str() {
+                "folded" => QuerySampling::Folded,
+                "per-layer" => QuerySampling::PerLayer,
+                _ => return None,
+            },
+        })
+    }
+
+    // proves and verifies the statement, returning the prover transcript
+    // once checked against the verifier one
+    fn transcript(&self) -> Vec<TranscriptEvent> {
+        let trace_length = 1 << self.steps_log2;
+        let seed = FE::from(self.seed);
+        let trace = air::HashChain::trace(&seed, trace_length);
+        let digest = trace[trace_length - 1];
+        let public_input = PublicInput(
+            MontgomeryConfigStark252PrimeField::MODULUS,
+            self.steps_log2,
+            self.steps_log2 + self.blowup_log2,
+            self.num_queries,
+            seed,
+            digest,
+            None,
+        );
+        let air = air::HashChain { trace_length, seed, digest };
+        let options = ProofOptions { query_sampling: self.query_sampling, ..Default::default() };
+
+        let (proof, prover_events) = prover::prove_recorded(&air, &trace, public_input.clone(), &options);
+        let (result, verifier_events) = verifier::verify_recorded(&air, public_input, &proof, &options);
+        channel::assert_transcripts_match(&prover_events, &verifier_events);
+        assert_eq!(result, Ok(()), "{}", self.header());
+        prover_events
+    }
+}
+
+fn fixtures_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/transcripts")
+}
+
+fn parse_event(line: &str) -> Option<TranscriptEvent> {
+    let (label, hex) = line.split_once(' ')?;
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some(TranscriptEvent { op: TranscriptOp::from_label(label)?, bytes })
+}
+
+fn bless() {
+    let dir = fixtures_dir();
+    fs::create_dir_all(&dir).unwrap();
+    let mut rng = SplitMix64(101);
+    for i in 0..RANDOM_STATEMENTS {
+        let statement = Statement::random(&mut rng);
+        let lines = std::iter::once(statement.header())
+            .chain(statement.transcript().iter().map(TranscriptEvent::to_string))
+            .collect::<Vec<String>>();
+        fs::write(dir.join(format!("hash_chain_{}.txt", i)), lines.join("\n") + "\n").unwrap();
+    }
+}
+
+#[test]
+fn transcripts_match_the_fixtures() {
+    if std::env::var_os("STARK101_BLESS").is_some() {
+        bless();
+    }
+
+    let mut fixtures = fs::read_dir(fixtures_dir())
+        .expect("no transcript fixtures, record them with STARK101_BLESS=1")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "txt"))
+        .collect::<Vec<PathBuf>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "no transcript fixtures, record them with STARK101_BLESS=1");
+
+    let divergences = fixtures
+        .iter()
+        .filter_map(|path| {
+            let name = path.file_name().unwrap().to_string_lossy();
+            let text = fs::read_to_string(path).unwrap();
+            let mut lines = text.lines();
+            let statement = Statement::parse(lines.next().unwrap_or_default())
+                .unwrap_or_else(|| panic!("{}: unsupported statement header", name));
+            let expected = lines
+                .enumerate()
+                .map(|(i, line)| parse_event(line).unwrap_or_else(|| panic!("{}: malformed line {}", name, i + 2)))
+                .collect::<Vec<TranscriptEvent>>();
+
+            let actual = statement.transcript();
+            channel::first_divergence(&expected, &actual).map(|i| {
+                let show = |events: &[TranscriptEvent]| events.get(i).map_or("nothing".to_string(), |e| e.to_string());
+                format!("{}: operation {} expected {}, got {}", name, i, show(&expected), show(&actual))
+            })
+        })
+        .collect::<Vec<String>>();
+
+    assert!(divergences.is_empty(), "transcripts diverge from the fixtures:\n{}", divergences.join("\n"));
+}
@@ -0,0 +1,61 @@
+// encodings of proofs and field elements decode back to the same values
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+use lambdaworks_math::traits::ByteConversion;
+
+use stark101::air::HashChain;
+use stark101::common::{self, ElementBytesError, ProofOptions, PublicInput};
+use stark101::encoding::{self, DecodeError};
+use stark101::fri::QuerySampling;
+use stark101::{prover, verifier};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn compact_proofs_decode_to_the_same_proof() {
+    let seed = FE::from(3141592_u64);
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let trace = HashChain::trace(&seed, 256);
+        let digest = trace[255];
+        let air = HashChain { trace_length: 256, seed, digest };
+        let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 8, 11, 16, seed, digest, None);
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+        let compact = encoding::encode_proof_compact(&proof);
+        let decoded = encoding::decode_proof_compact(&compact).unwrap();
+        assert_eq!(encoding::encode_proof(&decoded), encoding::encode_proof(&proof));
+        assert_eq!(verifier::verify(&air, public_input, &decoded, &options), Ok(()));
+        // the shared nodes of the paths are sent once
+        assert!(compact.len() < encoding::encode_proof(&proof).len() * 3 / 4);
+
+        assert!(matches!(encoding::decode_proof_compact(&compact[..compact.len() - 1]), Err(DecodeError::UnexpectedEnd { .. })));
+        assert_eq!(encoding::decode_proof(&compact).map(|_| ()), Err(DecodeError::Magic));
+    }
+}
+
+#[test]
+fn field_elements_have_a_single_encoding() {
+    let element = FE::from(0x0102_u64);
+    let be = common::fe_to_bytes_be(&element);
+    let le = common::fe_to_bytes_le(&element);
+    assert_eq!(&be[30..], &[1, 2]);
+    assert_eq!(be.iter().rev().copied().collect::<Vec<u8>>(), le);
+    assert_eq!(common::fe_from_bytes_be::<F>(&be), Ok(element));
+    assert_eq!(common::fe_from_bytes_le::<F>(&le), Ok(element));
+    assert_eq!(common::fe_from_hex::<F>("0x102"), Ok(element));
+    assert_eq!(common::fe_from_hex::<F>("0102"), Ok(element));
+
+    // the modulus is zero, but only the bytes of zero decode to it
+    let modulus = MontgomeryConfigStark252PrimeField::MODULUS.to_bytes_be();
+    assert_eq!(common::fe_from_bytes_be::<F>(&modulus), Err(ElementBytesError::NonCanonical));
+    assert_eq!(common::fe_from_hex::<F>("800000000000011000000000000000000000000000000000000000000000001"), Err(ElementBytesError::NonCanonical));
+    assert_eq!(common::fe_from_hex::<F>("800000000000011000000000000000000000000000000000000000000000000"), Ok(-FE::one()));
+    assert_eq!(common::fe_from_bytes_be::<F>(&be[1..]), Err(ElementBytesError::Length { expected: 32, found: 31 }));
+    assert_eq!(common::fe_from_hex::<F>("0x"), Err(ElementBytesError::Length { expected: 64, found: 0 }));
+    assert_eq!(common::fe_from_hex::<F>("12g4"), Err(ElementBytesError::Hex));
+}
@@ -0,0 +1,69 @@
+// proofs sealed in a self-describing cbor envelope
+use stark101::common::ProofOptions;
+use stark101::encoding::DecodeMode;
+use stark101::envelope::{self, Envelope, EnvelopeError, ProofMetadata};
+use stark101::fri::QuerySampling;
+use stark101::verifier::VerifyError;
+use stark101::{encoding, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+#[test]
+fn sealed_proofs_are_recognized_and_opened() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let sealed = envelope::seal(&proof, &options);
+    // self described cbor, a map of 4 items and the protocol
+    assert_eq!(&sealed[..14], b"\xd9\xd9\xf7\xa4\x01\x68stark101");
+
+    let envelope = Envelope::parse(&sealed).unwrap();
+    assert_eq!(envelope.payload, &encoding::encode_proof(&proof)[..]);
+    let opened = envelope.proof(&options).unwrap();
+    assert_eq!(verifier::verify(&air, public_input, &opened, &options), Ok(()));
+
+    let per_layer = ProofOptions { query_sampling: QuerySampling::PerLayer, ..Default::default() };
+    assert_eq!(envelope.proof(&per_layer).map(|_| ()), Err(EnvelopeError::Options));
+    assert_eq!(Envelope::parse(&sealed[..sealed.len() - 1]).map(|_| ()), Err(EnvelopeError::Malformed { offset: 52 }));
+
+    let mut other = sealed.clone();
+    other[6..14].copy_from_slice(b"stark102");
+    assert_eq!(Envelope::parse(&other).map(|_| ()), Err(EnvelopeError::Protocol("stark102".to_string())));
+}
+
+#[test]
+fn metadata_is_reported_but_not_checked() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let metadata = ProofMetadata::new(&options, "nightly build");
+    assert_eq!(metadata.prover_version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(metadata.options_digest, envelope::options_digest(&options));
+    let sealed = envelope::seal_with_metadata(&proof, &options, &metadata);
+    // a map of 5 items, recognized as an envelope all the same
+    assert_eq!(sealed[3], 0xa5);
+    assert!(envelope::is_envelope(&sealed));
+    assert!(!envelope::is_envelope(&encoding::encode_proof(&proof)));
+
+    let envelope = Envelope::parse(&sealed).unwrap();
+    assert_eq!(envelope.metadata.as_ref(), Some(&metadata));
+    let report = envelope.verify_with_report(&air, public_input.clone(), DecodeMode::Lenient, &options);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.metadata, Some(metadata.clone()));
+
+    // other metadata, e.g. a forged tag, verifies the same
+    let forged = ProofMetadata { tag: "release build".to_string(), created_at: 0, ..metadata };
+    let resealed = envelope::seal_with_metadata(&proof, &options, &forged);
+    let report = Envelope::parse(&resealed).unwrap().verify_with_report(&air, public_input.clone(), DecodeMode::Lenient, &options);
+    assert_eq!(report.result, Ok(()));
+    assert_eq!(report.metadata, Some(forged));
+
+    let per_layer = ProofOptions { query_sampling: QuerySampling::PerLayer, ..Default::default() };
+    let report = envelope.verify_with_report(&air, public_input, DecodeMode::Lenient, &per_layer);
+    assert_eq!(report.result, Err(VerifyError::Envelope(EnvelopeError::Options)));
+    assert!(report.metadata.is_some());
+}
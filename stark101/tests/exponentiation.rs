@@ -0,0 +1,78 @@
+// the exponentiation statement accepts the bases of its power only
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+
+use stark101::air::{self, Air};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::exponentiation::{ExponentiationError, ExponentiationInput};
+use stark101::malicious::{self, Attack};
+use stark101::{encoding, prover, verifier};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// 13 bits, the 53 rows of the trace padded to 64
+const EXPONENT: u64 = 0b1_0110_0111_0101;
+
+fn input(base: &FE) -> ExponentiationInput {
+    ExponentiationInput { exponent: EXPONENT, power: base.pow(EXPONENT), blowup_log2: 3, num_queries: 8 }
+}
+
+#[test]
+fn the_power_of_the_base_is_proven() {
+    let base = FE::from(3141592_u64);
+    let input = input(&base);
+    let air = input.air();
+    let trace = input.trace(&base).unwrap();
+    assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, input.public_input(), &options);
+    assert_eq!(verifier::verify(&air, input.public_input(), &proof, &options), Ok(()));
+}
+
+#[test]
+fn a_non_boolean_bit_is_rejected() {
+    let base = FE::from(3141592_u64);
+    let input = input(&base);
+    assert_eq!(input.trace(&FE::from(2_u64)), Err(ExponentiationError::NotTheBase));
+
+    // the first two bits 1, 0 replaced by 0, 2, which recompose the same
+    // exponent but multiply the power by 2 * base - 1. the trace of the
+    // powers from these bits breaks the boolean constraint only, for a
+    // power other than the one of the base
+    let mut trace = input.trace(&base).unwrap();
+    trace[1] = FE::zero();
+    trace[3] = FE::zero();
+    trace[5] = FE::from(2_u64);
+    let steps = input.steps();
+    for step in 0..steps {
+        trace[4 * step + 4] = trace[4 * step].square() * (trace[4 * step + 1] * (base - FE::one()) + FE::one());
+    }
+    let input = ExponentiationInput { power: trace[4 * steps], ..input };
+    assert_ne!(input.power, base.pow(EXPONENT));
+
+    let air = input.air();
+    assert_eq!(
+        air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()),
+        Err(air::TraceError::Transition { constraint: 1, step: 5 })
+    );
+    let options = ProofOptions::default();
+    // the boundary holds, the attack only proves the trace as is
+    let proof = malicious::prove_dishonestly(&air, &trace, input.public_input(), &options, Attack::ForgedBoundary);
+    assert!(matches!(verifier::verify(&air, input.public_input(), &proof, &options), Err(verifier::VerifyError::Fri(_))));
+}
+
+#[test]
+fn the_public_input_round_trips() {
+    let input = input(&FE::from(5_u64));
+    let decoded = encoding::decode_public_input(&encoding::encode_public_input(&input.public_input())).unwrap();
+    assert_eq!(ExponentiationInput::from_public_input(&decoded), Ok(input));
+
+    // an exponent of 4 bits has a trace of 32 rows
+    let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, power, _, _) = decoded;
+    let other = PublicInput(modulus, interp_two_power, eval_two_power, num_queries, power, FE::from(9_u64), None);
+    assert_eq!(ExponentiationInput::from_public_input(&other), Err(ExponentiationError::NotAnExponentiation));
+}
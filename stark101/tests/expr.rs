@@ -0,0 +1,53 @@
+// constraints written as expressions read the rows, and have the
+// degree, of the closures they replace, and evaluate as them
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::air::{self, Air, TransitionConstraint};
+use stark101::common::ProofOptions;
+use stark101::expr::Expr;
+use stark101::{prover, verifier};
+
+mod common;
+
+use common::seeded_statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn an_expression_gives_the_frame_and_degree_of_the_constraint() {
+    let expr = Expr::row(4) - Expr::col(0).square() * (Expr::next(0) * (Expr::row(2) - FE::one()) + FE::one());
+    assert_eq!(expr.offsets(), vec![0, 1, 2, 4]);
+    assert_eq!(expr.degree(), 4);
+
+    let constraint = TransitionConstraint::from_expr(&[7, 8], expr);
+    assert_eq!(constraint.frame_size, 5);
+    assert_eq!(constraint.offsets, vec![0, 1, 2, 4]);
+    assert_eq!(constraint.degree, 4);
+    assert_eq!(constraint.exemptions, vec![7, 8]);
+
+    let fibonacci = air::fibonacci_square_transition::<F>(1024);
+    assert_eq!((fibonacci.frame_size, fibonacci.offsets.clone(), fibonacci.degree), (3, vec![0, 1, 2], 2));
+
+    for i in 0..16_u64 {
+        let frame = (0..3)
+            .map(|k| FE::from(i * i * 7919 + k * 104729 + 1).pow(5_u64))
+            .collect::<Vec<FE>>();
+        assert_eq!((fibonacci.evaluate)(&frame), frame[2] - frame[1].square() - frame[0].square());
+
+        let negated = -(Expr::next(0) - Expr::constant(FE::from(3_u64))).pow(5);
+        assert_eq!(negated.evaluate(&frame), -(frame[1] - FE::from(3_u64)).pow(5_u64));
+    }
+}
+
+#[test]
+fn statements_of_expression_constraints_prove_and_verify() {
+    let (air, trace, public_input) = seeded_statement(5);
+    let transitions = air.transition_constraints();
+    assert_eq!((transitions[0].frame_size, transitions[0].degree), (2, 3));
+    assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &transitions), Ok(()));
+
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(verifier::verify(&air, public_input, &proof, &options), Ok(()));
+}
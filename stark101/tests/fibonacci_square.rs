@@ -0,0 +1,49 @@
+// the public input of the fibonacci square statement built from a
+// witness candidate
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::FibonacciSquare;
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::{prover, verifier};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn the_claimed_element_is_the_one_of_the_trace() {
+    let fib_squared_0 = FE::one();
+    // the statement of the tutorial
+    assert_eq!(
+        FibonacciSquare::fib_squared_1022(&fib_squared_0, &FE::from(3141592_u64)),
+        FE::from_hex_unchecked("6A317721EF632FF24FB815C9BBD4D4582BC7E21A43CFBDD89A8B8F0BDA68252")
+    );
+    for witness in [FE::zero(), FE::from(2_u64), -FE::one()] {
+        let trace = FibonacciSquare::trace(&fib_squared_0, &witness, 1024);
+        assert_eq!(FibonacciSquare::fib_squared_1022(&fib_squared_0, &witness), trace[1022]);
+    }
+}
+
+#[test]
+fn a_public_input_built_from_a_witness_is_proven() {
+    let fib_squared_0 = FE::from(5_u64);
+    let witness = FE::from(271828_u64);
+    let fib_squared_1022 = FibonacciSquare::fib_squared_1022(&fib_squared_0, &witness);
+    let air = FibonacciSquare { trace_length: 1024, fib_squared_0, fib_squared_1022 };
+    let trace = FibonacciSquare::trace(&fib_squared_0, &witness, 1024);
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, 12, 8, fib_squared_0, fib_squared_1022, None);
+    let options = ProofOptions::default();
+
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert!(verifier::verify(&air, public_input.clone(), &proof, &options).is_ok());
+
+    // the statement and the proof are only borrowed, and can be checked again
+    assert_eq!(verifier::verify_proof(&public_input, &proof, &options), Ok(()));
+    let mut tampered = proof.clone();
+    tampered.trace_commitment.root[0] ^= 1;
+    assert!(!verifier::verify_proof_with_report(&public_input, &tampered, &options).is_valid());
+    assert_eq!(verifier::verify_proof(&public_input, &proof, &options), Ok(()));
+}
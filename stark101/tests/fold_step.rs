@@ -0,0 +1,81 @@
+// the folding of a layer checked at a single point, as an external
+// verifier would, against the folding of the coefficients
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    traits::IsFFTField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::fri;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn polynomial(degree_bound: u64) -> Polynomial<FE> {
+    Polynomial::new(
+        &(0..degree_bound)
+            .map(|i| FE::from(i * i + 7))
+            .collect::<Vec<FE>>()
+    )
+}
+
+// sum of beta^j f_j(y), f being the sum of x^j f_j(x^r)
+fn folded(p: &Polynomial<FE>, r: usize, beta: &FE, y: &FE) -> FE {
+    (0..r)
+        .map(|j| {
+            let part = p.coefficients()
+                .iter()
+                .skip(j)
+                .step_by(r)
+                .cloned()
+                .collect::<Vec<FE>>();
+            beta.pow(j) * Polynomial::new(&part).evaluate(y)
+        })
+        .fold(FE::zero(), |sum, term| sum + term)
+}
+
+fn coset_evals(p: &Polynomial<FE>, x: &FE, r: usize) -> Vec<FE> {
+    let z = F::get_primitive_root_of_unity(r.trailing_zeros() as u64).unwrap();
+    (0..r)
+        .map(|j| p.evaluate(&(x * z.pow(j))))
+        .collect::<Vec<FE>>()
+}
+
+#[test]
+fn a_step_folds_f_x_and_f_minus_x() {
+    let p = polynomial(16);
+    let x = FE::from(5_u64);
+    let beta = FE::from(1234567_u64);
+    let expected = folded(&p, 2, &beta, &x.square());
+
+    let evals = [p.evaluate(&x), p.evaluate(&-&x)];
+    assert_eq!(fri::fold_step(&x, &evals, &beta), fri::curr_layer_query_evals(&x, &evals[0], &evals[1], &beta));
+    assert!(fri::verify_fold_step(&x, &evals, &beta, &expected));
+    assert!(!fri::verify_fold_step(&x, &evals, &beta, &(expected + FE::one())));
+    assert!(!fri::verify_fold_step(&x, &evals, &(beta + FE::one()), &expected));
+}
+
+#[test]
+fn a_step_by_a_larger_factor_is_the_foldings_by_two() {
+    let p = polynomial(64);
+    let x = FE::from(11_u64);
+    let beta = FE::from(987654321_u64);
+    for r in [4, 8, 16] {
+        let evals = coset_evals(&p, &x, r);
+        let expected = folded(&p, r, &beta, &x.pow(r));
+        assert!(fri::verify_fold_step(&x, &evals, &beta, &expected), "folding by {}", r);
+        assert!(!fri::verify_fold_step(&x, &evals, &beta, &(expected + FE::one())), "folding by {}", r);
+
+        // the points z^j x and z^(j + r/2) x fold to the coset of x^2,
+        // folded again with beta^2 by r/2
+        let z = F::get_primitive_root_of_unity(r.trailing_zeros() as u64).unwrap();
+        let half = evals[..r / 2]
+            .iter()
+            .zip(&evals[r / 2..])
+            .enumerate()
+            .map(|(j, (f_y, f_minus_y))| fri::fold_step(&(x * z.pow(j)), &[*f_y, *f_minus_y], &beta))
+            .collect::<Vec<FE>>();
+        assert_eq!(fri::fold_step(&x.square(), &half, &beta.square()), expected, "folding by {}", r);
+    }
+}
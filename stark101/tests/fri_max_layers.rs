@@ -0,0 +1,89 @@
+// the fri layers stop at the most the options allow, the last one
+// sent as a remainder polynomial
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::air::Air;
+use stark101::common::ProofOptions;
+use stark101::encoding::{self, ProofView};
+use stark101::fri::{self, FriError, FriOptions, OffsetSchedule, QuerySampling};
+use stark101::merkle::MerkleBackend;
+use stark101::{prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn truncated_proofs_verify_and_round_trip() {
+    let (air, trace, public_input) = statement();
+    let full = prover::prove(&air, &trace, public_input.clone(), &ProofOptions::default());
+
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, fri_max_layers: Some(3), ..Default::default() };
+        let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+        assert_eq!(proof.composition_commitment.len(), 3);
+        let remainder = &proof.composition_commitment[2].remainder;
+        assert_eq!(remainder.len(), fri::degree_schedule(air.composition_degree_bound(), 512).unwrap()[2]);
+        assert!(full.composition_commitment.iter().all(|layer| layer.remainder.is_empty()));
+        assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+
+        // the remainder goes through every encoding
+        let bytes = encoding::encode_proof(&proof);
+        assert_eq!(encoding::encode_proof(&encoding::decode_proof(&bytes).unwrap()), bytes);
+        let view = ProofView::parse(&bytes).unwrap();
+        assert_eq!(verifier::verify(&air, public_input.clone(), &view, &options), Ok(()));
+        let compact = encoding::decode_proof_compact(&encoding::encode_proof_compact(&proof)).unwrap();
+        assert_eq!(encoding::encode_proof(&compact), bytes);
+        assert!(matches!(
+            encoding::decode_proof(&bytes[..bytes.len() - 32]),
+            Err(encoding::DecodeError::UnexpectedEnd { .. })
+        ));
+
+        // a remainder that is not the last layer is rejected. with per
+        // layer sampling, it also moves the queries of the inner layers
+        let mut tampered = proof.clone();
+        tampered.composition_commitment[2].remainder[0] += FE::one();
+        assert!(matches!(
+            verifier::verify(&air, public_input.clone(), &tampered, &options),
+            Err(verifier::VerifyError::Fri(FriError::Remainder { query: 0 } | FriError::Inclusion { .. }))
+        ));
+
+        // the limit is bound to the proof
+        assert!(verifier::verify(&air, public_input.clone(), &proof, &ProofOptions { query_sampling, ..Default::default() }).is_err());
+        let mut untruncated = proof.clone();
+        untruncated.composition_commitment[2].remainder.clear();
+        assert!(matches!(
+            verifier::verify(&air, public_input.clone(), &untruncated, &options),
+            Err(verifier::VerifyError::Fri(FriError::RemainderSize { received: 0, .. }))
+        ));
+    }
+}
+
+#[test]
+fn low_degree_proofs_send_the_remainder() {
+    let offset = FE::from(3_u64);
+    let coefficients = (1..=32_u64).map(FE::from).collect::<Vec<FE>>();
+    let evaluations = Polynomial::evaluate_offset_fft(&Polynomial::new(&coefficients), 1, Some(256), &offset).unwrap();
+    for offset_schedule in [OffsetSchedule::Squared, OffsetSchedule::Sampled] {
+        let options = FriOptions { num_queries: 8, query_sampling: QuerySampling::Folded, offset_schedule, max_layers: Some(1), merkle_backend: MerkleBackend::Keccak256 };
+        let proof = fri::prove_low_degree(&evaluations, &offset, 32, &options).unwrap();
+        // with a single layer, the remainder is the polynomial itself
+        assert_eq!(proof.commitment.len(), 1);
+        assert_eq!(proof.commitment[0].remainder, coefficients);
+        assert_eq!(fri::verify_low_degree(&proof, 256, &offset, 32, &options), Ok(()));
+
+        let options = FriOptions { max_layers: Some(4), ..options };
+        let mut proof = fri::prove_low_degree(&evaluations, &offset, 32, &options).unwrap();
+        assert_eq!(proof.commitment[3].remainder.len(), 4);
+        assert_eq!(fri::verify_low_degree(&proof, 256, &offset, 32, &options), Ok(()));
+        proof.commitment[3].remainder.push(FE::zero());
+        assert_eq!(
+            fri::verify_low_degree(&proof, 256, &offset, 32, &options),
+            Err(FriError::RemainderSize { expected: 4, received: 5 })
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// trace trees with several rows of the extension in each leaf, opened
+// leaf by leaf
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::common::{ProofOptions, PublicInputError, StarkProof};
+use stark101::merkle::LeafLayout;
+use stark101::verifier::{self, VerifyError};
+use stark101::{cost, encoding, prover};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn paths(proof: &StarkProof<F>) -> usize {
+    proof.trace_commitment.inclusion_proofs
+        .iter()
+        .filter(|opening| !opening.1.merkle_path.is_empty())
+        .count()
+}
+
+#[test]
+fn the_layout_packs_consecutive_rows_of_a_coset() {
+    let layout = LeafLayout::new(4, 8);
+    // the row 5 of the coset 3 is the second of the leaf 3 + 8
+    assert_eq!(layout.leaf_of(3 + 8 * 5), (11, 1));
+    assert_eq!(layout.positions(11).collect::<Vec<usize>>(), vec![35, 43, 51, 59]);
+    assert_eq!(layout.height(512), 7);
+    for position in 0..512 {
+        let (leaf, offset) = layout.leaf_of(position);
+        assert_eq!(layout.positions(leaf).nth(offset), Some(position));
+    }
+
+    // the frame rows of a query share a leaf unless they straddle two
+    let positions = stark101::common::trace_positions(&[3 + 8 * 5, 3 + 8 * 7], &[0, 1], &layout, 512);
+    assert_eq!(positions, vec![35, 43, 51, 59, 67, 75, 83, 91]);
+    assert_eq!(stark101::common::trace_positions(&[40, 3], &[0, 1], &LeafLayout::new(1, 8), 512), vec![3, 11, 40, 48]);
+}
+
+#[test]
+fn proofs_with_wide_leaves_verify() {
+    let (air, trace, public_input) = statement();
+    let narrow = ProofOptions::default();
+    for width in [2, 4, 64] {
+        let options = ProofOptions { trace_leaf_width: width, ..Default::default() };
+        let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+        let decoded = encoding::decode_proof(&encoding::encode_proof(&proof)).unwrap();
+        assert_eq!(verifier::verify(&air, public_input.clone(), &decoded, &options), Ok(()), "width {}", width);
+        assert!(verifier::verify(&air, public_input.clone(), &proof, &narrow).is_err());
+
+        // every opening is a whole leaf, under the path of its first row
+        assert_eq!(proof.trace_commitment.inclusion_proofs.len(), paths(&proof) * width);
+    }
+
+    // the two rows of a frame mostly share a leaf
+    let narrow_proof = prover::prove(&air, &trace, public_input.clone(), &narrow);
+    let wide_proof = prover::prove(&air, &trace, public_input, &ProofOptions { trace_leaf_width: 4, ..Default::default() });
+    assert!(paths(&wide_proof) < paths(&narrow_proof));
+}
+
+#[test]
+fn tampered_leaves_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions { trace_leaf_width: 4, ..Default::default() };
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    // a row that is not the first of its leaf
+    let mut tampered = proof.clone();
+    tampered.trace_commitment.inclusion_proofs[1].0 += FE::one();
+    assert!(matches!(
+        verifier::verify(&air, public_input.clone(), &tampered, &options),
+        Err(VerifyError::TraceInclusion { .. })
+    ));
+
+    // the path of a leaf sent with another of its rows
+    let mut misplaced = proof.clone();
+    let path = misplaced.trace_commitment.inclusion_proofs[0].1.clone();
+    misplaced.trace_commitment.inclusion_proofs[1].1 = path;
+    assert!(matches!(
+        verifier::verify(&air, public_input.clone(), &misplaced, &options),
+        Err(VerifyError::TracePosition { .. })
+    ));
+
+    // a leaf missing its last row
+    let mut truncated = proof;
+    truncated.trace_commitment.inclusion_proofs.pop();
+    assert!(verifier::verify(&air, public_input, &truncated, &options).is_err());
+}
+
+#[test]
+fn leaves_wider_than_the_trace_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &ProofOptions::default());
+    for width in [0, 3, 128] {
+        let options = ProofOptions { trace_leaf_width: width, ..Default::default() };
+        assert_eq!(
+            verifier::verify(&air, public_input.clone(), &proof, &options),
+            Err(VerifyError::PublicInput(PublicInputError::LeafWidth { width, trace_length: 64 }))
+        );
+    }
+}
+
+#[test]
+fn the_estimated_proof_size_bounds_the_proof() {
+    let (air, trace, public_input) = statement();
+    let narrow = cost::estimate_verifier_cost(&air, &public_input, &ProofOptions::default());
+    let options = ProofOptions { trace_leaf_width: 4, ..Default::default() };
+    let estimate = cost::estimate_verifier_cost(&air, &public_input, &options);
+    let report = cost::proof_size_report(&air, &public_input, &options);
+    assert_eq!(report.proof_bytes(), estimate.proof_bytes);
+    assert!(estimate.merkle_hashes < narrow.merkle_hashes);
+
+    let proof = prover::prove(&air, &trace, public_input, &options);
+    assert!(estimate.trace_openings >= proof.trace_commitment.inclusion_proofs.len());
+    assert!(estimate.proof_bytes >= encoding::encode_proof(&proof).len());
+}
+
+#[cfg(feature = "mmap")]
+#[test]
+fn the_trace_storage_does_not_change_the_proof() {
+    use stark101::storage::TraceStorage;
+
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions { trace_leaf_width: 4, ..Default::default() };
+    let disk = ProofOptions { trace_storage: TraceStorage::Disk(std::env::temp_dir()), ..options.clone() };
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let disk_proof = prover::prove(&air, &trace, public_input, &disk);
+    assert_eq!(encoding::encode_proof(&disk_proof), encoding::encode_proof(&proof));
+}
@@ -0,0 +1,176 @@
+// proofs of the previous versions of the format are still verified,
+// by the legacy entry points only
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use stark101::air::{Air, FibonacciSquare};
+use stark101::common::{IsPublicInput, ProofOptions, PublicInput, StarkProof};
+use stark101::encoding::{self, DecodeError, DecodeMode};
+use stark101::fri::QuerySampling;
+use stark101::key::VerifyingKey;
+use stark101::legacy;
+use stark101::merkle::LeafLayout;
+use stark101::prover;
+use stark101::verifier::{self, ProofVerifier, StarkVerifier, VerifyError};
+
+mod common;
+
+use common::{statement, BackwardFibonacci};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the queries of every layer in the order they are sampled, as the
+// prover of version 1 opened them
+fn sampled_queries<A: Air<F>>(air: &A, public_input: &PublicInput<F>, proof: &StarkProof<F>, options: &ProofOptions) -> Vec<Vec<usize>> {
+    let PublicInput(_, _, eval_two_power, num_queries, ..) = *public_input;
+    let num_layers = proof.composition_commitment.len();
+    let mut transcript = DefaultTranscript::<F>::new(&options.transcript_label);
+    transcript.append_bytes(&VerifyingKey::new(air, eval_two_power).digest(options));
+    public_input.absorb_into(&mut transcript);
+    for parameter in air.parameters() {
+        transcript.append_bytes(&stark101::common::fe_to_bytes_be(&parameter));
+    }
+    transcript.append_bytes(&[options.query_sampling as u8]);
+    transcript.append_bytes(&air.composition_degree_bound().to_be_bytes());
+    transcript.append_bytes(&num_layers.to_be_bytes());
+    transcript.append_bytes(&num_queries.to_be_bytes());
+    transcript.append_bytes(&proof.trace_commitment.root);
+    for _ in 0..=air.transition_constraints().len() {
+        transcript.sample_field_element();
+    }
+    let mut queries = vec![stark101::common::sampled_queries(num_queries, 1 << eval_two_power, &mut transcript).unwrap()];
+
+    if options.query_sampling == QuerySampling::PerLayer {
+        transcript.append_bytes(&proof.composition_commitment[0].root);
+        for layer in &proof.composition_commitment[1..] {
+            transcript.sample_field_element();
+            transcript.append_bytes(&layer.root);
+        }
+        for i in 1..num_layers - 1 {
+            queries.push(stark101::common::sampled_queries(num_queries, (1 << eval_two_power) >> i, &mut transcript).unwrap());
+        }
+    }
+    queries
+}
+
+// the openings in sorted query order, in the given sampled order
+fn unsorted<T: Clone>(openings: &[T], sampled: &[usize]) -> Vec<T> {
+    let mut sorted = sampled.to_owned();
+    sorted.sort_unstable();
+    sampled
+        .iter()
+        .map(|q| openings[sorted.binary_search(q).unwrap()].clone())
+        .collect::<Vec<T>>()
+}
+
+// the encoding of the proof by the prover of version 1
+fn encode_version_1<A: Air<F>>(air: &A, public_input: &PublicInput<F>, proof: &StarkProof<F>, options: &ProofOptions) -> Vec<u8> {
+    let PublicInput(_, interp_two_power, eval_two_power, ..) = *public_input;
+    let blowup_factor = 1 << (eval_two_power - interp_two_power);
+    let eval_order = 1 << eval_two_power;
+    let queries = sampled_queries(air, public_input, proof, options);
+    let order = |i: usize| match options.query_sampling {
+        QuerySampling::Folded => &queries[0],
+        QuerySampling::PerLayer => &queries[i],
+    };
+
+    let mut sorted = queries[0].clone();
+    sorted.sort_unstable();
+    let frame_offsets = air.frame_offsets();
+    let positions = stark101::common::trace_positions(&sorted, &frame_offsets, &LeafLayout::new(1, blowup_factor), eval_order);
+    let mut legacy = proof.clone();
+    // version 1 predates the binding
+    legacy.binding = None;
+    legacy.trace_commitment.inclusion_proofs = queries[0]
+        .iter()
+        .flat_map(|q| frame_offsets.iter().map(move |k| (q + k * blowup_factor) % eval_order))
+        .map(|row| proof.trace_commitment.inclusion_proofs[positions.binary_search(&row).unwrap()].clone())
+        .collect();
+    for (i, layer) in legacy.composition_commitment.iter_mut().enumerate() {
+        if !layer.validation_data.is_empty() {
+            layer.validation_data = unsorted(&layer.validation_data, order(i));
+        }
+        if !layer.landing_data.is_empty() {
+            layer.landing_data = unsorted(&layer.landing_data, order(i - 1));
+        }
+    }
+
+    let mut bytes = encoding::encode_proof(&legacy);
+    bytes[4] = legacy::VERSION;
+    bytes
+}
+
+#[test]
+fn proofs_of_version_1_are_verified() {
+    let (air, trace, mut public_input) = statement();
+    public_input.3 = 16;
+    let public_input_bytes = encoding::encode_public_input(&public_input);
+
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+        let bytes = encode_version_1(&air, &public_input, &proof, &options);
+        // the layout differs from the current one
+        assert_ne!(bytes[5..], encoding::encode_proof(&proof)[5..]);
+        assert_eq!(encoding::decode_proof(&bytes).map(|_| ()), Err(DecodeError::Version(legacy::VERSION)));
+
+        let mode = DecodeMode::Strict { eval_two_power: public_input.2 };
+        assert_eq!(legacy::verify_encoded(&air, public_input.clone(), &bytes, mode, &options), Ok(()));
+        assert_eq!(legacy::verify_encoded(&air, public_input.clone(), &encoding::encode_proof(&proof), mode, &options), Ok(()));
+
+        // the verifier of the current version does not read it
+        let verifier = StarkVerifier::hash_chain(options.clone());
+        assert_eq!(verifier.verify(&public_input_bytes, &bytes), Err(VerifyError::Decode(DecodeError::Version(legacy::VERSION))));
+
+        // an opening of version 1 is still checked
+        let mut tampered = bytes.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(legacy::verify_encoded(&air, public_input.clone(), &tampered, mode, &options).is_err());
+    }
+}
+
+#[test]
+fn proofs_of_version_1_with_frames_reaching_backwards_are_verified() {
+    let trace = FibonacciSquare::trace(&FE::one(), &FE::from(3141592_u64), 64);
+    let air = BackwardFibonacci { trace_length: 64, first: FE::one(), last: trace[62] };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 6, 9, 8, FE::one(), trace[62], None);
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let bytes = encode_version_1(&air, &public_input, &proof, &options);
+    assert_eq!(legacy::verify_encoded(&air, public_input.clone(), &bytes, DecodeMode::Lenient, &options), Ok(()));
+
+    // an opening of version 1 is still checked
+    let mut tampered = bytes.clone();
+    let last = tampered.len() - 1;
+    tampered[last] ^= 1;
+    assert!(legacy::verify_encoded(&air, public_input, &tampered, DecodeMode::Lenient, &options).is_err());
+}
+
+#[test]
+fn unsupported_versions_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let mut bytes = encoding::encode_proof(&prover::prove(&air, &trace, public_input.clone(), &options));
+
+    let verifier = StarkVerifier::hash_chain(options.clone());
+    let public_input_bytes = encoding::encode_public_input(&public_input);
+    for version in [0, 4, 255] {
+        bytes[4] = version;
+        assert_eq!(encoding::proof_version(&bytes), Ok(version));
+        assert_eq!(verifier.verify(&public_input_bytes, &bytes), Err(VerifyError::Decode(DecodeError::Version(version))));
+        assert_eq!(
+            legacy::verify_encoded(&air, public_input.clone(), &bytes, DecodeMode::Lenient, &options),
+            Err(VerifyError::Decode(DecodeError::Version(version)))
+        );
+    }
+    assert_eq!(
+        verifier::verify_encoded(&air, public_input, b"S10", encoding::DecodeMode::Lenient, &options),
+        Err(VerifyError::Decode(DecodeError::UnexpectedEnd { offset: 0 }))
+    );
+}
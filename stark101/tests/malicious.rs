@@ -0,0 +1,85 @@
+// every attack of the malicious provers is caught by the verifier,
+// by the check meant for it
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::FibonacciSquare;
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::fri::{FriError, QuerySampling};
+use stark101::malicious::{self, Attack};
+use stark101::verifier::{self, VerifyError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const TRACE_LENGTH: usize = 1024;
+
+fn statement() -> (FibonacciSquare<F>, PublicInput<F>) {
+    let fib_squared_0 = FE::one();
+    let fib_squared_1022 = FE::from_hex_unchecked("6A317721EF632FF24FB815C9BBD4D4582BC7E21A43CFBDD89A8B8F0BDA68252");
+    let air = FibonacciSquare { trace_length: TRACE_LENGTH, fib_squared_0, fib_squared_1022 };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, 12, 10, fib_squared_0, fib_squared_1022, None);
+    (air, public_input)
+}
+
+// the verification error of the proof produced with the attack,
+// which must be a fri one
+fn attack(witness: u64, attack: Attack, query_sampling: QuerySampling) -> FriError {
+    let (air, public_input) = statement();
+    let trace = FibonacciSquare::trace(&FE::one(), &FE::from(witness), TRACE_LENGTH);
+    let options = ProofOptions { query_sampling, ..Default::default() };
+    let proof = malicious::prove_dishonestly(&air, &trace, public_input.clone(), &options, attack);
+    match verifier::verify(&air, public_input, &proof, &options) {
+        Err(VerifyError::Fri(e)) => e,
+        result => panic!("{:?} not caught by fri: {:?}", attack, result),
+    }
+}
+
+#[test]
+fn forged_boundary_is_caught_by_the_last_layer() {
+    // the composition is not of low degree, its last folding is not constant
+    let e = attack(2, Attack::ForgedBoundary, QuerySampling::Folded);
+    assert!(matches!(e, FriError::NotConstant { .. }), "{:?}", e);
+}
+
+#[test]
+fn inconsistent_folding_is_caught_at_its_layer() {
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let e = attack(3141592, Attack::InconsistentFolding { layer: 3 }, query_sampling);
+        assert!(matches!(e, FriError::Folding { layer: 3, .. }), "{:?}", e);
+    }
+}
+
+#[test]
+fn reused_commitment_is_caught() {
+    // the reused layer does not fold from the previous one
+    let e = attack(3141592, Attack::ReusedCommitment { layer: 3 }, QuerySampling::Folded);
+    assert!(matches!(e, FriError::Folding { layer: 3, .. }), "{:?}", e);
+    // the reused root changes the queries sampled for every layer, at
+    // which the other layers are not opened
+    let e = attack(3141592, Attack::ReusedCommitment { layer: 3 }, QuerySampling::PerLayer);
+    assert!(matches!(e, FriError::Inclusion { .. }), "{:?}", e);
+}
+
+#[test]
+fn trace_openings_off_their_rows_are_caught() {
+    let (air, public_input) = statement();
+    let trace = FibonacciSquare::trace(&FE::one(), &FE::from(3141592_u64), TRACE_LENGTH);
+    let options = ProofOptions::default();
+    let proof = stark101::prover::prove(&air, &trace, public_input.clone(), &options);
+
+    // a path one level short or long is not the one of a row of the lde
+    let mut short = proof.clone();
+    short.trace_commitment.inclusion_proofs[0].1.merkle_path.pop();
+    let mut long = proof.clone();
+    long.trace_commitment.inclusion_proofs[0].1.merkle_path.push([0; 32]);
+    for tampered in [short, long] {
+        assert!(matches!(
+            verifier::verify(&air, public_input.clone(), &tampered, &options),
+            Err(VerifyError::TracePosition { .. })
+        ));
+    }
+}
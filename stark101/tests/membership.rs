@@ -0,0 +1,76 @@
+// the membership statement accepts the paths leading to its root only
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+
+use stark101::air::{self, Air};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::malicious::{self, Attack};
+use stark101::membership::{self, MembershipError, MembershipInput};
+use stark101::{encoding, prover, verifier};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// a tree of depth 3, whose trace is padded from 48 to 64 rows
+fn leaves() -> Vec<FE> {
+    (0..8_u64).map(|i| FE::from(1000 + i)).collect::<Vec<FE>>()
+}
+
+fn input(root: FE) -> MembershipInput {
+    MembershipInput { root, depth: 3, blowup_log2: 3, num_queries: 8 }
+}
+
+#[test]
+fn every_leaf_is_proven_a_member() {
+    let leaves = leaves();
+    let input = input(membership::merkle_root(&leaves));
+    let air = input.air();
+    let options = ProofOptions::default();
+
+    // the rounds and the links hold on rows of every block, the padding
+    // blocks included, and only the link of the last block is exempted
+    let transitions = air.transition_constraints();
+    assert_eq!(transitions.iter().map(|t| (t.period, t.rows.len(), t.exemptions.clone())).collect::<Vec<_>>(), [(16, 13, vec![]), (16, 1, vec![63])]);
+
+    for index in [0, 5, 7] {
+        let path = membership::authentication_path(&leaves, index);
+        let trace = input.trace(&leaves[index], index, &path).unwrap();
+        assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+        let proof = prover::prove(&air, &trace, input.public_input(), &options);
+        assert_eq!(verifier::verify(&air, input.public_input(), &proof, &options), Ok(()));
+    }
+}
+
+#[test]
+fn a_path_to_another_root_is_rejected() {
+    let leaves = leaves();
+    let input = input(membership::merkle_root(&leaves));
+    let path = membership::authentication_path(&leaves, 2);
+    assert_eq!(input.trace(&FE::from(5_u64), 2, &path), Err(MembershipError::NotTheRoot));
+    assert_eq!(input.trace(&leaves[2], 2, &path[1..]), Err(MembershipError::PathLength { depth: 3, siblings: 2 }));
+
+    // the trace of another tree, proven against the root of this one
+    let other = leaves.iter().map(|leaf| leaf.double()).collect::<Vec<FE>>();
+    let trace = MembershipInput { root: membership::merkle_root(&other), ..input.clone() }
+        .trace(&other[2], 2, &membership::authentication_path(&other, 2))
+        .unwrap();
+    let air = input.air();
+    let options = ProofOptions::default();
+    let proof = malicious::prove_dishonestly(&air, &trace, input.public_input(), &options, Attack::ForgedBoundary);
+    assert!(matches!(verifier::verify(&air, input.public_input(), &proof, &options), Err(verifier::VerifyError::Fri(_))));
+}
+
+#[test]
+fn the_public_input_round_trips() {
+    let input = input(membership::merkle_root(&leaves()));
+    let decoded = encoding::decode_public_input(&encoding::encode_public_input(&input.public_input())).unwrap();
+    assert_eq!(MembershipInput::from_public_input(&decoded), Ok(input.clone()));
+
+    // a depth whose trace is shorter than the public one
+    let shallow = MembershipInput { depth: 2, ..input }.public_input();
+    let PublicInput(modulus, _, eval_two_power, num_queries, root, depth, _) = shallow;
+    let other = PublicInput(modulus, 6, eval_two_power + 1, num_queries, root, depth, None);
+    assert_eq!(MembershipInput::from_public_input(&other), Err(MembershipError::NotAMembership));
+}
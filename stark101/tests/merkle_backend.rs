@@ -0,0 +1,51 @@
+// proofs committed with blake3 merkle trees verify with the same
+// backend only
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::common::ProofOptions;
+use stark101::fri::{self, FriOptions, OffsetSchedule, QuerySampling};
+use stark101::merkle::MerkleBackend;
+use stark101::{encoding, envelope, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn proofs_verify_with_the_backend_they_were_made_with() {
+    let (air, trace, public_input) = statement();
+    let keccak = ProofOptions::default();
+    let blake3 = ProofOptions { merkle_backend: MerkleBackend::Blake3, ..Default::default() };
+
+    let proof = prover::prove(&air, &trace, public_input.clone(), &blake3);
+    let decoded = encoding::decode_proof(&encoding::encode_proof(&proof)).unwrap();
+    assert_eq!(verifier::verify(&air, public_input.clone(), &decoded, &blake3), Ok(()));
+    assert!(verifier::verify(&air, public_input.clone(), &proof, &keccak).is_err());
+
+    // the backend is absorbed before the trace is committed
+    let keccak_proof = prover::prove(&air, &trace, public_input.clone(), &keccak);
+    assert_ne!(proof.trace_commitment.root, keccak_proof.trace_commitment.root);
+    assert!(verifier::verify(&air, public_input, &keccak_proof, &blake3).is_err());
+
+    let sealed = envelope::seal(&proof, &blake3);
+    let parsed = envelope::Envelope::parse(&sealed).unwrap();
+    assert!(parsed.proof(&blake3).is_ok());
+    assert!(parsed.proof(&keccak).is_err());
+}
+
+#[test]
+fn low_degree_proofs_use_the_backend_of_their_options() {
+    let offset = FE::from(3_u64);
+    let polynomial = Polynomial::new(&(1..=32_u64).map(FE::from).collect::<Vec<FE>>());
+    let evaluations = Polynomial::evaluate_offset_fft(&polynomial, 1, Some(256), &offset).unwrap();
+    let keccak = FriOptions { num_queries: 8, query_sampling: QuerySampling::Folded, offset_schedule: OffsetSchedule::Squared, max_layers: None, merkle_backend: MerkleBackend::Keccak256 };
+    let blake3 = FriOptions { merkle_backend: MerkleBackend::Blake3, ..keccak.clone() };
+
+    let proof = fri::prove_low_degree(&evaluations, &offset, 32, &blake3).unwrap();
+    assert_eq!(fri::verify_low_degree(&proof, 256, &offset, 32, &blake3), Ok(()));
+    assert!(fri::verify_low_degree(&proof, 256, &offset, 32, &keccak).is_err());
+}
@@ -0,0 +1,62 @@
+// the coset offsets of the fri layers follow the schedule of the options
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::common::ProofOptions;
+use stark101::fri::{self, FriError, FriOptions, OffsetSchedule, QuerySampling};
+use stark101::merkle::MerkleBackend;
+use stark101::malicious::{self, Attack};
+use stark101::{encoding, prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const SCHEDULES: [OffsetSchedule; 3] = [OffsetSchedule::Squared, OffsetSchedule::Fixed, OffsetSchedule::Sampled];
+
+#[test]
+fn proofs_verify_under_every_schedule() {
+    let (air, trace, public_input) = statement();
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let proofs = SCHEDULES.map(|offset_schedule| {
+            let options = ProofOptions { query_sampling, offset_schedule, ..Default::default() };
+            let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+            assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+            encoding::encode_proof(&proof)
+        });
+        assert!(proofs[0] != proofs[1] && proofs[1] != proofs[2] && proofs[0] != proofs[2]);
+
+        // the schedule is bound to the proof
+        for (i, proof) in proofs.iter().enumerate() {
+            let proof = encoding::decode_proof(proof).unwrap();
+            let other = ProofOptions { query_sampling, offset_schedule: SCHEDULES[(i + 1) % 3], ..Default::default() };
+            assert!(verifier::verify(&air, public_input.clone(), &proof, &other).is_err());
+        }
+    }
+}
+
+#[test]
+fn misfolding_is_caught_with_fixed_offsets() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions { offset_schedule: OffsetSchedule::Fixed, ..Default::default() };
+    let proof = malicious::prove_dishonestly(&air, &trace, public_input.clone(), &options, Attack::InconsistentFolding { layer: 2 });
+    assert!(matches!(
+        verifier::verify(&air, public_input, &proof, &options),
+        Err(verifier::VerifyError::Fri(FriError::Folding { layer: 2, .. }))
+    ));
+}
+
+#[test]
+fn low_degree_proofs_follow_the_schedule() {
+    let offset = FE::from(3_u64);
+    let polynomial = Polynomial::new(&(1..=32_u64).map(FE::from).collect::<Vec<FE>>());
+    let evaluations = Polynomial::evaluate_offset_fft(&polynomial, 1, Some(256), &offset).unwrap();
+    for offset_schedule in SCHEDULES {
+        let options = FriOptions { num_queries: 8, query_sampling: QuerySampling::Folded, offset_schedule, max_layers: None, merkle_backend: MerkleBackend::Keccak256 };
+        let proof = fri::prove_low_degree(&evaluations, &offset, 32, &options).unwrap();
+        assert_eq!(fri::verify_low_degree(&proof, 256, &offset, 32, &options), Ok(()));
+    }
+}
@@ -0,0 +1,77 @@
+// outputs of a computation read from its trace, checked by the verifier
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::air::{Air, BoundaryConstraints, HashChain, TransitionConstraint};
+use stark101::common::{ProofOptions, PublicInput, PublicInputError};
+use stark101::output;
+use stark101::verifier::VerifyError;
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the hash chain from a public seed, whose digest is computed by the
+// prover instead of claimed, exported with the middle of the chain
+#[derive(Clone)]
+struct ChainDigest {
+    chain: HashChain<F>,
+}
+
+impl Air<F> for ChainDigest {
+
+    fn trace_length(&self) -> usize {
+        self.chain.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "chain-digest".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        let mut boundary = self.chain.boundary_constraints();
+        boundary.constraints.retain(|c| c.step == 0);
+        boundary
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        self.chain.transition_constraints()
+    }
+
+    fn output_steps(&self) -> Vec<usize> {
+        vec![self.chain.trace_length / 2, self.chain.trace_length - 1]
+    }
+}
+
+fn statement() -> (ChainDigest, Vec<FE>, PublicInput<F>) {
+    let (mut chain, trace, mut public_input) = common::statement();
+    // the digest is not part of the statement
+    chain.digest = FE::zero();
+    public_input.5 = FE::zero();
+    (ChainDigest { chain }, trace, public_input)
+}
+
+#[test]
+fn outputs_are_the_values_of_the_trace() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let (proof, outputs) = output::prove_with_outputs(&air, &trace, public_input.clone(), &options);
+    assert_eq!(outputs, vec![trace[32], trace[63]]);
+    assert_eq!(output::verify_with_outputs(&air, public_input, &proof, &outputs, &options), Ok(()));
+}
+
+#[test]
+fn other_outputs_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let (proof, outputs) = output::prove_with_outputs(&air, &trace, public_input.clone(), &options);
+
+    let mut other = outputs.clone();
+    other[1] += FE::one();
+    assert!(output::verify_with_outputs(&air, public_input.clone(), &proof, &other, &options).is_err());
+
+    assert_eq!(
+        output::verify_with_outputs(&air, public_input, &proof, &outputs[..1], &options),
+        Err(VerifyError::PublicInput(PublicInputError::OutputCount { expected: 2, received: 1 }))
+    );
+}
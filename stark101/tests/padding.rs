@@ -0,0 +1,66 @@
+// statements whose number of steps is not a power of two
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::{self, Air, HashChain, Padded, Padding, TraceError};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::{prover, verifier};
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const STEPS: usize = 100;
+
+// the hash chain of STEPS steps, padded to 128 rows
+fn statement(padding: Padding) -> (Padded<HashChain<F>>, Vec<FE>, PublicInput<F>) {
+    let seed = FE::from(3141592_u64);
+    let inner_trace = HashChain::trace(&seed, STEPS);
+    let digest = inner_trace[STEPS - 1];
+    let air = Padded { inner: HashChain { trace_length: STEPS, seed, digest }, padding };
+    let trace = air.trace(&inner_trace);
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 7, 10, 8, seed, digest, None);
+    (air, trace, public_input)
+}
+
+#[test]
+fn padded_traces_are_proven() {
+    for padding in [Padding::Zero, Padding::RepeatLast] {
+        let (air, trace, public_input) = statement(padding);
+        assert_eq!(trace.len(), 128);
+        assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+
+        let options = ProofOptions::default();
+        let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+        assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+
+        // the padding is part of the statement
+        let other = Padded { padding: if padding == Padding::Zero { Padding::RepeatLast } else { Padding::Zero }, ..air.clone() };
+        assert!(verifier::verify(&other, public_input, &proof, &options).is_err());
+    }
+}
+
+#[test]
+fn the_padding_rows_are_constrained() {
+    for padding in [Padding::Zero, Padding::RepeatLast] {
+        let (air, mut trace, _) = statement(padding);
+        trace[110] += FE::one();
+        assert_eq!(
+            air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()),
+            Err(TraceError::Transition { constraint: 1, step: 109 })
+        );
+    }
+}
+
+#[test]
+fn statements_of_a_power_of_two_steps_are_unchanged() {
+    let (inner, trace, _) = common::statement();
+    let air = Padded { inner: inner.clone(), padding: Padding::Zero };
+    assert_eq!(air.trace(&trace), trace);
+    assert_eq!(air.transition_constraints().len(), inner.transition_constraints().len());
+    assert_eq!(air.composition_degree_bound(), inner.composition_degree_bound());
+}
@@ -0,0 +1,51 @@
+// products and powers in evaluation form are only computed over a
+// domain large enough for their degree
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::domain::EvaluationDomain;
+use stark101::poly::{self, DomainSizeError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn polynomial(degree: u64) -> Polynomial<FE> {
+    Polynomial::new(
+        &(0..=degree)
+            .map(|i| FE::from(5 * i + 2))
+            .collect::<Vec<FE>>()
+    )
+}
+
+#[test]
+fn products_up_to_the_domain_size_are_computed() {
+    let domain = EvaluationDomain::new(16, &FE::from(2_u64));
+    let (p, q) = (polynomial(8), polynomial(7));
+
+    // degree 15, the largest a domain of 16 points fits
+    assert_eq!(poly::polynomial_multiplication(&[&p, &q], &domain), Ok(&p * &q));
+    assert_eq!(poly::polynomial_power(&polynomial(5), 3, &domain), Ok(&(&polynomial(5) * &polynomial(5)) * &polynomial(5)));
+    assert_eq!(
+        poly::polynomial_multiplication(&[&p, &q, &polynomial(1)], &domain),
+        Err(DomainSizeError::DomainTooSmall { degree: 16, size: 16 })
+    );
+}
+
+#[test]
+fn powers_aliasing_on_the_domain_are_errors() {
+    let domain = EvaluationDomain::new(16, &FE::from(2_u64));
+    assert_eq!(
+        poly::polynomial_power(&polynomial(4), 4, &domain),
+        Err(DomainSizeError::DomainTooSmall { degree: 16, size: 16 })
+    );
+    // a degree overflowing u64 does not wrap around into the domain
+    assert_eq!(
+        poly::polynomial_power(&polynomial(2), u64::MAX, &domain),
+        Err(DomainSizeError::DomainTooSmall { degree: u64::MAX, size: 16 })
+    );
+    // constants stay constants at any power
+    assert_eq!(poly::polynomial_power(&polynomial(0), u64::MAX, &domain), Ok(Polynomial::new(&[FE::from(2_u64).pow(u64::MAX)])));
+}
@@ -0,0 +1,47 @@
+// the arithmetic in evaluation form of poly checked against the one in
+// coefficient form, passing when the domain fits the result and
+// panicking when it does not
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use stark101::domain::EvaluationDomain;
+use stark101::poly;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn polynomial(degree: u64) -> Polynomial<FE> {
+    Polynomial::new(
+        &(0..=degree)
+            .map(|i| FE::from(3 * i + 1))
+            .collect::<Vec<FE>>()
+    )
+}
+
+#[test]
+fn results_fitting_the_domain_pass_the_check() {
+    let domain = EvaluationDomain::new(32, &FE::from(2_u64));
+    let (p, q) = (polynomial(10), polynomial(7));
+
+    assert_eq!(poly::polynomial_multiplication(&[&p, &q], &domain).unwrap(), &p * &q);
+    assert_eq!(poly::polynomial_power(&q, 3, &domain).unwrap(), &(&q * &q) * &q);
+    assert_eq!(poly::polynomial_division(&(&p * &q), &q, &domain), p);
+}
+
+#[test]
+#[should_panic(expected = "polynomial_division: the denominator of degree 7 does not divide the numerator of degree 10")]
+fn a_division_with_a_remainder_panics() {
+    let domain = EvaluationDomain::new(16, &FE::from(2_u64));
+    poly::polynomial_division(&polynomial(10), &polynomial(7), &domain);
+}
+
+#[test]
+#[should_panic(expected = "the numerator of degree 17 does not fit in the domain")]
+fn a_numerator_aliasing_on_the_domain_panics() {
+    let domain = EvaluationDomain::new(16, &FE::from(2_u64));
+    let (p, q) = (polynomial(10), polynomial(7));
+    poly::polynomial_division(&(&p * &q), &q, &domain);
+}
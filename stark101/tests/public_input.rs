@@ -0,0 +1,83 @@
+// the public input printed and digested the same way for prover and
+// verifier
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+use lambdaworks_crypto::fiat_shamir::{default_transcript::DefaultTranscript, is_transcript::IsTranscript};
+
+use stark101::common::{IsPublicInput, ProofOptions, PublicInput, PublicInputError};
+use stark101::encoding::{self, DecodeError};
+use stark101::verifier::{ProofVerifier, StarkVerifier, VerifyError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn public_input() -> PublicInput<F> {
+    PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 6, 9, 8, FE::from(3_u64), FE::from(0xabc_u64), None)
+}
+
+#[test]
+fn the_public_input_is_displayed_on_one_line() {
+    assert_eq!(
+        public_input().to_string(),
+        "modulus 0x800000000000011000000000000000000000000000000000000000000000001, trace of 2^6 rows, lde of 2^9 points, 8 queries, public values 0x3 and 0xABC"
+    );
+    let committed = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 6, 9, 8, FE::from(3_u64), FE::from(0xabc_u64), Some(FE::from(17_u64)));
+    assert!(committed.to_string().ends_with(", witness commitment 0x11"));
+}
+
+#[test]
+fn the_digest_is_the_one_of_the_encoded_public_input() {
+    let public_input = public_input();
+    let encoded = encoding::encode_public_input(&public_input);
+    assert_eq!(public_input.digest(), DefaultTranscript::<F>::new(&encoded).state());
+    assert_eq!(encoding::decode_public_input(&encoded).unwrap().digest(), public_input.digest());
+
+    // every part of the statement changes it
+    let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, _) = public_input.clone();
+    let others = [
+        PublicInput(modulus, interp_two_power + 1, eval_two_power, num_queries, value_0, value_1, None),
+        PublicInput(modulus, interp_two_power, eval_two_power + 1, num_queries, value_0, value_1, None),
+        PublicInput(modulus, interp_two_power, eval_two_power, num_queries + 1, value_0, value_1, None),
+        PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_1, value_0, None),
+        PublicInput(modulus, interp_two_power, eval_two_power, num_queries, value_0, value_1, Some(FE::zero())),
+    ];
+    for other in others {
+        assert_ne!(other.digest(), public_input.digest(), "{}", other);
+    }
+}
+
+#[test]
+fn statements_of_hostile_sizes_are_rejected_before_the_air_is_built() {
+    let hash_chain = StarkVerifier::hash_chain(ProofOptions::default());
+    let fibonacci_square = StarkVerifier::fibonacci_square(ProofOptions::default());
+    let statement = |interp_two_power, eval_two_power| encoding::encode_public_input(
+        &PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, interp_two_power, eval_two_power, 8, FE::from(3_u64), FE::from(0xabc_u64), None)
+    );
+
+    // no trace length overflows, the two powers are bounded while decoding
+    for (interp_two_power, eval_two_power, offset) in [(64, 65, 32), (100, 101, 32), (40, 41, 36)] {
+        assert_eq!(
+            hash_chain.verify(&statement(interp_two_power, eval_two_power), &[]),
+            Err(VerifyError::Decode(DecodeError::Limit { limit: "max_two_power", offset }))
+        );
+    }
+    // and by validate for statements given decoded
+    let PublicInput(modulus, _, _, num_queries, value_0, value_1, _) = public_input();
+    assert_eq!(
+        PublicInput::<F>(modulus, 64, 65, num_queries, value_0, value_1, None).validate(),
+        Err(PublicInputError::DomainSizes { interp_two_power: 64, eval_two_power: 65 })
+    );
+
+    // no constraint of the air reaches before the first row
+    assert_eq!(
+        hash_chain.verify(&statement(0, 3), &[]),
+        Err(VerifyError::PublicInput(PublicInputError::TraceLength { trace_length: 1, min: 2 }))
+    );
+    assert_eq!(
+        fibonacci_square.verify(&statement(1, 4), &[]),
+        Err(VerifyError::PublicInput(PublicInputError::TraceLength { trace_length: 2, min: 1024 }))
+    );
+}
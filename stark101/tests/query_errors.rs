@@ -0,0 +1,44 @@
+// the helpers sampling and opening queries fail with an error instead
+// of panicking
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+use lambdaworks_crypto::fiat_shamir::default_transcript::DefaultTranscript;
+
+use stark101::common::{self, QueryError, VectorCommitment};
+use stark101::merkle::MerkleBackend;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn queries_are_not_sampled_from_an_empty_domain() {
+    let mut transcript = DefaultTranscript::<F>::new(b"queries");
+    assert_eq!(common::sample_queries(8, 0, &mut transcript), Err(QueryError::EmptyDomain));
+    assert_eq!(common::sampled_queries(8, 0, &mut transcript), Err(QueryError::EmptyDomain));
+
+    let sampled = common::sampled_queries(8, 64, &mut DefaultTranscript::<F>::new(b"queries")).unwrap();
+    let mut sorted = common::sample_queries(8, 64, &mut DefaultTranscript::<F>::new(b"queries")).unwrap();
+    assert!(sampled.iter().all(|query| *query < 64));
+    sorted.dedup();
+    assert!(sorted.iter().all(|query| sampled.contains(query)));
+}
+
+#[test]
+fn positions_outside_the_domain_are_not_opened() {
+    let evals = (0..16_u64).map(FE::from).collect::<Vec<FE>>();
+    let backend = MerkleBackend::default();
+    let tree = backend.build(&evals);
+    let mut commitment = VectorCommitment::new_from_tree(&tree);
+
+    assert_eq!(
+        commitment.generate_inclusion_proofs(&[3, 16, 5], &evals, &tree),
+        Err(QueryError::Position { position: 16, domain_size: 16 })
+    );
+    assert!(commitment.inclusion_proofs.is_empty());
+
+    assert_eq!(commitment.generate_inclusion_proofs(&[3, 15], &evals, &tree), Ok(()));
+    assert!(commitment.verify_inclusion_proofs(&[3, 15], backend));
+    assert_eq!(QueryError::Position { position: 16, domain_size: 16 }.to_string(), "position 16 outside of a domain of 16 points");
+}
@@ -0,0 +1,38 @@
+// the points of the queries computed from the squarings of the domain
+// generator, and their inverses computed at once, against the direct
+// computation
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    traits::IsFFTField
+};
+
+use stark101::domain;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn coset_points_are_the_offset_times_the_powers_of_the_generator() {
+    let offset = FE::from(3_u64);
+    let w = F::get_primitive_root_of_unity(13).unwrap();
+    let indices = [0, 1, 2, 1000, 4097, 8191];
+    let points = domain::coset_points(&offset, &w, 1 << 13, &indices);
+    for (point, i) in points.iter().zip(indices) {
+        assert_eq!(*point, offset * w.pow(i), "index {}", i);
+    }
+    assert!(domain::coset_points(&offset, &w, 1 << 13, &[]).is_empty());
+}
+
+#[test]
+fn batch_inverses_are_the_inverses_of_the_values() {
+    let values = (1..20_u64)
+        .map(|i| FE::from(i * i + 5))
+        .collect::<Vec<FE>>();
+    let inverses = domain::batch_inverses(&values);
+    assert_eq!(inverses.len(), values.len());
+    for (value, inverse) in values.iter().zip(&inverses) {
+        assert_eq!(value * inverse, FE::one());
+        assert_eq!(*inverse, value.inv().unwrap());
+    }
+}
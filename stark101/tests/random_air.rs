@@ -0,0 +1,235 @@
+// proves and verifies random valid statements: a trace following a
+// random recurrence of bounded degree, with random boundary steps,
+// exemptions, rows, lde sizes and query parameters. every proof must be
+// accepted by the verifier of the same crate, with matching transcripts
+use std::sync::Arc;
+
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::{self, Air, BoundaryConstraint, BoundaryConstraints, TransitionConstraint};
+use stark101::channel;
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::fri::QuerySampling;
+use stark101::prover;
+
+mod common;
+use common::SplitMix64;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+const RANDOM_STATEMENTS: u64 = 32;
+
+// coefficient times the product of the previous rows of the frame,
+// each raised to its exponent
+#[derive(Debug)]
+struct Monomial {
+    coefficient: FE,
+    exponents: Vec<u64>,
+}
+
+// the next row is a polynomial of the frame_size - 1 rows before it
+#[derive(Debug)]
+struct Recurrence {
+    frame_size: usize,
+    degree: usize,
+    monomials: Vec<Monomial>,
+}
+
+impl Recurrence {
+
+    fn random(rng: &mut SplitMix64) -> Self {
+        let frame_size = 2 + rng.below(3);
+        let degree = 1 + rng.below(3);
+        // the first monomial has the full degree, the others at most
+        let monomials = (0..1 + rng.below(3))
+            .map(|i| {
+                let total = if i == 0 { degree } else { rng.below(degree + 1) };
+                let mut exponents = vec![0; frame_size - 1];
+                for _ in 0..total {
+                    exponents[rng.below(frame_size - 1)] += 1;
+                }
+                Monomial { coefficient: FE::from(rng.next()), exponents }
+            })
+            .collect::<Vec<Monomial>>();
+        Self { frame_size, degree, monomials }
+    }
+
+    fn next_row(&self, rows: &[FE]) -> FE {
+        self.monomials
+            .iter()
+            .fold(FE::zero(), |sum, monomial| {
+                sum + rows
+                    .iter()
+                    .zip(&monomial.exponents)
+                    .fold(monomial.coefficient, |product, (row, exponent)| product * row.pow(*exponent))
+            })
+    }
+}
+
+struct RandomAir {
+    trace_length: usize,
+    recurrence: Arc<Recurrence>,
+    // a multiple of the recurrence constraint, if any, so that the
+    // statement has more than one transition constraint, on some rows
+    // of blocks of a period of steps
+    multiple: Option<(FE, Vec<usize>, usize, Vec<usize>)>,
+    exemptions: Vec<usize>,
+    boundary: Vec<(usize, FE)>,
+}
+
+impl RandomAir {
+
+    // a random statement with a trace satisfying it
+    fn random(rng: &mut SplitMix64) -> (Self, Vec<FE>) {
+        let trace_length = 1 << (2 + rng.below(5));
+        let recurrence = Recurrence::random(rng);
+        let frame_size = recurrence.frame_size;
+
+        let mut trace = (0..frame_size - 1)
+            .map(|_| FE::from(rng.next()))
+            .collect::<Vec<FE>>();
+        for i in frame_size - 1..trace_length {
+            let row = recurrence.next_row(&trace[i + 1 - frame_size..i]);
+            trace.push(row);
+        }
+
+        // the frames starting on the last rows wrap around the trace
+        let mut exemptions = (trace_length + 1 - frame_size..trace_length).collect::<Vec<usize>>();
+        if rng.below(2) == 0 {
+            exemptions.push(rng.below(trace_length + 1 - frame_size));
+        }
+        let multiple = (rng.below(2) == 0).then(|| {
+            let mut exemptions = exemptions.clone();
+            exemptions.push(rng.below(trace_length + 1 - frame_size));
+            exemptions.sort_unstable();
+            exemptions.dedup();
+            let period = 1 << rng.below(trace_length.trailing_zeros() as usize + 1);
+            let mut rows = (0..period).filter(|_| rng.below(2) == 0).collect::<Vec<usize>>();
+            if rows.is_empty() {
+                rows.push(rng.below(period));
+            }
+            exemptions.retain(|step| rows.contains(&(step % period)));
+            (FE::from(rng.next()), exemptions, period, rows)
+        });
+        exemptions.sort_unstable();
+        exemptions.dedup();
+
+        let mut steps = (0..1 + rng.below(3))
+            .map(|_| rng.below(trace_length))
+            .collect::<Vec<usize>>();
+        steps.sort_unstable();
+        steps.dedup();
+        let boundary = steps
+            .into_iter()
+            .map(|step| (step, trace[step]))
+            .collect::<Vec<(usize, FE)>>();
+
+        let air = Self {
+            trace_length,
+            recurrence: Arc::new(recurrence),
+            multiple,
+            exemptions,
+            boundary,
+        };
+        (air, trace)
+    }
+
+    fn constraint(&self, factor: FE, exemptions: &[usize]) -> TransitionConstraint<F> {
+        let recurrence = Arc::clone(&self.recurrence);
+        TransitionConstraint::new(
+            recurrence.frame_size,
+            recurrence.degree,
+            exemptions,
+            move |frame| factor * (frame[recurrence.frame_size - 1] - recurrence.next_row(&frame[..recurrence.frame_size - 1]))
+        )
+    }
+}
+
+impl Air<F> for RandomAir {
+
+    fn trace_length(&self) -> usize {
+        self.trace_length
+    }
+
+    fn identifier(&self) -> String {
+        "random".to_string()
+    }
+
+    fn boundary_constraints(&self) -> BoundaryConstraints<F> {
+        BoundaryConstraints::new(
+            self.boundary
+                .iter()
+                .map(|(step, value)| BoundaryConstraint { step: *step, value: *value })
+                .collect::<Vec<BoundaryConstraint<F>>>()
+        )
+    }
+
+    fn transition_constraints(&self) -> Vec<TransitionConstraint<F>> {
+        std::iter::once(self.constraint(FE::one(), &self.exemptions))
+            .chain(self.multiple.iter().map(|(factor, exemptions, period, rows)| self.constraint(*factor, exemptions).on_rows(*period, rows)))
+            .collect::<Vec<TransitionConstraint<F>>>()
+    }
+}
+
+// the smallest lde larger than the trace in which the composition
+// folds down to a constant, possibly doubled
+fn eval_two_power(air: &RandomAir, rng: &mut SplitMix64) -> usize {
+    let interp_two_power = air.trace_length.trailing_zeros() as usize;
+    let smallest = (interp_two_power + 1..)
+        .find(|e| air.composition_degree_bound() <= 1 << (e - 1))
+        .unwrap();
+    smallest + rng.below(2)
+}
+
+fn public_input(air: &RandomAir, eval_two_power: usize, num_queries: usize) -> PublicInput<F> {
+    PublicInput(
+        MontgomeryConfigStark252PrimeField::MODULUS,
+        air.trace_length.trailing_zeros() as usize,
+        eval_two_power,
+        num_queries,
+        FE::zero(),
+        FE::zero(),
+        None,
+    )
+}
+
+#[test]
+fn random_statements_are_proven_and_verified() {
+    for seed in 0..RANDOM_STATEMENTS {
+        let mut rng = SplitMix64(seed);
+        let (air, trace) = RandomAir::random(&mut rng);
+        assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+
+        let eval_two_power = eval_two_power(&air, &mut rng);
+        let public_input = public_input(&air, eval_two_power, 1 + rng.below(8));
+        let query_sampling = if rng.below(2) == 0 { QuerySampling::Folded } else { QuerySampling::PerLayer };
+        let options = ProofOptions { query_sampling, ..Default::default() };
+
+        let result = std::panic::catch_unwind(|| {
+            channel::prove_and_verify_recorded(&air, &trace, public_input, &options)
+        });
+        assert!(
+            result.is_ok(),
+            "seed {}: trace of {} rows, lde of 2^{}, {:?}",
+            seed, air.trace_length, eval_two_power, air.recurrence
+        );
+    }
+}
+
+#[test]
+#[should_panic(expected = "too small for a composition")]
+fn an_lde_too_small_for_the_composition_is_refused() {
+    // a cubic recurrence needs an lde at least four times the trace
+    let mut rng = SplitMix64(0);
+    let (mut air, mut trace) = RandomAir::random(&mut rng);
+    while air.recurrence.degree != 3 || air.trace_length < 16 {
+        (air, trace) = RandomAir::random(&mut rng);
+    }
+    let interp_two_power = air.trace_length.trailing_zeros() as usize;
+    prover::prove(&air, &trace, public_input(&air, interp_two_power + 1, 4), &ProofOptions::default());
+}
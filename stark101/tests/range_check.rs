@@ -0,0 +1,89 @@
+// the range check statement accepts values below 2^16 only
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+
+use stark101::air::{self, Air, TraceError};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::range_check::{RangeCheckError, RangeCheckInput, RANGE_CHECK_BITS};
+use stark101::verifier::{self, VerifyError};
+use stark101::{encoding, prover};
+
+mod common;
+use common::SplitMix64;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn input() -> RangeCheckInput {
+    RangeCheckInput { values_log2: 5, blowup_log2: 2, num_queries: 8 }
+}
+
+fn values(seed: u64) -> Vec<u64> {
+    let mut rng = SplitMix64(seed);
+    let mut values = (0..1 << input().values_log2)
+        .map(|_| rng.below(1 << RANGE_CHECK_BITS) as u64)
+        .collect::<Vec<u64>>();
+    // both ends of the range
+    values[0] = 0;
+    values[1] = (1 << RANGE_CHECK_BITS) - 1;
+    values
+}
+
+#[test]
+fn values_in_range_are_proven() {
+    let input = input();
+    let air = input.air();
+    let trace = input.trace(&values(0)).unwrap();
+    assert_eq!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()), Ok(()));
+
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, input.public_input(), &options);
+    assert_eq!(verifier::verify(&air, input.public_input(), &proof, &options), Ok(()));
+}
+
+#[test]
+fn a_value_out_of_range_has_no_trace() {
+    let mut values = values(1);
+    values[7] = 1 << RANGE_CHECK_BITS;
+    assert_eq!(input().trace(&values), Err(RangeCheckError::OutOfRange { index: 7, value: 1 << RANGE_CHECK_BITS }));
+}
+
+#[test]
+fn a_forged_decomposition_is_rejected() {
+    // 2^16 decomposed as 2^15 doubled, the last step adding 2^15 rather than a bit
+    let input = input();
+    let mut trace = input.trace(&values(2)).unwrap();
+    let block = 3 * RANGE_CHECK_BITS;
+    for (row, value) in trace[block..block + RANGE_CHECK_BITS].iter_mut().enumerate() {
+        *value = FE::from(1_u64 << row.min(RANGE_CHECK_BITS - 2));
+    }
+    trace[block + RANGE_CHECK_BITS - 1] = FE::from(1_u64 << RANGE_CHECK_BITS);
+
+    let air = input.air();
+    assert_eq!(
+        air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()),
+        Err(TraceError::Transition { constraint: 0, step: block + RANGE_CHECK_BITS - 2 })
+    );
+
+    // the prover refuses the trace in debug builds, and its proof is
+    // rejected otherwise
+    let options = ProofOptions::default();
+    match std::panic::catch_unwind(|| prover::prove(&air, &trace, input.public_input(), &options)) {
+        Ok(proof) => assert!(matches!(verifier::verify(&air, input.public_input(), &proof, &options), Err(VerifyError::Fri(_)))),
+        Err(_) if cfg!(debug_assertions) => {},
+        Err(e) => std::panic::resume_unwind(e),
+    }
+}
+
+#[test]
+fn the_public_input_round_trips() {
+    let input = input();
+    let decoded = encoding::decode_public_input(&encoding::encode_public_input(&input.public_input())).unwrap();
+    assert_eq!(RangeCheckInput::from_public_input(&decoded), Ok(input));
+
+    let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, _, zero, _) = decoded;
+    let other = PublicInput(modulus, interp_two_power, eval_two_power, num_queries, FE::from(8_u64), zero, None);
+    assert_eq!(RangeCheckInput::from_public_input(&other), Err(RangeCheckError::NotARangeCheck));
+}
@@ -0,0 +1,83 @@
+// proofs of several statements verified by one registry, dispatched
+// by the statement named in their envelope
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::FibonacciSquare;
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::envelope::{self, EnvelopeError};
+use stark101::fri::QuerySampling;
+use stark101::registry::{self, Registry, StatementError};
+use stark101::verifier::{StarkVerifier, VerifyError};
+use stark101::{encoding, prover};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn registry() -> Registry {
+    let (hash_chain_air, _, hash_chain_input) = statement();
+    let fib_squared_0 = FE::one();
+    let fibonacci_square_air = FibonacciSquare {
+        trace_length: 1024,
+        fib_squared_0,
+        fib_squared_1022: FibonacciSquare::fib_squared_1022(&fib_squared_0, &FE::from(3141592_u64)),
+    };
+    let fibonacci_square_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, 12, 8, fib_squared_0, fibonacci_square_air.fib_squared_1022, None);
+    let per_layer = ProofOptions { query_sampling: QuerySampling::PerLayer, ..Default::default() };
+
+    let mut registry = Registry::new();
+    registry.register(
+        "hash-chain",
+        StarkVerifier::hash_chain(ProofOptions::default()),
+        registry::key_digest(&hash_chain_air, &hash_chain_input, &ProofOptions::default())
+    );
+    registry.register(
+        "fibonacci-square",
+        StarkVerifier::fibonacci_square(per_layer.clone()),
+        registry::key_digest(&fibonacci_square_air, &fibonacci_square_input, &per_layer)
+    );
+    registry
+}
+
+#[test]
+fn proofs_are_verified_against_the_statement_they_name() {
+    let registry = registry();
+    assert_eq!(registry.statements().collect::<Vec<&str>>(), ["fibonacci-square", "hash-chain"]);
+    assert_eq!(registry.options("fibonacci-square").map(|options| options.query_sampling), Some(QuerySampling::PerLayer));
+
+    let options = ProofOptions::default();
+    let (air, trace, public_input) = statement();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let encoded_input = encoding::encode_public_input(&public_input);
+
+    let sealed = envelope::seal_statement(&proof, &options, "hash-chain", None);
+    assert_eq!(registry.verify(&encoded_input, &sealed), Ok(()));
+
+    // the options registered for the other statement are not the ones of the proof
+    let misnamed = envelope::seal_statement(&proof, &options, "fibonacci-square", None);
+    assert_eq!(registry.verify(&encoded_input, &misnamed), Err(VerifyError::Envelope(EnvelopeError::Options)));
+    let unknown = envelope::seal_statement(&proof, &options, "range-check", None);
+    assert_eq!(registry.verify(&encoded_input, &unknown), Err(VerifyError::Statement(StatementError::Unknown("range-check".to_string()))));
+    assert_eq!(registry.verify(&encoded_input, &envelope::seal(&proof, &options)), Err(VerifyError::Statement(StatementError::Unnamed)));
+}
+
+#[test]
+fn proofs_with_another_verifying_key_are_rejected() {
+    let registry = registry();
+    let options = ProofOptions::default();
+
+    // a valid proof of the hash chain, with a larger lde than the registered one
+    let (air, trace, mut public_input) = statement();
+    public_input.2 = 10;
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let sealed = envelope::seal_statement(&proof, &options, "hash-chain", None);
+    let report = registry.verify_with_report(&encoding::encode_public_input(&public_input), &sealed);
+    assert_eq!(report.result, Err(VerifyError::Statement(StatementError::Key)));
+}
@@ -0,0 +1,59 @@
+// verification reports as json, with stable field names
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::common::ProofOptions;
+use stark101::encoding::{self, DecodeMode};
+use stark101::report::Phase;
+use stark101::{prover, verifier};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn reports_of_valid_proofs_time_every_phase() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let bytes = encoding::encode_proof(&proof);
+
+    let report = verifier::verify_encoded_with_report(&air, public_input, &bytes, DecodeMode::Lenient, &options);
+    assert_eq!(
+        report.durations.iter().map(|(phase, _)| *phase).collect::<Vec<Phase>>(),
+        [Phase::Decoding, Phase::PublicInput, Phase::TraceOpenings, Phase::CompositionConsistency, Phase::Fri]
+    );
+    let json = report.to_json();
+    let value = serde_json::from_str::<serde_json::Value>(&json).unwrap();
+    assert_eq!(value["schema"], 1);
+    assert_eq!(value["status"], "verified");
+    assert!(value["failed_check"].is_null() && value["layer"].is_null() && value["query"].is_null());
+    assert_eq!(value["num_queries"], 8);
+    assert!(value["durations"]["fri"].is_f64());
+    assert!(value["metadata"].is_null());
+}
+
+#[test]
+fn reports_of_rejected_proofs_name_the_failed_check() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let mut proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let last = proof.composition_commitment.len() - 1;
+    proof.composition_commitment[last].validation_data[2].eval += FE::one();
+
+    let report = verifier::verify_with_report(&air, public_input, &proof, &options);
+    // the folding of the layer before does not land on the opened value
+    assert_eq!(report.failed_check(), Some("fri_folding"));
+    // the phase it stopped in is the last one timed
+    assert_eq!(report.durations.last().map(|(phase, _)| *phase), Some(Phase::Fri));
+
+    let value = serde_json::from_str::<serde_json::Value>(&report.to_json()).unwrap();
+    assert_eq!(value["status"], "rejected");
+    assert_eq!(value["failed_check"], "fri_folding");
+    assert_eq!(value["phase"], "fri");
+    assert_eq!(value["layer"], last);
+    assert_eq!(value["query"], 2);
+    assert_eq!(value["error"], report.result.unwrap_err().to_string());
+}
@@ -0,0 +1,87 @@
+// the rows after a point of the lde are read by rotating the extension
+// of the trace, with no evaluation of a shifted polynomial
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField,
+    traits::IsFFTField
+};
+use lambdaworks_math::polynomial::Polynomial;
+
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::MontgomeryConfigStark252PrimeField;
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::{self, Air, BoundaryConstraints, FibonacciSquare, TraceError, TransitionConstraint};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::expr::Expr;
+use stark101::key::{ProvingKey, VerifyingKey};
+use stark101::pipeline;
+use stark101::prover;
+use stark101::verifier;
+
+mod common;
+
+use common::{statement, BackwardFibonacci};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn the_rotated_extension_is_the_one_of_the_shifted_trace() {
+    let (air, trace, _) = statement();
+    let key = ProvingKey::new(&air, 9);
+    let trace_poly = pipeline::interpolate_trace(&key, &trace);
+    let trace_lde = pipeline::extend_trace(&key, &trace_poly);
+
+    let g = F::get_primitive_root_of_unity(6).unwrap();
+    let offset = FE::from(2_u64);
+    for k in [0, 1, 2, 63, 64] {
+        let shifted = pipeline::shift_trace(&key, &trace_lde, k);
+        // t(g^k x) as a polynomial, evaluated over the lde coset
+        let shifted_poly = trace_poly.scale(&g.pow(k as u64));
+        let evaluated = Polynomial::evaluate_offset_fft(&shifted_poly, 8, None, &offset).unwrap();
+        assert_eq!(shifted, evaluated, "shift by {} rows", k);
+        assert_eq!(shifted[5], trace_lde[stark101::common::shifted_position(5, k, 8, 512)]);
+    }
+}
+
+#[test]
+fn frames_reaching_backwards_wrap_around_the_trace() {
+    let trace = FibonacciSquare::trace(&FE::one(), &FE::from(3141592_u64), 64);
+    let air = BackwardFibonacci { trace_length: 64, first: FE::one(), last: trace[62] };
+    // the previous rows are the last ones of the trace
+    assert_eq!(air.frame_offsets(), vec![0, 62, 63]);
+    assert_eq!(air.transition_constraints()[0].rotations(64), vec![62, 63, 0]);
+    assert!(air::check_trace(&trace, &air.boundary_constraints(), &air.transition_constraints()).is_ok());
+
+    let options = ProofOptions::default();
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 6, 9, 8, FE::one(), trace[62], None);
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(verifier::verify(&air, public_input.clone(), &proof, &options), Ok(()));
+    let mut tampered = proof.clone();
+    tampered.trace_commitment.inclusion_proofs[1].0 += FE::one();
+    assert!(verifier::verify(&air, public_input, &tampered, &options).is_err());
+
+    // the origin is part of the statement
+    let forward = TransitionConstraint::<F>::from_expr(&[61, 62, 63], Expr::row(2) - Expr::next(0).square() - Expr::col(0).square());
+    let backward = forward.clone().starting_at(2);
+    assert_eq!(forward.rotations(64), vec![0, 1, 2]);
+    assert_ne!(
+        VerifyingKey::new(&air, 9).digest(&options),
+        VerifyingKey::new(&FibonacciSquare { trace_length: 64, fib_squared_0: FE::one(), fib_squared_1022: trace[62] }, 9).digest(&options)
+    );
+    assert_eq!(backward.frame(64, |k| FE::from(k as u64)), vec![FE::from(62_u64), FE::from(63_u64), FE::zero()]);
+}
+
+#[test]
+fn the_first_steps_read_the_last_rows() {
+    // a(n) = a(n-1), not exempted on step 0, where it reads the last row
+    let constraints = [TransitionConstraint::<F>::from_expr(&[], Expr::next(0) - Expr::col(0)).starting_at(1)];
+    let constant = vec![FE::from(7_u64); 8];
+    assert!(air::check_trace(&constant, &BoundaryConstraints::new(vec![]), &constraints).is_ok());
+    let mut trace = constant.clone();
+    trace[7] = FE::one();
+    assert!(matches!(
+        air::check_trace(&trace, &BoundaryConstraints::new(vec![]), &constraints),
+        Err(TraceError::Transition { constraint: 0, step: 0 })
+    ));
+}
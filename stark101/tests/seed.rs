@@ -0,0 +1,36 @@
+// statements of the fibonacci square derived from a seed string
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::{self, FibonacciSquare};
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::{prover, verifier};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn a_seed_always_gives_the_same_witness() {
+    let witness = air::witness_from_seed::<F>("exercise 1");
+    assert_eq!(witness, air::witness_from_seed::<F>("exercise 1"));
+    assert_ne!(witness, air::witness_from_seed::<F>("exercise 2"));
+    assert_ne!(witness, air::witness_from_seed::<F>(""));
+}
+
+#[test]
+fn the_statement_of_a_seed_is_proven() {
+    let trace_length = 1024;
+    let fib_squared_0 = FE::one();
+    let witness = air::witness_from_seed::<F>("exercise 1");
+    let trace = FibonacciSquare::trace(&fib_squared_0, &witness, trace_length);
+    let fib_squared_1022 = trace[trace_length - 2];
+    let air = FibonacciSquare { trace_length, fib_squared_0, fib_squared_1022 };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, 12, 8, fib_squared_0, fib_squared_1022, None);
+    let options = ProofOptions::default();
+
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert!(verifier::verify(&air, public_input, &proof, &options).is_ok());
+}
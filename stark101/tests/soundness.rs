@@ -0,0 +1,139 @@
+// for every check of the verifier, a proof violating that check alone
+// is rejected with the error of the check, so that no refactoring can
+// drop one of them unnoticed. each proof is the honest one with a
+// single mutation, or the one of a malicious prover
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+use lambdaworks_math::unsigned_integer::element::U256;
+
+use stark101::common::{ProofOptions, PublicInput, PublicInputError, StarkProof};
+use stark101::encoding::{self, DecodeError, DecodeMode};
+use stark101::fri::{FriError, QuerySampling};
+use stark101::malicious::{self, Attack};
+use stark101::verifier::{self, VerifyError};
+use stark101::prover;
+
+mod common;
+
+use common::{seeded_statement, statement};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+// the verification of the honest proof once mutated
+fn mutated(options: &ProofOptions, mutate: impl FnOnce(&mut StarkProof<F>)) -> Result<(), VerifyError> {
+    let (air, trace, public_input) = statement();
+    let mut proof = prover::prove(&air, &trace, public_input.clone(), options);
+    assert_eq!(verifier::verify(&air, public_input.clone(), &proof, options), Ok(()));
+    mutate(&mut proof);
+    verifier::verify(&air, public_input, &proof, options)
+}
+
+// the verification of the proof of a malicious prover
+fn attacked(trace: &[FE], attack: Attack, options: &ProofOptions) -> Result<(), VerifyError> {
+    let (air, _, public_input) = statement();
+    let proof = malicious::prove_dishonestly(&air, trace, public_input.clone(), options, attack);
+    verifier::verify(&air, public_input, &proof, options)
+}
+
+#[test]
+fn malformed_proofs_are_rejected_by_the_decoding() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let bytes = encoding::encode_proof(&prover::prove(&air, &trace, public_input.clone(), &options));
+    let result = verifier::verify_encoded(&air, public_input, &bytes[..bytes.len() - 1], DecodeMode::Lenient, &options);
+    assert!(matches!(result, Err(VerifyError::Decode(DecodeError::UnexpectedEnd { .. }))), "{:?}", result);
+}
+
+#[test]
+fn invalid_public_inputs_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let PublicInput(_, interp_two_power, eval_two_power, num_queries, seed, digest, commitment) = public_input;
+    let other_modulus = PublicInput(U256::from_u64(101), interp_two_power, eval_two_power, num_queries, seed, digest, commitment);
+    assert_eq!(
+        verifier::verify(&air, other_modulus, &proof, &options),
+        Err(VerifyError::PublicInput(PublicInputError::Modulus))
+    );
+}
+
+#[test]
+fn proofs_off_their_anchor_are_rejected() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let mut anchor = proof.commitment_digest(&public_input);
+    anchor[0] ^= 1;
+    assert_eq!(verifier::verify_anchored(&air, public_input, &proof, &options, &anchor), Err(VerifyError::Anchor));
+}
+
+#[test]
+fn trace_openings_off_their_rows_are_rejected() {
+    let result = mutated(&ProofOptions::default(), |proof| {
+        proof.trace_commitment.inclusion_proofs[0].1.merkle_path.pop();
+    });
+    assert!(matches!(result, Err(VerifyError::TracePosition { .. })), "{:?}", result);
+}
+
+#[test]
+fn trace_openings_off_the_commitment_are_rejected() {
+    let result = mutated(&ProofOptions::default(), |proof| {
+        proof.trace_commitment.inclusion_proofs[0].0 += FE::one();
+    });
+    assert!(matches!(result, Err(VerifyError::TraceInclusion { .. })), "{:?}", result);
+}
+
+#[test]
+fn composition_openings_off_the_trace_are_rejected() {
+    let result = mutated(&ProofOptions::default(), |proof| {
+        proof.composition_commitment[0].validation_data[2].eval += FE::one();
+    });
+    assert_eq!(result, Err(VerifyError::CompositionMismatch { query: 2 }));
+}
+
+#[test]
+fn missing_fri_layers_are_rejected() {
+    let result = mutated(&ProofOptions::default(), |proof| {
+        proof.composition_commitment.pop();
+    });
+    assert!(matches!(result, Err(VerifyError::Fri(FriError::LayerCount { .. }))), "{:?}", result);
+}
+
+#[test]
+fn missing_fri_openings_are_rejected() {
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        let result = mutated(&options, |proof| {
+            proof.composition_commitment[1].validation_data.pop();
+        });
+        assert_eq!(result, Err(VerifyError::Fri(FriError::QueryCount { layer: 1 })));
+    }
+    let options = ProofOptions { query_sampling: QuerySampling::PerLayer, ..Default::default() };
+    let result = mutated(&options, |proof| {
+        proof.composition_commitment[2].landing_data.pop();
+    });
+    assert_eq!(result, Err(VerifyError::Fri(FriError::QueryCount { layer: 2 })));
+}
+
+#[test]
+fn fri_openings_off_their_layer_are_rejected() {
+    let result = mutated(&ProofOptions::default(), |proof| {
+        proof.composition_commitment[1].validation_data[0].sym_eval += FE::one();
+    });
+    assert_eq!(result, Err(VerifyError::Fri(FriError::Inclusion { layer: 1, query: 0 })));
+}
+
+#[test]
+fn inconsistent_foldings_are_rejected() {
+    let (_, trace, _) = statement();
+    let result = attacked(&trace, Attack::InconsistentFolding { layer: 2 }, &ProofOptions::default());
+    assert!(matches!(result, Err(VerifyError::Fri(FriError::Folding { layer: 2, .. }))), "{:?}", result);
+}
+
+#[test]
+fn compositions_of_high_degree_are_rejected() {
+    // a trace of another seed, with the boundary rows of the statement
+    let (_, wrong_trace, _) = seeded_statement(2);
+    let result = attacked(&wrong_trace, Attack::ForgedBoundary, &ProofOptions::default());
+    assert!(matches!(result, Err(VerifyError::Fri(FriError::NotConstant { .. }))), "{:?}", result);
+}
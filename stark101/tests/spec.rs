@@ -0,0 +1,50 @@
+// the protocol specification follows the transcript of the prover
+use stark101::channel::{self, RecordingChannel, TranscriptOp};
+use stark101::common::ProofOptions;
+use stark101::fri::QuerySampling;
+use stark101::key::ProvingKey;
+use stark101::{pipeline, prover, spec};
+
+mod common;
+
+use common::statement;
+
+#[test]
+fn every_transcript_operation_of_the_prover_is_specified() {
+    let (air, trace, public_input) = statement();
+
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        let spec = spec::protocol_spec(&air, &trace, public_input.clone(), &options);
+        assert!(spec.unlabelled().is_empty(), "unlabelled steps {:?}", spec.unlabelled());
+
+        let (_, events) = prover::prove_recorded(&air, &trace, public_input.clone(), &options);
+        assert_eq!(spec.steps.iter().map(|step| step.count).sum::<usize>(), events.len());
+
+        let names = spec.steps
+            .iter()
+            .map(|step| step.name)
+            .collect::<Vec<&str>>();
+        assert_eq!(names[..11], [
+            "verifying_key", "modulus", "interp_two_power", "eval_two_power", "num_queries", "public_value_0",
+            "public_value_1", "query_sampling", "degree_bound", "num_layers", "num_queries",
+        ]);
+        assert_eq!(names[11..15], ["trace_root", "boundary_coefficient", "transition_coefficient", "query_index"]);
+        let queries = &spec.steps[14];
+        assert_eq!((queries.op, queries.size, queries.count), (TranscriptOp::Sample, 32, 8));
+        assert_eq!(names.contains(&"fri_layer_query_index"), query_sampling == QuerySampling::PerLayer);
+        assert!(spec.to_json().contains("{\"op\": \"append\", \"name\": \"trace_root\", \"encoding\": \"keccak256 merkle root\", \"size\": 32, \"count\": 1}"));
+    }
+}
+
+#[test]
+fn the_pipeline_labels_the_trace_root() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let key = ProvingKey::new(&air, 9);
+    let mut transcript = RecordingChannel::new(pipeline::start_transcript(&air, &public_input, &options));
+    let trace_lde = pipeline::extend_trace(&key, &pipeline::interpolate_trace(&key, &trace));
+    let tree = pipeline::commit_evaluations(&key, &trace_lde, &options, &mut transcript);
+    assert_eq!(transcript.events[0].bytes, tree.root());
+    assert_eq!(transcript.labels, [("trace_root", channel::DIGEST)]);
+}
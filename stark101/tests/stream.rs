@@ -0,0 +1,64 @@
+// a feed of proofs verified by a bounded number of workers, each
+// result given as soon as its proof is verified
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use stark101::common::ProofOptions;
+use stark101::stream::{self, ProofPair};
+use stark101::verifier::{StarkVerifier, VerifyError};
+use stark101::{encoding, prover, threads};
+
+mod common;
+
+use common::seeded_statement;
+
+// the encoded public input and proof of a hash chain from each seed
+fn pairs(seeds: &[u64]) -> Vec<ProofPair> {
+    let options = ProofOptions::default();
+    seeds
+        .iter()
+        .map(|seed| {
+            let (air, trace, public_input) = seeded_statement(*seed);
+            let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+            (encoding::encode_public_input(&public_input), encoding::encode_proof(&proof))
+        })
+        .collect::<Vec<ProofPair>>()
+}
+
+#[test]
+fn every_proof_of_the_feed_is_verified_once() {
+    let mut pairs = pairs(&[1, 2, 3, 4, 5]);
+    // the proof of the second pair for the statement of the fourth
+    pairs[3].1 = pairs[1].1.clone();
+    let verifier = Arc::new(StarkVerifier::hash_chain(ProofOptions::default()));
+
+    let single_threaded = threads::single_threaded(|| stream::verify_stream(Arc::clone(&verifier), pairs.clone(), 2).collect::<Vec<(usize, Result<(), VerifyError>)>>());
+    let mut results = stream::verify_stream(verifier, pairs, 2).collect::<Vec<(usize, Result<(), VerifyError>)>>();
+    results.sort_by_key(|(index, _)| *index);
+    assert_eq!(results, single_threaded);
+    assert_eq!(results.iter().map(|(index, _)| *index).collect::<Vec<usize>>(), [0, 1, 2, 3, 4]);
+    assert_eq!(results.iter().filter(|(_, result)| result.is_err()).map(|(index, _)| *index).collect::<Vec<usize>>(), [3]);
+}
+
+#[test]
+fn the_feed_is_read_as_the_results_are_consumed() {
+    let pairs = pairs(&[1]);
+    let taken = Arc::new(AtomicUsize::new(0));
+    let counter = Arc::clone(&taken);
+    // an endless feed, counting the pairs taken from it
+    let feed = std::iter::repeat(pairs[0].clone()).inspect(move |_| {
+        counter.fetch_add(1, Ordering::SeqCst);
+    });
+    let verifier = Arc::new(StarkVerifier::hash_chain(ProofOptions::default()));
+
+    let concurrency = 2;
+    let mut stream = stream::verify_stream(verifier, feed, concurrency);
+    for _ in 0..10 {
+        assert_eq!(stream.next().map(|(_, result)| result), Some(Ok(())));
+    }
+    std::thread::sleep(std::time::Duration::from_millis(100));
+    // the ten results, at most one pair and one result queued for each
+    // worker, one pair verified by each worker and one held by the feed
+    assert!(taken.load(Ordering::SeqCst) <= 10 + 4 * concurrency + 1);
+    drop(stream);
+}
@@ -0,0 +1,51 @@
+// proofs generated on a single thread are the ones of the default,
+// multi-threaded prover
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::air::HashChain;
+use stark101::common::{ProofOptions, PublicInput};
+use stark101::encoding;
+use stark101::metrics::Backend;
+use stark101::prover;
+use stark101::threads;
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn statement(seed: u64) -> (HashChain<F>, Vec<FE>, PublicInput<F>) {
+    let (air, trace, mut public_input) = common::sized_statement(seed, 10);
+    // enough queries for parallel_map to split the openings
+    public_input.2 = 12;
+    public_input.3 = 1024;
+    (air, trace, public_input)
+}
+
+#[test]
+fn a_single_threaded_proof_is_the_same() {
+    let (air, trace, public_input) = statement(3);
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let single = threads::single_threaded(|| {
+        assert!(threads::is_single_threaded());
+        assert_eq!(Backend::current().threads, 1);
+        prover::prove(&air, &trace, public_input.clone(), &options)
+    });
+    assert!(!threads::is_single_threaded());
+    assert_eq!(encoding::encode_proof(&single), encoding::encode_proof(&proof));
+}
+
+#[test]
+fn a_single_threaded_batch_is_the_same() {
+    let witnesses = (1..3)
+        .map(statement)
+        .collect::<Vec<(HashChain<F>, Vec<FE>, PublicInput<F>)>>();
+    let options = ProofOptions::default();
+    let proofs = prover::prove_many(&witnesses, 2, &options);
+    let single = threads::single_threaded(|| prover::prove_many(&witnesses, 2, &options));
+    for (single, proof) in single.iter().zip(&proofs) {
+        assert_eq!(encoding::encode_proof(single), encoding::encode_proof(proof));
+    }
+}
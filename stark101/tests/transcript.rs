@@ -0,0 +1,131 @@
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air;
+use stark101::channel::{self, TranscriptOp};
+use stark101::common::{ProofOptions, PublicInput, Session};
+use stark101::fri::QuerySampling;
+use stark101::key::VerifyingKey;
+use stark101::{prover, verifier};
+
+mod common;
+
+use common::{sized_statement, statement};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn hash_chain(steps_log2: usize, blowup_log2: usize, num_queries: usize) -> (air::HashChain<F>, Vec<FE>, PublicInput<F>) {
+    let (air, trace, mut public_input) = sized_statement(3141592, steps_log2);
+    public_input.2 = steps_log2 + blowup_log2;
+    public_input.3 = num_queries;
+    (air, trace, public_input)
+}
+
+#[test]
+fn transcripts_match_for_both_query_samplings() {
+    let (air, trace, public_input) = statement();
+    for query_sampling in [QuerySampling::Folded, QuerySampling::PerLayer] {
+        let options = ProofOptions { query_sampling, ..Default::default() };
+        channel::prove_and_verify_recorded(&air, &trace, public_input.clone(), &options);
+    }
+}
+
+#[test]
+fn transcripts_match_for_the_fibonacci_square() {
+    let fib_squared_0 = FE::one();
+    let fib_squared_1022 = FE::from_hex_unchecked("6A317721EF632FF24FB815C9BBD4D4582BC7E21A43CFBDD89A8B8F0BDA68252");
+    let mut trace = vec![fib_squared_0, FE::from(3141592_u64)];
+    for i in 2..1023 {
+        trace.push(trace[i - 2].square() + trace[i - 1].square());
+    }
+    trace.push(FE::zero());
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 10, 13, 4, fib_squared_0, fib_squared_1022, None);
+    let air = air::FibonacciSquare { trace_length: 1024, fib_squared_0, fib_squared_1022 };
+    channel::prove_and_verify_recorded(&air, &trace, public_input, &ProofOptions::default());
+}
+
+#[test]
+fn the_recurrence_coefficients_are_absorbed_after_the_public_input() {
+    let air = air::QuadraticRecurrence {
+        trace_length: 64,
+        alpha: FE::from(2_u64),
+        beta: FE::from(3_u64),
+        gamma: FE::from(5_u64),
+        first: FE::one(),
+        last: FE::zero(),
+    };
+    let trace = air.trace(&FE::from(3141592_u64));
+    let air = air::QuadraticRecurrence { last: trace[62], ..air };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, 6, 9, 4, air.first, air.last, None);
+    channel::prove_and_verify_recorded(&air, &trace, public_input.clone(), &ProofOptions::default());
+    let (_, events) = prover::prove_recorded(&air, &trace, public_input, &ProofOptions::default());
+    assert_eq!(events[7].bytes, air.alpha.to_bytes_be());
+    assert_eq!(events[9].bytes, air.gamma.to_bytes_be());
+
+    // the fibonacci square is the recurrence of coefficients 1, 1 and 0
+    let fibonacci = air::QuadraticRecurrence { alpha: FE::one(), beta: FE::one(), gamma: FE::zero(), ..air };
+    assert_eq!(fibonacci.trace(&FE::from(3_u64)), air::FibonacciSquare::trace(&FE::one(), &FE::from(3_u64), 64));
+}
+
+#[test]
+fn proofs_are_bound_to_the_verifying_key() {
+    let (air, trace, public_input) = hash_chain(5, 2, 4);
+    let options = ProofOptions::default();
+    let (proof, events) = prover::prove_recorded(&air, &trace, public_input.clone(), &options);
+    let key = VerifyingKey::new(&air, 7);
+    assert_eq!(events[0].bytes, key.digest(&options));
+
+    // the same constraints under another air identifier or other options
+    // are another configuration
+    let claims = air::BoundaryClaims { inner: air.clone(), claims: vec![] };
+    assert_ne!(VerifyingKey::new(&claims, 7).digest(&options), key.digest(&options));
+    assert!(verifier::verify(&claims, public_input.clone(), &proof, &options).is_err());
+    let relabelled = ProofOptions { transcript_label: b"other".to_vec(), ..Default::default() };
+    assert_ne!(key.digest(&relabelled), key.digest(&options));
+    assert_eq!(verifier::verify(&air, public_input, &proof, &options), Ok(()));
+}
+
+#[test]
+fn transcripts_match_within_a_session() {
+    let (air, trace, public_input) = hash_chain(5, 2, 4);
+    let options = ProofOptions { session: Some(Session { seed: [7; 32], index: 3 }), ..Default::default() };
+    channel::prove_and_verify_recorded(&air, &trace, public_input, &options);
+}
+
+#[test]
+fn the_verifier_transcript_stops_at_the_failed_check() {
+    let (air, trace, public_input) = hash_chain(5, 2, 4);
+    let options = ProofOptions::default();
+    let (mut proof, prover_events) = prover::prove_recorded(&air, &trace, public_input.clone(), &options);
+    proof.composition_commitment.pop();
+    let (result, verifier_events) = stark101::verifier::verify_recorded(&air, public_input, &proof, &options);
+    assert!(result.is_err());
+    assert!(verifier_events.len() < prover_events.len());
+    assert_eq!(verifier_events, prover_events[..verifier_events.len()]);
+}
+
+#[test]
+fn samples_are_recorded_after_the_statement() {
+    let (air, trace, public_input) = hash_chain(5, 2, 4);
+    let (_, events) = prover::prove_recorded(&air, &trace, public_input, &ProofOptions::default());
+    let first_sample = events.iter().position(|e| e.op != TranscriptOp::Append).unwrap();
+    // the digest of the verifying key, the six public input values, the
+    // query sampling, the three values of the proof shape and the trace
+    // root come before any challenge, the label seeds the transcript
+    // without being recorded
+    assert_eq!(first_sample, 12);
+}
+
+#[test]
+#[should_panic(expected = "transcripts diverge at operation 1")]
+fn diverging_transcripts_are_reported() {
+    let (air, trace, public_input) = hash_chain(5, 2, 4);
+    let (_, mut events) = prover::prove_recorded(&air, &trace, public_input, &ProofOptions::default());
+    let verifier_events = events.clone();
+    events[1].bytes.push(0);
+    channel::assert_transcripts_match(&events, &verifier_events);
+}
@@ -0,0 +1,63 @@
+// reports of every failed check of a proof, against the first one only
+use lambdaworks_math::field::{element::FieldElement, fields::fft_friendly::stark_252_prime_field::Stark252PrimeField};
+
+use stark101::common::ProofOptions;
+use stark101::encoding::{self, DecodeMode};
+use stark101::prover;
+use stark101::report::{CheckStatus, Phase, VerificationMode};
+use stark101::verifier::{self, VerifierContext, VerifyError};
+
+mod common;
+
+use common::statement;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn exhaustive_reports_hold_every_failed_check() {
+    let (air, trace, public_input) = statement();
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+
+    let fail_fast = VerifierContext::new(&air, &public_input, &options);
+    let exhaustive = fail_fast.clone().with_mode(VerificationMode::Exhaustive);
+    let report = verifier::verify_proof_with_context_report(&exhaustive, &air, public_input.clone(), &proof);
+    assert!(report.is_valid());
+    assert!(report.failures.is_empty());
+
+    // the first and the last trace openings, of different queries
+    let mut tampered = proof.clone();
+    let openings = &mut tampered.trace_commitment.inclusion_proofs;
+    let last = openings.len() - 1;
+    openings[0].0 += FE::one();
+    openings[last].0 += FE::one();
+
+    let first = verifier::verify_proof_with_context_report(&fail_fast, &air, public_input.clone(), &tampered);
+    assert_eq!(first.failures.len(), 1);
+    assert_eq!(first.status(Phase::CompositionConsistency), CheckStatus::NotReached);
+    assert_eq!(first.status(Phase::Fri), CheckStatus::NotReached);
+
+    let every = verifier::verify_proof_with_context_report(&exhaustive, &air, public_input.clone(), &tampered);
+    // the first failure is the one of fail fast mode
+    assert_eq!(every.result, first.result);
+    assert_eq!(every.failures[0], first.failures[0]);
+    let trace_inclusions = every.failures.iter().filter(|e| matches!(e, VerifyError::TraceInclusion { .. })).count();
+    assert!(trace_inclusions >= 2);
+    // the composition is recomputed from the tampered openings, fri
+    // only reads the composition commitment
+    assert_eq!(every.status(Phase::TraceOpenings), CheckStatus::Failed);
+    assert_eq!(every.status(Phase::CompositionConsistency), CheckStatus::Failed);
+    assert_eq!(every.status(Phase::Fri), CheckStatus::Passed);
+    let json = every.to_json();
+    assert!(json.contains("\"mode\": \"exhaustive\""));
+    assert!(json.contains("\"check\": \"composition_mismatch\""));
+
+    // missing openings fail the queries relying on them, the others go on
+    let mut truncated = proof.clone();
+    truncated.trace_commitment.inclusion_proofs.truncate(4);
+    let bytes = encoding::encode_proof(&truncated);
+    let report = verifier::verify_encoded_with_context_report(&exhaustive, &air, public_input, &bytes, DecodeMode::Lenient);
+    assert!(report.failures.len() > 1);
+    assert_eq!(report.status(Phase::Fri), CheckStatus::Passed);
+}
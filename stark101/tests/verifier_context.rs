@@ -0,0 +1,133 @@
+// proofs of statements of the same shape verified with one context
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::{MontgomeryConfigStark252PrimeField, Stark252PrimeField}
+};
+use lambdaworks_math::field::fields::montgomery_backed_prime_fields::IsModulus;
+
+use stark101::air::HashChain;
+use stark101::common::{ProofOptions, PublicInput, PublicInputError};
+use stark101::encoding::{self, DecodeError, DecodeLimits, DecodeMode};
+use stark101::prover;
+use stark101::verifier::{self, VerifierContext, VerifyError};
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+fn statement(seed: u64, trace_log2: usize) -> (HashChain<F>, Vec<FE>, PublicInput<F>) {
+    let seed = FE::from(seed);
+    let trace = HashChain::trace(&seed, 1 << trace_log2);
+    let air = HashChain { trace_length: 1 << trace_log2, seed, digest: trace[(1 << trace_log2) - 1] };
+    let public_input = PublicInput(MontgomeryConfigStark252PrimeField::MODULUS, trace_log2, trace_log2 + 3, 8, seed, air.digest, None);
+    (air, trace, public_input)
+}
+
+#[test]
+fn one_context_verifies_every_proof_of_the_shape() {
+    let options = ProofOptions::default();
+    let (first, _, first_input) = statement(1, 6);
+    let context = VerifierContext::new(&first, &first_input, &options);
+
+    for seed in 1..4 {
+        let (air, trace, public_input) = statement(seed, 6);
+        assert!(context.fits(&air, &public_input));
+        let mut proof = prover::prove(&air, &trace, public_input.clone(), &options);
+        assert_eq!(verifier::verify_proof_with_context(&context, &air, public_input.clone(), &proof), Ok(()));
+
+        proof.trace_commitment.root[0] ^= 1;
+        assert_eq!(
+            verifier::verify_proof_with_context(&context, &air, public_input.clone(), &proof),
+            verifier::verify(&air, public_input, &proof, &options)
+        );
+    }
+}
+
+#[test]
+fn a_context_does_not_fit_another_shape() {
+    let options = ProofOptions::default();
+    let (air, _, public_input) = statement(1, 6);
+    let context = VerifierContext::new(&air, &public_input, &options);
+
+    let (longer, longer_trace, longer_input) = statement(1, 7);
+    assert!(!context.fits(&longer, &longer_input));
+    let PublicInput(modulus, interp_two_power, eval_two_power, num_queries, seed, digest, _) = public_input;
+    let larger_lde = PublicInput(modulus, interp_two_power, eval_two_power + 1, num_queries, seed, digest, None);
+    assert!(!context.fits(&air, &larger_lde));
+
+    // a statement verified with the context of another is rejected, as
+    // is one given an air of another trace length
+    let proof = prover::prove(&longer, &longer_trace, longer_input.clone(), &options);
+    assert_eq!(
+        verifier::verify_proof_with_context(&context, &longer, longer_input.clone(), &proof),
+        Err(VerifyError::PublicInput(PublicInputError::Shape))
+    );
+    assert_eq!(
+        verifier::verify(&air, longer_input, &proof, &options),
+        Err(VerifyError::PublicInput(PublicInputError::Shape))
+    );
+}
+
+#[test]
+fn proofs_past_the_limits_of_the_context_are_rejected() {
+    let options = ProofOptions::default();
+    let (air, trace, public_input) = statement(1, 6);
+    let context = VerifierContext::new(&air, &public_input, &options);
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    let bytes = encoding::encode_proof(&proof);
+    let verify = |context: &VerifierContext, bytes: &[u8]| verifier::verify_encoded_with_context(context, &air, public_input.clone(), bytes, DecodeMode::Lenient);
+    assert_eq!(verify(&context, &bytes), Ok(()));
+
+    let limit = |limits: DecodeLimits| match verify(&context.clone().with_limits(limits), &bytes) {
+        Err(VerifyError::Decode(DecodeError::Limit { limit, .. })) => limit,
+        result => panic!("expected a limit to be exceeded, got {:?}", result),
+    };
+    let limits = *context.limits();
+    assert_eq!(limit(DecodeLimits { max_bytes: bytes.len() - 1, ..limits }), "max_bytes");
+    assert_eq!(limit(DecodeLimits { max_path_len: 8, ..limits }), "max_path_len");
+    assert_eq!(limit(DecodeLimits { max_openings: 7, ..limits }), "max_openings");
+    assert_eq!(limit(DecodeLimits { max_layers: 1, ..limits }), "max_layers");
+
+    // the number of trace openings claimed before any of them is read
+    let mut crafted = bytes[..73].to_vec();
+    crafted[69..73].copy_from_slice(&u32::MAX.to_be_bytes());
+    assert_eq!(verify(&context, &crafted), Err(VerifyError::Decode(DecodeError::Limit { limit: "max_openings", offset: 69 })));
+
+    // more queries than openings accepted, whatever the proof
+    let fewer_openings = context.clone().with_limits(DecodeLimits { max_openings: 7, ..limits });
+    assert_eq!(
+        verifier::verify_proof_with_context(&fewer_openings, &air, public_input.clone(), &proof),
+        Err(VerifyError::PublicInput(PublicInputError::TooManyQueries { num_queries: 8, max: 7 }))
+    );
+}
+
+#[test]
+fn public_inputs_past_the_limits_of_the_context_are_rejected() {
+    let options = ProofOptions::default();
+    let (air, trace, public_input) = statement(1, 6);
+    let context = VerifierContext::new(&air, &public_input, &options);
+    let bytes = encoding::encode_public_input(&public_input);
+    assert_eq!(context.decode_public_input(&bytes).unwrap().digest(), public_input.digest());
+
+    // the two powers of the trace and lde sizes, at bytes 32 and 36, are
+    // rejected as they are read
+    for (offset, two_power) in [(32, u32::MAX), (32, 64), (36, 100), (36, 41)] {
+        let mut hostile = bytes.clone();
+        hostile[offset..offset + 4].copy_from_slice(&two_power.to_be_bytes());
+        assert_eq!(
+            context.decode_public_input(&hostile).err(),
+            Some(VerifyError::Decode(DecodeError::Limit { limit: "max_two_power", offset }))
+        );
+    }
+
+    // a lower limit, also for statements given decoded
+    let smaller = context.clone().with_limits(DecodeLimits { max_two_power: 8, ..*context.limits() });
+    assert_eq!(
+        smaller.decode_public_input(&bytes).err(),
+        Some(VerifyError::Decode(DecodeError::Limit { limit: "max_two_power", offset: 36 }))
+    );
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(
+        verifier::verify_proof_with_context(&smaller, &air, public_input, &proof),
+        Err(VerifyError::PublicInput(PublicInputError::DomainSizes { interp_two_power: 6, eval_two_power: 9 }))
+    );
+}
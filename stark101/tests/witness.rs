@@ -0,0 +1,74 @@
+// the witness read from integers, bytes and hex strings, in their
+// canonical encoding only
+use lambdaworks_math::field::{
+    element::FieldElement,
+    fields::fft_friendly::stark_252_prime_field::Stark252PrimeField
+};
+
+use stark101::air::{self, Air, CommittedWitness, TraceError};
+use stark101::common::{ElementBytesError, ProofOptions, PublicInput};
+use stark101::witness::Witness;
+use stark101::{prover, verifier};
+
+mod common;
+
+type F = Stark252PrimeField;
+type FE = FieldElement<F>;
+
+#[test]
+fn every_constructor_reads_the_same_witness() {
+    let witness = Witness::<F>::example();
+    assert_eq!(witness.value(), &FE::from(3141592_u64));
+    assert_eq!(Witness::<F>::from(3141592), witness);
+    assert_eq!(Witness::<F>::from_hex("0x2FEFD8"), Ok(witness.clone()));
+    assert_eq!(Witness::<F>::from_hex(&witness.to_hex()), Ok(witness.clone()));
+    assert_eq!(Witness::<F>::from_bytes_be(&witness.to_bytes_be()), Ok(witness.clone()));
+    assert_eq!(Witness::<F>::from_seed("exercise 1").into_value(), air::witness_from_seed::<F>("exercise 1"));
+    assert_eq!(witness.commitment(), air::commit_witness(witness.value()));
+
+    // the value stays out of the debug output
+    assert_eq!(format!("{:?}", witness), "Witness(..)");
+}
+
+#[test]
+fn non_canonical_encodings_are_rejected() {
+    // the modulus, 2^251 + 17 * 2^192 + 1
+    let modulus = "0x0800000000000011000000000000000000000000000000000000000000000001";
+    assert_eq!(Witness::<F>::from_hex(modulus), Err(ElementBytesError::NonCanonical));
+    assert_eq!(
+        Witness::<F>::from_hex("0x0800000000000011000000000000000000000000000000000000000000000000").map(|witness| witness.into_value()),
+        Ok(-FE::one())
+    );
+    assert_eq!(Witness::<F>::from_bytes_be(&[0xff; 32]), Err(ElementBytesError::NonCanonical));
+    assert_eq!(Witness::<F>::from_bytes_be(&[1; 31]), Err(ElementBytesError::Length { expected: 32, found: 31 }));
+    assert_eq!(Witness::<F>::from_hex("0xx1"), Err(ElementBytesError::Hex));
+}
+
+#[test]
+fn a_committed_witness_is_proven() {
+    let (inner, inner_trace, public_input) = common::statement();
+    let commitment = air::commit_witness(&inner.seed);
+    let air = CommittedWitness { inner, witness_row: 0, commitment };
+    let trace = air.trace(&inner_trace);
+    let boundary = air.boundary_constraints();
+    let transitions = air.transition_constraints();
+    assert_eq!(air::check_trace(&trace, &boundary, &transitions), Ok(()));
+
+    // the copy of the witness holds on the first row of the chain only
+    let chain_start = air.trace_length() - air::WITNESS_COMMITMENT_ROUNDS - 1;
+    let copy = transitions.len() - 1;
+    assert!((0..air.trace_length()).filter(|step| transitions[copy].applies(*step)).eq([chain_start]));
+    let mut forged = trace.clone();
+    forged[chain_start] += FE::one();
+    assert_eq!(
+        air::check_trace(&forged, &boundary, &transitions[copy..]),
+        Err(TraceError::Transition { constraint: 0, step: chain_start })
+    );
+
+    let PublicInput(modulus, _, _, _, seed, digest, _) = public_input;
+    let interp_two_power = air.trace_length().trailing_zeros() as usize;
+    let public_input = PublicInput(modulus, interp_two_power, interp_two_power + 3, 8, seed, digest, Some(commitment));
+    let options = ProofOptions::default();
+    let proof = prover::prove(&air, &trace, public_input.clone(), &options);
+    assert_eq!(verifier::verify(&air, public_input, &proof, &options), Ok(()));
+}
@@ -0,0 +1,23 @@
+// the witness serialized as the hex string of its canonical bytes
+use lambdaworks_math::field::fields::fft_friendly::stark_252_prime_field::Stark252PrimeField;
+
+use stark101::witness::Witness;
+
+type F = Stark252PrimeField;
+
+#[test]
+fn witnesses_are_serialized_as_hex_strings() {
+    let witness = Witness::<F>::example();
+    let json = serde_json::to_string(&witness).unwrap();
+    assert_eq!(json, "\"0x00000000000000000000000000000000000000000000000000000000002fefd8\"");
+    assert_eq!(serde_json::from_str::<Witness<F>>(&json).unwrap(), witness);
+    assert_eq!(serde_json::from_str::<Witness<F>>("\"2FEFD8\"").unwrap(), witness);
+}
+
+#[test]
+fn non_canonical_witnesses_are_not_deserialized() {
+    let modulus = "\"0x0800000000000011000000000000000000000000000000000000000000000001\"";
+    assert!(serde_json::from_str::<Witness<F>>(modulus).is_err());
+    assert!(serde_json::from_str::<Witness<F>>("3141592").is_err());
+    assert!(serde_json::from_str::<Witness<F>>("\"\"").is_err());
+}